@@ -6,6 +6,7 @@ use database::{Environment, ReadTransaction, WriteTransaction};
 use database as db;
 use hash::Blake2bHash;
 use keys::Address;
+use primitives::policy::AlbatrossPolicy;
 use transaction::{Transaction, TransactionFlags};
 use tree_primitives::accounts_proof::AccountsProof;
 use tree_primitives::accounts_tree_chunk::AccountsTreeChunk;
@@ -18,12 +19,20 @@ type ReceiptsMap<'a> = HashMap<u16, &'a Vec<u8>>;
 pub struct Accounts {
     env: Environment,
     tree: AccountsTree<Account>,
+    policy: Option<AlbatrossPolicy>,
 }
 
 impl Accounts {
     pub fn new(env: Environment) -> Self {
         let tree = AccountsTree::new(env.clone());
-        Accounts { env, tree, }
+        Accounts { env, tree, policy: None }
+    }
+
+    /// Creates the accounts of an Albatross chain. Transactions are additionally checked against
+    /// the parts of `policy` that apply to accounts, e.g. the minimum stake.
+    pub fn with_policy(env: Environment, policy: AlbatrossPolicy) -> Self {
+        let tree = AccountsTree::new(env.clone());
+        Accounts { env, tree, policy: Some(policy) }
     }
 
     pub fn init(&self, txn: &mut WriteTransaction, genesis_accounts: Vec<(Address, Account)>) {
@@ -93,10 +102,20 @@ impl Accounts {
                                                     |account, inherent, _| account.commit_inherent(inherent, block_height))?);
 
         receipts.append(&mut self.process_senders(txn, transactions, block_height, HashMap::new(),
-                                                  |account, transaction, block_height, _| account.commit_outgoing_transaction(transaction, block_height))?);
+                                                  |account, transaction, block_height, _| {
+                                                      if let Some(ref policy) = self.policy {
+                                                          account.check_outgoing_policy(transaction, block_height, policy)?;
+                                                      }
+                                                      account.commit_outgoing_transaction(transaction, block_height)
+                                                  })?);
 
         receipts.append(&mut self.process_recipients(txn, transactions, block_height, HashMap::new(),
-                                                     |account, transaction, block_height, _| account.commit_incoming_transaction(transaction, block_height))?);
+                                                     |account, transaction, block_height, _| {
+                                                         if let Some(ref policy) = self.policy {
+                                                             account.check_incoming_policy(transaction, policy)?;
+                                                         }
+                                                         account.commit_incoming_transaction(transaction, block_height)
+                                                     })?);
 
         self.create_contracts(txn, transactions, block_height)?;
 
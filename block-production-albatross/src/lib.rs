@@ -1,7 +1,6 @@
 extern crate nimiq_block_albatross as block;
 extern crate nimiq_blockchain_albatross as blockchain;
 extern crate nimiq_blockchain_base as blockchain_base;
-extern crate nimiq_bls as bls;
 extern crate nimiq_collections as collections;
 extern crate nimiq_database as database;
 extern crate nimiq_hash as hash;
@@ -9,6 +8,7 @@ extern crate nimiq_keys as keys;
 extern crate nimiq_mempool as mempool;
 extern crate nimiq_network_primitives as network_primitives;
 extern crate nimiq_primitives as primitives;
+extern crate nimiq_signer as signer;
 extern crate nimiq_vrf as vrf;
 
 use std::sync::Arc;
@@ -19,34 +19,33 @@ use block::ForkProof;
 use block::MicroJustification;
 use blockchain::blockchain::Blockchain;
 use blockchain_base::AbstractBlockchain;
-use bls::bls12_381::KeyPair;
 use database::WriteTransaction;
 use hash::{Blake2bHash, Hash};
 use mempool::Mempool;
-use primitives::policy;
 use primitives::slot::ValidatorSlots;
+use signer::{Signer, SignerError};
 use vrf::VrfSeed;
 
 pub struct BlockProducer {
     pub blockchain: Arc<Blockchain>,
     pub mempool: Option<Arc<Mempool<Blockchain>>>,
-    pub validator_key: KeyPair,
+    pub signer: Arc<dyn Signer>,
 }
 
 impl BlockProducer {
-    pub fn new(blockchain: Arc<Blockchain>, mempool: Arc<Mempool<Blockchain>>, validator_key: KeyPair) -> Self {
-        BlockProducer { blockchain, mempool: Some(mempool), validator_key }
+    pub fn new(blockchain: Arc<Blockchain>, mempool: Arc<Mempool<Blockchain>>, signer: Arc<dyn Signer>) -> Self {
+        BlockProducer { blockchain, mempool: Some(mempool), signer }
     }
 
-    pub fn new_without_mempool(blockchain: Arc<Blockchain>, validator_key: KeyPair) -> Self {
-        BlockProducer { blockchain, mempool: None, validator_key }
+    pub fn new_without_mempool(blockchain: Arc<Blockchain>, signer: Arc<dyn Signer>) -> Self {
+        BlockProducer { blockchain, mempool: None, signer }
     }
 
-    pub fn next_macro_block_proposal(&self, timestamp: u64, view_number: u32, view_change_proof: Option<ViewChangeProof>) -> (PbftProposal, MacroExtrinsics) {
+    pub fn next_macro_block_proposal(&self, timestamp: u64, view_number: u32, view_change_proof: Option<ViewChangeProof>) -> Result<(PbftProposal, MacroExtrinsics), SignerError> {
         //  Lock blockchain/mempool while constructing the block.
         let _lock = self.blockchain.lock();
 
-        let seed = self.blockchain.head().seed().sign_next(&self.validator_key.secret);
+        let seed = self.signer.sign_next_seed(self.blockchain.head().seed())?;
         let mut txn = self.blockchain.write_transaction();
 
         let mut header = self.next_macro_header(&mut txn, timestamp, view_number, &seed);
@@ -55,34 +54,53 @@ impl BlockProducer {
 
         txn.abort();
 
-        (PbftProposal {
+        Ok((PbftProposal {
             header,
             view_change: view_change_proof,
-        }, extrinsics)
+        }, extrinsics))
     }
 
-    pub fn next_micro_block(&self, fork_proofs: Vec<ForkProof>, timestamp: u64, view_number: u32, extra_data: Vec<u8>, view_change_proof: Option<ViewChangeProof>) -> MicroBlock {
+    pub fn next_micro_block(&self, fork_proofs: Vec<ForkProof>, timestamp: u64, view_number: u32, extra_data: Vec<u8>, view_change_proof: Option<ViewChangeProof>) -> Result<MicroBlock, SignerError> {
         // Lock blockchain/mempool while constructing the block.
         let _lock = self.blockchain.lock();
 
-        let view_changes = ViewChanges::new(self.blockchain.block_number() + 1, self.blockchain.next_view_number(), view_number);
-        let extrinsics = self.next_micro_extrinsics(fork_proofs, extra_data, &view_changes);
-        let header = self.next_micro_header(timestamp, view_number, &extrinsics, &view_changes);
-        let signature = self.validator_key.sign(&header).compress();
+        let (header, extrinsics) = self.build_micro_block(fork_proofs, timestamp, view_number, extra_data)?;
+        self.sign_micro_block(header, extrinsics, view_change_proof)
+    }
+
+    /// Creates the header and extrinsics of the next micro block without signing the header,
+    /// e.g. to record it in the slashing protection first. The block is signed with
+    /// `sign_micro_block`.
+    pub fn next_unsigned_micro_block(&self, fork_proofs: Vec<ForkProof>, timestamp: u64, view_number: u32, extra_data: Vec<u8>) -> Result<(MicroHeader, MicroExtrinsics), SignerError> {
+        // Lock blockchain/mempool while constructing the block.
+        let _lock = self.blockchain.lock();
+
+        self.build_micro_block(fork_proofs, timestamp, view_number, extra_data)
+    }
 
-        MicroBlock {
+    pub fn sign_micro_block(&self, header: MicroHeader, extrinsics: MicroExtrinsics, view_change_proof: Option<ViewChangeProof>) -> Result<MicroBlock, SignerError> {
+        let signature = self.signer.sign_micro_header(&header)?;
+
+        Ok(MicroBlock {
             header,
             extrinsics: Some(extrinsics),
             justification: MicroJustification {
                 signature,
                 view_change_proof,
             },
-        }
+        })
+    }
+
+    fn build_micro_block(&self, fork_proofs: Vec<ForkProof>, timestamp: u64, view_number: u32, extra_data: Vec<u8>) -> Result<(MicroHeader, MicroExtrinsics), SignerError> {
+        let view_changes = ViewChanges::new(self.blockchain.block_number() + 1, self.blockchain.next_view_number(), view_number);
+        let extrinsics = self.next_micro_extrinsics(fork_proofs, extra_data, &view_changes);
+        let header = self.next_micro_header(timestamp, view_number, &extrinsics, &view_changes)?;
+        Ok((header, extrinsics))
     }
 
     pub fn next_macro_extrinsics(&self, txn: &mut WriteTransaction, seed: &VrfSeed) -> MacroExtrinsics {
         // Determine slashed set without txn, so that it is not garbage collected yet.
-        let prev_epoch = policy::epoch_at(self.blockchain.height() + 1) - 1;
+        let prev_epoch = self.blockchain.policy.epoch_at(self.blockchain.height() + 1) - 1;
         let slashed_set = self.blockchain.state()
             .reward_registry()
             .slashed_set(prev_epoch, None);
@@ -163,7 +181,7 @@ impl BlockProducer {
 
         let state_root = state.accounts().hash(Some(txn));
 
-        let transactions_root = self.blockchain.get_transactions_root(policy::epoch_at(block_number), Some(txn))
+        let transactions_root = self.blockchain.get_transactions_root(self.blockchain.policy.epoch_at(block_number), Some(txn))
             .expect("Failed to compute transactions root, micro blocks missing");
 
         let validators = self.blockchain.next_validators(seed, Some(txn));
@@ -175,7 +193,7 @@ impl BlockProducer {
         header
     }
 
-    fn next_micro_header(&self, timestamp: u64, view_number: u32, extrinsics: &MicroExtrinsics, view_changes: &Option<ViewChanges>) -> MicroHeader {
+    fn next_micro_header(&self, timestamp: u64, view_number: u32, extrinsics: &MicroExtrinsics, view_changes: &Option<ViewChanges>) -> Result<MicroHeader, SignerError> {
         let block_number = self.blockchain.height() + 1;
         let timestamp = u64::max(timestamp, self.blockchain.head().timestamp() + 1);
 
@@ -188,9 +206,9 @@ impl BlockProducer {
             .hash_with(&extrinsics.transactions, &inherents, block_number)
             .expect("Failed to compute accounts hash during block production");
 
-        let seed = self.blockchain.head().seed().sign_next(&self.validator_key.secret);
+        let seed = self.signer.sign_next_seed(self.blockchain.head().seed())?;
 
-        MicroHeader {
+        Ok(MicroHeader {
             version: Block::VERSION,
             block_number,
             view_number,
@@ -199,6 +217,6 @@ impl BlockProducer {
             state_root,
             seed,
             timestamp,
-        }
+        })
     }
 }
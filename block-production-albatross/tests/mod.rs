@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
-use beserial::Deserialize;
+use beserial::{Deserialize, Serialize};
+use nimiq_account::{Inherent, InherentType};
 use nimiq_block_albatross::{Block, ForkProof, MacroBlock, MacroExtrinsics, PbftCommitMessage, PbftPrepareMessage, PbftProofBuilder, PbftProposal, SignedPbftCommitMessage, SignedPbftPrepareMessage, SignedViewChange, ViewChange, ViewChangeProof, ViewChangeProofBuilder};
 use nimiq_block_production_albatross::BlockProducer;
 use nimiq_blockchain_albatross::blockchain::{Blockchain, PushResult};
@@ -9,24 +10,33 @@ use nimiq_bls::{KeyPair, SecretKey};
 use nimiq_bls::bls12_381::lazy::LazyPublicKey;
 use nimiq_database::volatile::VolatileEnvironment;
 use nimiq_hash::{Blake2bHash, Hash};
-use nimiq_mempool::{Mempool, MempoolConfig};
-use nimiq_network_primitives::{networks::NetworkId};
-use nimiq_primitives::policy;
+use nimiq_keys::{Address, KeyPair as AccountKeyPair, SecureGenerate};
+use nimiq_mempool::{Mempool, MempoolConfig, ReturnCode};
+use nimiq_network_primitives::networks::{NetworkId, NetworkInfo};
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::policy::AlbatrossPolicy;
 use nimiq_primitives::slot::{ValidatorSlots, ValidatorSlotBand};
+use nimiq_signer::LocalSigner;
+use nimiq_transaction::{SignatureProof, Transaction};
 
 /// Secret key of validator. Tests run with `network-primitives/src/genesis/unit-albatross.toml`
 const SECRET_KEY: &'static str = "49ea68eb6b8afdf4ca4d4c0a0b295c76ca85225293693bc30e755476492b707f";
 
+/// Policy of the network the tests run with.
+fn policy() -> AlbatrossPolicy {
+    NetworkInfo::from_network_id(NetworkId::UnitAlbatross).unwrap().albatross_policy()
+}
+
 #[test]
 fn it_can_produce_micro_blocks() {
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(12).unwrap();
     let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
     let mempool = Mempool::new(Arc::clone(&blockchain), MempoolConfig::default());
     let keypair = KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
-    let producer = BlockProducer::new(Arc::clone(&blockchain), mempool, keypair.clone());
+    let producer = BlockProducer::new(Arc::clone(&blockchain), mempool, Arc::new(LocalSigner::new(keypair.clone())));
 
     // #1.0: Empty standard micro block
-    let block = producer.next_micro_block(vec![], 1565713920000, 0, vec![0x41], None);
+    let block = producer.next_micro_block(vec![], 1565713920000, 0, vec![0x41], None).unwrap();
     assert_eq!(blockchain.push(Block::Micro(block.clone())), Ok(PushResult::Extended));
     assert_eq!(blockchain.block_number(), 1);
 
@@ -45,13 +55,13 @@ fn it_can_produce_micro_blocks() {
     }
 
     // #2.0: Empty micro block with fork proof
-    let block = producer.next_micro_block(vec![fork_proof], 1565713922000, 0, vec![0x41], None);
+    let block = producer.next_micro_block(vec![fork_proof], 1565713922000, 0, vec![0x41], None).unwrap();
     assert_eq!(blockchain.push(Block::Micro(block)), Ok(PushResult::Extended));
     assert_eq!(blockchain.block_number(), 2);
 
     // #2.1: Empty view-changed micro block
     let view_change = sign_view_change(3, 1);
-    let block = producer.next_micro_block(vec![], 1565713924000, 1, vec![0x41], Some(view_change));
+    let block = producer.next_micro_block(vec![], 1565713924000, 1, vec![0x41], Some(view_change)).unwrap();
     assert_eq!(blockchain.push(Block::Micro(block)), Ok(PushResult::Extended));
     assert_eq!(blockchain.block_number(), 3);
     assert_eq!(blockchain.next_view_number(), 1);
@@ -60,9 +70,9 @@ fn it_can_produce_micro_blocks() {
 // Fill epoch with micro blocks
 fn fill_micro_blocks(producer: &BlockProducer, blockchain: &Arc<Blockchain>) {
     let init_height = blockchain.head_height();
-    let macro_block_number = policy::macro_block_after(init_height + 1);
+    let macro_block_number = policy().macro_block_after(init_height + 1);
     for i in (init_height + 1)..macro_block_number {
-        let last_micro_block = producer.next_micro_block(vec![], 1565713920000 + i as u64 * 2000, 0, vec![0x42], None);
+        let last_micro_block = producer.next_micro_block(vec![], 1565713920000 + i as u64 * 2000, 0, vec![0x42], None).unwrap();
         assert_eq!(blockchain.push(Block::Micro(last_micro_block)), Ok(PushResult::Extended));
     }
     assert_eq!(blockchain.head_height(), macro_block_number - 1);
@@ -85,8 +95,8 @@ fn sign_macro_block(proposal: PbftProposal, extrinsics: Option<MacroExtrinsics>)
 
     // create proof
     let mut pbft_proof = PbftProofBuilder::new();
-    pbft_proof.add_prepare_signature(&keypair.public, policy::SLOTS, &prepare);
-    pbft_proof.add_commit_signature(&keypair.public, policy::SLOTS, &commit);
+    pbft_proof.add_prepare_signature(&keypair.public, policy().slots, &prepare);
+    pbft_proof.add_commit_signature(&keypair.public, policy().slots, &commit);
 
     MacroBlock {
         header: proposal.header,
@@ -102,31 +112,76 @@ fn sign_view_change(block_number: u32, new_view_number: u32) -> ViewChangeProof
     let signed_view_change = SignedViewChange::from_message(view_change.clone(), &keypair.secret, 0);
 
     let mut proof_builder = ViewChangeProofBuilder::new();
-    proof_builder.add_signature(&keypair.public, policy::SLOTS, &signed_view_change);
-    assert_eq!(proof_builder.verify(&view_change, policy::TWO_THIRD_SLOTS), Ok(()));
+    proof_builder.add_signature(&keypair.public, policy().slots, &signed_view_change);
+    assert_eq!(proof_builder.verify(&view_change, policy().two_third_slots()), Ok(()));
 
     let proof = proof_builder.build();
-    let validators = ValidatorSlots::new(vec![ValidatorSlotBand::new(LazyPublicKey::from(keypair.public), policy::SLOTS)]);
-    assert_eq!(proof.verify(&view_change, &validators, policy::TWO_THIRD_SLOTS), Ok(()));
+    let validators = ValidatorSlots::new(vec![ValidatorSlotBand::new(LazyPublicKey::from(keypair.public), policy().slots)]);
+    assert_eq!(proof.verify(&view_change, &validators, policy().two_third_slots()), Ok(()));
 
     proof
 }
 
 #[test]
 fn it_can_produce_macro_blocks() {
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(12).unwrap();
     let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
     let mempool = Mempool::new(Arc::clone(&blockchain), MempoolConfig::default());
 
     let keypair = KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
-    let producer = BlockProducer::new(Arc::clone(&blockchain), mempool, keypair);
+    let producer = BlockProducer::new(Arc::clone(&blockchain), mempool, Arc::new(LocalSigner::new(keypair)));
 
     fill_micro_blocks(&producer, &blockchain);
 
-    let (proposal, extrinsics) = producer.next_macro_block_proposal(1565720000000u64, 0u32, None);
+    let (proposal, extrinsics) = producer.next_macro_block_proposal(1565720000000u64, 0u32, None).unwrap();
 
     let block = sign_macro_block(proposal, Some(extrinsics));
     assert_eq!(blockchain.push_block(Block::Macro(block), true), Ok(PushResult::Extended));
 }
 
 // TODO Test transactions
+
+#[test]
+fn it_evicts_replacements_of_mined_transactions() {
+    let env = VolatileEnvironment::new(12).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+    let mempool = Mempool::new(Arc::clone(&blockchain), MempoolConfig::default());
+    let keypair = KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+    let producer = BlockProducer::new(Arc::clone(&blockchain), Arc::clone(&mempool), Arc::new(LocalSigner::new(keypair)));
+
+    // Give the sender some balance without a block
+    let account_key = AccountKeyPair::generate_default_csprng();
+    let sender = Address::from(&account_key.public);
+    let inherent = Inherent {
+        ty: InherentType::Reward,
+        target: sender.clone(),
+        value: Coin::from_u64_unchecked(10_000),
+        data: vec![],
+    };
+    let mut txn = blockchain.write_transaction();
+    blockchain.state().accounts().commit(&mut txn, &[], &[inherent], 0).unwrap();
+    txn.commit();
+
+    let recipient = Address::from([2u8; Address::SIZE]);
+    let sign = |fee: u64| {
+        let mut tx = Transaction::new_basic(sender.clone(), recipient.clone(), Coin::from_u64_unchecked(10), Coin::from_u64_unchecked(fee), 1, NetworkId::UnitAlbatross);
+        let signature_proof = SignatureProof::from(account_key.public.clone(), account_key.sign(&tx.serialize_content()));
+        tx.proof = signature_proof.serialize_to_vec();
+        tx
+    };
+    let tx = sign(0);
+    assert_eq!(mempool.push_transaction(tx.clone()), ReturnCode::Accepted);
+
+    // The original transaction is mined, although it was replaced in the meantime.
+    let block = producer.next_micro_block(vec![], 1565713920000, 0, vec![0x41], None).unwrap();
+    assert_eq!(block.extrinsics.as_ref().unwrap().transactions, vec![tx]);
+
+    let replacement = sign(1000);
+    let replacement_hash: Blake2bHash = replacement.hash();
+    assert_eq!(mempool.push_transaction(replacement), ReturnCode::Accepted);
+    assert!(mempool.contains(&replacement_hash));
+
+    assert_eq!(blockchain.push(Block::Micro(block)), Ok(PushResult::Extended));
+    assert!(!mempool.contains(&replacement_hash));
+    assert_eq!(mempool.num_transactions(), 0);
+}
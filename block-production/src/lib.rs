@@ -13,7 +13,6 @@ use blockchain::Blockchain;
 use hash::Hash;
 use keys::Address;
 use mempool::Mempool;
-use network_primitives::networks::NetworkInfo;
 
 pub struct BlockProducer {
     blockchain: Arc<Blockchain>,
@@ -80,7 +79,7 @@ impl BlockProducer {
         let timestamp = u32::max(timestamp, self.blockchain.head().header.timestamp + 1);
 
         let prev_hash = self.blockchain.head_hash();
-        let genesis_hash = NetworkInfo::from_network_id(self.blockchain.network_id).genesis_hash().clone();
+        let genesis_hash = self.blockchain.network_info.genesis_hash().clone();
         let interlink_hash = interlink.hash(genesis_hash);
         let body_hash = body.hash();
         let accounts_hash = self.blockchain.state().accounts()
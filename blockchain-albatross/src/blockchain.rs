@@ -25,8 +25,8 @@ use network_primitives::networks::NetworkInfo;
 use network_primitives::time::NetworkTime;
 use primitives::coin::Coin;
 use primitives::networks::NetworkId;
-use primitives::policy;
-use primitives::slot::{Slots, Slot, ValidatorSlots, SlotIndex, SlotBand};
+use primitives::policy::AlbatrossPolicy;
+use primitives::slot::{Slots, Slot, ValidatorSlots, SlotCollection, SlotIndex, SlotBand};
 use transaction::{Transaction as BlockchainTransaction, TransactionReceipt, TransactionsProof};
 use tree_primitives::accounts_proof::AccountsProof;
 use tree_primitives::accounts_tree_chunk::AccountsTreeChunk;
@@ -36,8 +36,11 @@ use vrf::{VrfSeed, VrfUseCase, AliasMethod};
 
 use crate::chain_info::ChainInfo;
 use crate::chain_store::ChainStore;
+use crate::fork_detection::{ForkEvent, SignedHeaderCache};
 use crate::reward_registry::{EpochStateError, SlashRegistry};
-use crate::transaction_cache::TransactionCache;
+use crate::transaction_cache::{EpochTransactionHashes, TransactionCache};
+#[cfg(feature = "transaction-store")]
+use crate::transaction_store::TransactionStore;
 
 
 pub type PushResult = blockchain_base::PushResult;
@@ -89,14 +92,21 @@ impl<T> From<Option<T>> for OptionalCheck<T> {
 pub struct Blockchain {
     pub(crate) env: Environment,
     pub network_id: NetworkId,
+    pub network_info: &'static NetworkInfo,
+    pub policy: AlbatrossPolicy,
     // TODO network_time: Arc<NetworkTime>,
     pub notifier: RwLock<Notifier<'static, BlockchainEvent>>,
+    pub fork_notifier: RwLock<Notifier<'static, ForkEvent>>,
     pub chain_store: Arc<ChainStore>,
     pub(crate) state: RwLock<BlockchainState>,
     pub push_lock: Mutex<()>, // TODO: Not very nice to have this public
+    pub(crate) signed_headers: Mutex<SignedHeaderCache>,
 
     #[cfg(feature = "metrics")]
     metrics: BlockchainMetrics,
+
+    #[cfg(feature = "transaction-store")]
+    pub(crate) transaction_store: TransactionStore,
 }
 
 pub struct BlockchainState {
@@ -105,7 +115,7 @@ pub struct BlockchainState {
     pub reward_registry: SlashRegistry,
 
     pub(crate) main_chain: ChainInfo,
-    head_hash: Blake2bHash,
+    pub(crate) head_hash: Blake2bHash,
 
     macro_head: MacroBlock,
     macro_head_hash: Blake2bHash,
@@ -146,11 +156,11 @@ impl BlockchainState {
     }
 
     pub fn current_slashed_set(&self) -> BitSet {
-        self.reward_registry.slashed_set(policy::epoch_at(self.block_number()), None)
+        self.reward_registry.slashed_set(self.reward_registry.policy().epoch_at(self.block_number()), None)
     }
 
     pub fn last_slashed_set(&self) -> BitSet {
-        self.reward_registry.slashed_set(policy::epoch_at(self.block_number()) - 1, None)
+        self.reward_registry.slashed_set(self.reward_registry.policy().epoch_at(self.block_number()) - 1, None)
     }
 
     pub fn reward_registry(&self) -> &SlashRegistry {
@@ -160,16 +170,18 @@ impl BlockchainState {
 
 impl Blockchain {
     pub fn new(env: Environment, network_id: NetworkId) -> Result<Self, BlockchainError> {
-        let chain_store = Arc::new(ChainStore::new(env.clone()));
+        let network_info = NetworkInfo::from_network_id(network_id)?;
+        let policy = network_info.albatross_policy();
+
+        let chain_store = Arc::new(ChainStore::new(env.clone(), policy));
         Ok(match chain_store.get_head(None) {
-            Some(head_hash) => Blockchain::load(env, network_id, chain_store, head_hash)?,
-            None => Blockchain::init(env, network_id, chain_store)?
+            Some(head_hash) => Blockchain::load(env, network_info, policy, chain_store, head_hash)?,
+            None => Blockchain::init(env, network_info, policy, chain_store)?
         })
     }
 
-    fn load(env: Environment, network_id: NetworkId, chain_store: Arc<ChainStore>, head_hash: Blake2bHash) -> Result<Self, BlockchainError> {
+    fn load(env: Environment, network_info: &'static NetworkInfo, policy: AlbatrossPolicy, chain_store: Arc<ChainStore>, head_hash: Blake2bHash) -> Result<Self, BlockchainError> {
         // Check that the correct genesis block is stored.
-        let network_info = NetworkInfo::from_network_id(network_id);
         let genesis_info = chain_store.get_chain_info(network_info.genesis_hash(), false, None);
         if !genesis_info.map(|i| i.on_main_chain).unwrap_or(false) {
             return Err(BlockchainError::InvalidGenesisBlock)
@@ -181,14 +193,14 @@ impl Blockchain {
             .ok_or(BlockchainError::FailedLoadingMainChain)?;
 
         // Check that chain/accounts state is consistent.
-        let accounts = Accounts::new(env.clone());
+        let accounts = Accounts::with_policy(env.clone(), policy);
         if main_chain.head.state_root() != &accounts.hash(None) {
             return Err(BlockchainError::InconsistentState);
         }
 
         // Load macro chain from store.
         let macro_chain_info = chain_store
-            .get_chain_info_at(policy::last_macro_block(main_chain.head.block_number()), true, None)
+            .get_chain_info_at(policy.last_macro_block(main_chain.head.block_number()), true, None)
             .ok_or(BlockchainError::FailedLoadingMainChain)?;
         let macro_head = match macro_chain_info.head {
             Block::Macro(macro_head) => macro_head,
@@ -197,16 +209,15 @@ impl Blockchain {
         let macro_head_hash = macro_head.hash();
 
         // Initialize TransactionCache.
-        let mut transaction_cache = TransactionCache::new();
-        let blocks = chain_store.get_blocks_backward(&head_hash, transaction_cache.missing_blocks() - 1, true, None);
-        for block in blocks.iter().rev() {
-            transaction_cache.push_block(block);
-        }
-        transaction_cache.push_block(&main_chain.head);
-        assert_eq!(transaction_cache.missing_blocks(), policy::TRANSACTION_VALIDITY_WINDOW_ALBATROSS.saturating_sub(main_chain.head.block_number() + 1));
+        let mut transaction_cache = TransactionCache::new(policy);
+        Self::prepend_to_transaction_cache(&chain_store, &mut transaction_cache, head_hash.clone(), None);
+        assert_eq!(transaction_cache.missing_blocks(), policy.transaction_validity_window.saturating_sub(main_chain.head.block_number() + 1));
 
         // Initialize SlashRegistry.
-        let slash_registry = SlashRegistry::new(env.clone(), Arc::clone(&chain_store));
+        let slash_registry = SlashRegistry::new(env.clone(), Arc::clone(&chain_store), policy);
+
+        #[cfg(feature = "transaction-store")]
+        let transaction_store = TransactionStore::new(env.clone());
 
         // Current slots and validators
         let current_slots = Self::slots_from_block(&macro_head);
@@ -221,9 +232,12 @@ impl Blockchain {
 
         Ok(Blockchain {
             env,
-            network_id,
+            network_id: network_info.network_id(),
+            network_info,
+            policy,
             //network_time,
             notifier: RwLock::new(Notifier::new()),
+            fork_notifier: RwLock::new(Notifier::new()),
             chain_store,
             state: RwLock::new(BlockchainState {
                 accounts,
@@ -237,22 +251,25 @@ impl Blockchain {
                 previous_slots: Some(last_slots),
             }),
             push_lock: Mutex::new(()),
+            signed_headers: Mutex::new(SignedHeaderCache::default()),
 
             #[cfg(feature = "metrics")]
-            metrics: BlockchainMetrics::default()
+            metrics: BlockchainMetrics::default(),
+
+            #[cfg(feature = "transaction-store")]
+            transaction_store,
         })
     }
 
-    fn init(env: Environment, network_id: NetworkId, chain_store: Arc<ChainStore>) -> Result<Self, BlockchainError> {
+    fn init(env: Environment, network_info: &'static NetworkInfo, policy: AlbatrossPolicy, chain_store: Arc<ChainStore>) -> Result<Self, BlockchainError> {
         // Initialize chain & accounts with genesis block.
-        let network_info = NetworkInfo::from_network_id(network_id);
         let genesis_block = network_info.genesis_block::<Block>();
         let genesis_macro_block = (match genesis_block { Block::Macro(ref macro_block) => Some(macro_block), _ => None }).unwrap();
         let main_chain = ChainInfo::initial(genesis_block.clone());
         let head_hash = network_info.genesis_hash().clone();
 
         // Initialize accounts.
-        let accounts = Accounts::new(env.clone());
+        let accounts = Accounts::with_policy(env.clone(), policy);
         let mut txn = WriteTransaction::new(&env);
         accounts.init(&mut txn, network_info.genesis_accounts());
 
@@ -264,11 +281,15 @@ impl Blockchain {
         chain_store.set_head(&mut txn, &head_hash);
         txn.commit();
 
-        // Initialize empty TransactionCache.
-        let transaction_cache = TransactionCache::new();
+        // Initialize TransactionCache with the genesis block, as it is when loading the chain.
+        let mut transaction_cache = TransactionCache::new(policy);
+        transaction_cache.push_block(&genesis_block);
 
         // Initialize SlashRegistry.
-        let slash_registry = SlashRegistry::new(env.clone(), Arc::clone(&chain_store));
+        let slash_registry = SlashRegistry::new(env.clone(), Arc::clone(&chain_store), policy);
+
+        #[cfg(feature = "transaction-store")]
+        let transaction_store = TransactionStore::new(env.clone());
 
         // current slots and validators
         let current_slots = Self::slots_from_block(&genesis_macro_block);
@@ -276,9 +297,12 @@ impl Blockchain {
 
         Ok(Blockchain {
             env,
-            network_id,
+            network_id: network_info.network_id(),
+            network_info,
+            policy,
             //network_time,
             notifier: RwLock::new(Notifier::new()),
+            fork_notifier: RwLock::new(Notifier::new()),
             chain_store,
             state: RwLock::new(BlockchainState {
                 accounts,
@@ -292,19 +316,52 @@ impl Blockchain {
                 previous_slots: Some(last_slots),
             }),
             push_lock: Mutex::new(()),
+            signed_headers: Mutex::new(SignedHeaderCache::default()),
 
             #[cfg(feature = "metrics")]
-            metrics: BlockchainMetrics::default()
+            metrics: BlockchainMetrics::default(),
+
+            #[cfg(feature = "transaction-store")]
+            transaction_store,
         })
     }
 
+    /// Prepends the block with the given hash and its predecessors to the TransactionCache until
+    /// it covers the transaction validity window. Epochs that were received through macro block
+    /// sync don't have micro blocks and are restored from their stored transaction hashes.
+    fn prepend_to_transaction_cache(chain_store: &ChainStore, transaction_cache: &mut TransactionCache, mut hash: Blake2bHash, txn_option: Option<&Transaction>) {
+        while transaction_cache.missing_blocks() > 0 {
+            let block = match chain_store.get_block(&hash, true, txn_option) {
+                Some(block) => block,
+                None => break,
+            };
+
+            let epoch_hashes = match block {
+                Block::Macro(ref macro_block) => chain_store.get_epoch_transaction_hashes(macro_block.header.block_number, txn_option),
+                Block::Micro(_) => None,
+            };
+
+            hash = match epoch_hashes {
+                Some(epoch_hashes) => {
+                    let macro_block = block.unwrap_macro_ref();
+                    transaction_cache.prepend_epoch(macro_block, epoch_hashes.hashes);
+                    macro_block.header.parent_macro_hash.clone()
+                },
+                None => {
+                    transaction_cache.prepend_block(&block);
+                    block.parent_hash().clone()
+                },
+            };
+        }
+    }
+
     fn slots_from_block(block: &MacroBlock) -> Slots {
         block.clone().try_into().unwrap()
     }
 
     pub fn get_slots_for_epoch(&self, epoch: u32) -> Option<Slots> {
         let state = self.state.read();
-        let current_epoch = policy::epoch_at(state.main_chain.head.block_number());
+        let current_epoch = self.policy.epoch_at(state.main_chain.head.block_number());
 
         let slots = if epoch == current_epoch {
             state.current_slots.as_ref()?.clone()
@@ -313,7 +370,7 @@ impl Blockchain {
             state.previous_slots.as_ref()?.clone()
         }
         else {
-            let macro_block = self.get_block_at(policy::macro_block_of(epoch), true)?
+            let macro_block = self.get_block_at(self.policy.macro_block_of(epoch), true)?
                 .unwrap_macro();
             macro_block.try_into().unwrap()
         };
@@ -323,7 +380,7 @@ impl Blockchain {
 
     pub fn get_validators_for_epoch(&self, epoch: u32) -> Option<ValidatorSlots> {
         let state = self.state.read();
-        let current_epoch = policy::epoch_at(state.main_chain.head.block_number());
+        let current_epoch = self.policy.epoch_at(state.main_chain.head.block_number());
 
         let validators = if epoch == current_epoch {
             state.current_validators()?.clone()
@@ -332,7 +389,7 @@ impl Blockchain {
             state.last_validators()?.clone()
         }
         else {
-            self.get_block_at(policy::macro_block_of(epoch), true)?
+            self.get_block_at(self.policy.macro_block_of(epoch), true)?
                 .unwrap_macro().header.validators.into()
         };
 
@@ -362,7 +419,7 @@ impl Blockchain {
         }
 
         // Check if a view change occurred - if so, validate the proof
-        let view_number = if policy::is_macro_block_at(header.block_number() - 1) {
+        let view_number = if self.policy.is_macro_block_at(header.block_number() - 1) {
             0 // Reset view number in new epoch
         } else {
             prev_info.head.view_number()
@@ -383,7 +440,7 @@ impl Blockchain {
                         block_number: header.block_number(),
                         new_view_number: header.view_number(),
                     };
-                    if let Err(e) = view_change_proof.verify(&view_change, &self.current_validators(), policy::TWO_THIRD_SLOTS) {
+                    if let Err(e) = view_change_proof.verify(&view_change, &self.current_validators(), self.policy.two_third_slots()) {
                         warn!("Rejecting block - bad view change proof: {:?}", e);
                         return Err(PushError::InvalidBlock(BlockError::InvalidJustification));
                     }
@@ -408,7 +465,13 @@ impl Blockchain {
                 return Err(PushError::InvalidSuccessor);
             }
 
-            let transactions_root = self.get_transactions_root(policy::epoch_at(header.block_number), txn_opt)
+            // Check that the validators have the number of slots of the policy
+            if header.validators.total_slots() != self.policy.slots {
+                warn!("Rejecting block - wrong number of validator slots ({})", header.validators.total_slots());
+                return Err(PushError::InvalidBlock(BlockError::InvalidValidators));
+            }
+
+            let transactions_root = self.get_transactions_root(self.policy.epoch_at(header.block_number), txn_opt)
                 .ok_or(PushError::BlockchainError(BlockchainError::FailedLoadingMainChain))?;
             if header.transactions_root != transactions_root {
                 warn!("Rejecting block - wrong transactions root");
@@ -438,7 +501,7 @@ impl Blockchain {
         }
 
         // Check (sort of) intrinsic block invariants.
-        if let Err(e) = block.verify(self.network_id) {
+        if let Err(e) = block.verify(self.network_id, &self.policy) {
             warn!("Rejecting block - verification failed ({:?})", e);
             return Err(PushError::InvalidBlock(e));
         }
@@ -486,6 +549,13 @@ impl Blockchain {
                 return Err(PushError::InvalidBlock(BlockError::InvalidJustification));
             }
 
+            // The justification is valid, so any other block signed for this slot proves that
+            // the slot owner equivocated.
+            if let Some(fork_proof) = self.detect_fork(micro_block, Some(&read_txn)) {
+                warn!("Detected fork at block #{}.{}", fork_proof.block_number(), fork_proof.view_number());
+                self.fork_notifier.read().notify(ForkEvent::Detected(fork_proof));
+            }
+
             // Validate slash inherents
             for fork_proof in &micro_block.extrinsics.as_ref().unwrap().fork_proofs {
                 // NOTE: if this returns None, that means that at least the previous block doesn't exist, so that fork proof is invalid anyway.
@@ -508,7 +578,7 @@ impl Blockchain {
                 },
                 Some(ref justification) => {
                     if let Err(e) = justification.verify(
-                        macro_block.hash(), &self.current_validators(), policy::TWO_THIRD_SLOTS
+                        macro_block.hash(), &self.current_validators(), self.policy.two_third_slots()
                     ) {
                         warn!("Rejecting block - macro block with bad justification: {}", e);
                         return Err(PushError::InvalidBlock(BlockError::InvalidJustification));
@@ -572,7 +642,7 @@ impl Blockchain {
         // Get the slashed set used to finalize the previous epoch before garbage collecting it below.
         let mut slashed_set: Option<BitSet> = None;
         if chain_info.head.ty() == BlockType::Macro {
-            slashed_set = Some(state.reward_registry.slashed_set(policy::epoch_at(chain_info.head.block_number()) - 1, Some(&txn)));
+            slashed_set = Some(state.reward_registry.slashed_set(self.policy.epoch_at(chain_info.head.block_number()) - 1, Some(&txn)));
         }
 
         if let Err(e) = state.reward_registry.commit_block(&mut txn, &chain_info.head, prev_info.head.next_view_number()) {
@@ -625,6 +695,9 @@ impl Blockchain {
         let mut state = self.state.write();
         state.transaction_cache.push_block(&chain_info.head);
 
+        #[cfg(feature = "transaction-store")]
+        self.transaction_store.put(&chain_info.head, &mut txn);
+
         if let Block::Macro(ref macro_block) = chain_info.head {
             state.macro_head = macro_block.clone();
             state.macro_head_hash = block_hash.clone();
@@ -638,6 +711,9 @@ impl Blockchain {
 
         let block_type = chain_info.head.ty();
 
+        #[cfg(feature = "metrics")]
+        self.metrics.note_view_changes(chain_info.head.view_number().saturating_sub(prev_info.head.next_view_number()) as usize);
+
         state.main_chain = chain_info;
         state.head_hash = block_hash.clone();
         txn.commit();
@@ -719,15 +795,12 @@ impl Blockchain {
         // Fetch missing blocks for TransactionCache.
         assert!(cache_txn.is_empty() || cache_txn.head_hash() == ancestor.0);
         let start_hash = if cache_txn.is_empty() {
-            ancestor.1.main_chain_successor.unwrap()
+            ancestor.0.clone()
         } else {
-            cache_txn.tail_hash()
+            cache_txn.tail_prev_hash()
         };
-        let blocks = self.chain_store.get_blocks_backward(&start_hash, cache_txn.missing_blocks(), true, Some(&read_txn));
-        for block in blocks.iter() {
-            cache_txn.prepend_block(block)
-        }
-        assert_eq!(cache_txn.missing_blocks(), policy::TRANSACTION_VALIDITY_WINDOW_ALBATROSS.saturating_sub(ancestor.1.head.block_number() + 1));
+        Self::prepend_to_transaction_cache(&self.chain_store, &mut cache_txn, start_hash, Some(&read_txn));
+        assert_eq!(cache_txn.missing_blocks(), self.policy.transaction_validity_window.saturating_sub(ancestor.1.head.block_number() + 1));
 
         // Check each fork block against TransactionCache & commit to AccountsTree and SlashRegistry.
         let mut prev_view_number = ancestor.1.head.next_view_number();
@@ -777,6 +850,9 @@ impl Blockchain {
             reverted_block.1.on_main_chain = false;
             reverted_block.1.main_chain_successor = None;
             self.chain_store.put_chain_info(&mut write_txn, &reverted_block.0, &reverted_block.1, false);
+
+            #[cfg(feature = "transaction-store")]
+            self.transaction_store.remove(&reverted_block.1.head, &mut write_txn);
         }
 
         // Update the mainChainSuccessor of the common ancestor block.
@@ -797,6 +873,9 @@ impl Blockchain {
 
             // Include the body of the new block (at position 0).
             self.chain_store.put_chain_info(&mut write_txn, &fork_block.0, &fork_block.1, i == 0);
+
+            #[cfg(feature = "transaction-store")]
+            self.transaction_store.put(&fork_block.1.head, &mut write_txn);
         }
 
         // Commit transaction & update head.
@@ -868,7 +947,7 @@ impl Blockchain {
         Ok(())
     }
 
-    fn revert_accounts(&self, accounts: &Accounts, txn: &mut WriteTransaction, micro_block: &MicroBlock, prev_view_number: u32) -> Result<(), PushError> {
+    pub(crate) fn revert_accounts(&self, accounts: &Accounts, txn: &mut WriteTransaction, micro_block: &MicroBlock, prev_view_number: u32) -> Result<(), PushError> {
         assert_eq!(micro_block.header.state_root, accounts.hash(Some(&txn)),
                    "Failed to revert - inconsistent state");
 
@@ -913,7 +992,7 @@ impl Blockchain {
         }
 
         // Check (sort of) intrinsic block invariants.
-        if let Err(e) = block.verify(self.network_id) {
+        if let Err(e) = block.verify(self.network_id, &self.policy) {
             warn!("Rejecting block - verification failed ({:?})", e);
             return Err(PushError::InvalidBlock(e));
         }
@@ -926,11 +1005,17 @@ impl Blockchain {
             })?;
 
         // Check the block number
-        if policy::macro_block_after(prev_info.head.block_number()) != macro_block.header.block_number {
+        if self.policy.macro_block_after(prev_info.head.block_number()) != macro_block.header.block_number {
             warn!("Rejecting block - wrong block number ({:?})", macro_block.header.block_number);
             return Err(PushError::InvalidSuccessor);
         }
 
+        // Check that the validators have the number of slots of the policy
+        if macro_block.header.validators.total_slots() != self.policy.slots {
+            warn!("Rejecting block - wrong number of validator slots ({})", macro_block.header.validators.total_slots());
+            return Err(PushError::InvalidBlock(BlockError::InvalidValidators));
+        }
+
         // Check transactions root
         let hashes: Vec<Blake2bHash> = transactions.iter().map(|tx| tx.hash()).collect();
         let transactions_root = merkle::compute_root_from_hashes::<Blake2bHash>(&hashes);
@@ -946,7 +1031,7 @@ impl Blockchain {
                 return Err(PushError::InvalidBlock(BlockError::NoJustification));
             },
             Some(ref justification) => {
-                if let Err(_) = justification.verify(macro_block.hash(),&self.current_validators(), policy::TWO_THIRD_SLOTS) {
+                if let Err(_) = justification.verify(macro_block.hash(),&self.current_validators(), self.policy.two_third_slots()) {
                     warn!("Rejecting block - macro block with bad justification");
                     return Err(PushError::InvalidBlock(BlockError::NoJustification));
                 }
@@ -1036,13 +1121,22 @@ impl Blockchain {
         self.chain_store.put_chain_info(&mut txn, &chain_info.head.parent_hash(), &prev_info, false);
         self.chain_store.set_head(&mut txn, &block_hash);
 
+        // The micro blocks of this epoch are not available, thus we store the transaction hashes
+        // (which have been verified against the transactions root) to provide replay protection.
+        let epoch_hashes = EpochTransactionHashes {
+            hashes: transactions.iter().map(|tx| tx.hash()).collect(),
+        };
+        self.chain_store.put_epoch_transaction_hashes(&mut txn, block_number, &epoch_hashes);
+
         // Acquire write lock & commit changes.
         let mut state = self.state.write();
-        // FIXME: Macro block sync does not preserve transaction replay protection right now.
-        // But this is not an issue in the UTXO model.
-        //state.transaction_cache.push_block(&chain_info.head);
 
         if let Block::Macro(ref macro_block) = chain_info.head {
+            state.transaction_cache.push_epoch(macro_block, epoch_hashes.hashes);
+
+            #[cfg(feature = "transaction-store")]
+            self.transaction_store.put_epoch(macro_block, transactions, &mut txn);
+
             state.macro_head = macro_block.clone();
             state.macro_head_hash = block_hash.clone();
 
@@ -1094,7 +1188,7 @@ impl Blockchain {
     }
 
     pub fn get_epoch_transactions(&self, epoch: u32, txn_option: Option<&Transaction>) -> Option<TransactionsIterator> {
-        let first_block = policy::first_block_of(epoch);
+        let first_block = self.policy.first_block_of(epoch);
         let first_block = self.chain_store.get_block_at(first_block, true, txn_option)
             .or_else(|| {
                 debug!("get_block_at didn't return first block of epoch: block_height={}", first_block);
@@ -1107,10 +1201,10 @@ impl Blockchain {
             .transactions.into_iter();
 
         // Excludes current block and macro block.
-        let blocks = self.chain_store.get_blocks(&first_hash, policy::EPOCH_LENGTH - 2, true, Direction::Forward, txn_option);
+        let blocks = self.chain_store.get_blocks(&first_hash, self.policy.epoch_length - 2, true, Direction::Forward, txn_option);
         // We need to make sure that we have all micro blocks.
-        if blocks.len() as u32 != policy::EPOCH_LENGTH - 2 {
-            debug!("Exptected {} blocks, but get_blocks returned {}", policy::EPOCH_LENGTH - 2, blocks.len());
+        if blocks.len() as u32 != self.policy.epoch_length - 2 {
+            debug!("Exptected {} blocks, but get_blocks returned {}", self.policy.epoch_length - 2, blocks.len());
             for block in &blocks {
                 debug!("Returned block {} - {}", block.block_number(), block.hash());
             }
@@ -1170,10 +1264,10 @@ impl Blockchain {
     }
 
     pub fn next_slots(&self, seed: &VrfSeed, txn_option: Option<&Transaction>) -> Slots {
-        let validator_registry = NetworkInfo::from_network_id(self.network_id).validator_registry_address().expect("No ValidatorRegistry");
+        let validator_registry = self.network_info.validator_registry_address().expect("No ValidatorRegistry");
         let staking_account = self.state.read().accounts().get(validator_registry, txn_option);
         if let Account::Staking(ref staking_contract) = staking_account {
-            return staking_contract.select_validators(seed);
+            return staking_contract.select_validators(seed, &self.policy);
         }
         panic!("Account at validator registry address is not the stacking contract!");
     }
@@ -1189,7 +1283,7 @@ impl Blockchain {
 
         };
 
-        if policy::is_macro_block_at(last_block_number + 1) {
+        if self.policy.is_macro_block_at(last_block_number + 1) {
             BlockType::Macro
         } else {
             BlockType::Micro
@@ -1211,16 +1305,16 @@ impl Blockchain {
 
         // Gets slots collection from either the cached ones, or from the macro block.
         let slots_owned;
-        let slots = if policy::epoch_at(state.block_number()) == policy::epoch_at(block_number) {
+        let slots = if self.policy.epoch_at(state.block_number()) == self.policy.epoch_at(block_number) {
             state.current_slots.as_ref().expect("Missing current epoch's slots")
         }
-        else if policy::epoch_at(state.block_number()) == policy::epoch_at(block_number) + 1 {
+        else if self.policy.epoch_at(state.block_number()) == self.policy.epoch_at(block_number) + 1 {
             state.previous_slots.as_ref()
                 .unwrap_or_else(|| panic!("Missing previous epoch's slots for block {}.{}", block_number, view_number))
         }
         else {
             let macro_block = self.chain_store
-                .get_block_at(policy::macro_block_before(block_number), true, Some(&txn))?
+                .get_block_at(self.policy.macro_block_before(block_number), true, Some(&txn))?
                 .unwrap_macro();
 
             // Get slots of epoch
@@ -1251,7 +1345,7 @@ impl Blockchain {
     pub fn inherent_from_fork_proof(&self, fork_proof: &ForkProof, txn_option: Option<&Transaction>) -> Inherent {
         let (producer, _) = self.get_slot_at(fork_proof.header1.block_number, fork_proof.header1.view_number, txn_option)
             .unwrap();
-        let validator_registry = NetworkInfo::from_network_id(self.network_id).validator_registry_address().expect("No ValidatorRegistry");
+        let validator_registry = self.network_info.validator_registry_address().expect("No ValidatorRegistry");
         Inherent {
             ty: InherentType::Slash,
             target: validator_registry.clone(),
@@ -1262,7 +1356,7 @@ impl Blockchain {
 
     /// Expects a *verified* proof!
     pub fn inherents_from_view_changes(&self, view_changes: &ViewChanges, txn_option: Option<&Transaction>) -> Vec<Inherent> {
-        let validator_registry = NetworkInfo::from_network_id(self.network_id).validator_registry_address().expect("No ValidatorRegistry");
+        let validator_registry = self.network_info.validator_registry_address().expect("No ValidatorRegistry");
 
         (view_changes.first_view_number .. view_changes.last_view_number).map(|view_number| {
             let (producer, _) = self.get_slot_at(view_changes.block_number, view_number, txn_option)
@@ -1280,7 +1374,7 @@ impl Blockchain {
 
     /// Expects a *verified* proof!
     pub fn inherents_from_slashed_set(&self, slashed_set: &BitSet, slots: &Slots) -> Vec<Inherent> {
-        let validator_registry = NetworkInfo::from_network_id(self.network_id).validator_registry_address()
+        let validator_registry = self.network_info.validator_registry_address()
             .expect("No ValidatorRegistry");
 
         slashed_set.iter().map(|slot_number| {
@@ -1314,7 +1408,7 @@ impl Blockchain {
 
     pub fn finalize_last_epoch(&self, state: &BlockchainState, macro_header: &MacroHeader) -> Vec<Inherent> {
         // It might be that we don't have any micro blocks, thus we need to look at the next macro block.
-        let epoch = policy::epoch_at(macro_header.block_number) - 1;
+        let epoch = self.policy.epoch_at(macro_header.block_number) - 1;
 
         // Special case for first epoch: Epoch 0 is finalized by definition.
         if epoch == 0 {
@@ -1336,7 +1430,7 @@ impl Blockchain {
 
         // Number of slots that are eligible for a reward. That is the total number minus all
         // slashed slots
-        let num_eligible = (policy::SLOTS as u64) - (slashed_set.len() as u64);
+        let num_eligible = (self.policy.slots as u64) - (slashed_set.len() as u64);
 
         // Distribute reward between all slots
         let slot_reward = reward_pot / num_eligible;
@@ -1347,14 +1441,10 @@ impl Blockchain {
         // Peekable iterator to collect slashed slots for stake
         let mut slashed_set_iter = slashed_set.iter().peekable();
 
-        // All accepted inherents.
-        let mut inherents = Vec::new();
-        // Remember the number of eligible slots a stake had (that was able to accept the inherent)
-        let mut num_eligible_slots_for_accepted_inherent = Vec::new();
-        // Total number of slots that were able to accept the inherent
-        let mut total_accepting_slots = 0;
+        // Reward and number of eligible slots of each stake slot band
+        let mut slot_rewards = Vec::new();
 
-        // Compute inherents
+        // Compute rewards
         for stake_slot in stake_slots.iter() {
             // The interval of slot numbers for the current slot band is
             // [first_slot_number, last_slot_number). So it actually doesn't include
@@ -1382,10 +1472,63 @@ impl Blockchain {
             let reward = slot_reward.checked_mul(num_eligible_slots as u64)
                 .expect("Overflow in reward");
 
+            slot_rewards.push((stake_slot.staker_address().clone(), reward, num_eligible_slots));
+        }
+
+        // Split the rewards of the validators with their delegators.
+        let validator_registry = self.network_info
+            .validator_registry_address()
+            .expect("No ValidatorRegistry");
+        let delegator_rewards = match state.accounts.get(validator_registry, None) {
+            Account::Staking(contract) => {
+                let validator_rewards: Vec<(Address, Coin)> = slot_rewards.iter()
+                    .map(|(validator_address, reward, _)| (validator_address.clone(), *reward))
+                    .collect();
+                contract.delegator_rewards(&validator_rewards)
+            },
+            _ => panic!("Validator registry has a wrong account type"),
+        };
+
+        // All accepted inherents of validators.
+        let mut inherents = Vec::new();
+        // All accepted inherents of delegators.
+        let mut delegator_inherents = Vec::new();
+        // Remember the number of eligible slots a stake had (that was able to accept the inherent)
+        let mut num_eligible_slots_for_accepted_inherent = Vec::new();
+        // Total number of slots that were able to accept the inherent
+        let mut total_accepting_slots = 0;
+
+        // Compute inherents
+        for (stake_slot, ((_, reward, num_eligible_slots), delegator_rewards)) in stake_slots.iter().zip(slot_rewards.into_iter().zip(delegator_rewards)) {
+            let mut validator_reward = reward;
+
+            for (delegator_address, delegator_reward) in delegator_rewards {
+                if delegator_reward.is_zero() {
+                    continue;
+                }
+                validator_reward -= delegator_reward;
+
+                let inherent = Inherent {
+                    ty: InherentType::Reward,
+                    target: delegator_address,
+                    value: delegator_reward,
+                    data: vec![],
+                };
+
+                // Test whether account will accept inherent.
+                let account = state.accounts.get(&inherent.target, None);
+                if account.check_inherent(&inherent, macro_header.block_number).is_err() {
+                    debug!("{} can't accept delegator reward {}", inherent.target, inherent.value);
+                    remainder += delegator_reward;
+                } else {
+                    delegator_inherents.push(inherent);
+                }
+            }
+
             let inherent = Inherent {
                 ty: InherentType::Reward,
                 target: stake_slot.reward_address().clone(),
-                value: reward,
+                value: validator_reward,
                 data: vec![],
             };
 
@@ -1393,7 +1536,7 @@ impl Blockchain {
             let account = state.accounts.get(&inherent.target, None);
             if account.check_inherent(&inherent, macro_header.block_number).is_err() {
                 debug!("{} can't accept epoch reward {}", inherent.target, inherent.value);
-                remainder += reward;
+                remainder += validator_reward;
             } else {
                 num_eligible_slots_for_accepted_inherent.push(num_eligible_slots);
                 total_accepting_slots += num_eligible_slots;
@@ -1417,10 +1560,9 @@ impl Blockchain {
             remainder -= Coin::from_u64_unchecked(1);
         }
 
+        inherents.append(&mut delegator_inherents);
+
         // Push finalize epoch inherent for automatically retiring inactive/malicious validators.
-        let validator_registry = NetworkInfo::from_network_id(self.network_id)
-            .validator_registry_address()
-            .expect("No ValidatorRegistry");
         inherents.push(Inherent {
             ty: InherentType::FinalizeEpoch,
             target: validator_registry.clone(),
@@ -1450,7 +1592,7 @@ impl Blockchain {
         }
 
         let mut step = 2;
-        let mut height = policy::last_macro_block(self.height()).saturating_sub((10 + step) * policy::EPOCH_LENGTH);
+        let mut height = self.policy.last_macro_block(self.height()).saturating_sub((10 + step) * self.policy.epoch_length);
         let mut opt_block = self.chain_store.get_block_at(height, false, None);
         while let Some(block) = opt_block {
             assert_eq!(block.ty(), BlockType::Macro);
@@ -1462,7 +1604,7 @@ impl Blockchain {
             }
 
             step *= 2;
-            height = height.saturating_sub(step * policy::EPOCH_LENGTH);
+            height = height.saturating_sub(step * self.policy.epoch_length);
             // 0 or underflow means we need to end the loop
             if height == 0 {
                 break;
@@ -1472,7 +1614,7 @@ impl Blockchain {
         }
 
         // Push the genesis block hash.
-        let genesis_hash = NetworkInfo::from_network_id(self.network_id).genesis_hash();
+        let genesis_hash = self.network_info.genesis_hash();
         if locators.is_empty() || locators.last().unwrap() != genesis_hash {
             // Respect max count, make space for genesis hash if necessary
             if locators.len() >= max_count {
@@ -1510,6 +1652,10 @@ impl AbstractBlockchain for Blockchain {
         self.network_id
     }
 
+    fn network_info(&self) -> &'static NetworkInfo {
+        self.network_info
+    }
+
     fn head_block(&self) -> MappedRwLockReadGuard<Self::Block> {
         self.head()
     }
@@ -1569,7 +1715,7 @@ impl AbstractBlockchain for Blockchain {
         }
 
         // Push the genesis block hash.
-        let genesis_hash = NetworkInfo::from_network_id(self.network_id).genesis_hash();
+        let genesis_hash = self.network_info.genesis_hash();
         if locators.is_empty() || locators.last().unwrap() != genesis_hash {
             // Respect max count, make space for genesis hash if necessary
             if locators.len() >= max_count {
@@ -1593,19 +1739,19 @@ impl AbstractBlockchain for Blockchain {
         self.contains(hash, include_forks)
     }
 
-    #[allow(unused_variables)]
     fn get_accounts_proof(&self, block_hash: &Blake2bHash, addresses: &[Address]) -> Option<AccountsProof<Account>> {
-        unimplemented!()
+        self.get_accounts_proof(block_hash, addresses)
     }
 
-    #[allow(unused_variables)]
     fn get_transactions_proof(&self, block_hash: &Blake2bHash, addresses: &HashSet<Address>) -> Option<TransactionsProof> {
-        unimplemented!()
+        self.get_transactions_proof(block_hash, addresses)
     }
 
-    #[allow(unused_variables)]
     fn get_transaction_receipts_by_address(&self, address: &Address, sender_limit: usize, recipient_limit: usize) -> Vec<TransactionReceipt> {
-        unimplemented!()
+        #[cfg(feature = "transaction-store")]
+        return self.get_transaction_receipts_by_address(address, sender_limit, recipient_limit);
+        #[cfg(not(feature = "transaction-store"))]
+        Vec::new()
     }
 
     fn register_listener<T: Listener<BlockchainEvent> + 'static>(&self, listener: T) -> ListenerHandle {
@@ -1624,6 +1770,14 @@ impl AbstractBlockchain for Blockchain {
         self.state.read().transaction_cache.contains(tx_hash)
     }
 
+    fn transaction_validity_window(&self) -> u32 {
+        self.policy.transaction_validity_window
+    }
+
+    fn albatross_policy(&self) -> Option<AlbatrossPolicy> {
+        Some(self.policy)
+    }
+
     #[allow(unused_variables)]
     fn head_hash_from_store(&self, txn: &ReadTransaction) -> Option<Blake2bHash> {
         unimplemented!()
@@ -5,9 +5,10 @@ use database::{Database, DatabaseFlags, Environment, ReadTransaction, Transactio
 use database::cursor::ReadCursor;
 use database::cursor::WriteCursor;
 use hash::Blake2bHash;
-use primitives::policy;
+use primitives::policy::AlbatrossPolicy;
 
 use crate::chain_info::ChainInfo;
+use crate::transaction_cache::EpochTransactionHashes;
 
 #[derive(Debug)]
 pub struct ChainStore {
@@ -16,6 +17,8 @@ pub struct ChainStore {
     block_db: Database,
     height_idx: Database,
     receipt_db: Database,
+    epoch_transactions_db: Database,
+    policy: AlbatrossPolicy,
 }
 
 impl ChainStore {
@@ -23,17 +26,20 @@ impl ChainStore {
     const BLOCK_DB_NAME: &'static str = "Block";
     const HEIGHT_IDX_NAME: &'static str = "HeightIdx";
     const RECEIPT_DB_NAME: &'static str = "Receipts";
+    const EPOCH_TRANSACTIONS_DB_NAME: &'static str = "EpochTransactionHashes";
 
     const HEAD_KEY: &'static str = "head";
 
-    pub fn new(env: Environment) -> Self {
+    pub fn new(env: Environment, policy: AlbatrossPolicy) -> Self {
         let chain_db = env.open_database(Self::CHAIN_DB_NAME.to_string());
         let block_db = env.open_database(Self::BLOCK_DB_NAME.to_string());
         let height_idx = env.open_database_with_flags(Self::HEIGHT_IDX_NAME.to_string(),
                                                       DatabaseFlags::DUPLICATE_KEYS | DatabaseFlags::DUP_FIXED_SIZE_VALUES);
         let receipt_db = env.open_database_with_flags(Self::RECEIPT_DB_NAME.to_string(),
                                                       DatabaseFlags::UINT_KEYS);
-        ChainStore { env, chain_db, block_db, height_idx, receipt_db }
+        let epoch_transactions_db = env.open_database_with_flags(Self::EPOCH_TRANSACTIONS_DB_NAME.to_string(),
+                                                      DatabaseFlags::UINT_KEYS);
+        ChainStore { env, chain_db, block_db, height_idx, receipt_db, epoch_transactions_db, policy }
     }
 
     pub fn get_head(&self, txn_option: Option<&Transaction>) -> Option<Blake2bHash> {
@@ -138,6 +144,44 @@ impl ChainStore {
         Some(chain_info)
     }
 
+    /// Returns the chain infos of all known blocks at the given height, including forks.
+    pub fn get_chain_infos_at(&self, block_height: u32, include_body: bool, txn_option: Option<&Transaction>) -> Vec<ChainInfo> {
+        let read_txn: ReadTransaction;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = ReadTransaction::new(&self.env);
+                &read_txn
+            }
+        };
+
+        let mut chain_infos = Vec::new();
+        let mut cursor = txn.cursor(&self.height_idx);
+        let mut block_hash = match cursor.seek_key::<u32, Blake2bHash>(&block_height) {
+            Some(hash) => hash,
+            None => return chain_infos
+        };
+
+        loop {
+            let mut chain_info: ChainInfo = txn.get(&self.chain_db, &block_hash)
+                .expect("Corrupted store: ChainInfo referenced from index not found");
+
+            if include_body {
+                if let Some(block) = txn.get(&self.block_db, &block_hash) {
+                    chain_info.head = block;
+                } else {
+                    warn!("Block body requested but not present");
+                }
+            }
+            chain_infos.push(chain_info);
+
+            block_hash = match cursor.next_duplicate::<u32, Blake2bHash>() {
+                Some((_, hash)) => hash,
+                None => return chain_infos
+            };
+        }
+    }
+
     pub fn get_block(&self, hash: &Blake2bHash, include_body: bool, txn_option: Option<&Transaction>) -> Option<Block> {
         let read_txn: ReadTransaction;
         let txn = match txn_option {
@@ -280,11 +324,11 @@ impl ChainStore {
             None => return Some(blocks),
         };
 
-        let mut next_macro_block = policy::macro_block_after(block.header.block_number);
+        let mut next_macro_block = self.policy.macro_block_after(block.header.block_number);
         while (blocks.len() as u32) < count {
             let block_opt = self.get_block_at(next_macro_block, include_body, Some(&txn));
             if let Some(Block::Macro(block)) = block_opt {
-                next_macro_block = policy::macro_block_after(block.header.block_number);
+                next_macro_block = self.policy.macro_block_after(block.header.block_number);
                 blocks.push(Block::Macro(block));
             } else {
                 break;
@@ -328,4 +372,36 @@ impl ChainStore {
             pos = cursor.next();
         }
     }
+
+    /// Stores the transaction hashes of an epoch that was received through macro block sync.
+    /// Hashes of epochs that have left the transaction validity window are removed.
+    pub fn put_epoch_transaction_hashes(&self, txn: &mut WriteTransaction, block_number: u32, hashes: &EpochTransactionHashes) {
+        let cutoff = block_number.saturating_sub(self.policy.transaction_validity_window);
+        let mut cursor = txn.write_cursor(&self.epoch_transactions_db);
+        let mut pos: Option<(u32, EpochTransactionHashes)> = cursor.first();
+
+        while let Some((epoch_block_number, _)) = pos {
+            if epoch_block_number > cutoff {
+                break;
+            }
+            cursor.remove();
+            pos = cursor.next();
+        }
+        drop(cursor);
+
+        txn.put_reserve(&self.epoch_transactions_db, &block_number, hashes);
+    }
+
+    pub fn get_epoch_transaction_hashes(&self, block_number: u32, txn_option: Option<&Transaction>) -> Option<EpochTransactionHashes> {
+        let read_txn: ReadTransaction;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = ReadTransaction::new(&self.env);
+                &read_txn
+            }
+        };
+
+        txn.get(&self.epoch_transactions_db, &block_number)
+    }
 }
@@ -0,0 +1,102 @@
+use std::collections::{BTreeMap, HashSet};
+
+use block::{Block, ForkProof, MicroBlock, MicroHeader};
+use bls::bls12_381::CompressedSignature;
+use database::{ReadTransaction, Transaction};
+use hash::{Blake2bHash, Hash};
+use primitives::policy::AlbatrossPolicy;
+
+use crate::Blockchain;
+
+/// Emitted when a slot owner was caught signing two different micro blocks for the same slot.
+#[derive(Clone, Debug)]
+pub enum ForkEvent {
+    Detected(ForkProof),
+}
+
+/// Remembers the first signed micro header seen for each slot, so that equivocations are also
+/// detected if the first block was never stored, e.g. because its body was invalid.
+#[derive(Default)]
+pub struct SignedHeaderCache {
+    headers: BTreeMap<(u32, u32), (MicroHeader, CompressedSignature)>,
+}
+
+impl SignedHeaderCache {
+    fn get(&self, block_number: u32, view_number: u32) -> Option<&(MicroHeader, CompressedSignature)> {
+        self.headers.get(&(block_number, view_number))
+    }
+
+    fn insert(&mut self, header: &MicroHeader, signature: &CompressedSignature) {
+        self.headers.entry((header.block_number, header.view_number))
+            .or_insert_with(|| (header.clone(), signature.clone()));
+    }
+
+    /// Forgets headers that are too old to be used in fork proofs at `block_number`.
+    fn prune(&mut self, block_number: u32, policy: &AlbatrossPolicy) {
+        let cutoff = policy.first_block_of_registry(policy.epoch_at(block_number));
+        self.headers = self.headers.split_off(&(cutoff, 0));
+    }
+}
+
+impl Blockchain {
+    /// Checks whether a different micro block was signed for the slot of the given block and
+    /// returns a proof of the equivocation if so. Both blocks stored in the chain store and
+    /// headers seen earlier are considered.
+    ///
+    /// The justification of `micro_block` must have been verified.
+    pub(crate) fn detect_fork(&self, micro_block: &MicroBlock, txn_option: Option<&Transaction>) -> Option<ForkProof> {
+        let header = &micro_block.header;
+        let signature = &micro_block.justification.signature;
+        let hash: Blake2bHash = header.hash();
+
+        let mut signed_headers = self.signed_headers.lock();
+        let cached = signed_headers.get(header.block_number, header.view_number).cloned();
+        let stored = self.chain_store.get_chain_infos_at(header.block_number, false, txn_option)
+            .into_iter()
+            .filter_map(|chain_info| match chain_info.head {
+                Block::Micro(block) => Some((block.header, block.justification.signature)),
+                Block::Macro(_) => None,
+            });
+
+        let fork_proof = stored.chain(cached)
+            .find(|(other, _)| other.view_number == header.view_number && other.hash::<Blake2bHash>() != hash)
+            .map(|(other, other_signature)| ForkProof {
+                header1: other,
+                header2: header.clone(),
+                justification1: other_signature,
+                justification2: signature.clone(),
+            })
+            .filter(|fork_proof| fork_proof.is_valid_at(self.block_number() + 1, &self.policy));
+
+        signed_headers.insert(header, signature);
+        signed_headers.prune(self.block_number(), &self.policy);
+
+        fork_proof
+    }
+
+    /// Selects the fork proofs that may be included in the next block: Only one proof per slot
+    /// and none for slots that are already slashed.
+    pub fn select_fork_proofs(&self, fork_proofs: Vec<ForkProof>) -> Vec<ForkProof> {
+        let state = self.state.read();
+        let txn = ReadTransaction::new(&self.env);
+
+        let block_number = state.block_number() + 1;
+        let mut slashed_slots = HashSet::new();
+        fork_proofs.into_iter()
+            .filter(|fork_proof| {
+                if !fork_proof.is_valid_at(block_number, &self.policy) {
+                    return false;
+                }
+
+                let epoch = self.policy.epoch_at(fork_proof.block_number());
+                let slot_number = match state.reward_registry.get_slot_number_at(fork_proof.block_number(), fork_proof.view_number(), Some(&txn)) {
+                    Some(slot_number) => slot_number,
+                    None => return false,
+                };
+
+                !state.reward_registry.slashed_set(epoch, Some(&txn)).contains(slot_number as usize)
+                    && slashed_slots.insert((epoch, slot_number))
+            })
+            .collect()
+    }
+}
@@ -22,7 +22,12 @@ extern crate nimiq_vrf as vrf;
 pub mod blockchain;
 pub mod chain_info;
 pub mod chain_store;
+pub mod fork_detection;
+pub mod proofs;
 pub mod reward_registry;
 pub mod transaction_cache;
 
+#[cfg(feature = "transaction-store")]
+pub mod transaction_store;
+
 pub use blockchain::Blockchain;
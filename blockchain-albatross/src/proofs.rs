@@ -0,0 +1,129 @@
+use std::collections::HashSet;
+use std::convert::TryFrom;
+
+use account::Account;
+use block::{Block, EpochChainProof, EpochProof};
+use database::{ReadTransaction, WriteTransaction};
+use hash::{Blake2bHash, Hash};
+use keys::Address;
+use transaction::TransactionsProof;
+use tree_primitives::accounts_proof::AccountsProof;
+use utils::merkle::Blake2bMerkleProof;
+
+use crate::Blockchain;
+
+impl Blockchain {
+    /// Maximum number of blocks behind the head for which accounts proofs are created. Each of
+    /// these blocks has to be reverted while the chain is locked.
+    pub const ACCOUNTS_PROOF_MAX_DEPTH: u32 = 10;
+
+    /// Creates a proof for the transactions of the given addresses.
+    ///
+    /// Micro blocks don't commit to their transactions in a merkle tree, thus proofs can only be
+    /// created for macro blocks. The proof covers all transactions of the epoch finalized by the
+    /// macro block and verifies against its `transactions_root`.
+    pub fn get_transactions_proof(&self, block_hash: &Blake2bHash, addresses: &HashSet<Address>) -> Option<TransactionsProof> {
+        let block = self.get_block(block_hash, /*include_forks*/ false, /*include_body*/ false)?;
+        let macro_block = match block {
+            Block::Macro(macro_block) => macro_block,
+            Block::Micro(_) => return None,
+        };
+
+        // The genesis block doesn't finalize an epoch.
+        if macro_block.header.block_number == 0 {
+            return None;
+        }
+
+        let txn = ReadTransaction::new(&self.env);
+        let transactions = self.get_epoch_transactions(self.policy.epoch_at(macro_block.header.block_number), Some(&txn))?;
+
+        let mut hashes = Vec::new();
+        let mut matches = Vec::new();
+        for transaction in transactions {
+            hashes.push(transaction.hash());
+            if addresses.contains(&transaction.sender) || addresses.contains(&transaction.recipient) {
+                matches.push(transaction);
+            }
+        }
+
+        let matching_hashes: Vec<Blake2bHash> = matches.iter().map(Hash::hash).collect();
+        let proof = Blake2bMerkleProof::new(&hashes, &matching_hashes);
+        Some(TransactionsProof {
+            transactions: matches,
+            proof,
+        })
+    }
+
+    /// Creates a proof for the given accounts at the state after the given block.
+    ///
+    /// Besides the head, proofs can be created for main chain blocks up to
+    /// `ACCOUNTS_PROOF_MAX_DEPTH` blocks behind the head, but not before the last macro block.
+    /// The accounts tree is temporarily reverted to the requested block for this. Receipts of
+    /// blocks before the last macro block are not available anymore.
+    pub fn get_accounts_proof(&self, block_hash: &Blake2bHash, addresses: &[Address]) -> Option<AccountsProof<Account>> {
+        {
+            let state = self.state.read();
+            if block_hash == &state.head_hash {
+                let txn = ReadTransaction::new(&self.env);
+                return Some(state.accounts.get_accounts_proof(&txn, addresses));
+            }
+        }
+
+        // Prevent the chain from changing while we revert blocks.
+        let _push_lock = self.push_lock.lock();
+        let state = self.state.read();
+
+        let macro_head_number = self.policy.last_macro_block(state.block_number());
+        let target_info = self.chain_store.get_chain_info(block_hash, false, None)?;
+        if !target_info.on_main_chain
+            || target_info.head.block_number() < macro_head_number
+            || target_info.head.block_number() + Self::ACCOUNTS_PROOF_MAX_DEPTH < state.block_number() {
+            return None;
+        }
+
+        // The changes made to the accounts tree here are never committed.
+        let mut txn = WriteTransaction::new(&self.env);
+        let mut current = state.main_chain.head.clone();
+        while &current.hash() != block_hash {
+            let micro_block = match current {
+                Block::Micro(ref micro_block) => micro_block,
+                Block::Macro(_) => unreachable!("Reverting across macro block"),
+            };
+
+            let prev_info = self.chain_store
+                .get_chain_info(&micro_block.header.parent_hash, true, Some(&txn))
+                .expect("Corrupted store: Failed to find main chain predecessor");
+
+            self.revert_accounts(&state.accounts, &mut txn, micro_block, prev_info.head.next_view_number())
+                .ok()?;
+
+            current = prev_info.head;
+        }
+
+        let proof = state.accounts.get_accounts_proof(&txn, addresses);
+        txn.abort();
+
+        Some(proof)
+    }
+
+    /// Creates a proof of the validator handoffs for up to `max_epochs` epochs following the
+    /// given macro block. Returns `None` if the block is not a main chain macro block.
+    pub fn get_epoch_proof(&self, known_macro_hash: &Blake2bHash, max_epochs: u32) -> Option<EpochChainProof> {
+        let txn = ReadTransaction::new(&self.env);
+        let known_info = self.chain_store.get_chain_info(known_macro_hash, false, Some(&txn))?;
+        if !known_info.on_main_chain {
+            return None;
+        }
+
+        let macro_blocks = self.chain_store.get_macro_blocks_forward(known_macro_hash, max_epochs, /*include_body*/ false, Some(&txn))?;
+        let epochs = macro_blocks.into_iter()
+            .map(|block| match block {
+                Block::Macro(macro_block) => EpochProof::try_from(macro_block)
+                    .expect("Corrupted store: Macro block without justification"),
+                Block::Micro(_) => unreachable!("Macro block chain contains micro block"),
+            })
+            .collect();
+
+        Some(EpochChainProof::new(epochs))
+    }
+}
@@ -14,7 +14,7 @@ use database::{AsDatabaseBytes, Database, DatabaseFlags, Environment, FromDataba
                ReadTransaction, Transaction, WriteTransaction};
 use database::cursor::{ReadCursor, WriteCursor};
 use primitives::coin::Coin;
-use primitives::policy;
+use primitives::policy::AlbatrossPolicy;
 use primitives::slot::{Slots, Slot, SlotIndex};
 use transaction::Transaction as BlockchainTransaction;
 use vrf::VrfUseCase;
@@ -29,6 +29,7 @@ pub struct SlashRegistry {
     chain_store: Arc<ChainStore>,
     slash_registry_db: Database,
     reward_pot: RewardPot,
+    policy: AlbatrossPolicy,
 }
 
 // TODO Better error messages
@@ -63,18 +64,24 @@ struct BlockDescriptor {
 impl SlashRegistry {
     const SLASH_REGISTRY_DB_NAME: &'static str = "SlashRegistry";
 
-    pub fn new(env: Environment, chain_store: Arc<ChainStore>) -> Self {
+    pub fn new(env: Environment, chain_store: Arc<ChainStore>, policy: AlbatrossPolicy) -> Self {
         let slash_registry_db = env.open_database_with_flags(SlashRegistry::SLASH_REGISTRY_DB_NAME.to_string(), DatabaseFlags::UINT_KEYS);
-        let reward_pot = RewardPot::new(env.clone());
+        let reward_pot = RewardPot::new(env.clone(), policy);
 
         Self {
             env,
             chain_store,
             slash_registry_db,
             reward_pot,
+            policy,
         }
     }
 
+    #[inline]
+    pub fn policy(&self) -> &AlbatrossPolicy {
+        &self.policy
+    }
+
     #[inline]
     pub fn current_reward_pot(&self) -> Coin {
         self.reward_pot.current_reward_pot()
@@ -95,7 +102,7 @@ impl SlashRegistry {
             Block::Macro(ref macro_block) => {
                 self.reward_pot.commit_macro_block(macro_block, txn);
                 self.commit_macro_block(txn, macro_block, prev_view_number)?;
-                self.gc(txn, policy::epoch_at(macro_block.header.block_number));
+                self.gc(txn, self.policy.epoch_at(macro_block.header.block_number));
                 Ok(())
             },
             Block::Micro(ref micro_block) => {
@@ -114,13 +121,13 @@ impl SlashRegistry {
 
         // Put descriptor into database.
         txn.put(&self.slash_registry_db, &block_number, &descriptor);
-        self.gc(txn, policy::epoch_at(block_number));
+        self.gc(txn, self.policy.epoch_at(block_number));
 
         Ok(())
     }
 
     fn get_epoch_state(&self, txn: &mut WriteTransaction, block_number: u32) -> BlockDescriptor {
-        let block_epoch = policy::epoch_at(block_number);
+        let block_epoch = self.policy.epoch_at(block_number);
 
         // Lookup slash state.
         let mut cursor = txn.cursor(&self.slash_registry_db);
@@ -132,11 +139,11 @@ impl SlashRegistry {
         let prev_epoch_state: BitSet;
         let epoch_state: BitSet;
         if let Some((change_block_number, change)) = last_change {
-            if change_block_number >= policy::first_block_of(block_epoch) {
+            if change_block_number >= self.policy.first_block_of(block_epoch) {
                 // last_change was in current epoch
                 prev_epoch_state = change.prev_epoch_state;
                 epoch_state = change.epoch_state;
-            } else if block_epoch > 0 && change_block_number >= policy::first_block_of(block_epoch - 1) {
+            } else if block_epoch > 0 && change_block_number >= self.policy.first_block_of(block_epoch - 1) {
                 // last_change was in previous epoch
                 prev_epoch_state = change.epoch_state;
                 epoch_state = BitSet::new();
@@ -183,7 +190,7 @@ impl SlashRegistry {
     }
 
     fn commit_micro_block(&self, txn: &mut WriteTransaction, block: &MicroBlock, prev_view_number: u32) -> Result<(), SlashPushError> {
-        let block_epoch = policy::epoch_at(block.header.block_number);
+        let block_epoch = self.policy.epoch_at(block.header.block_number);
         let mut epoch_diff = BitSet::new();
         let mut prev_epoch_diff = BitSet::new();
 
@@ -195,7 +202,7 @@ impl SlashRegistry {
             let slot_number = self.get_slot_number_at(block_number, view_number, Some(&txn))
                 .unwrap();
 
-            let slash_epoch = policy::epoch_at(block_number);
+            let slash_epoch = self.policy.epoch_at(block_number);
             if block_epoch == slash_epoch {
                 if epoch_diff.contains(slot_number as usize) {
                     return Err(SlashPushError::DuplicateForkProof);
@@ -235,7 +242,7 @@ impl SlashRegistry {
     }
 
     fn gc(&self, txn: &mut WriteTransaction, current_epoch: u32) {
-        let cutoff = policy::first_block_of_registry(current_epoch);
+        let cutoff = self.policy.first_block_of_registry(current_epoch);
         if cutoff == 0u32 {
             // The first possible block is part of the registry.
             // We can't delete any descriptors.
@@ -289,14 +296,14 @@ impl SlashRegistry {
         //   you'll have the proper slot_number instead of a "honest slot index".
 
         // Get slashed set for epoch
-        let slashed_set = self.slashed_set_at(policy::epoch_at(block_number), block_number, txn_option)
+        let slashed_set = self.slashed_set_at(self.policy.epoch_at(block_number), block_number, txn_option)
             .ok()?;
 
         // RNG for slot selection
         let mut rng = prev_block.seed().rng(VrfUseCase::SlotSelection, view_number);
 
         let slot_number = loop {
-            let slot_number = rng.next_u64_max(policy::SLOTS as u64) as u16;
+            let slot_number = rng.next_u64_max(self.policy.slots as u64) as u16;
 
             // Sample until we find a slot that is not slashed
             if !slashed_set.contains(slot_number as usize) {
@@ -309,14 +316,14 @@ impl SlashRegistry {
 
     // Get latest known slash set of epoch
     pub fn slashed_set(&self, epoch_number: u32, txn_option: Option<&Transaction>) -> BitSet {
-        self.slashed_set_at(epoch_number, policy::first_block_of(epoch_number + 2), txn_option)
+        self.slashed_set_at(epoch_number, self.policy.first_block_of(epoch_number + 2), txn_option)
             .unwrap()
     }
 
     // Get slash set of epoch at specific block number
     // Returns slash set before applying block with that block_number (TODO Tests)
     pub fn slashed_set_at(&self, epoch_number: u32, block_number: u32, txn_option: Option<&Transaction>) -> Result<BitSet, EpochStateError> {
-        let epoch_start = policy::first_block_of(policy::epoch_at(block_number));
+        let epoch_start = self.policy.first_block_of(self.policy.epoch_at(block_number));
 
         // Epoch cannot have slashes if in the future
         if block_number < epoch_start {
@@ -325,7 +332,7 @@ impl SlashRegistry {
 
         // Epoch slashes are only tracked for two epochs
         // First block of (epoch + 2) is fine because upper lookup bound is exclusive.
-        if block_number > policy::first_block_of(epoch_number + 2) {
+        if block_number > self.policy.first_block_of(epoch_number + 2) {
             return Err(EpochStateError::HistoricEpoch);
         }
 
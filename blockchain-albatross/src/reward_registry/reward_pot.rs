@@ -1,13 +1,14 @@
 use block::{MacroBlock, MicroBlock};
 use database::{Database, Environment, ReadTransaction, WriteTransaction};
 use primitives::coin::Coin;
-use primitives::policy;
+use primitives::policy::{self, AlbatrossPolicy};
 use transaction::Transaction as BlockchainTransaction;
 
 
 pub struct RewardPot {
     env: Environment,
     reward_pot: Database,
+    policy: AlbatrossPolicy,
 }
 
 impl RewardPot {
@@ -15,12 +16,13 @@ impl RewardPot {
     const CURRENT_EPOCH_KEY: &'static str = "curr";
     const PREVIOUS_EPOCH_KEY: &'static str = "prev";
 
-    pub fn new(env: Environment) -> Self {
+    pub fn new(env: Environment, policy: AlbatrossPolicy) -> Self {
         let reward_pot = env.open_database(RewardPot::REWARD_POT_DB_NAME.to_string());
 
         Self {
             env,
             reward_pot,
+            policy,
         }
     }
 
@@ -36,13 +38,13 @@ impl RewardPot {
     }
 
     pub(super) fn commit_epoch(&self, block_number: u32, transactions: &[BlockchainTransaction], txn: &mut WriteTransaction) {
-        assert!(policy::is_macro_block_at(block_number));
-        let epoch = policy::epoch_at(block_number);
+        assert!(self.policy.is_macro_block_at(block_number));
+        let epoch = self.policy.epoch_at(block_number);
 
         let mut reward = Coin::ZERO;
 
         // All blocks of the epoch.
-        for block_number in policy::first_block_of(epoch)..=block_number {
+        for block_number in self.policy.first_block_of(epoch)..=block_number {
             reward += policy::block_reward_at(block_number);
         }
 
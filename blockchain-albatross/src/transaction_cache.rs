@@ -1,14 +1,20 @@
 use std::collections::{HashSet, VecDeque};
+use std::io;
 
+use beserial::{Deserialize, Serialize};
+use database::{FromDatabaseValue, IntoDatabaseValue};
 use hash::{Blake2bHash, Hash};
-use block::Block;
-use primitives::policy;
+use block::{Block, MacroBlock};
+use primitives::policy::AlbatrossPolicy;
 
 #[derive(Debug, Clone)]
 struct BlockDescriptor {
     hash: Blake2bHash,
     prev_hash: Blake2bHash,
-    transaction_hashes: Vec<Blake2bHash>
+    transaction_hashes: Vec<Blake2bHash>,
+    /// Number of blocks covered by this descriptor.
+    /// Epochs received through macro block sync are covered by a single descriptor.
+    num_blocks: u32,
 }
 
 impl<'a> From<&'a Block> for BlockDescriptor {
@@ -20,29 +26,64 @@ impl<'a> From<&'a Block> for BlockDescriptor {
             hash: block.hash(),
             prev_hash: block.parent_hash().clone(),
             transaction_hashes: hashes,
+            num_blocks: 1,
         }
     }
 }
 
+impl BlockDescriptor {
+    fn from_epoch(macro_block: &MacroBlock, transaction_hashes: Vec<Blake2bHash>, epoch_length: u32) -> Self {
+        BlockDescriptor {
+            hash: macro_block.hash(),
+            prev_hash: macro_block.header.parent_macro_hash.clone(),
+            transaction_hashes,
+            num_blocks: epoch_length,
+        }
+    }
+}
+
+/// The hashes of all transactions of an epoch that was received through macro block sync.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EpochTransactionHashes {
+    #[beserial(len_type(u32))]
+    pub hashes: Vec<Blake2bHash>,
+}
+
+impl FromDatabaseValue for EpochTransactionHashes {
+    fn copy_from_database(bytes: &[u8]) -> Result<Self, io::Error> where Self: Sized {
+        let mut cursor = io::Cursor::new(bytes);
+        Ok(Deserialize::deserialize(&mut cursor)?)
+    }
+}
+
+impl IntoDatabaseValue for EpochTransactionHashes {
+    fn database_byte_size(&self) -> usize {
+        self.serialized_size()
+    }
+
+    fn copy_into_database(&self, mut bytes: &mut [u8]) {
+        Serialize::serialize(&self, &mut bytes).unwrap();
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TransactionCache {
     transaction_hashes: HashSet<Blake2bHash>,
-    block_order: VecDeque<BlockDescriptor>
+    block_order: VecDeque<BlockDescriptor>,
+    /// Number of blocks covered by `block_order`.
+    num_blocks: u32,
+    policy: AlbatrossPolicy,
 }
 
-impl Default for TransactionCache {
-    fn default() -> Self {
+impl TransactionCache {
+    pub fn new(policy: AlbatrossPolicy) -> Self {
         TransactionCache {
             transaction_hashes: HashSet::new(),
-            block_order: VecDeque::with_capacity(policy::TRANSACTION_VALIDITY_WINDOW_ALBATROSS as usize)
+            block_order: VecDeque::with_capacity(policy.transaction_validity_window as usize),
+            num_blocks: 0,
+            policy,
         }
     }
-}
-
-impl TransactionCache {
-    pub fn new() -> Self {
-        Self::default()
-    }
 
     pub fn contains(&self, transaction_hash: &Blake2bHash) -> bool {
         self.transaction_hashes.contains(&transaction_hash)
@@ -65,15 +106,25 @@ impl TransactionCache {
 
     pub fn push_block(&mut self, block: &Block) {
         assert!(self.block_order.is_empty() || *block.parent_hash() == self.block_order.back().as_ref().unwrap().hash);
+        self.push_descriptor(BlockDescriptor::from(block));
+    }
+
+    /// Adds an epoch that was received through macro block sync, i.e. without its micro blocks.
+    pub fn push_epoch(&mut self, macro_block: &MacroBlock, transaction_hashes: Vec<Blake2bHash>) {
+        assert!(self.block_order.is_empty() || macro_block.header.parent_macro_hash == self.block_order.back().as_ref().unwrap().hash);
+        self.push_descriptor(BlockDescriptor::from_epoch(macro_block, transaction_hashes, self.policy.epoch_length));
+    }
 
-        let descriptor = BlockDescriptor::from(block);
+    fn push_descriptor(&mut self, descriptor: BlockDescriptor) {
         for hash in &descriptor.transaction_hashes {
             let is_new = self.transaction_hashes.insert(hash.clone());
             assert!(is_new);
         }
+        self.num_blocks += descriptor.num_blocks;
         self.block_order.push_back(descriptor);
 
-        if self.block_order.len() as u32 > policy::TRANSACTION_VALIDITY_WINDOW_ALBATROSS {
+        // Epochs are only removed as a whole, so we might keep some blocks more than necessary.
+        while self.num_blocks - self.block_order.front().as_ref().unwrap().num_blocks >= self.policy.transaction_validity_window {
             self.shift_block();
         }
     }
@@ -85,18 +136,29 @@ impl TransactionCache {
             for hash in &descriptor.transaction_hashes {
                 self.transaction_hashes.remove(hash);
             }
+            self.num_blocks -= descriptor.num_blocks;
         }
     }
 
     pub fn prepend_block(&mut self, block: &Block) {
         assert!(self.block_order.is_empty() || block.hash() == self.block_order.front().as_ref().unwrap().prev_hash);
         assert!(self.missing_blocks() > 0);
+        self.prepend_descriptor(BlockDescriptor::from(block));
+    }
 
-        let descriptor = BlockDescriptor::from(block);
+    /// Prepends an epoch that was received through macro block sync, i.e. without its micro blocks.
+    pub fn prepend_epoch(&mut self, macro_block: &MacroBlock, transaction_hashes: Vec<Blake2bHash>) {
+        assert!(self.block_order.is_empty() || macro_block.hash() == self.block_order.front().as_ref().unwrap().prev_hash);
+        assert!(self.missing_blocks() > 0);
+        self.prepend_descriptor(BlockDescriptor::from_epoch(macro_block, transaction_hashes, self.policy.epoch_length));
+    }
+
+    fn prepend_descriptor(&mut self, descriptor: BlockDescriptor) {
         for hash in &descriptor.transaction_hashes {
             let is_new = self.transaction_hashes.insert(hash.clone());
             assert!(is_new);
         }
+        self.num_blocks += descriptor.num_blocks;
         self.block_order.push_front(descriptor);
     }
 
@@ -106,11 +168,12 @@ impl TransactionCache {
             for hash in &descriptor.transaction_hashes {
                 self.transaction_hashes.remove(hash);
             }
+            self.num_blocks -= descriptor.num_blocks;
         }
     }
 
     pub fn missing_blocks(&self) -> u32 {
-        policy::TRANSACTION_VALIDITY_WINDOW_ALBATROSS - self.block_order.len() as u32
+        self.policy.transaction_validity_window.saturating_sub(self.num_blocks)
     }
 
     pub fn is_empty(&self) -> bool {
@@ -124,4 +187,9 @@ impl TransactionCache {
     pub fn tail_hash(&self) -> Blake2bHash {
         self.block_order.front().as_ref().unwrap().hash.clone()
     }
+
+    /// The hash of the block preceding the oldest block (or epoch) in the cache.
+    pub fn tail_prev_hash(&self) -> Blake2bHash {
+        self.block_order.front().as_ref().unwrap().prev_hash.clone()
+    }
 }
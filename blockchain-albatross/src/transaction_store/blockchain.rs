@@ -0,0 +1,42 @@
+use database::ReadTransaction;
+use hash::Blake2bHash;
+use keys::Address;
+use transaction::TransactionReceipt;
+
+use crate::blockchain::Blockchain;
+use crate::transaction_store::TransactionInfo;
+
+impl From<TransactionInfo> for TransactionReceipt {
+    fn from(info: TransactionInfo) -> Self {
+        TransactionReceipt {
+            transaction_hash: info.transaction_hash,
+            block_hash: info.block_hash,
+            block_height: info.block_number,
+        }
+    }
+}
+
+impl Blockchain {
+    pub fn get_transaction_receipts_by_address(&self, address: &Address, sender_limit: usize, recipient_limit: usize) -> Vec<TransactionReceipt> {
+        let mut receipts;
+
+        let txn = ReadTransaction::new(&self.env);
+        receipts = self.transaction_store.get_by_sender(address, sender_limit, Some(&txn));
+        receipts.extend(self.transaction_store.get_by_recipient(address, recipient_limit, Some(&txn)));
+
+        // Merge sent and received transactions, most recent first. Transactions to oneself are
+        // found in both indexes.
+        receipts.sort_unstable_by(|a, b| (b.block_number, b.index).cmp(&(a.block_number, a.index)));
+        receipts.dedup_by(|a, b| a.transaction_hash == b.transaction_hash);
+
+        receipts.drain(..).map(TransactionReceipt::from).collect()
+    }
+
+    pub fn get_transaction_info_by_hash(&self, transaction_hash: &Blake2bHash) -> Option<TransactionInfo> {
+        self.transaction_store.get_by_hash(transaction_hash, None)
+    }
+
+    pub fn get_transaction_receipt_by_hash(&self, transaction_hash: &Blake2bHash) -> Option<TransactionReceipt> {
+        self.get_transaction_info_by_hash(transaction_hash).map(TransactionReceipt::from)
+    }
+}
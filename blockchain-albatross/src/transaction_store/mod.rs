@@ -0,0 +1,328 @@
+use std::io;
+use std::os::raw::c_uint;
+
+use beserial::{Deserialize, Serialize};
+use block::{Block, MacroBlock};
+use database::{Database, DatabaseFlags, Environment, FromDatabaseValue, IntoDatabaseValue, ReadTransaction, Transaction, WriteTransaction};
+use database::cursor::ReadCursor;
+use hash::{Blake2bHash, Hash};
+use keys::Address;
+use transaction::Transaction as BlockchainTransaction;
+
+pub mod blockchain;
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TransactionInfo {
+    pub transaction_hash: Blake2bHash,
+    pub block_hash: Blake2bHash,
+    pub block_number: u32,
+    pub timestamp: u64,
+    /// Index of the transaction in its micro block.
+    /// Transactions that were received through macro block sync are attributed to the macro block
+    /// of their epoch. In that case, this is the index within the epoch's transactions.
+    pub index: u32,
+    /// The transaction itself. It is stored alongside, because micro blocks skipped by macro
+    /// block sync are not available to look it up.
+    pub transaction: BlockchainTransaction,
+}
+
+impl FromDatabaseValue for TransactionInfo {
+    fn copy_from_database(bytes: &[u8]) -> Result<Self, io::Error> where Self: Sized {
+        let mut cursor = io::Cursor::new(bytes);
+        Ok(Deserialize::deserialize(&mut cursor)?)
+    }
+}
+
+impl IntoDatabaseValue for TransactionInfo {
+    fn database_byte_size(&self) -> usize {
+        self.serialized_size()
+    }
+
+    fn copy_into_database(&self, mut bytes: &mut [u8]) {
+        Serialize::serialize(&self, &mut bytes).unwrap();
+    }
+}
+
+impl TransactionInfo {
+    pub fn from_block(block: &Block) -> Vec<TransactionInfo> {
+        let transactions = match block.transactions() {
+            Some(transactions) => transactions,
+            None => return Vec::new(),
+        };
+
+        let block_hash = block.hash();
+        transactions.iter().enumerate()
+            .map(|(index, tx)| TransactionInfo {
+                transaction_hash: tx.hash(),
+                block_hash: block_hash.clone(),
+                block_number: block.block_number(),
+                timestamp: block.timestamp(),
+                index: index as u32,
+                transaction: tx.clone(),
+            })
+            .collect()
+    }
+
+    pub fn from_epoch(macro_block: &MacroBlock, transactions: &[BlockchainTransaction]) -> Vec<TransactionInfo> {
+        let block_hash: Blake2bHash = macro_block.hash();
+        transactions.iter().enumerate()
+            .map(|(index, tx)| TransactionInfo {
+                transaction_hash: tx.hash(),
+                block_hash: block_hash.clone(),
+                block_number: macro_block.header.block_number,
+                timestamp: macro_block.header.timestamp,
+                index: index as u32,
+                transaction: tx.clone(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct TransactionStore {
+    env: Environment,
+    transaction_db: Database,
+    sender_idx: Database,
+    recipient_idx: Database,
+    transaction_hash_idx: Database,
+}
+
+impl TransactionStore {
+    const TRANSACTION_DB_NAME: &'static str = "TransactionData";
+    const SENDER_IDX_NAME: &'static str = "SenderIdx";
+    const RECIPIENT_IDX_NAME: &'static str = "RecipientIdx";
+    const TRANSACTION_HASH_IDX_NAME: &'static str = "TransactionHashIdx";
+    const HEAD_KEY: c_uint = 0;
+    const HEAD_DEFAULT: c_uint = 1;
+
+    pub fn new(env: Environment) -> Self {
+        let transaction_db = env.open_database_with_flags(
+            Self::TRANSACTION_DB_NAME.to_string(),
+            DatabaseFlags::UINT_KEYS
+        );
+        let sender_idx = env.open_database_with_flags(
+            Self::SENDER_IDX_NAME.to_string(),
+            DatabaseFlags::DUPLICATE_KEYS | DatabaseFlags::DUP_FIXED_SIZE_VALUES | DatabaseFlags::DUP_UINT_VALUES
+        );
+        let recipient_idx = env.open_database_with_flags(
+            Self::RECIPIENT_IDX_NAME.to_string(),
+            DatabaseFlags::DUPLICATE_KEYS | DatabaseFlags::DUP_FIXED_SIZE_VALUES | DatabaseFlags::DUP_UINT_VALUES
+        );
+        let transaction_hash_idx = env.open_database(
+            Self::TRANSACTION_HASH_IDX_NAME.to_string()
+        );
+        TransactionStore { env, transaction_db, sender_idx, recipient_idx, transaction_hash_idx }
+    }
+
+    fn get_head(&self, txn_option: Option<&Transaction>) -> c_uint {
+        match txn_option {
+            Some(txn) => txn.get(&self.transaction_db, &TransactionStore::HEAD_KEY),
+            None => ReadTransaction::new(&self.env).get(&self.transaction_db, &TransactionStore::HEAD_KEY)
+        }.unwrap_or(Self::HEAD_DEFAULT)
+    }
+
+    fn set_head(&self, txn: &mut WriteTransaction, id: c_uint) {
+        txn.put(&self.transaction_db, &TransactionStore::HEAD_KEY, &id);
+    }
+
+    fn get_id(&self, transaction_hash: &Blake2bHash, txn_option: Option<&Transaction>) -> Option<c_uint> {
+        match txn_option {
+            Some(txn) => txn.get(&self.transaction_hash_idx, transaction_hash),
+            None => ReadTransaction::new(&self.env).get(&self.transaction_hash_idx, transaction_hash)
+        }
+    }
+
+    pub fn get_by_hash(&self, transaction_hash: &Blake2bHash, txn_option: Option<&Transaction>) -> Option<TransactionInfo> {
+        let read_txn: ReadTransaction;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = ReadTransaction::new(&self.env);
+                &read_txn
+            }
+        };
+
+        let index = self.get_id(transaction_hash, Some(txn))?;
+        txn.get(&self.transaction_db, &index)
+    }
+
+    fn get_by_address(&self, database: &Database, address: &Address, limit: usize, txn: &Transaction) -> Vec<TransactionInfo> {
+        let mut transactions = Vec::new();
+
+        // Shortcut for a 0 limit.
+        if limit == 0 {
+            return transactions;
+        }
+
+        // Start collecting transactions.
+        let mut cursor = txn.cursor(database);
+
+        // Address not found.
+        // Move to last transaction of that address.
+        if cursor.seek_key::<Address, c_uint>(address).is_none() {
+            return transactions;
+        }
+
+        let mut id: Option<c_uint> = cursor.last_duplicate();
+        while let Some(index) = id {
+            let info = txn.get(&self.transaction_db, &index)
+                .expect("Corrupted store: TransactionInfo referenced from index not found");
+            transactions.push(info);
+
+            // Stop if we have enough transactions.
+            if transactions.len() >= limit {
+                break;
+            }
+
+            id = cursor.prev_duplicate().map(|(_, value): (Address, c_uint)| value);
+        }
+
+        transactions
+    }
+
+    pub fn get_by_sender(&self, sender: &Address, limit: usize, txn_option: Option<&Transaction>) -> Vec<TransactionInfo> {
+        let read_txn: ReadTransaction;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = ReadTransaction::new(&self.env);
+                &read_txn
+            }
+        };
+
+        self.get_by_address(&self.sender_idx, sender, limit, txn)
+    }
+
+    pub fn get_by_recipient(&self, recipient: &Address, limit: usize, txn_option: Option<&Transaction>) -> Vec<TransactionInfo> {
+        let read_txn: ReadTransaction;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = ReadTransaction::new(&self.env);
+                &read_txn
+            }
+        };
+
+        self.get_by_address(&self.recipient_idx, recipient, limit, txn)
+    }
+
+    fn put_infos(&self, infos: &[TransactionInfo], txn: &mut WriteTransaction) {
+        let mut current_id = self.get_head(Some(txn));
+        for info in infos.iter() {
+            txn.put_reserve(&self.transaction_db, &current_id, info);
+            txn.put(&self.transaction_hash_idx, &info.transaction_hash, &current_id);
+            txn.put(&self.sender_idx, &info.transaction.sender, &current_id);
+            txn.put(&self.recipient_idx, &info.transaction.recipient, &current_id);
+            current_id += 1;
+        }
+        self.set_head(txn, current_id);
+    }
+
+    /// Indexes all transactions of a block. Macro blocks don't contain transactions.
+    pub fn put(&self, block: &Block, txn: &mut WriteTransaction) {
+        self.put_infos(&TransactionInfo::from_block(block), txn);
+    }
+
+    /// Indexes the transactions of an epoch that was pushed with `push_isolated_macro_block`.
+    pub fn put_epoch(&self, macro_block: &MacroBlock, transactions: &[BlockchainTransaction], txn: &mut WriteTransaction) {
+        self.put_infos(&TransactionInfo::from_epoch(macro_block, transactions), txn);
+    }
+
+    pub fn remove(&self, block: &Block, txn: &mut WriteTransaction) {
+        if let Some(transactions) = block.transactions() {
+            // Remove all transactions.
+            for tx in transactions.iter() {
+                let hash: Blake2bHash = tx.hash();
+                // Delete transaction from every store.
+                if let Some(id) = self.get_id(&hash, Some(txn)) {
+                    txn.remove(&self.transaction_hash_idx, &hash);
+                    txn.remove(&self.transaction_db, &id);
+                    txn.remove_item(&self.sender_idx, &tx.sender, &id);
+                    txn.remove_item(&self.recipient_idx, &tx.recipient, &id);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use database::volatile::VolatileEnvironment;
+    use primitives::coin::Coin;
+    use primitives::networks::NetworkId;
+
+    use super::*;
+
+    #[test]
+    fn it_can_store_the_head_id() {
+        let env = VolatileEnvironment::new(4).unwrap();
+        let store = TransactionStore::new(env.clone());
+        assert_eq!(store.get_head(None), TransactionStore::HEAD_DEFAULT);
+
+        let head = 5;
+        let mut txn = WriteTransaction::new(&env);
+        store.set_head(&mut txn, head);
+        txn.commit();
+
+        assert_eq!(store.get_head(None), head);
+    }
+
+    #[test]
+    fn it_can_get_an_id() {
+        let env = VolatileEnvironment::new(4).unwrap();
+        let store = TransactionStore::new(env.clone());
+
+        let hash = Blake2bHash::default();
+        let id = 5;
+        let mut txn = WriteTransaction::new(&env);
+        txn.put(&store.transaction_hash_idx, &hash, &id);
+        txn.commit();
+
+        assert_eq!(store.get_id(&hash, None), Some(id));
+    }
+
+    fn put_info(store: &TransactionStore, txn: &mut WriteTransaction, id: c_uint, address: &Address, index: u32) {
+        let transaction = BlockchainTransaction::new_basic(address.clone(), Address::default(), Coin::ZERO, Coin::ZERO, 1, NetworkId::UnitAlbatross);
+        let info = TransactionInfo {
+            transaction_hash: Blake2bHash::default(),
+            block_hash: Blake2bHash::default(),
+            block_number: 1337,
+            timestamp: 0,
+            index,
+            transaction,
+        };
+        txn.put_reserve(&store.transaction_db, &id, &info);
+        txn.put(&store.sender_idx, address, &id);
+    }
+
+    #[test]
+    fn it_can_get_by_address() {
+        let env = VolatileEnvironment::new(4).unwrap();
+        let store = TransactionStore::new(env.clone());
+
+        let address = Address::default();
+
+        {
+            let mut txn = WriteTransaction::new(&env);
+            // Insert tx 1.
+            put_info(&store, &mut txn, 5, &address, 12);
+            // Insert tx 2.
+            put_info(&store, &mut txn, 8, &address, 8);
+            txn.commit();
+        }
+
+        let txn = ReadTransaction::new(&env);
+        assert_eq!(store.get_by_address(&store.sender_idx, &address, 0, &txn).len(), 0);
+
+        // 1 transaction.
+        let txs = store.get_by_address(&store.sender_idx, &address, 1, &txn);
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].index, 8);
+
+        // 2 transaction.
+        let txs = store.get_by_address(&store.sender_idx, &address, 3, &txn);
+        assert_eq!(txs.len(), 2);
+        assert_eq!(txs[0].index, 8);
+        assert_eq!(txs[1].index, 12);
+    }
+}
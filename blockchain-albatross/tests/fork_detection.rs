@@ -0,0 +1,57 @@
+use std::sync::{Arc, Mutex};
+
+use beserial::Deserialize;
+use nimiq_block_albatross::Block;
+use nimiq_block_production_albatross::BlockProducer;
+use nimiq_blockchain_albatross::blockchain::{Blockchain, PushResult};
+use nimiq_blockchain_albatross::fork_detection::ForkEvent;
+use nimiq_bls::{KeyPair, SecretKey};
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_network_primitives::networks::NetworkId;
+use nimiq_signer::LocalSigner;
+
+/// Secret key of validator. Tests run with `network-primitives/src/genesis/unit-albatross.toml`
+const SECRET_KEY: &'static str = "49ea68eb6b8afdf4ca4d4c0a0b295c76ca85225293693bc30e755476492b707f";
+
+#[test]
+fn it_detects_equivocating_micro_blocks() {
+    let env = VolatileEnvironment::new(12).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+    let keypair = KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+    let producer = BlockProducer::new_without_mempool(Arc::clone(&blockchain), Arc::new(LocalSigner::new(keypair.clone())));
+
+    let detected = Arc::new(Mutex::new(Vec::new()));
+    let detected1 = Arc::clone(&detected);
+    blockchain.fork_notifier.write().register(move |event: &ForkEvent| {
+        let ForkEvent::Detected(fork_proof) = event;
+        detected1.lock().unwrap().push(fork_proof.clone());
+    });
+
+    // Two different blocks for the same slot.
+    let block1 = producer.next_micro_block(vec![], 1565713920000, 0, vec![0x41], None).unwrap();
+    let block2 = producer.next_micro_block(vec![], 1565713920001, 0, vec![0x41], None).unwrap();
+
+    assert_eq!(blockchain.push(Block::Micro(block1.clone())), Ok(PushResult::Extended));
+    assert!(detected.lock().unwrap().is_empty());
+
+    assert_eq!(blockchain.push(Block::Micro(block2.clone())), Ok(PushResult::Forked));
+    let fork_proofs = detected.lock().unwrap().clone();
+    assert_eq!(fork_proofs.len(), 1);
+    assert_eq!(fork_proofs[0].header1, block1.header);
+    assert_eq!(fork_proofs[0].header2, block2.header);
+    assert!(fork_proofs[0].verify(&keypair.public).is_ok());
+
+    // Known blocks don't produce another proof.
+    assert_eq!(blockchain.push(Block::Micro(block2)), Ok(PushResult::Known));
+    assert_eq!(detected.lock().unwrap().len(), 1);
+
+    // Only one proof per slot can be included.
+    let selected = blockchain.select_fork_proofs(vec![fork_proofs[0].clone(), fork_proofs[0].clone()]);
+    assert_eq!(selected.len(), 1);
+
+    let block3 = producer.next_micro_block(selected, 1565713922000, 0, vec![0x41], None).unwrap();
+    assert_eq!(blockchain.push(Block::Micro(block3)), Ok(PushResult::Extended));
+
+    // The slot is slashed now.
+    assert!(blockchain.select_fork_proofs(fork_proofs).is_empty());
+}
@@ -3,24 +3,30 @@ use std::sync::Arc;
 use beserial::Deserialize;
 use nimiq_bls::{KeyPair, SecretKey};
 use nimiq_block_production_albatross::BlockProducer;
-use nimiq_block_albatross::{Block, MacroBlock, PbftProposal, PbftProofBuilder, PbftPrepareMessage, PbftCommitMessage, SignedPbftPrepareMessage, SignedPbftCommitMessage};
-use nimiq_blockchain_albatross::blockchain::{Blockchain, PushResult};
+use nimiq_block_albatross::{Block, BlockError, EpochProofError, MacroBlock, PbftProposal, PbftProofBuilder, PbftPrepareMessage, PbftCommitMessage, SignedPbftPrepareMessage, SignedPbftCommitMessage};
+use nimiq_blockchain_albatross::blockchain::{Blockchain, PushError, PushResult};
 use nimiq_database::volatile::VolatileEnvironment;
 use nimiq_hash::{Blake2bHash, Hash};
-use nimiq_network_primitives::{networks::NetworkId};
-use nimiq_primitives::policy;
+use nimiq_network_primitives::networks::{NetworkId, NetworkInfo};
+use nimiq_primitives::policy::AlbatrossPolicy;
 use nimiq_blockchain_base::AbstractBlockchain;
 use nimiq_blockchain_base::Direction;
+use nimiq_signer::LocalSigner;
 
 /// Secret key of validator. Tests run with `network-primitives/src/genesis/unit-albatross.toml`
 const SECRET_KEY: &'static str = "49ea68eb6b8afdf4ca4d4c0a0b295c76ca85225293693bc30e755476492b707f";
 
+/// Policy of the network the tests run with.
+fn policy() -> AlbatrossPolicy {
+    NetworkInfo::from_network_id(NetworkId::UnitAlbatross).unwrap().albatross_policy()
+}
+
 // Fill epoch with micro blocks
 fn fill_micro_blocks(producer: &BlockProducer, blockchain: &Arc<Blockchain>) {
     let init_height = blockchain.head_height();
-    let macro_block_number = policy::macro_block_after(init_height + 1);
+    let macro_block_number = policy().macro_block_after(init_height + 1);
     for i in (init_height + 1)..macro_block_number {
-        let last_micro_block = producer.next_micro_block(vec![], 1565713920000 + i as u64 * 2000, 0, vec![0x42], None);
+        let last_micro_block = producer.next_micro_block(vec![], 1565713920000 + i as u64 * 2000, 0, vec![0x42], None).unwrap();
         assert_eq!(blockchain.push(Block::Micro(last_micro_block)), Ok(PushResult::Extended));
     }
     assert_eq!(blockchain.head_height(), macro_block_number - 1);
@@ -43,8 +49,8 @@ fn sign_macro_block(proposal: PbftProposal) -> MacroBlock {
 
     // create proof
     let mut pbft_proof = PbftProofBuilder::new();
-    pbft_proof.add_prepare_signature(&keypair.public, policy::SLOTS, &prepare);
-    pbft_proof.add_commit_signature(&keypair.public, policy::SLOTS, &commit);
+    pbft_proof.add_prepare_signature(&keypair.public, policy().slots, &prepare);
+    pbft_proof.add_commit_signature(&keypair.public, policy().slots, &commit);
 
     MacroBlock {
         header: proposal.header,
@@ -58,7 +64,7 @@ fn produce_macro_blocks(num_macro: usize, producer: &BlockProducer, blockchain:
         fill_micro_blocks(producer, blockchain);
 
         let next_block_height = blockchain.head_height() + 1;
-        let (proposal, _extrinsics) = producer.next_macro_block_proposal(1565713920000 + next_block_height as u64 * 2000, 0u32, None);
+        let (proposal, _extrinsics) = producer.next_macro_block_proposal(1565713920000 + next_block_height as u64 * 2000, 0u32, None).unwrap();
 
         let block = sign_macro_block(proposal);
         assert_eq!(blockchain.push_block(Block::Macro(block), true), Ok(PushResult::Extended));
@@ -67,12 +73,12 @@ fn produce_macro_blocks(num_macro: usize, producer: &BlockProducer, blockchain:
 
 #[test]
 fn it_can_sync_macro_blocks() {
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(12).unwrap();
     let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
     let genesis_hash = blockchain.head_hash();
 
     let keypair = KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
-    let producer = BlockProducer::new_without_mempool(Arc::clone(&blockchain), keypair);
+    let producer = BlockProducer::new_without_mempool(Arc::clone(&blockchain), Arc::new(LocalSigner::new(keypair)));
 
     produce_macro_blocks(2, &producer, &blockchain);
 
@@ -80,7 +86,7 @@ fn it_can_sync_macro_blocks() {
     assert_eq!(macro_blocks.len(), 2);
 
     // Create a second blockchain to push these blocks.
-    let env2 = VolatileEnvironment::new(10).unwrap();
+    let env2 = VolatileEnvironment::new(12).unwrap();
     let blockchain2 = Arc::new(Blockchain::new(env2.clone(), NetworkId::UnitAlbatross).unwrap());
 
     for block in macro_blocks {
@@ -88,4 +94,101 @@ fn it_can_sync_macro_blocks() {
     }
 }
 
+#[test]
+fn it_rejects_macro_blocks_with_the_wrong_number_of_slots() {
+    let env = VolatileEnvironment::new(12).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+    let genesis_hash = blockchain.head_hash();
+
+    let keypair = KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+    let producer = BlockProducer::new_without_mempool(Arc::clone(&blockchain), Arc::new(LocalSigner::new(keypair)));
+
+    produce_macro_blocks(1, &producer, &blockchain);
+
+    let mut macro_blocks = blockchain.get_macro_blocks(&genesis_hash, 10, true, Direction::Forward).unwrap();
+
+    // A chain whose policy has a different number of slots doesn't accept the validators.
+    let env2 = VolatileEnvironment::new(12).unwrap();
+    let mut blockchain2 = Blockchain::new(env2.clone(), NetworkId::UnitAlbatross).unwrap();
+    blockchain2.policy.slots = policy().slots / 2;
+    assert_eq!(blockchain2.push_isolated_macro_block(macro_blocks.remove(0), &[]), Err(PushError::InvalidBlock(BlockError::InvalidValidators)));
+}
+
+#[test]
+fn it_restores_the_transaction_cache_after_macro_sync() {
+    let env = VolatileEnvironment::new(12).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+    let genesis_hash = blockchain.head_hash();
+
+    let keypair = KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+    let producer = BlockProducer::new_without_mempool(Arc::clone(&blockchain), Arc::new(LocalSigner::new(keypair)));
+
+    produce_macro_blocks(2, &producer, &blockchain);
+
+    let macro_blocks = blockchain.get_macro_blocks(&genesis_hash, 10, true, Direction::Forward).unwrap();
+
+    let env2 = VolatileEnvironment::new(12).unwrap();
+    let blockchain2 = Arc::new(Blockchain::new(env2.clone(), NetworkId::UnitAlbatross).unwrap());
+    for block in macro_blocks {
+        assert_eq!(blockchain2.push_isolated_macro_block(block, &[]), Ok(PushResult::Extended));
+    }
+
+    // The genesis block and both epochs are covered by the cache.
+    let missing_blocks = policy().transaction_validity_window - 2 * policy().epoch_length - 1;
+    assert_eq!(blockchain2.state().transaction_cache().missing_blocks(), missing_blocks);
+    drop(blockchain2);
+
+    // The cache is restored from the stored epoch transactions when loading the chain.
+    let blockchain2 = Blockchain::new(env2.clone(), NetworkId::UnitAlbatross).unwrap();
+    assert_eq!(blockchain2.head_hash(), blockchain.head_hash());
+    assert_eq!(blockchain2.state().transaction_cache().missing_blocks(), missing_blocks);
+}
+
+#[test]
+fn it_can_create_and_verify_epoch_proofs() {
+    let env = VolatileEnvironment::new(12).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+    let genesis_hash = blockchain.head_hash();
+    let genesis_header = blockchain.macro_head().header.clone();
+
+    let keypair = KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+    let producer = BlockProducer::new_without_mempool(Arc::clone(&blockchain), Arc::new(LocalSigner::new(keypair)));
+
+    produce_macro_blocks(2, &producer, &blockchain);
+
+    let proof = blockchain.get_epoch_proof(&genesis_hash, 10).unwrap();
+    assert_eq!(proof.len(), 2);
+    assert_eq!(proof.verify(&genesis_header, &policy()).unwrap(), &blockchain.macro_head().header);
+
+    // The number of epochs is limited.
+    let proof = blockchain.get_epoch_proof(&genesis_hash, 1).unwrap();
+    assert_eq!(proof.len(), 1);
+    assert_eq!(proof.verify(&genesis_header, &policy()).unwrap().block_number, policy().macro_block_of(1));
+
+    // Nothing to prove after the macro head.
+    let proof = blockchain.get_epoch_proof(&blockchain.macro_head_hash(), 10).unwrap();
+    assert!(proof.is_empty());
+
+    // Micro blocks can't be used as a starting point.
+    let micro_hash = blockchain.get_block_at(1, false).unwrap().hash();
+    assert!(blockchain.get_epoch_proof(&micro_hash, 10).is_none());
+
+    // The validators must have the number of slots of the policy.
+    let proof = blockchain.get_epoch_proof(&genesis_hash, 10).unwrap();
+    let other_policy = AlbatrossPolicy { slots: policy().slots / 2, ..policy() };
+    assert_eq!(proof.verify(&genesis_header, &other_policy), Err(EpochProofError::InvalidSlotCount(policy().macro_block_of(1))));
+
+    // The proof has to start right after the trusted header.
+    let mut proof = blockchain.get_epoch_proof(&genesis_hash, 10).unwrap();
+    let epoch1_header = proof.epochs[0].header.clone();
+    assert_eq!(proof.verify(&epoch1_header, &policy()), Err(EpochProofError::InvalidBlockNumber(policy().macro_block_of(1))));
+
+    // Tampered headers are rejected.
+    proof.epochs[1].header.timestamp += 1;
+    match proof.verify(&genesis_header, &policy()) {
+        Err(EpochProofError::InvalidJustification(block_number, _)) => assert_eq!(block_number, policy().macro_block_of(2)),
+        res => panic!("Unexpected result: {:?}", res),
+    }
+}
+
 // TODO Test transactions
@@ -1,2 +1,4 @@
 mod signed;
 mod macro_block_sync;
+mod proofs;
+mod transaction_store;
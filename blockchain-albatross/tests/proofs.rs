@@ -0,0 +1,86 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use beserial::Deserialize;
+use nimiq_block_albatross::{Block, ForkProof};
+use nimiq_block_production_albatross::BlockProducer;
+use nimiq_blockchain_albatross::blockchain::{Blockchain, PushResult};
+use nimiq_bls::{KeyPair, SecretKey};
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_keys::Address;
+use nimiq_network_primitives::networks::{NetworkId, NetworkInfo};
+use nimiq_signer::LocalSigner;
+
+/// Secret key of validator. Tests run with `network-primitives/src/genesis/unit-albatross.toml`
+const SECRET_KEY: &'static str = "49ea68eb6b8afdf4ca4d4c0a0b295c76ca85225293693bc30e755476492b707f";
+
+#[test]
+fn it_can_create_accounts_proofs_for_recent_blocks() {
+    let env = VolatileEnvironment::new(12).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+    let keypair = KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+    let producer = BlockProducer::new_without_mempool(Arc::clone(&blockchain), Arc::new(LocalSigner::new(keypair.clone())));
+
+    let validator_registry = NetworkInfo::from_network_id(NetworkId::UnitAlbatross).unwrap()
+        .validator_registry_address().unwrap().clone();
+    let genesis_hash = blockchain.head_hash();
+
+    // #1: Empty micro block
+    let block1 = producer.next_micro_block(vec![], 1565713920000, 0, vec![0x41], None).unwrap();
+    assert_eq!(blockchain.push(Block::Micro(block1.clone())), Ok(PushResult::Extended));
+
+    // #2: Micro block with a fork proof, which changes the staking contract.
+    let fork_proof = {
+        let header1 = block1.header.clone();
+        let justification1 = block1.justification.signature.clone();
+        let mut header2 = header1.clone();
+        header2.timestamp += 1;
+        let justification2 = keypair.sign(&header2).compress();
+        ForkProof {
+            header1, header2,
+            justification1, justification2,
+        }
+    };
+    let block2 = producer.next_micro_block(vec![fork_proof], 1565713922000, 0, vec![0x41], None).unwrap();
+    assert_eq!(blockchain.push(Block::Micro(block2.clone())), Ok(PushResult::Extended));
+
+    let addresses = vec![validator_registry];
+
+    // Proof for the head.
+    let mut proof = blockchain.get_accounts_proof(&block2.hash(), &addresses).unwrap();
+    assert!(proof.verify());
+    assert_eq!(proof.root_hash(), block2.header.state_root);
+
+    // Proof for a previous block.
+    let mut proof = blockchain.get_accounts_proof(&block1.hash(), &addresses).unwrap();
+    assert!(proof.verify());
+    assert_eq!(proof.root_hash(), block1.header.state_root);
+    assert_ne!(block1.header.state_root, block2.header.state_root);
+
+    // Proof for the last macro block.
+    let macro_state_root = blockchain.macro_head().header.state_root.clone();
+    let mut proof = blockchain.get_accounts_proof(&genesis_hash, &addresses).unwrap();
+    assert!(proof.verify());
+    assert_eq!(proof.root_hash(), macro_state_root);
+
+    // The head must not have changed.
+    assert_eq!(blockchain.head_hash(), block2.hash());
+    assert_eq!(blockchain.state().accounts().hash(None), block2.header.state_root);
+
+    // Micro blocks don't have a transactions root.
+    let mut transaction_addresses = HashSet::new();
+    transaction_addresses.insert(Address::default());
+    assert!(blockchain.get_transactions_proof(&block2.hash(), &transaction_addresses).is_none());
+
+    // Proofs are only created for blocks up to `ACCOUNTS_PROOF_MAX_DEPTH` blocks behind the head.
+    for i in 3..(3 + Blockchain::ACCOUNTS_PROOF_MAX_DEPTH) {
+        let block = producer.next_micro_block(vec![], 1565713920000 + u64::from(i) * 2000, 0, vec![0x41], None).unwrap();
+        assert_eq!(blockchain.push(Block::Micro(block)), Ok(PushResult::Extended));
+    }
+    assert_eq!(blockchain.block_number(), 2 + Blockchain::ACCOUNTS_PROOF_MAX_DEPTH);
+    assert!(blockchain.get_accounts_proof(&genesis_hash, &addresses).is_none());
+    assert!(blockchain.get_accounts_proof(&block1.hash(), &addresses).is_none());
+    let mut proof = blockchain.get_accounts_proof(&block2.hash(), &addresses).unwrap();
+    assert!(proof.verify());
+    assert_eq!(proof.root_hash(), block2.header.state_root);
+}
@@ -6,7 +6,7 @@ extern crate nimiq_hash as hash;
 
 use block_albatross::{ViewChange, ViewChangeProofBuilder, SignedViewChange, PbftPrepareMessage, SignedPbftCommitMessage, PbftCommitMessage};
 use bls::bls12_381::KeyPair;
-use primitives::policy;
+use primitives::policy::AlbatrossPolicy;
 use block_albatross::signed::Message;
 use bls::bls12_381::lazy::LazyPublicKey;
 use beserial::Deserialize;
@@ -28,15 +28,17 @@ fn test_view_change_single_signature() {
         new_view_number: 42
     };
 
+    let policy = AlbatrossPolicy::default();
+
     // sign view change and build view change proof
     let signed_message = SignedViewChange::from_message(view_change.clone(), &key_pair.secret, 0);
     let mut proof_builder = ViewChangeProofBuilder::new();
-    proof_builder.add_signature(&key_pair.public, policy::SLOTS, &signed_message);
+    proof_builder.add_signature(&key_pair.public, policy.slots, &signed_message);
     let view_change_proof = proof_builder.build();
 
     // verify view change proof
-    let validators = ValidatorSlots::new(vec![ValidatorSlotBand::new(LazyPublicKey::from(key_pair.public), policy::SLOTS)]);
-    view_change_proof.verify(&view_change, &validators, policy::TWO_THIRD_SLOTS).unwrap();
+    let validators = ValidatorSlots::new(vec![ValidatorSlotBand::new(LazyPublicKey::from(key_pair.public), policy.slots)]);
+    view_change_proof.verify(&view_change, &validators, policy.two_third_slots()).unwrap();
 }
 
 #[test]
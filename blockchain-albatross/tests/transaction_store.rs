@@ -0,0 +1,219 @@
+use std::sync::Arc;
+
+use beserial::{Deserialize, Serialize};
+use nimiq_account::{Inherent, InherentType};
+use nimiq_block_albatross::{Block, MacroBlock, MacroExtrinsics, PbftCommitMessage, PbftPrepareMessage, PbftProofBuilder, PbftProposal, SignedPbftCommitMessage, SignedPbftPrepareMessage, SignedViewChange, ViewChange, ViewChangeProof, ViewChangeProofBuilder};
+use nimiq_block_production_albatross::BlockProducer;
+use nimiq_blockchain_albatross::blockchain::{Blockchain, PushResult};
+use nimiq_blockchain_base::{AbstractBlockchain, Direction};
+use nimiq_bls::{KeyPair, SecretKey};
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_keys::{Address, KeyPair as AccountKeyPair, SecureGenerate};
+use nimiq_mempool::{Mempool, MempoolConfig, ReturnCode};
+use nimiq_network_primitives::networks::{NetworkId, NetworkInfo};
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::policy::AlbatrossPolicy;
+use nimiq_signer::LocalSigner;
+use nimiq_transaction::{SignatureProof, Transaction};
+
+/// Secret key of validator. Tests run with `network-primitives/src/genesis/unit-albatross.toml`
+const SECRET_KEY: &'static str = "49ea68eb6b8afdf4ca4d4c0a0b295c76ca85225293693bc30e755476492b707f";
+
+/// Policy of the network the tests run with.
+fn policy() -> AlbatrossPolicy {
+    NetworkInfo::from_network_id(NetworkId::UnitAlbatross).unwrap().albatross_policy()
+}
+
+fn validator_key() -> KeyPair {
+    KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap())
+}
+
+// Give `address` some balance without a block, such that it can send transactions.
+fn fund_account(blockchain: &Blockchain, address: &Address) {
+    let inherent = Inherent {
+        ty: InherentType::Reward,
+        target: address.clone(),
+        value: Coin::from_u64_unchecked(10_000),
+        data: vec![],
+    };
+    let mut txn = blockchain.write_transaction();
+    blockchain.state().accounts().commit(&mut txn, &[], &[inherent], 0).unwrap();
+    txn.commit();
+}
+
+fn create_transaction(key: &AccountKeyPair, recipient: &Address, value: u64) -> Transaction {
+    let mut tx = Transaction::new_basic(Address::from(&key.public), recipient.clone(), Coin::from_u64_unchecked(value), Coin::ZERO, 1, NetworkId::UnitAlbatross);
+    let signature_proof = SignatureProof::from(key.public.clone(), key.sign(&tx.serialize_content()));
+    tx.proof = signature_proof.serialize_to_vec();
+    tx
+}
+
+// Fill epoch with micro blocks
+fn fill_micro_blocks(producer: &BlockProducer, blockchain: &Arc<Blockchain>, view_number: u32) {
+    let init_height = blockchain.head_height();
+    let macro_block_number = policy().macro_block_after(init_height + 1);
+    for i in (init_height + 1)..macro_block_number {
+        let last_micro_block = producer.next_micro_block(vec![], 1565713920000 + i as u64 * 2000, view_number, vec![0x42], None).unwrap();
+        assert_eq!(blockchain.push(Block::Micro(last_micro_block)), Ok(PushResult::Extended));
+    }
+    assert_eq!(blockchain.head_height(), macro_block_number - 1);
+}
+
+fn sign_macro_block(proposal: PbftProposal, extrinsics: Option<MacroExtrinsics>) -> MacroBlock {
+    let keypair = validator_key();
+
+    let block_hash = proposal.header.hash::<Blake2bHash>();
+
+    // create signed prepare and commit
+    let prepare = SignedPbftPrepareMessage::from_message(
+        PbftPrepareMessage { block_hash: block_hash.clone() },
+        &keypair.secret,
+        0);
+    let commit = SignedPbftCommitMessage::from_message(
+        PbftCommitMessage { block_hash: block_hash.clone() },
+        &keypair.secret,
+        0);
+
+    // create proof
+    let mut pbft_proof = PbftProofBuilder::new();
+    pbft_proof.add_prepare_signature(&keypair.public, policy().slots, &prepare);
+    pbft_proof.add_commit_signature(&keypair.public, policy().slots, &commit);
+
+    MacroBlock {
+        header: proposal.header,
+        justification: Some(pbft_proof.build()),
+        extrinsics,
+    }
+}
+
+fn sign_view_change(block_number: u32, new_view_number: u32) -> ViewChangeProof {
+    let keypair = validator_key();
+
+    let view_change = ViewChange { block_number, new_view_number };
+    let signed_view_change = SignedViewChange::from_message(view_change.clone(), &keypair.secret, 0);
+
+    let mut proof_builder = ViewChangeProofBuilder::new();
+    proof_builder.add_signature(&keypair.public, policy().slots, &signed_view_change);
+    proof_builder.build()
+}
+
+#[test]
+fn it_stores_transactions_of_pushed_rebranched_and_synced_blocks() {
+    let env = VolatileEnvironment::new(12).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+    let mempool = Mempool::new(Arc::clone(&blockchain), MempoolConfig::default());
+    let producer = BlockProducer::new(Arc::clone(&blockchain), Arc::clone(&mempool), Arc::new(LocalSigner::new(validator_key())));
+    let genesis_hash = blockchain.head_hash();
+
+    let account_key = AccountKeyPair::generate_default_csprng();
+    let sender = Address::from(&account_key.public);
+    fund_account(&blockchain, &sender);
+
+    let recipient = Address::from([2u8; Address::SIZE]);
+    let tx = create_transaction(&account_key, &recipient, 10);
+    let hash: Blake2bHash = tx.hash();
+    assert_eq!(mempool.push_transaction(tx.clone()), ReturnCode::Accepted);
+
+    // #1.0: The transaction is stored when the block is pushed.
+    let block1 = producer.next_micro_block(vec![], 1565713920000, 0, vec![0x41], None).unwrap();
+    assert_eq!(block1.extrinsics.as_ref().unwrap().transactions, vec![tx.clone()]);
+    assert_eq!(blockchain.push(Block::Micro(block1.clone())), Ok(PushResult::Extended));
+
+    let info = blockchain.get_transaction_info_by_hash(&hash).unwrap();
+    assert_eq!(info.block_hash, block1.hash());
+    assert_eq!(info.block_number, 1);
+    assert_eq!(info.index, 0);
+    assert_eq!(info.transaction, tx);
+    assert_eq!(receipt_hashes(&blockchain, &sender), vec![hash.clone()]);
+    assert_eq!(receipt_hashes(&blockchain, &recipient), vec![hash.clone()]);
+
+    // #1.1: A view-changed block without the transaction is produced on a second chain with the
+    // same state and wins over #1.0.
+    let env2 = VolatileEnvironment::new(12).unwrap();
+    let blockchain2 = Arc::new(Blockchain::new(env2.clone(), NetworkId::UnitAlbatross).unwrap());
+    fund_account(&blockchain2, &sender);
+    let producer2 = BlockProducer::new_without_mempool(Arc::clone(&blockchain2), Arc::new(LocalSigner::new(validator_key())));
+    let fork_block = producer2.next_micro_block(vec![], 1565713922000, 1, vec![0x41], Some(sign_view_change(1, 1))).unwrap();
+    assert_eq!(blockchain.push(Block::Micro(fork_block.clone())), Ok(PushResult::Rebranched));
+    assert_eq!(blockchain.head_hash(), fork_block.hash());
+
+    // The reverted transaction is removed from the store and goes back into the mempool.
+    assert!(blockchain.get_transaction_info_by_hash(&hash).is_none());
+    assert!(receipt_hashes(&blockchain, &sender).is_empty());
+    assert!(receipt_hashes(&blockchain, &recipient).is_empty());
+    assert!(mempool.contains(&hash));
+
+    // #2.1: The transaction is included again.
+    fill_micro_blocks(&producer, &blockchain, 1);
+    let info = blockchain.get_transaction_info_by_hash(&hash).unwrap();
+    assert_eq!(info.block_hash, blockchain.get_block_at(2, false).unwrap().hash());
+    assert_eq!(info.block_number, 2);
+    assert_eq!(info.index, 0);
+    assert_eq!(receipt_hashes(&blockchain, &sender), vec![hash.clone()]);
+    assert_eq!(receipt_hashes(&blockchain, &recipient), vec![hash.clone()]);
+
+    let next_block_height = blockchain.head_height() + 1;
+    let (proposal, extrinsics) = producer.next_macro_block_proposal(1565713920000 + next_block_height as u64 * 2000, 1u32, None).unwrap();
+    assert_eq!(blockchain.push_block(Block::Macro(sign_macro_block(proposal, Some(extrinsics))), true), Ok(PushResult::Extended));
+
+    // A third chain syncs the epoch by its macro block. The transaction is attributed to the
+    // macro block, since the micro blocks are not available.
+    let macro_block = blockchain.get_macro_blocks(&genesis_hash, 1, true, Direction::Forward).unwrap().pop().unwrap();
+    let transactions: Vec<Transaction> = blockchain.get_epoch_transactions(1, None).unwrap().collect();
+    assert_eq!(transactions, vec![tx.clone()]);
+
+    let env3 = VolatileEnvironment::new(12).unwrap();
+    let blockchain3 = Arc::new(Blockchain::new(env3.clone(), NetworkId::UnitAlbatross).unwrap());
+    fund_account(&blockchain3, &sender);
+    assert!(blockchain3.get_transaction_info_by_hash(&hash).is_none());
+    assert_eq!(blockchain3.push_isolated_macro_block(macro_block.clone(), &transactions), Ok(PushResult::Extended));
+
+    let info = blockchain3.get_transaction_info_by_hash(&hash).unwrap();
+    assert_eq!(info.block_hash, macro_block.hash());
+    assert_eq!(info.block_number, policy().macro_block_of(1));
+    assert_eq!(info.index, 0);
+    assert_eq!(info.transaction, tx);
+    let receipts = blockchain3.get_transaction_receipts_by_address(&sender, 10, 10);
+    assert_eq!(receipts.len(), 1);
+    assert_eq!(receipts[0].transaction_hash, hash);
+    assert_eq!(receipts[0].block_hash, macro_block.hash());
+    assert_eq!(receipt_hashes(&blockchain3, &recipient), vec![hash]);
+}
+
+fn receipt_hashes(blockchain: &Blockchain, address: &Address) -> Vec<Blake2bHash> {
+    blockchain.get_transaction_receipts_by_address(address, 10, 10).into_iter()
+        .map(|receipt| receipt.transaction_hash)
+        .collect()
+}
+
+#[test]
+fn it_lists_sent_and_received_transactions_most_recent_first() {
+    let env = VolatileEnvironment::new(12).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+    let mempool = Mempool::new(Arc::clone(&blockchain), MempoolConfig::default());
+    let producer = BlockProducer::new(Arc::clone(&blockchain), Arc::clone(&mempool), Arc::new(LocalSigner::new(validator_key())));
+
+    let key_a = AccountKeyPair::generate_default_csprng();
+    let key_b = AccountKeyPair::generate_default_csprng();
+    let address_a = Address::from(&key_a.public);
+    let address_b = Address::from(&key_b.public);
+    fund_account(&blockchain, &address_a);
+    fund_account(&blockchain, &address_b);
+
+    // A sends in #1 and #3 and receives in #2.
+    let transactions = vec![
+        create_transaction(&key_a, &address_b, 1),
+        create_transaction(&key_b, &address_a, 2),
+        create_transaction(&key_a, &address_b, 3),
+    ];
+    for (i, tx) in transactions.iter().enumerate() {
+        assert_eq!(mempool.push_transaction(tx.clone()), ReturnCode::Accepted);
+        let block = producer.next_micro_block(vec![], 1565713920000 + i as u64 * 2000, 0, vec![0x41], None).unwrap();
+        assert_eq!(blockchain.push(Block::Micro(block)), Ok(PushResult::Extended));
+    }
+
+    let hashes: Vec<Blake2bHash> = transactions.iter().map(|tx| tx.hash()).collect();
+    assert_eq!(receipt_hashes(&blockchain, &address_a), vec![hashes[2].clone(), hashes[1].clone(), hashes[0].clone()]);
+    assert_eq!(receipt_hashes(&blockchain, &address_b), vec![hashes[2].clone(), hashes[1].clone(), hashes[0].clone()]);
+}
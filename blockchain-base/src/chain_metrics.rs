@@ -11,6 +11,7 @@ pub struct BlockchainMetrics {
     block_extended_count: AtomicUsize,
     block_rebranched_count: AtomicUsize,
     block_forked_count: AtomicUsize,
+    view_change_count: AtomicUsize,
 }
 
 impl BlockchainMetrics {
@@ -85,4 +86,14 @@ impl BlockchainMetrics {
     pub fn block_forked_count(&self) -> usize {
         self.block_forked_count.load(Ordering::Acquire)
     }
+
+    #[inline]
+    pub fn note_view_changes(&self, count: usize) {
+        self.view_change_count.fetch_add(count, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn view_change_count(&self) -> usize {
+        self.view_change_count.load(Ordering::Acquire)
+    }
 }
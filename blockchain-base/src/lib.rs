@@ -22,8 +22,10 @@ use database::{ReadTransaction, Transaction};
 use database::Environment;
 use hash::Blake2bHash;
 use keys::Address;
+use nimiq_network_primitives::networks::{NetworkInfo, NetworkInfoError};
 use nimiq_network_primitives::time::NetworkTime;
 use primitives::networks::NetworkId;
+use primitives::policy::AlbatrossPolicy;
 use transaction::{TransactionReceipt, TransactionsProof};
 use transaction::Transaction as BlockchainTransaction;
 use tree_primitives::accounts_proof::AccountsProof;
@@ -50,6 +52,9 @@ pub trait AbstractBlockchain: Sized + Send + Sync {
     /// Returns the network ID
     fn network_id(&self) -> NetworkId;
 
+    /// Returns the genesis data and seeds of the network
+    fn network_info(&self) -> &'static NetworkInfo;
+
 
     /// Returns the current head block
     fn head_block(&self) -> MappedRwLockReadGuard<Self::Block>;
@@ -104,6 +109,12 @@ pub trait AbstractBlockchain: Sized + Send + Sync {
 
     fn contains_tx_in_validity_window(&self, tx_hash: &Blake2bHash) -> bool;
 
+    /// Number of blocks a transaction is valid for after its validity start height.
+    fn transaction_validity_window(&self) -> u32;
+
+    /// Policy of an Albatross chain, which staking transactions are checked against.
+    fn albatross_policy(&self) -> Option<AlbatrossPolicy>;
+
 
     /* Required by AccountsChunkCache */
     // TODO Why do we need this? Remove if possible.
@@ -130,8 +141,14 @@ pub enum BlockchainError {
     FailedLoadingMainChain,
     #[fail(display = "Inconsistent chain/accounts state. Reset your consensus database.")]
     InconsistentState,
-    #[fail(display = "No network for: {:?}", _0)]
-    NoNetwork(NetworkId),
+    #[fail(display = "No network: {}", _0)]
+    NoNetwork(#[cause] NetworkInfoError),
+}
+
+impl From<NetworkInfoError> for BlockchainError {
+    fn from(e: NetworkInfoError) -> Self {
+        BlockchainError::NoNetwork(e)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
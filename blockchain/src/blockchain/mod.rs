@@ -19,7 +19,7 @@ use keys::Address;
 use network_primitives::networks::NetworkInfo;
 use network_primitives::time::NetworkTime;
 use primitives::networks::NetworkId;
-use primitives::policy;
+use primitives::policy::{self, AlbatrossPolicy};
 use transaction::{TransactionReceipt, TransactionsProof};
 use transaction::Transaction as BlockchainTransaction;
 use tree_primitives::accounts_proof::AccountsProof;
@@ -41,6 +41,7 @@ pub type BlockchainEvent = blockchain_base::BlockchainEvent<Block>;
 pub struct Blockchain {
     pub(crate) env: Environment,
     pub network_id: NetworkId,
+    pub network_info: &'static NetworkInfo,
     network_time: Arc<NetworkTime>,
     pub notifier: RwLock<Notifier<'static, BlockchainEvent>>,
     pub(crate) chain_store: ChainStore,
@@ -78,16 +79,16 @@ impl BlockchainState {
 
 impl Blockchain {
     pub fn new(env: Environment, network_id: NetworkId, network_time: Arc<NetworkTime>) -> Result<Self, BlockchainError> {
+        let network_info = NetworkInfo::from_network_id(network_id)?;
         let chain_store = ChainStore::new(env.clone());
         Ok(match chain_store.get_head(None) {
-            Some(head_hash) => Blockchain::load(env, network_time, network_id, chain_store, head_hash)?,
-            None => Blockchain::init(env, network_time, network_id, chain_store)?
+            Some(head_hash) => Blockchain::load(env, network_time, network_info, chain_store, head_hash)?,
+            None => Blockchain::init(env, network_time, network_info, chain_store)?
         })
     }
 
-    fn load(env: Environment, network_time: Arc<NetworkTime>, network_id: NetworkId, chain_store: ChainStore, head_hash: Blake2bHash) -> Result<Self, BlockchainError> {
+    fn load(env: Environment, network_time: Arc<NetworkTime>, network_info: &'static NetworkInfo, chain_store: ChainStore, head_hash: Blake2bHash) -> Result<Self, BlockchainError> {
         // Check that the correct genesis block is stored.
-        let network_info = NetworkInfo::from_network_id(network_id);
         let genesis_info = chain_store.get_chain_info(network_info.genesis_hash(), false, None);
 
         if !genesis_info.map(|i| i.on_main_chain).unwrap_or(false) {
@@ -119,7 +120,8 @@ impl Blockchain {
 
         Ok(Blockchain {
             env,
-            network_id,
+            network_id: network_info.network_id(),
+            network_info,
             network_time,
             notifier: RwLock::new(Notifier::new()),
             chain_store,
@@ -140,9 +142,8 @@ impl Blockchain {
         })
     }
 
-    fn init(env: Environment, network_time: Arc<NetworkTime>, network_id: NetworkId, chain_store: ChainStore) -> Result<Self, BlockchainError> {
+    fn init(env: Environment, network_time: Arc<NetworkTime>, network_info: &'static NetworkInfo, chain_store: ChainStore) -> Result<Self, BlockchainError> {
         // Initialize chain & accounts with genesis block.
-        let network_info = NetworkInfo::from_network_id(network_id);
         let genesis_block = network_info.genesis_block::<Block>();
         let main_chain = ChainInfo::initial(genesis_block.clone());
         let head_hash = network_info.genesis_hash().clone();
@@ -176,7 +177,8 @@ impl Blockchain {
 
         Ok(Blockchain {
             env,
-            network_id,
+            network_id: network_info.network_id(),
+            network_info,
             network_time,
             notifier: RwLock::new(Notifier::new()),
             chain_store,
@@ -202,7 +204,7 @@ impl Blockchain {
         assert!(block.body.is_some(), "Block body expected");
 
         // Check (sort of) intrinsic block invariants.
-        let genesis_hash = NetworkInfo::from_network_id(self.network_id).genesis_hash().clone();
+        let genesis_hash = self.network_info.genesis_hash().clone();
         if let Err(e) = block.verify(self.network_time.now(), self.network_id, genesis_hash) {
             warn!("Rejecting block - verification failed ({:?})", e);
             #[cfg(feature = "metrics")]
@@ -664,7 +666,7 @@ impl Blockchain {
         }
 
         // Push the genesis block hash.
-        let genesis_hash = NetworkInfo::from_network_id(self.network_id).genesis_hash();
+        let genesis_hash = self.network_info.genesis_hash();
         if locators.is_empty() || locators.last().unwrap() != genesis_hash {
             // Respect max count, make space for genesis hash if necessary
             if locators.len() >= max_count {
@@ -743,6 +745,10 @@ impl AbstractBlockchain for Blockchain {
         self.network_id
     }
 
+    fn network_info(&self) -> &'static NetworkInfo {
+        self.network_info
+    }
+
     fn head_block(&self) -> MappedRwLockReadGuard<Self::Block> {
         self.head()
     }
@@ -810,6 +816,14 @@ impl AbstractBlockchain for Blockchain {
         self.state.read().transaction_cache.contains(tx_hash)
     }
 
+    fn transaction_validity_window(&self) -> u32 {
+        policy::TRANSACTION_VALIDITY_WINDOW
+    }
+
+    fn albatross_policy(&self) -> Option<AlbatrossPolicy> {
+        None
+    }
+
     fn head_hash_from_store(&self, txn: &ReadTransaction) -> Option<Blake2bHash> {
         self.head_hash_from_store(txn)
     }
@@ -2,7 +2,6 @@ use std::time::Instant;
 
 use database::{Transaction, ReadTransaction};
 use hash::Blake2bHash;
-use network_primitives::networks::NetworkInfo;
 use block::{Block, BlockHeader, Target};
 use block::proof::ChainProof;
 use utils::iterators::Merge;
@@ -87,7 +86,7 @@ impl Blockchain {
         }
 
         if (chain.is_empty() || chain[chain.len() - 1].head.header.height > 1) && tail_height == 1 {
-            let genesis_block = NetworkInfo::from_network_id(self.network_id).genesis_block::<Block>().clone();
+            let genesis_block = self.network_info.genesis_block::<Block>().clone();
             chain.push(ChainInfo::initial(genesis_block.into_light()));
         }
 
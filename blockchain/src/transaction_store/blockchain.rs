@@ -24,6 +24,11 @@ impl Blockchain {
         receipts = self.transaction_store.get_by_sender(address, sender_limit, Some(&txn));
         receipts.extend(self.transaction_store.get_by_recipient(address, recipient_limit, Some(&txn)));
 
+        // Merge sent and received transactions, most recent first. Transactions to oneself are
+        // found in both indexes.
+        receipts.sort_unstable_by(|a, b| (b.block_height, b.index).cmp(&(a.block_height, a.index)));
+        receipts.dedup_by(|a, b| a.transaction_hash == b.transaction_hash);
+
         receipts.drain(..).map(TransactionReceipt::from).collect()
     }
 
@@ -135,7 +135,7 @@ impl<'bc> BlockBuilder<'bc> {
             self.interlink = Some(head.get_next_interlink(&next_target));
         }
 
-        let info = NetworkInfo::from_network_id(self.blockchain.network_id);
+        let info = NetworkInfo::from_network_id(self.blockchain.network_id).unwrap();
         self.header.interlink_hash = self.interlink.as_ref().unwrap().hash(info.genesis_hash().clone());
 
         // XXX Use default accounts hash if body fails to apply.
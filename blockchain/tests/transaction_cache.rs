@@ -16,7 +16,7 @@ fn it_can_push_blocks() {
     assert!(cache.is_empty());
     assert_eq!(cache.missing_blocks(), policy::TRANSACTION_VALIDITY_WINDOW);
 
-    let mut block = NetworkInfo::from_network_id(NetworkId::Main).genesis_block::<Block>().clone();
+    let mut block = NetworkInfo::from_network_id(NetworkId::Main).unwrap().genesis_block::<Block>().clone();
     let mut hash = block.header.hash::<Blake2bHash>();
     let tail_hash = hash.clone();
 
@@ -76,7 +76,7 @@ fn it_can_push_blocks() {
 #[test]
 fn it_can_revert_blocks() {
     let mut cache = TransactionCache::new();
-    let mut block = NetworkInfo::from_network_id(NetworkId::Main).genesis_block::<Block>().clone();
+    let mut block = NetworkInfo::from_network_id(NetworkId::Main).unwrap().genesis_block::<Block>().clone();
     let mut hash = block.header.hash::<Blake2bHash>();
     cache.push_block(&block);
 
@@ -148,7 +148,7 @@ fn it_can_revert_blocks() {
 #[test]
 fn it_removes_blocks_outside_the_validity_window() {
     let mut cache = TransactionCache::new();
-    let mut block = NetworkInfo::from_network_id(NetworkId::Main).genesis_block::<Block>().clone();
+    let mut block = NetworkInfo::from_network_id(NetworkId::Main).unwrap().genesis_block::<Block>().clone();
     let mut hash = block.header.hash::<Blake2bHash>();
     cache.push_block(&block);
 
@@ -210,7 +210,7 @@ fn it_removes_blocks_outside_the_validity_window() {
 #[test]
 fn it_can_prepend_blocks() {
     let mut cache = TransactionCache::new();
-    let mut block = NetworkInfo::from_network_id(NetworkId::Main).genesis_block::<Block>().clone();
+    let mut block = NetworkInfo::from_network_id(NetworkId::Main).unwrap().genesis_block::<Block>().clone();
     let mut hash = block.header.hash::<Blake2bHash>();
 
     let mut blocks = vec![block.clone()];
@@ -75,7 +75,7 @@ fn it_can_store_and_remove_transactions() {
     let (tx1, tx2, tx3) = create_transactions();
 
     // Block 1 (empty)
-    let block1 = NetworkInfo::from_network_id(NetworkId::Main).genesis_block::<Block>().clone();
+    let block1 = NetworkInfo::from_network_id(NetworkId::Main).unwrap().genesis_block::<Block>().clone();
     let infos1 = TransactionInfo::from_block(&block1);
     assert_eq!(infos1.len(), 0);
 
@@ -72,6 +72,15 @@ impl<E: Engine> SecretKey<E> {
     }
 }
 
+/// The zero key. It is only used to clear secret keys from memory, e.g. in encrypted key files.
+impl<E: Engine> Default for SecretKey<E> {
+    fn default() -> Self {
+        SecretKey {
+            x: E::Fr::zero(),
+        }
+    }
+}
+
 impl<E: Engine> SecureGenerate for SecretKey<E> {
     fn generate<R: Rng + CryptoRng>(rng: &mut R) -> Self {
         SecretKey {
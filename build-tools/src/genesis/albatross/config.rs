@@ -11,6 +11,7 @@ use bls::bls12_381::{
 };
 use keys::Address;
 use primitives::coin::Coin;
+use primitives::policy::AlbatrossPolicy;
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct GenesisConfig {
@@ -31,6 +32,35 @@ pub struct GenesisConfig {
     #[serde(default)]
     #[serde(deserialize_with = "deserialize_nimiq_address_opt")]
     pub staking_contract: Option<Address>,
+
+    #[serde(default)]
+    pub policy: Option<GenesisPolicy>,
+
+    /// Network ID of a custom network. Must be at least `NetworkId::CUSTOM_ALBATROSS_MIN`.
+    pub network_id: Option<u8>,
+}
+
+/// Consensus parameters of the network. Parameters that are not set take their default value.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct GenesisPolicy {
+    pub epoch_length: Option<u32>,
+    pub slots: Option<u16>,
+    pub min_stake: Option<u64>,
+    pub unstaking_delay: Option<u32>,
+    pub transaction_validity_window: Option<u32>,
+}
+
+impl From<GenesisPolicy> for AlbatrossPolicy {
+    fn from(policy: GenesisPolicy) -> Self {
+        let default = AlbatrossPolicy::default();
+        AlbatrossPolicy {
+            epoch_length: policy.epoch_length.unwrap_or(default.epoch_length),
+            slots: policy.slots.unwrap_or(default.slots),
+            min_stake: policy.min_stake.unwrap_or(default.min_stake),
+            unstaking_delay: policy.unstaking_delay.unwrap_or(default.unstaking_delay),
+            transaction_validity_window: policy.transaction_validity_window.unwrap_or(default.transaction_validity_window),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -46,7 +76,11 @@ pub struct GenesisStake {
     pub balance: Coin,
 
     #[serde(deserialize_with = "deserialize_bls_public_key")]
-    pub validator_key: BlsPublicKey
+    pub validator_key: BlsPublicKey,
+
+    /// Commission of the validator in basis points.
+    #[serde(default)]
+    pub commission: u16,
 }
 
 #[derive(Clone, Debug, Deserialize)]
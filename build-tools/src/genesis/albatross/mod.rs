@@ -21,6 +21,9 @@ use database::WriteTransaction;
 use hash::{Blake2bHash, Blake2bHasher, Hash, Hasher};
 use keys::Address;
 use primitives::coin::Coin;
+use primitives::networks::NetworkId;
+use primitives::policy::AlbatrossPolicy;
+use primitives::slot;
 use vrf::VrfSeed;
 
 mod config;
@@ -43,6 +46,12 @@ pub enum GenesisBuilderError {
     StakingError(#[cause] AccountError),
     #[fail(display = "Database error")]
     DatabaseError(#[cause] VolatileDatabaseError),
+    #[fail(display = "Invalid policy: {:?}", _0)]
+    InvalidPolicy(AlbatrossPolicy),
+    #[fail(display = "Policy has {} slots, but slot allocations are encoded with {} slots", _0, _1)]
+    SlotsMismatch(u16, u16),
+    #[fail(display = "Invalid network ID for a custom network: {}", _0)]
+    InvalidNetworkId(u8),
 }
 
 impl From<SerializingError> for GenesisBuilderError {
@@ -91,6 +100,8 @@ pub struct GenesisBuilder {
     pub stakes: Vec<config::GenesisStake>,
     pub accounts: Vec<config::GenesisAccount>,
     pub staking_contract_address: Option<Address>,
+    pub policy: Option<AlbatrossPolicy>,
+    pub network_id: Option<NetworkId>,
 }
 
 impl GenesisBuilder {
@@ -114,12 +125,23 @@ impl GenesisBuilder {
         self
     }
 
+    pub fn with_policy(&mut self, policy: AlbatrossPolicy) -> &mut Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    pub fn with_network_id(&mut self, network_id: NetworkId) -> &mut Self {
+        self.network_id = Some(network_id);
+        self
+    }
+
     pub fn with_genesis_stake(&mut self, staker_address: Address, reward_address: Option<Address>, validator_key: BlsPublicKey, balance: Coin) -> &mut Self {
         self.stakes.push(config::GenesisStake {
             staker_address,
             reward_address,
             validator_key,
-            balance
+            balance,
+            commission: 0,
         });
         self
     }
@@ -140,12 +162,21 @@ impl GenesisBuilder {
             mut stakes,
             mut accounts,
             staking_contract,
+            policy,
+            network_id,
         } = toml::from_str(&read_to_string(path)?)?;
 
+        if let Some(id) = network_id {
+            let network_id = NetworkId::custom_albatross(id)
+                .ok_or(GenesisBuilderError::InvalidNetworkId(id))?;
+            self.with_network_id(network_id);
+        }
+
         signing_key.map(|skey| self.with_signing_key(skey));
         seed_message.map(|msg| self.with_seed_message(msg));
         timestamp.map(|t| self.with_timestamp(t));
         staking_contract.map(|address| self.with_staking_contract_address(address));
+        policy.map(|policy| self.with_policy(policy.into()));
         self.stakes.append(&mut stakes);
         self.accounts.append(&mut accounts);
 
@@ -155,6 +186,15 @@ impl GenesisBuilder {
     pub fn generate(&self) -> Result<GenesisInfo, GenesisBuilderError> {
         let timestamp = self.timestamp.unwrap_or_else(Utc::now);
 
+        // the slot selection depends on the policy
+        let policy = self.policy.unwrap_or_default();
+        if !policy.is_valid() {
+            return Err(GenesisBuilderError::InvalidPolicy(policy));
+        }
+        if policy.slots != slot::num_slots() {
+            return Err(GenesisBuilderError::SlotsMismatch(policy.slots, slot::num_slots()));
+        }
+
         // generate seeds
         let signing_key = self.signing_key.as_ref().ok_or(GenesisBuilderError::NoSigningKey)?;
         // random message used as seed for VRF that generates pre-genesis seed
@@ -175,7 +215,7 @@ impl GenesisBuilder {
         debug!("Staking contract: {:#?}", staking_contract);
 
         // generate slot allocation from staking contract
-        let slots = staking_contract.select_validators(&seed);
+        let slots = staking_contract.select_validators(&seed, &policy);
         debug!("Slots: {:#?}", slots);
 
         // extrinsics
@@ -237,10 +277,17 @@ impl GenesisBuilder {
     fn generate_staking_contract(&self) -> Result<StakingContract, GenesisBuilderError> {
         let mut contract = StakingContract::default();
 
+        // Every genesis staker runs its own validator.
         for stake in self.stakes.iter() {
-            contract.stake(&stake.staker_address, stake.balance, stake.validator_key.compress(), stake.reward_address.clone())?;
+            if contract.get_validator(&stake.staker_address).is_none() {
+                contract.register_validator(&stake.staker_address, stake.validator_key.compress(), stake.reward_address.clone(), stake.commission)?;
+            }
+            contract.stake(&stake.staker_address, stake.balance, &stake.staker_address)?;
         }
 
+        // The validators of the first epoch are selected from the genesis contract.
+        contract.snapshot_delegations();
+
         Ok(contract)
     }
 
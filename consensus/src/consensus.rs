@@ -13,6 +13,7 @@ use blockchain_base::{AbstractBlockchain, BlockchainEvent};
 use database::Environment;
 use macros::upgrade_weak;
 use mempool::{Mempool, MempoolEvent, MempoolConfig};
+use mempool::store::MempoolStore;
 use network::{Network, NetworkConfig, NetworkEvent, Peer};
 use network_primitives::networks::NetworkId;
 use network_primitives::time::NetworkTime;
@@ -73,7 +74,12 @@ impl<P: ConsensusProtocol> Consensus<P> {
     pub fn new(env: Environment, network_id: NetworkId, network_config: NetworkConfig, mempool_config: MempoolConfig) -> Result<Arc<Self>, Error> {
         let network_time = Arc::new(NetworkTime::new());
         let blockchain = Arc::new(<P::Blockchain as AbstractBlockchain>::new(env.clone(), network_id, Arc::clone(&network_time))?);
-        let mempool = Mempool::new(Arc::clone(&blockchain), mempool_config);
+        let mempool_store = if mempool_config.persistent {
+            Some(MempoolStore::new(env.clone()))
+        } else {
+            None
+        };
+        let mempool = Mempool::with_store(Arc::clone(&blockchain), mempool_config, mempool_store);
         let network = Network::new(Arc::clone(&blockchain), network_config, network_time, network_id)?;
         let accounts_chunk_cache = AccountsChunkCache::new(env.clone(), Arc::clone(&blockchain));
 
@@ -124,6 +130,7 @@ impl<P: ConsensusProtocol> Consensus<P> {
                 MempoolEvent::TransactionRestored(transaction) => this.on_transaction_added(transaction),
                 MempoolEvent::TransactionEvicted(transaction) => this.on_transaction_removed(transaction),
                 MempoolEvent::TransactionMined(transaction) => this.on_transaction_removed(transaction),
+                MempoolEvent::TransactionReplaced(transaction, _) => this.on_transaction_removed(transaction),
             }
         });
 
@@ -15,8 +15,8 @@ use mempool::{Mempool, ReturnCode};
 use network::connection::close_type::CloseType;
 use network::Peer;
 use network_messages::{
-    GetBlockProofMessage,
     GetBlocksMessage,
+    GetEpochProofMessage,
     GetEpochTransactionsMessage,
     MessageType,
     RejectMessage,
@@ -80,8 +80,15 @@ pub struct ConsensusAgentState {
     /// Rate limit for AccountsProof messages.
     accounts_proof_limit: RateLimit,
 
+    /// Rate limit for AccountsProof messages for blocks other than the head, which are more
+    /// expensive to answer.
+    historic_accounts_proof_limit: RateLimit,
+
     /// Rate limit for GetEpochTransactions messages.
     epoch_transactions_limit: RateLimit,
+
+    /// Rate limit for GetEpochProof messages.
+    epoch_proof_limit: RateLimit,
 }
 
 #[derive(Ord, PartialOrd, PartialEq, Eq, Hash, Clone, Copy, Debug)]
@@ -120,7 +127,9 @@ impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
     const TRANSACTION_RECEIPTS_RATE_LIMIT: usize = 30; // per minute
     const TRANSACTIONS_PROOF_RATE_LIMIT: usize = 60; // per minute
     const ACCOUNTS_PROOF_RATE_LIMIT: usize = 60; // per minute
+    const HISTORIC_ACCOUNTS_PROOF_RATE_LIMIT: usize = 10; // per minute
     const EPOCH_TRANSACTIONS_RATE_LIMIT: usize = 100; // per minute
+    const EPOCH_PROOF_RATE_LIMIT: usize = 30; // per minute
 
     /// Minimum time to wait before triggering the initial mempool request.
     const MEMPOOL_DELAY_MIN: u64 = 2 * 1000; // in ms
@@ -154,7 +163,9 @@ impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
                 transaction_receipts_limit: RateLimit::new_per_minute(Self::TRANSACTION_RECEIPTS_RATE_LIMIT),
                 transactions_proof_limit: RateLimit::new_per_minute(Self::TRANSACTIONS_PROOF_RATE_LIMIT),
                 accounts_proof_limit: RateLimit::new_per_minute(Self::ACCOUNTS_PROOF_RATE_LIMIT),
+                historic_accounts_proof_limit: RateLimit::new_per_minute(Self::HISTORIC_ACCOUNTS_PROOF_RATE_LIMIT),
                 epoch_transactions_limit: RateLimit::new_per_minute(Self::EPOCH_TRANSACTIONS_RATE_LIMIT),
+                epoch_proof_limit: RateLimit::new_per_minute(Self::EPOCH_PROOF_RATE_LIMIT),
             }),
 
             notifier: RwLock::new(Notifier::new()),
@@ -196,6 +207,9 @@ impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
         msg_notifier.get_epoch_transactions.write().register(weak_passthru_listener(
             Arc::downgrade(this),
             |this, msg: GetEpochTransactionsMessage| this.on_get_epoch_transactions(msg)));
+        msg_notifier.get_epoch_proof.write().register(weak_passthru_listener(
+            Arc::downgrade(this),
+            |this, msg: GetEpochProofMessage| this.on_get_epoch_proof(msg)));
     }
 
     pub fn relay_block(&self, block: &<P::Blockchain as AbstractBlockchain>::Block) -> bool {
@@ -320,18 +334,6 @@ impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
             Self::GET_BLOCKS_TIMEOUT);
     }
 
-    fn on_get_chain_proof(&self) {
-        // TODO: Implement
-        // XXX This will be used here, so remove the warning
-        let _ = self.state.read().chain_proof_limit;
-    }
-
-    fn on_get_block_proof(&self, _message: GetBlockProofMessage) {
-        // TODO: Implement
-        // XXX This will be used here, so remove the warning
-        let _ = self.state.read().block_proof_limit;
-    }
-
     fn on_inventory_event(&self, event: &InventoryEvent<<<P::Blockchain as AbstractBlockchain>::Block as Block>::Error>) {
         match event {
             InventoryEvent::KnownBlockAnnounced(hash) => self.on_known_block_announced(hash),
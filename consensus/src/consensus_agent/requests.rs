@@ -6,15 +6,17 @@ use tokio::prelude::*;
 
 use blockchain_base::AbstractBlockchain;
 use hash::Blake2bHash;
+use network::connection::close_type::CloseType;
 use network_messages::{
     AccountsProofMessage,
     AccountsTreeChunkData,
-    //GetBlockProofMessage,
-    //BlockProofMessage,
     AccountsTreeChunkMessage,
+    BlockProofMessage,
     EpochTransactionsMessage,
     GetAccountsProofMessage,
     GetAccountsTreeChunkMessage,
+    GetBlockProofMessage,
+    GetEpochProofMessage,
     GetEpochTransactionsMessage,
     GetTransactionReceiptsMessage,
     GetTransactionsProofMessage,
@@ -29,31 +31,30 @@ use crate::consensus_agent::ConsensusAgent;
 use crate::ConsensusProtocol;
 
 impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
-    // FIXME
-//    pub(super) fn on_get_chain_proof(&self) {
-//        trace!("[GET-CHAIN-PROOF] from {}", self.peer.peer_address());
-//        if !self.state.write().chain_proof_limit.note_single() {
-//            warn!("Rejecting GetChainProof message - rate-limit exceeded");
-//            self.peer.channel.close(CloseType::RateLimitExceeded);
-//            return;
-//        }
-//
-//        let chain_proof = self.blockchain.get_chain_proof();
-//        self.peer.channel.send_or_close(Message::ChainProof(Box::new(chain_proof)));
-//    }
-
-    // FIXME
-//    pub(super) fn on_get_block_proof(&self, msg: GetBlockProofMessage) {
-//        trace!("[GET-BLOCK-PROOF] from {}", self.peer.peer_address());
-//        if !self.state.write().block_proof_limit.note_single() {
-//            warn!("Rejecting GetBlockProof message - rate-limit exceeded");
-//            self.peer.channel.send_or_close(BlockProofMessage::empty());
-//            return;
-//        }
-//
-//        let block_proof = self.blockchain.get_block_proof(&msg.block_hash_to_prove, &msg.known_block_hash);
-//        self.peer.channel.send_or_close(BlockProofMessage::new(block_proof));
-//    }
+    pub(super) fn on_get_chain_proof(&self) {
+        trace!("[GET-CHAIN-PROOF] from {}", self.peer.peer_address());
+        if !self.state.write().chain_proof_limit.note_single() {
+            warn!("Rejecting GetChainProof message - rate-limit exceeded");
+            self.peer.channel.close(CloseType::RateLimitExceeded);
+            return;
+        }
+
+        match P::chain_proof_message(&self.blockchain) {
+            Some(msg) => self.peer.channel.send_or_close(msg),
+            None => debug!("[GET-CHAIN-PROOF] Chain proofs are not supported by this protocol"),
+        }
+    }
+
+    pub(super) fn on_get_block_proof(&self, msg: GetBlockProofMessage) {
+        trace!("[GET-BLOCK-PROOF] from {}", self.peer.peer_address());
+        if !self.state.write().block_proof_limit.note_single() {
+            warn!("Rejecting GetBlockProof message - rate-limit exceeded");
+            self.peer.channel.send_or_close(BlockProofMessage::empty());
+            return;
+        }
+
+        self.peer.channel.send_or_close(P::block_proof_message(&self.blockchain, &msg));
+    }
 
     pub(super) fn on_get_transaction_receipts(&self, msg: GetTransactionReceiptsMessage) {
         trace!("[GET-TRANSACTION-RECEIPTS] from {}", self.peer.peer_address());
@@ -91,8 +92,16 @@ impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
 
         // TODO: This is a deviation from the JavaScript client. If the given hash is the 0 hash, assume the current head.
         let mut hash = msg.block_hash;
+        let head_hash = self.blockchain.head_hash();
         if hash == Blake2bHash::default() {
-            hash = self.blockchain.head_hash();
+            hash = head_hash.clone();
+        }
+
+        // Proofs for previous blocks require reverting blocks.
+        if hash != head_hash && !self.state.write().historic_accounts_proof_limit.note_single() {
+            warn!("Rejecting GetAccountsProof message for previous block - rate-limit exceeded");
+            self.peer.channel.send_or_close(AccountsProofMessage::new(hash, None));
+            return;
         }
 
         let proof = self.blockchain.get_accounts_proof(&hash, &msg.addresses);
@@ -143,4 +152,163 @@ impl<P: ConsensusProtocol + 'static> ConsensusAgent<P> {
             ));
         }
     }
+
+    pub(super) fn on_get_epoch_proof(&self, msg: GetEpochProofMessage) {
+        trace!("[GET-EPOCH-PROOF] from {}", self.peer.peer_address());
+        if !self.state.write().epoch_proof_limit.note_single() {
+            warn!("Rejecting GetEpochProof message - rate-limit exceeded");
+            self.peer.channel.close(CloseType::RateLimitExceeded);
+            return;
+        }
+
+        match P::epoch_proof_message(&self.blockchain, &msg) {
+            Some(msg) => self.peer.channel.send_or_close(msg),
+            None => debug!("[GET-EPOCH-PROOF] Epoch proofs are not supported by this protocol"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures::sync::mpsc::{unbounded, UnboundedReceiver};
+
+    use beserial::{Deserialize, Serialize};
+    use blockchain::{Blockchain, PushResult};
+    use blockchain_albatross::Blockchain as AlbatrossBlockchain;
+    use database::Environment;
+    use database::volatile::VolatileEnvironment;
+    use hash::Hash;
+    use mempool::{Mempool, MempoolConfig};
+    use network::connection::network_connection::AddressInfo;
+    use network::peer_channel::PeerChannel;
+    use network::Peer;
+    use network::websocket::Message as WebSocketMessage;
+    use network_primitives::networks::{NetworkId, NetworkInfo};
+    use network_primitives::time::NetworkTime;
+    use nimiq_block::Block;
+
+    use crate::{AlbatrossConsensusProtocol, NimiqConsensusProtocol};
+    use crate::accounts_chunk_cache::AccountsChunkCache;
+    use crate::inventory::InventoryManager;
+
+    use super::*;
+
+    // Blocks 2 to 4 of the main network.
+    const BLOCKS: [&str; 3] = [
+        "0001264aaf8a4f9828a76c550635da078eb466306a189fcc03710bee9f649c869d120492e3986e75ac0d1466b5d6a7694c86839767a30980f8ba0d8c6e48631bc9cdd8a3eb957567d76963ad10d11e65453f763928fb9619e5f396a0906e946cce3ca7fcbb5fb2e35055de071e868381ba426a8d79d97cb48dab8345baeb9a9abb091f010000000000025ad23a98000046fe0180010000000000000000000000000000000000000000184d696e65642077697468206c6f766520627920526963687900000000",
+        "0001bab534467866d83060b1af0b3493dd0f97d7071b16e1562cf4b18bdf73e71ccb4aa1fea2b8cdf2a63411776c6391a7659aef4dd25317a615499c7b461e9a0405385dbed68e76f74317cc6f4cd40db832eb71b8338fad024ddbb88f9abc79f199dd6a3500aeb5479eb460afeab3363783e243a6e551536c3c01c8fca21d7afbbb1f00fddd000000035ad23a980000968102c0010000000000000000000000000000000000000000184d696e65642077697468206c6f76652062792054616d6d6f00000000",
+        "0001622b0536bbe764a5723f17cde03d2fa2b67a3f42f7cab082c72222eb1e48db7a607f7686d7636b500cfa620567ede30a15a12f69e22d35dd004bbdbfcaefc12520428a900c8dfb339b99aebb1d14cc4d5cebedf562aa1806f272deecbf3c5263b62534d1cda41d1a7bf70a6850c6c82936adb9b2ef66b7421ca3c55664c1417f1f00fbb7000000045ad23a9800022dc60280bab534467866d83060b1af0b3493dd0f97d7071b16e1562cf4b18bdf73e71ccb0100000000000000000000000000000000000000001b4d696e65642077697468206c6f7665206279204372697374696e6100000000",
+    ];
+
+    fn powchain() -> (Arc<Blockchain>, Environment) {
+        let env = VolatileEnvironment::new(10).unwrap();
+        let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::Main, Arc::new(NetworkTime::new())).unwrap());
+        for block in BLOCKS.iter() {
+            let block = Block::deserialize_from_vec(&hex::decode(block).unwrap()).unwrap();
+            assert_eq!(blockchain.push(block), Ok(PushResult::Extended));
+        }
+        (blockchain, env)
+    }
+
+    fn albatross_chain() -> (Arc<AlbatrossBlockchain>, Environment) {
+        let env = VolatileEnvironment::new(12).unwrap();
+        let blockchain = Arc::new(AlbatrossBlockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+        (blockchain, env)
+    }
+
+    /// Creates an agent for a peer whose messages from us end up in the returned receiver.
+    fn new_agent<P: ConsensusProtocol + 'static>(blockchain: Arc<P::Blockchain>, env: Environment) -> (Arc<ConsensusAgent<P>>, UnboundedReceiver<WebSocketMessage>) {
+        let (tx, rx) = unbounded();
+        let peer_address = NetworkInfo::from_network_id(NetworkId::Main).unwrap().seed_peers()[0].clone();
+        let channel = PeerChannel::from_sink(tx, AddressInfo::new(None, Some(Arc::new(peer_address))));
+        let peer = Arc::new(Peer::new(Arc::new(channel), 1, blockchain.head_hash(), 0, None));
+
+        let mempool = Mempool::new(Arc::clone(&blockchain), MempoolConfig::default());
+        let accounts_chunk_cache = AccountsChunkCache::new(env, Arc::clone(&blockchain));
+        let agent = ConsensusAgent::new(blockchain, mempool, InventoryManager::new(), accounts_chunk_cache, peer);
+        (agent, rx)
+    }
+
+    /// Returns the next message that was sent to the peer, if any.
+    fn sent_message(rx: &mut UnboundedReceiver<WebSocketMessage>) -> Option<Message> {
+        match future::lazy(|| rx.poll()).wait() {
+            Ok(Async::Ready(Some(WebSocketMessage::Message(msg)))) => Some(msg),
+            _ => None,
+        }
+    }
+
+    fn get_block_proof(block_hash_to_prove: Blake2bHash, known_block_hash: Blake2bHash) -> Message {
+        Message::GetBlockProof(Box::new(GetBlockProofMessage {
+            block_hash_to_prove,
+            known_block_hash,
+        }))
+    }
+
+    #[test]
+    fn it_serves_chain_proofs_on_the_powchain() {
+        let (blockchain, env) = powchain();
+        let (agent, mut rx) = new_agent::<NimiqConsensusProtocol>(Arc::clone(&blockchain), env);
+
+        agent.peer.channel.msg_notifier.notify(Message::GetChainProof);
+        match sent_message(&mut rx) {
+            Some(Message::ChainProof(proof)) => {
+                assert_eq!(proof.suffix.last().unwrap().hash::<Blake2bHash>(), blockchain.head_hash());
+                assert_eq!(proof.serialize_to_vec(), blockchain.get_chain_proof().serialize_to_vec());
+            },
+            msg => panic!("Expected a chain proof, got {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn it_serves_block_proofs_on_the_powchain() {
+        let (blockchain, env) = powchain();
+        let (agent, mut rx) = new_agent::<NimiqConsensusProtocol>(Arc::clone(&blockchain), env);
+
+        let block_2 = Block::deserialize_from_vec(&hex::decode(BLOCKS[0]).unwrap()).unwrap();
+        let hash_to_prove: Blake2bHash = block_2.header.hash();
+        let known_hash = blockchain.head_hash();
+
+        agent.peer.channel.msg_notifier.notify(get_block_proof(hash_to_prove.clone(), known_hash.clone()));
+        match sent_message(&mut rx) {
+            Some(Message::BlockProof(msg)) => {
+                let proof = msg.proof.expect("Expected a block proof");
+                assert!(proof.iter().any(|block| block.header.hash::<Blake2bHash>() == hash_to_prove));
+                assert_eq!(Some(proof), blockchain.get_block_proof(&hash_to_prove, &known_hash));
+            },
+            msg => panic!("Expected a block proof, got {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn it_answers_block_proof_requests_with_an_empty_proof_on_albatross() {
+        let (blockchain, env) = albatross_chain();
+        let (agent, mut rx) = new_agent::<AlbatrossConsensusProtocol>(Arc::clone(&blockchain), env);
+
+        let head_hash = blockchain.head_hash();
+        agent.peer.channel.msg_notifier.notify(get_block_proof(head_hash.clone(), head_hash));
+        match sent_message(&mut rx) {
+            Some(Message::BlockProof(msg)) => assert!(msg.proof.is_none()),
+            msg => panic!("Expected an empty block proof, got {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn it_sends_nothing_for_unsupported_proofs() {
+        let (blockchain, env) = albatross_chain();
+        assert!(AlbatrossConsensusProtocol::chain_proof_message(&blockchain).is_none());
+        let (agent, mut rx) = new_agent::<AlbatrossConsensusProtocol>(blockchain, env);
+        agent.peer.channel.msg_notifier.notify(Message::GetChainProof);
+        assert!(sent_message(&mut rx).is_none());
+        assert!(!agent.peer.channel.closed());
+
+        let (blockchain, env) = powchain();
+        let msg = GetEpochProofMessage { known_macro_hash: blockchain.head_hash() };
+        assert!(NimiqConsensusProtocol::epoch_proof_message(&blockchain, &msg).is_none());
+        let (agent, mut rx) = new_agent::<NimiqConsensusProtocol>(blockchain, env);
+        agent.peer.channel.msg_notifier.notify(Message::GetEpochProof(Box::new(msg)));
+        assert!(sent_message(&mut rx).is_none());
+        assert!(!agent.peer.channel.closed());
+    }
 }
@@ -7,16 +7,17 @@ use std::time::Duration;
 use parking_lot::{RwLock, RwLockUpgradableReadGuard};
 
 use block_albatross::Block as AlbatrossBlock;
+use block_albatross::MacroHeader;
 use block_albatross::BlockError as AlbatrossBlockError;
 use block_base::{Block, BlockError};
 use blockchain_albatross::Blockchain as AlbatrossBlockchain;
 use blockchain_base::{AbstractBlockchain, PushError, PushResult};
-use hash::Blake2bHash;
+use hash::{Blake2bHash, Hash};
 use macros::upgrade_weak;
 use network::connection::close_type::CloseType;
 use network::peer::Peer;
-use network_messages::{EpochTransactionsMessage, GetBlocksDirection, GetBlocksMessage, GetEpochTransactionsMessage};
-use primitives::policy;
+use network_messages::{EpochProofMessage, EpochTransactionsMessage, GetBlocksDirection, GetBlocksMessage, GetEpochProofMessage, GetEpochTransactionsMessage};
+use primitives::slot::ValidatorSlots;
 use transaction::Transaction;
 use utils::merkle::partial::PartialMerkleProofResult;
 use utils::mutable_once::MutableOnce;
@@ -30,6 +31,7 @@ pub trait SyncProtocol<B: AbstractBlockchain>: Send + Sync {
     fn request_blocks(&self, locators: Vec<Blake2bHash>, max_results: u16);
     fn on_block(&self, block: B::Block);
     fn on_epoch_transactions(&self, epoch_transactions: EpochTransactionsMessage);
+    fn on_epoch_proof(&self, epoch_proof: EpochProofMessage);
     fn on_no_new_objects_announced(&self) {}
     fn on_all_objects_received(&self) {}
     fn register_listener<L: PassThroughListener<SyncEvent<<B::Block as Block>::Error>> + 'static>(&self, listener: L);
@@ -79,6 +81,11 @@ impl<B: AbstractBlockchain> SyncProtocol<B> for FullSync<B> {
         self.peer.channel.close(CloseType::UnexpectedEpochTransactions);
     }
 
+    fn on_epoch_proof(&self, _epoch_proof: EpochProofMessage) {
+        warn!("We didn't expect an epoch proof from {} - discarding and closing the channel", self.peer.peer_address());
+        self.peer.channel.close(CloseType::UnexpectedEpochProof);
+    }
+
     fn register_listener<L: PassThroughListener<SyncEvent<<B::Block as Block>::Error>> + 'static>(&self, listener: L) {
         self.notifier.write().register(listener)
     }
@@ -160,7 +167,7 @@ impl MacroBlockSync {
         if !state.processing_epoch && !state.block_cache.is_empty() {
             state.processing_epoch = true;
             let block = state.block_cache.front().unwrap();
-            let epoch = policy::epoch_at(block.block_number());
+            let epoch = self.blockchain.policy.epoch_at(block.block_number());
 
             // Set timeout.
             let weak = self.self_weak.clone();
@@ -261,7 +268,7 @@ impl SyncProtocol<AlbatrossBlockchain> for MacroBlockSync {
         let expected_root;
         match state.block_cache.front() {
             Some(AlbatrossBlock::Macro(ref macro_block)) => {
-                if policy::epoch_at(macro_block.header.block_number) != epoch_transactions.epoch {
+                if self.blockchain.policy.epoch_at(macro_block.header.block_number) != epoch_transactions.epoch {
                     warn!("We didn't expect any transactions for epoch {} from {} - discarding and closing the channel", epoch_transactions.epoch, self.peer.peer_address());
                     self.peer.channel.close(CloseType::UnexpectedEpochTransactions);
                     return;
@@ -320,6 +327,11 @@ impl SyncProtocol<AlbatrossBlockchain> for MacroBlockSync {
         }
     }
 
+    fn on_epoch_proof(&self, _epoch_proof: EpochProofMessage) {
+        warn!("We didn't expect an epoch proof from {} - discarding and closing the channel", self.peer.peer_address());
+        self.peer.channel.close(CloseType::UnexpectedEpochProof);
+    }
+
     fn on_no_new_objects_announced(&self) {
         let mut state = self.state.write();
         match state.phase {
@@ -342,3 +354,183 @@ impl SyncProtocol<AlbatrossBlockchain> for MacroBlockSync {
     }
 }
 
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum EpochProofSyncPhase {
+    EpochProof,
+    Finished,
+}
+
+struct EpochProofSyncState {
+    /// The latest macro header whose validator handoffs have been verified. Epoch proofs are
+    /// requested starting from it.
+    macro_head: MacroHeader,
+    /// The current state of the syncing.
+    phase: EpochProofSyncPhase,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum EpochProofSyncTimer {
+    EpochProof,
+}
+
+/// Verifies the validator handoffs from our macro head to the peer's latest macro block using
+/// only macro headers and their pBFT proofs. Each epoch's commit proof has to be signed by the
+/// validators elected in the previous macro block. Blocks are processed as in `FullSync`.
+pub struct EpochProofSync {
+    blockchain: Arc<AlbatrossBlockchain>,
+    state: RwLock<EpochProofSyncState>,
+    peer: Arc<Peer>,
+    notifier: RwLock<PassThroughNotifier<'static, SyncEvent<AlbatrossBlockError>>>,
+    timers: Timers<EpochProofSyncTimer>,
+    self_weak: MutableOnce<Weak<EpochProofSync>>,
+}
+
+impl EpochProofSync {
+    /// Maximum time to wait for an epoch proof after requesting it.
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// The latest macro header that has been verified.
+    pub fn macro_head(&self) -> MacroHeader {
+        self.state.read().macro_head.clone()
+    }
+
+    /// The validators elected in the latest verified macro header.
+    pub fn validators(&self) -> ValidatorSlots {
+        self.state.read().macro_head.validators.clone()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state.read().phase == EpochProofSyncPhase::Finished
+    }
+
+    fn request_epoch_proof(&self, known_macro_hash: Blake2bHash) {
+        let weak = self.self_weak.clone();
+        self.timers.reset_delay(EpochProofSyncTimer::EpochProof, move || {
+            let this = upgrade_weak!(weak);
+            this.peer.channel.close(CloseType::GetEpochProofTimeout);
+        }, Self::REQUEST_TIMEOUT);
+
+        self.peer.channel.send_or_close(GetEpochProofMessage::new(known_macro_hash));
+    }
+
+    fn on_close(&self) {
+        self.timers.clear_all();
+    }
+}
+
+impl SyncProtocol<AlbatrossBlockchain> for EpochProofSync {
+    fn new(blockchain: Arc<AlbatrossBlockchain>, peer: Arc<Peer>) -> Arc<Self> {
+        let macro_head = blockchain.macro_head().header.clone();
+        let this = Arc::new(Self {
+            peer,
+            blockchain,
+            state: RwLock::new(EpochProofSyncState {
+                macro_head,
+                phase: EpochProofSyncPhase::Finished,
+            }),
+            notifier: RwLock::new(PassThroughNotifier::new()),
+            timers: Timers::new(),
+            self_weak: MutableOnce::new(Weak::new()),
+        });
+
+        // Update the self weak reference.
+        unsafe {
+            let weak = Arc::downgrade(&this);
+            this.self_weak.replace(weak);
+        }
+
+        {
+            let mut close_notifier = this.peer.channel.close_notifier.write();
+            close_notifier.register(weak_listener(
+                Arc::downgrade(&this),
+                |this, _| this.on_close()));
+        }
+
+        this
+    }
+
+    fn initiate_sync(&self) {
+        let mut state = self.state.write();
+        if state.phase == EpochProofSyncPhase::Finished {
+            state.phase = EpochProofSyncPhase::EpochProof;
+            let known_macro_hash: Blake2bHash = state.macro_head.hash();
+            drop(state);
+            self.request_epoch_proof(known_macro_hash);
+        }
+    }
+
+    fn get_block_locators(&self, max_count: usize) -> Vec<Blake2bHash> {
+        self.blockchain.get_block_locators(max_count)
+    }
+
+    fn request_blocks(&self, locators: Vec<Blake2bHash>, max_results: u16) {
+        self.peer.channel.send_or_close(GetBlocksMessage::new(
+            locators,
+            max_results,
+            GetBlocksDirection::Forward,
+        ));
+    }
+
+    fn on_block(&self, block: AlbatrossBlock) {
+        let hash = block.hash();
+        let result = self.blockchain.push(block);
+        self.notifier.read().notify(SyncEvent::BlockProcessed(hash, result));
+    }
+
+    fn on_epoch_transactions(&self, _epoch_transactions: EpochTransactionsMessage) {
+        warn!("We didn't expect any epoch transactions from {} - discarding and closing the channel", self.peer.peer_address());
+        self.peer.channel.close(CloseType::UnexpectedEpochTransactions);
+    }
+
+    fn on_epoch_proof(&self, epoch_proof: EpochProofMessage) {
+        let mut state = self.state.write();
+
+        let known_macro_hash: Blake2bHash = state.macro_head.hash();
+        if state.phase != EpochProofSyncPhase::EpochProof || epoch_proof.known_macro_hash != known_macro_hash {
+            warn!("We didn't expect an epoch proof from {} - discarding and closing the channel", self.peer.peer_address());
+            self.peer.channel.close(CloseType::UnexpectedEpochProof);
+            return;
+        }
+
+        self.timers.clear_delay(&EpochProofSyncTimer::EpochProof);
+
+        // The peer doesn't know our macro head, i.e. it is behind us.
+        let proof = match epoch_proof.proof {
+            Some(proof) => proof,
+            None => {
+                debug!("Peer {} doesn't know our macro head {}", self.peer.peer_address(), known_macro_hash);
+                state.phase = EpochProofSyncPhase::Finished;
+                return;
+            },
+        };
+
+        let macro_head = match proof.verify(&state.macro_head, &self.blockchain.policy) {
+            Ok(macro_head) => macro_head.clone(),
+            Err(e) => {
+                warn!("We received an invalid epoch proof ({}) from {} - discarding and closing the channel", e, self.peer.peer_address());
+                self.peer.channel.close(CloseType::InvalidEpochProof);
+                return;
+            },
+        };
+        state.macro_head = macro_head;
+
+        // Request more epochs if the peer hit the limit.
+        if proof.len() >= EpochProofMessage::MAX_EPOCHS {
+            let known_macro_hash: Blake2bHash = state.macro_head.hash();
+            drop(state);
+            self.request_epoch_proof(known_macro_hash);
+        } else {
+            debug!("Verified validator handoffs up to macro block #{} from {}", state.macro_head.block_number, self.peer.peer_address());
+            state.phase = EpochProofSyncPhase::Finished;
+        }
+    }
+
+    fn register_listener<L: PassThroughListener<SyncEvent<<AlbatrossBlock as Block>::Error>> + 'static>(&self, listener: L) {
+        self.notifier.write().register(listener)
+    }
+
+    fn deregister_listener(&self) {
+        self.notifier.write().deregister()
+    }
+}
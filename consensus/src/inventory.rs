@@ -17,6 +17,7 @@ use mempool::{Mempool, ReturnCode};
 use network::connection::close_type::CloseType;
 use network::Peer;
 use network_messages::{
+    EpochProofMessage,
     EpochTransactionsMessage,
     GetBlocksDirection,
     GetBlocksMessage,
@@ -26,7 +27,6 @@ use network_messages::{
     MessageAdapter,
     TxMessage,
 };
-use network_primitives::networks::NetworkInfo;
 use network_primitives::subscription::Subscription;
 use transaction::Transaction;
 use utils::{
@@ -370,6 +370,9 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         msg_notifier.epoch_transactions.write().register(weak_passthru_listener(
             Arc::downgrade(this),
             |this, msg: EpochTransactionsMessage| this.on_epoch_transactions(msg)));
+        msg_notifier.epoch_proof.write().register(weak_passthru_listener(
+            Arc::downgrade(this),
+            |this, msg: EpochProofMessage| this.on_epoch_proof(msg)));
 
         msg_notifier.subscribe.write().register(weak_passthru_listener(
             Arc::downgrade(this),
@@ -793,7 +796,7 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         // chain, ignore the rest. If none of the requested hashes is found,
         // pick the genesis block hash. Send the main chain starting from the
         // picked hash back to the peer.
-        let network_info = NetworkInfo::from_network_id(self.blockchain.network_id());
+        let network_info = self.blockchain.network_info();
         let mut start_block_hash = network_info.genesis_hash().clone();
         for locator in msg.locators.iter() {
             if self.blockchain.get_block(locator, false).is_some() {
@@ -839,7 +842,7 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         // chain, ignore the rest. If none of the requested hashes is found,
         // pick the genesis block hash. Send the main chain starting from the
         // picked hash back to the peer.
-        let network_info = NetworkInfo::from_network_id(self.blockchain.network_id());
+        let network_info = self.blockchain.network_info();
         let mut start_block_hash = network_info.genesis_hash().clone();
         for locator in msg.locators.iter() {
             if self.blockchain.get_block(locator, false).is_some() {
@@ -968,6 +971,10 @@ impl<P: ConsensusProtocol + 'static> InventoryAgent<P> {
         self.sync_protocol.on_epoch_transactions(epoch_transactions_message);
     }
 
+    fn on_epoch_proof(&self, epoch_proof_message: EpochProofMessage) {
+        self.sync_protocol.on_epoch_proof(epoch_proof_message);
+    }
+
     pub fn relay_block(&self, block: &<P::Blockchain as AbstractBlockchain>::Block) -> bool {
         // Only relay block if it matches the peer's subscription.
         if !self.state.read().remote_subscription.matches_block() {
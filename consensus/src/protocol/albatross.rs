@@ -1,5 +1,5 @@
 use blockchain_albatross::Blockchain;
-use network_messages::AlbatrossMessageAdapter;
+use network_messages::{AlbatrossMessageAdapter, BlockProofMessage, EpochProofMessage, GetBlockProofMessage, GetEpochProofMessage, Message};
 
 use crate::protocol::ConsensusProtocol;
 use crate::consensus_agent::sync::FullSync;
@@ -9,4 +9,19 @@ impl ConsensusProtocol for AlbatrossConsensusProtocol {
     type Blockchain = Blockchain;
     type MessageAdapter = AlbatrossMessageAdapter;
     type SyncProtocol = FullSync<Self::Blockchain>;
+
+    // NiPoPoW chain proofs only exist for the proof-of-work chain.
+    fn chain_proof_message(_blockchain: &Self::Blockchain) -> Option<Message> {
+        None
+    }
+
+    // Block proofs are made of proof-of-work blocks and can't be created for Albatross.
+    fn block_proof_message(_blockchain: &Self::Blockchain, _msg: &GetBlockProofMessage) -> Message {
+        BlockProofMessage::empty()
+    }
+
+    fn epoch_proof_message(blockchain: &Self::Blockchain, msg: &GetEpochProofMessage) -> Option<Message> {
+        let proof = blockchain.get_epoch_proof(&msg.known_macro_hash, EpochProofMessage::MAX_EPOCHS as u32);
+        Some(EpochProofMessage::new(msg.known_macro_hash.clone(), proof))
+    }
 }
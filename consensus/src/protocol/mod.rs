@@ -1,5 +1,5 @@
 use blockchain_base::AbstractBlockchain;
-use network_messages::MessageAdapter;
+use network_messages::{GetBlockProofMessage, GetEpochProofMessage, Message, MessageAdapter};
 
 use crate::consensus_agent::sync::SyncProtocol;
 
@@ -10,4 +10,15 @@ pub trait ConsensusProtocol {
     type Blockchain: AbstractBlockchain + 'static;
     type MessageAdapter: MessageAdapter<<Self::Blockchain as AbstractBlockchain>::Block> + 'static;
     type SyncProtocol: SyncProtocol<Self::Blockchain> + 'static;
+
+    /// Creates the response to a GetChainProof message.
+    /// Returns `None` if the protocol doesn't support chain proofs.
+    fn chain_proof_message(blockchain: &Self::Blockchain) -> Option<Message>;
+
+    /// Creates the response to a GetBlockProof message.
+    fn block_proof_message(blockchain: &Self::Blockchain, msg: &GetBlockProofMessage) -> Message;
+
+    /// Creates the response to a GetEpochProof message.
+    /// Returns `None` if the protocol doesn't support epoch proofs.
+    fn epoch_proof_message(blockchain: &Self::Blockchain, msg: &GetEpochProofMessage) -> Option<Message>;
 }
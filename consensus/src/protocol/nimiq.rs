@@ -1,5 +1,5 @@
 use blockchain::Blockchain;
-use network_messages::NimiqMessageAdapter;
+use network_messages::{BlockProofMessage, GetBlockProofMessage, GetEpochProofMessage, Message, NimiqMessageAdapter};
 
 use crate::consensus_agent::sync::FullSync;
 use crate::protocol::ConsensusProtocol;
@@ -9,4 +9,17 @@ impl ConsensusProtocol for NimiqConsensusProtocol {
     type Blockchain = Blockchain;
     type MessageAdapter = NimiqMessageAdapter;
     type SyncProtocol = FullSync<Self::Blockchain>;
+
+    fn chain_proof_message(blockchain: &Self::Blockchain) -> Option<Message> {
+        Some(Message::ChainProof(Box::new(blockchain.get_chain_proof())))
+    }
+
+    fn block_proof_message(blockchain: &Self::Blockchain, msg: &GetBlockProofMessage) -> Message {
+        BlockProofMessage::new(blockchain.get_block_proof(&msg.block_hash_to_prove, &msg.known_block_hash))
+    }
+
+    // The proof-of-work chain has no epochs.
+    fn epoch_proof_message(_blockchain: &Self::Blockchain, _msg: &GetEpochProofMessage) -> Option<Message> {
+        None
+    }
 }
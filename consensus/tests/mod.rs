@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use futures::{future, Async, Future, Stream};
+use futures::sync::mpsc::{unbounded, UnboundedReceiver};
+use tokio::runtime::current_thread::Runtime;
+
+use beserial::Deserialize;
+use nimiq_block_albatross::{Block, MacroBlock, PbftCommitMessage, PbftPrepareMessage, PbftProofBuilder, PbftProposal, SignedPbftCommitMessage, SignedPbftPrepareMessage};
+use nimiq_block_production_albatross::BlockProducer;
+use nimiq_blockchain_albatross::blockchain::{Blockchain, PushResult};
+use nimiq_blockchain_base::AbstractBlockchain;
+use nimiq_bls::{KeyPair, SecretKey};
+use nimiq_consensus::{AlbatrossConsensusProtocol, ConsensusProtocol};
+use nimiq_consensus::consensus_agent::sync::{EpochProofSync, SyncProtocol};
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_messages::{EpochProofMessage, GetEpochProofMessage, Message};
+use nimiq_network::connection::network_connection::AddressInfo;
+use nimiq_network::Peer;
+use nimiq_network::peer_channel::PeerChannel;
+use nimiq_network::websocket::Message as WebSocketMessage;
+use nimiq_network_primitives::networks::{NetworkId, NetworkInfo};
+use nimiq_signer::LocalSigner;
+
+/// Secret key of validator. Tests run with `network-primitives/src/genesis/unit-albatross.toml`
+const SECRET_KEY: &'static str = "49ea68eb6b8afdf4ca4d4c0a0b295c76ca85225293693bc30e755476492b707f";
+
+fn keypair() -> KeyPair {
+    KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap())
+}
+
+// Fill epoch with micro blocks
+fn fill_micro_blocks(producer: &BlockProducer, blockchain: &Arc<Blockchain>) {
+    let init_height = blockchain.head_height();
+    let macro_block_number = blockchain.policy.macro_block_after(init_height + 1);
+    for i in (init_height + 1)..macro_block_number {
+        let last_micro_block = producer.next_micro_block(vec![], 1565713920000 + i as u64 * 2000, 0, vec![0x42], None).unwrap();
+        assert_eq!(blockchain.push(Block::Micro(last_micro_block)), Ok(PushResult::Extended));
+    }
+    assert_eq!(blockchain.head_height(), macro_block_number - 1);
+}
+
+fn sign_macro_block(blockchain: &Blockchain, proposal: PbftProposal) -> MacroBlock {
+    let keypair = keypair();
+    let block_hash = proposal.header.hash::<Blake2bHash>();
+
+    // create signed prepare and commit
+    let prepare = SignedPbftPrepareMessage::from_message(
+        PbftPrepareMessage { block_hash: block_hash.clone() },
+        &keypair.secret,
+        0);
+    let commit = SignedPbftCommitMessage::from_message(
+        PbftCommitMessage { block_hash: block_hash.clone() },
+        &keypair.secret,
+        0);
+
+    // create proof
+    let mut pbft_proof = PbftProofBuilder::new();
+    pbft_proof.add_prepare_signature(&keypair.public, blockchain.policy.slots, &prepare);
+    pbft_proof.add_commit_signature(&keypair.public, blockchain.policy.slots, &commit);
+
+    MacroBlock {
+        header: proposal.header,
+        justification: Some(pbft_proof.build()),
+        extrinsics: None,
+    }
+}
+
+/// Creates a chain on which the validators handed off to their successors `num_macro` times.
+fn produce_chain(num_macro: usize) -> Arc<Blockchain> {
+    let env = VolatileEnvironment::new(12).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross).unwrap());
+    let producer = BlockProducer::new_without_mempool(Arc::clone(&blockchain), Arc::new(LocalSigner::new(keypair())));
+
+    for _ in 0..num_macro {
+        fill_micro_blocks(&producer, &blockchain);
+
+        let next_block_height = blockchain.head_height() + 1;
+        let (proposal, _extrinsics) = producer.next_macro_block_proposal(1565713920000 + next_block_height as u64 * 2000, 0u32, None).unwrap();
+
+        let block = sign_macro_block(&blockchain, proposal);
+        assert_eq!(blockchain.push_block(Block::Macro(block), true), Ok(PushResult::Extended));
+    }
+    blockchain
+}
+
+/// Creates a peer whose messages from us end up in the returned receiver.
+fn peer() -> (Arc<Peer>, UnboundedReceiver<WebSocketMessage>) {
+    let (tx, rx) = unbounded();
+    let peer_address = NetworkInfo::from_network_id(NetworkId::Main).unwrap().seed_peers()[0].clone();
+    let channel = PeerChannel::from_sink(tx, AddressInfo::new(None, Some(Arc::new(peer_address))));
+    (Arc::new(Peer::new(Arc::new(channel), 1, Blake2bHash::default(), 0, None)), rx)
+}
+
+/// Returns the next message that was sent to the peer, if any.
+fn sent_message(rx: &mut UnboundedReceiver<WebSocketMessage>) -> Option<Message> {
+    match future::lazy(|| rx.poll()).wait() {
+        Ok(Async::Ready(Some(WebSocketMessage::Message(msg)))) => Some(msg),
+        _ => None,
+    }
+}
+
+/// Returns the epoch proof that was requested from the peer.
+fn requested_epoch_proof(rx: &mut UnboundedReceiver<WebSocketMessage>) -> GetEpochProofMessage {
+    match sent_message(rx) {
+        Some(Message::GetEpochProof(msg)) => *msg,
+        msg => panic!("Expected an epoch proof request, got {:?}", msg),
+    }
+}
+
+/// Answers the request like a peer with the given chain does.
+fn epoch_proof(blockchain: &Blockchain, request: &GetEpochProofMessage) -> EpochProofMessage {
+    match AlbatrossConsensusProtocol::epoch_proof_message(blockchain, request) {
+        Some(Message::EpochProof(msg)) => *msg,
+        msg => panic!("Expected an epoch proof, got {:?}", msg),
+    }
+}
+
+/// Runs `f` on a runtime, because timers and closing a channel need one.
+fn run<F: FnOnce()>(f: F) {
+    Runtime::new().unwrap().block_on(future::lazy(|| {
+        f();
+        future::ok::<(), ()>(())
+    })).unwrap();
+}
+
+#[test]
+fn it_syncs_validator_handoffs_with_epoch_proofs() {
+    let blockchain = produce_chain(3);
+    let client = Arc::new(Blockchain::new(VolatileEnvironment::new(12).unwrap(), NetworkId::UnitAlbatross).unwrap());
+
+    run(|| {
+        let (peer, mut rx) = peer();
+        let sync = EpochProofSync::new(Arc::clone(&client), Arc::clone(&peer));
+        assert!(sync.is_finished());
+
+        // The proof is requested from our trusted macro head.
+        sync.initiate_sync();
+        assert!(!sync.is_finished());
+        let request = requested_epoch_proof(&mut rx);
+        assert_eq!(request.known_macro_hash, client.macro_head_hash());
+
+        let response = epoch_proof(&blockchain, &request);
+        assert_eq!(response.proof.as_ref().unwrap().len(), 3);
+        sync.on_epoch_proof(response);
+
+        assert!(sync.is_finished());
+        assert!(!peer.channel.closed());
+        assert_eq!(sync.macro_head(), blockchain.macro_head().header);
+        assert_eq!(sync.validators(), blockchain.macro_head().header.validators);
+
+        // The verified macro header is the start of the next sync.
+        sync.initiate_sync();
+        let request = requested_epoch_proof(&mut rx);
+        assert_eq!(request.known_macro_hash, blockchain.macro_head_hash());
+        sync.on_epoch_proof(epoch_proof(&blockchain, &request));
+        assert!(sync.is_finished());
+        assert_eq!(sync.macro_head(), blockchain.macro_head().header);
+        assert!(!peer.channel.closed());
+    });
+}
+
+#[test]
+fn it_closes_the_channel_on_unsolicited_epoch_proofs() {
+    let blockchain = produce_chain(2);
+    let client = Arc::new(Blockchain::new(VolatileEnvironment::new(12).unwrap(), NetworkId::UnitAlbatross).unwrap());
+
+    run(|| {
+        let (peer, _rx) = peer();
+        let sync = EpochProofSync::new(Arc::clone(&client), Arc::clone(&peer));
+
+        let request = GetEpochProofMessage { known_macro_hash: client.macro_head_hash() };
+        sync.on_epoch_proof(epoch_proof(&blockchain, &request));
+
+        assert!(peer.channel.closed());
+        assert_eq!(sync.macro_head(), client.macro_head().header);
+    });
+}
+
+#[test]
+fn it_closes_the_channel_on_invalid_epoch_proofs() {
+    let blockchain = produce_chain(2);
+    let client = Arc::new(Blockchain::new(VolatileEnvironment::new(12).unwrap(), NetworkId::UnitAlbatross).unwrap());
+
+    run(|| {
+        let (peer, mut rx) = peer();
+        let sync = EpochProofSync::new(Arc::clone(&client), Arc::clone(&peer));
+
+        sync.initiate_sync();
+        let request = requested_epoch_proof(&mut rx);
+        let mut response = epoch_proof(&blockchain, &request);
+
+        // Skip the first handoff.
+        response.proof.as_mut().unwrap().epochs.remove(0);
+        sync.on_epoch_proof(response);
+
+        assert!(peer.channel.closed());
+        assert_eq!(sync.macro_head(), client.macro_head().header);
+    });
+}
@@ -4,6 +4,8 @@ use std::fmt;
 use macros::upgrade_weak;
 use parking_lot::{RwLock, RwLockUpgradableReadGuard};
 use futures::{future, Future};
+use rand::{Rng, thread_rng};
+use tokio::clock;
 
 use utils::observer::PassThroughNotifier;
 use utils::mutable_once::MutableOnce;
@@ -31,7 +33,7 @@ pub enum AggregationEvent {
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum AggregationTimer {
-    Timeout,
+    Timeout(usize),
     Update,
 }
 
@@ -41,8 +43,6 @@ struct AggregationState {
 
     /// Our contribution
     contribution: Option<IndividualSignature>,
-
-    next_level_timeout: usize,
 }
 
 
@@ -75,7 +75,13 @@ pub struct Aggregation<P: Protocol> {
 
 impl<P: Protocol + fmt::Debug> Aggregation<P> {
     pub fn new(protocol: P, config: Config) -> Arc<Self> {
-        let levels = Level::create_levels(protocol.partitioner());
+        Self::with_rng(protocol, config, &mut thread_rng())
+    }
+
+    /// Creates an aggregation whose peers are ordered by `rng`. Use a seeded RNG to get the same
+    /// order on every run.
+    pub fn with_rng<R: Rng>(protocol: P, config: Config, rng: &mut R) -> Arc<Self> {
+        let levels = Level::create_levels(protocol.partitioner(), rng);
         let todos = Arc::new(TodoList::new(protocol.evaluator()));
 
         // create aggregation
@@ -87,7 +93,6 @@ impl<P: Protocol + fmt::Debug> Aggregation<P> {
             timers: Timers::new(),
             state: RwLock::new(AggregationState {
                 result: None,
-                next_level_timeout: 0,
                 contribution: None,
             }),
             self_weak: MutableOnce::new(Weak::new()),
@@ -149,22 +154,7 @@ impl<P: Protocol + fmt::Debug> Aggregation<P> {
         }, this.config.update_interval);
 
         // register timer for level timeouts
-        // TODO: This ignores the timeout strategy
-        let weak = Arc::downgrade(this);
-        this.timers.set_interval(AggregationTimer::Timeout, move || {
-            let this = upgrade_weak!(weak);
-            let mut state = this.state.write();
-            let level = state.next_level_timeout;
-            if level < this.num_levels() {
-                trace!("Timeout for {:?} at level {}", this.protocol, level);
-                state.next_level_timeout += 1;
-                drop(state);
-                this.start_level(level);
-            }
-            else {
-                this.timers.clear_interval(&AggregationTimer::Timeout);
-            }
-        }, this.config.timeout);
+        this.schedule_level_timeout(0);
 
         // spawn thread handling TODOs
         //tokio::spawn(Arc::clone(&this.todos).into_future());
@@ -174,6 +164,24 @@ impl<P: Protocol + fmt::Debug> Aggregation<P> {
         self.levels.len()
     }
 
+    /// Starts `level` after the timeout given by the timeout strategy. Afterwards the timeout for
+    /// the next level is scheduled.
+    fn schedule_level_timeout(&self, level: usize) {
+        if level >= self.num_levels() {
+            return;
+        }
+
+        let timeout = self.config.timeout_strategy.timeout(level);
+        let weak = Weak::clone(&self.self_weak);
+        self.timers.set_delay(AggregationTimer::Timeout(level), move || {
+            let this = upgrade_weak!(weak);
+            this.timers.clear_delay(&AggregationTimer::Timeout(level));
+            trace!("Timeout for {:?} at level {}", this.protocol, level);
+            this.start_level(level);
+            this.schedule_level_timeout(level + 1);
+        }, timeout);
+    }
+
     /// Starts level `level`
     fn start_level(&self, level: usize) {
         let level = self.levels.get(level)
@@ -232,6 +240,10 @@ impl<P: Protocol + fmt::Debug> Aggregation<P> {
                 trace!("Level {} complete", level.id);
                 level_state.receive_completed = true;
 
+                if let Some(started_at) = level_state.started_at {
+                    self.config.timeout_strategy.level_completed(level.id, clock::now().duration_since(started_at));
+                }
+
                 if level.id + 1 < self.levels.len() {
                     // activate next level
                     self.start_level(level.id + 1)
@@ -1,5 +1,8 @@
+use std::sync::Arc;
 use std::time::Duration;
 
+use crate::timeout::{LinearTimeout, TimeoutStrategy};
+
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -9,8 +12,8 @@ pub struct Config {
     /// Frequency at which updates are sent to peers
     pub update_interval: Duration,
 
-    /// Timeout for levels
-    pub timeout: Duration,
+    /// Decides when levels are started, if the previous level didn't complete yet
+    pub timeout_strategy: Arc<dyn TimeoutStrategy>,
 
     /// How many peers are contacted at each level
     pub peer_count: usize,
@@ -23,7 +26,7 @@ impl Default for Config {
         Config {
             update_count: 1,
             update_interval: Duration::from_millis(100),
-            timeout: Duration::from_millis(500),
+            timeout_strategy: Arc::new(LinearTimeout::default()),
             peer_count: 10,
         }
     }
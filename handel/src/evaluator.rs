@@ -2,12 +2,38 @@ use std::sync::Arc;
 
 use parking_lot::RwLock;
 
+use collections::bitset::BitSet;
+
 use crate::multisig::Signature;
 use crate::identity::WeightRegistry;
 use crate::store::SignatureStore;
 use crate::partitioner::Partitioner;
 
 
+/// Base score of a signature that completes its level. This is higher than any score of a
+/// signature that doesn't complete its level, such that those are always verified first.
+const SCORE_COMPLETES_LEVEL: usize = 1_000_000;
+
+/// Base score of a signature that adds new signers to its level
+const SCORE_ADDS_SIGNERS: usize = 100_000;
+
+/// Penalty per level for signatures completing a level. Completing lower levels first lets us
+/// send complete signatures to our peers earlier.
+const LEVEL_PENALTY_COMPLETE: usize = 10;
+
+/// Penalty per level for signatures not completing a level
+const LEVEL_PENALTY_INCOMPLETE: usize = 100;
+
+/// Reward per added vote
+///
+/// NOTE: Signatures completing a level only score higher than all others, as long as the total
+/// number of votes is below 90 000. Albatross has 512 slots.
+const ADDED_VOTE_REWARD: usize = 10;
+
+/// Score of an individual signature that adds nothing right now. It's still worth to verify it,
+/// since it can be combined with multi-signatures we receive later.
+const SCORE_INDIVIDUAL: usize = 1;
+
 
 pub trait Evaluator {
     fn evaluate(&self, signature: &Signature, level: usize) -> usize;
@@ -17,15 +43,15 @@ pub trait Evaluator {
 
 /// Every signature counts as a single vote
 pub struct SingleVote<S: SignatureStore, P: Partitioner> {
-    signature_store: Arc<S>,
+    store: Arc<RwLock<S>>,
     partitioner: Arc<P>,
-    threshold: usize,
+    pub threshold: usize,
 }
 
 impl<S: SignatureStore, P: Partitioner> SingleVote<S, P> {
-    pub fn new(signature_store: Arc<S>, partitioner: Arc<P>, threshold: usize) -> Self {
+    pub fn new(store: Arc<RwLock<S>>, partitioner: Arc<P>, threshold: usize) -> Self {
         Self {
-            signature_store,
+            store,
             partitioner,
             threshold,
         }
@@ -33,13 +59,8 @@ impl<S: SignatureStore, P: Partitioner> SingleVote<S, P> {
 }
 
 impl<S: SignatureStore, P: Partitioner> Evaluator for SingleVote<S, P> {
-    fn evaluate(&self, _signature: &Signature, _level: usize) -> usize {
-        // This is going to be used here, and we don't want any warnings
-        let _ = (&self.signature_store, &self.partitioner);
-
-        // TODO: The code from `WeightedVote` is what actually belongs here. And then the code in
-        // `WeightedVote` must be adapted to consider the weight of a signature.
-        unimplemented!();
+    fn evaluate(&self, signature: &Signature, level: usize) -> usize {
+        score(&*self.store.read(), &*self.partitioner, signature, level, |signers| Some(signers.len()))
     }
 
     fn is_final(&self, signature: &Signature) -> bool {
@@ -52,9 +73,10 @@ impl<S: SignatureStore, P: Partitioner> Evaluator for SingleVote<S, P> {
 
 
 
-/// A signature counts as it was signed N times, where N is the signers weight
+/// A signature counts as it was signed N times, where N is the signers weight. For validators the
+/// weight is their number of slots, thus signatures are prioritized by the slots they add.
 ///
-/// NOTE: This can be used for ViewChanges
+/// NOTE: This is used for view changes and pBFT.
 pub struct WeightedVote<S: SignatureStore, I: WeightRegistry, P: Partitioner> {
     store: Arc<RwLock<S>>,
     pub weights: Arc<I>,
@@ -75,108 +97,217 @@ impl<S: SignatureStore, I: WeightRegistry, P: Partitioner> WeightedVote<S, I, P>
 
 impl<S: SignatureStore, I: WeightRegistry, P: Partitioner> Evaluator for WeightedVote<S, I, P> {
     fn evaluate(&self, signature: &Signature, level: usize) -> usize {
-        // TODO: Consider weight
-        //let weight = self.weights.signature_weight(&signature)
-        //    .unwrap_or_else(|| panic!("No weight for signature: {:?}", signature));
-
-        let store = self.store.read();
-
-        // check if we already know this individual signature
-        if let Signature::Individual(individual) = signature {
-            if store.individual_signature(level, individual.signer).is_some() {
-                // If we already know it for this level, score it as 0
-                trace!("Individual signature from peer {} for level {} already known", level, individual.signer);
-                return 0
-            }
-        }
+        score(&*self.store.read(), &*self.partitioner, signature, level, |signers| self.weights.signers_weight(signers))
+    }
+
+    fn is_final(&self, signature: &Signature) -> bool {
+        let votes = self.weights.signature_weight(signature)
+            .unwrap_or_else(|| panic!("Missing weights for signature: {:?}", signature));
 
-        let to_receive = self.partitioner.level_size(level);
-        let best_signature = store.best(level);
-
-        if let Some(best_signature) = best_signature {
-            trace!("level = {}", level);
-            trace!("signature = {:#?}", signature);
-            trace!("best_signature = {:#?}", best_signature);
-
-            // check if the best signature for that level is already complete
-            if to_receive == best_signature.len() {
-                trace!("Best signature already complete");
-                return 0;
-            }
-
-            // check if the best signature is better than the new one
-            let best_is_better = match signature {
-                Signature::Individual(individual) => best_signature.signers.contains(individual.signer),
-                Signature::Multi(multisig) => best_signature.signers.is_superset(&multisig.signers),
-            };
-            if best_is_better {
-                trace!("Best signature is better");
-                return 0;
-            }
+        trace!("is_final(): votes={}, final={}", votes, votes >= self.threshold);
+        votes >= self.threshold
+    }
+}
+
+
+
+/// Scores `signature` for `level` by how many votes it adds to the best signature we have for that
+/// level. `votes` returns the number of votes for a set of signers, or `None` if a signer is unknown.
+///
+/// This is the scoring function of the Handel reference implementation, with signers replaced by
+/// votes. A score of 0 means the signature is useless and is not verified.
+fn score<S, P, V>(store: &S, partitioner: &P, signature: &Signature, level: usize, votes: V) -> usize
+    where S: SignatureStore,
+          P: Partitioner,
+          V: Fn(&BitSet) -> Option<usize>,
+{
+    // check if we already know this individual signature
+    if let Signature::Individual(individual) = signature {
+        if store.individual_signature(level, individual.signer).is_some() {
+            // If we already know it for this level, score it as 0
+            trace!("Individual signature from peer {} for level {} already known", individual.signer, level);
+            return 0
         }
+    }
 
-        // the signers of the signature
-        // NOTE: This is a little bit more efficient than `signature.signers().collect()`, since
-        // `signers()` returns a boxed iterator.
-        // NOTE: We compute the full `BitSet` (also for individual signatures), since we need it in
-        // a few places here
-        let signers = match signature {
-            Signature::Individual(individual) => {
-                let mut individuals = store.individual_verified(level).clone();
-                individuals.insert(individual.signer);
-                individuals
-            },
-            Signature::Multi(multisig) => multisig.signers.clone()
-        };
+    // the votes we need to complete this level
+    let to_receive = match partitioner.range(level) {
+        Ok(range) => votes(&range.collect()),
+        Err(e) => {
+            warn!("Can't evaluate signature for level {}: {}", level, e);
+            return 0;
+        },
+    };
+    let to_receive = match to_receive {
+        Some(to_receive) => to_receive,
+        None => {
+            warn!("Missing weights for level {}", level);
+            return 0;
+        },
+    };
+
+    let best_signature = store.best(level);
+    let best_total = match best_signature.map(|best_signature| votes(&best_signature.signers)) {
+        Some(Some(best_total)) => best_total,
+        Some(None) => {
+            warn!("Best signature for level {} contains signers without weight", level);
+            return 0;
+        },
+        None => 0,
+    };
+
+    if let Some(best_signature) = best_signature {
+        trace!("level = {}", level);
+        trace!("signature = {:#?}", signature);
+        trace!("best_signature = {:#?}", best_signature);
 
-        // compute bitset of signers combined with all (verified) individual signatures that we have
-        let with_individuals = &signers | store.individual_verified(level);
-
-        // ---------------------------------------------
-
-        let (new_total, added_sigs, combined_sigs) = if let Some(best_signature) = best_signature {
-            if signers.intersection_size(&best_signature.signers) > 0 {
-                // can't merge
-                let new_total = with_individuals.len();
-                (new_total, new_total.saturating_sub(best_signature.len()), new_total - signers.len())
-            }
-            else {
-                let final_sig = &with_individuals | &best_signature.signers;
-                let new_total = final_sig.len();
-                let combined_sigs = (final_sig ^ (&best_signature.signers | &signers)).len();
-                (new_total, new_total - best_signature.len(), combined_sigs)
-            }
+        // check if the best signature for that level is already complete
+        if best_total >= to_receive {
+            trace!("Best signature already complete");
+            return 0;
         }
-        else {
-            // best is the new signature with the individual signatures
-            let new_total = with_individuals.len();
-            (new_total, new_total, new_total - signers.len())
+
+        // check if the best signature is better than the new one
+        let best_is_better = match signature {
+            Signature::Individual(individual) => best_signature.signers.contains(individual.signer),
+            Signature::Multi(multisig) => best_signature.signers.is_superset(&multisig.signers),
         };
+        if best_is_better {
+            trace!("Best signature is better");
+            return 0;
+        }
+    }
+
+    // the signers of the signature
+    // NOTE: This is a little bit more efficient than `signature.signers().collect()`, since
+    // `signers()` returns a boxed iterator.
+    // NOTE: We compute the full `BitSet` (also for individual signatures), since we need it in
+    // a few places here
+    let signers = match signature {
+        Signature::Individual(individual) => {
+            let mut individuals = store.individual_verified(level).clone();
+            individuals.insert(individual.signer);
+            individuals
+        },
+        Signature::Multi(multisig) => multisig.signers.clone()
+    };
 
-        trace!("new_total={}, added_sigs={}, combined_sigs={}", new_total, added_sigs, combined_sigs);
+    // compute bitset of signers combined with all (verified) individual signatures that we have
+    let with_individuals = &signers | store.individual_verified(level);
 
-        // compute score
-        // TODO: Remove magic numbers! What do they mean? I don't think this is discussed in the paper.
-        if added_sigs == 0 {
-            // return 1 for an individual signature, otherwise 0
-            match signature {
-                Signature::Individual(_) => 1,
-                Signature::Multi(_) => 0,
-            }
+    // ---------------------------------------------
+
+    let (final_signers, combined_from) = match best_signature {
+        Some(best_signature) if signers.intersection_size(&best_signature.signers) == 0 => {
+            // merge with best signature
+            let final_signers = &with_individuals | &best_signature.signers;
+            let combined_from = &best_signature.signers | &signers;
+            (final_signers, combined_from)
+        },
+        // can't merge or there is no best signature yet, so the new signature with the
+        // individual signatures replaces the best signature
+        _ => (with_individuals, signers),
+    };
+
+    let (new_total, combined_total) = match (votes(&final_signers), votes(&combined_from)) {
+        (Some(new_total), Some(combined_total)) => (new_total, combined_total),
+        _ => {
+            warn!("Signature contains signers without weight: {:?}", signature);
+            return 0;
         }
-        else if new_total == to_receive {
-            1_000_000 - level * 10 - combined_sigs
+    };
+
+    // votes gained over the best signature
+    let added_votes = new_total.saturating_sub(best_total);
+    // votes of individual signatures that we need to combine with the signature
+    let combined_votes = new_total - combined_total;
+
+    trace!("new_total={}, added_votes={}, combined_votes={}, to_receive={}", new_total, added_votes, combined_votes, to_receive);
+
+    if added_votes == 0 {
+        match signature {
+            Signature::Individual(_) => SCORE_INDIVIDUAL,
+            Signature::Multi(_) => 0,
         }
-        else {
-            100_000 - level * 100 + added_sigs * 10 - combined_sigs
+    }
+    else if new_total >= to_receive {
+        SCORE_COMPLETES_LEVEL
+            .saturating_sub(level * LEVEL_PENALTY_COMPLETE)
+            .saturating_sub(combined_votes)
+    }
+    else {
+        (SCORE_ADDS_SIGNERS + added_votes * ADDED_VOTE_REWARD)
+            .saturating_sub(level * LEVEL_PENALTY_INCOMPLETE)
+            .saturating_sub(combined_votes)
+            .max(SCORE_INDIVIDUAL)
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use bls::bls12_381::KeyPair;
+    use bls::SecureGenerate;
+    use hash::{Blake2bHasher, Hasher};
+
+    use crate::multisig::IndividualSignature;
+    use crate::partitioner::BinomialPartitioner;
+    use crate::store::ReplaceStore;
+
+    use super::*;
+
+    struct TestWeights(Vec<usize>);
+
+    impl WeightRegistry for TestWeights {
+        fn weight(&self, id: usize) -> Option<usize> {
+            self.0.get(id).cloned()
         }
     }
 
-    fn is_final(&self, signature: &Signature) -> bool {
-        let votes = self.weights.signature_weight(signature)
-            .unwrap_or_else(|| panic!("Missing weights for signature: {:?}", signature));
+    fn individual(signer: usize) -> Signature {
+        let key_pair = KeyPair::generate(&mut rand::thread_rng());
+        Signature::Individual(IndividualSignature::new(key_pair.sign_hash(Blake2bHasher::default().digest(b"test")), signer))
+    }
 
-        trace!("is_final(): votes={}, final={}", votes, votes >= self.threshold);
-        votes >= self.threshold
+    fn evaluators(weights: Vec<usize>) -> (SingleVote<ReplaceStore<BinomialPartitioner>, BinomialPartitioner>, WeightedVote<ReplaceStore<BinomialPartitioner>, TestWeights, BinomialPartitioner>) {
+        let partitioner = Arc::new(BinomialPartitioner::new(0, weights.len()));
+        let store = Arc::new(RwLock::new(ReplaceStore::new(Arc::clone(&partitioner))));
+        let single = SingleVote::new(Arc::clone(&store), Arc::clone(&partitioner), 3);
+        let weighted = WeightedVote::new(store, Arc::new(TestWeights(weights)), partitioner, 3);
+        (single, weighted)
+    }
+
+    #[test]
+    fn it_prefers_signatures_with_more_weight() {
+        // Level 3 of node 0 contains the nodes 4 to 7.
+        let (single, weighted) = evaluators(vec![1, 1, 1, 1, 1, 10, 1, 1]);
+
+        assert_eq!(single.evaluate(&individual(4), 3), single.evaluate(&individual(5), 3));
+        assert!(weighted.evaluate(&individual(5), 3) > weighted.evaluate(&individual(4), 3));
+    }
+
+    #[test]
+    fn it_prefers_signatures_completing_a_level() {
+        // Level 1 of node 0 only contains node 1.
+        let (_, weighted) = evaluators(vec![1, 1, 1, 1, 1, 100, 1, 1]);
+
+        assert!(weighted.evaluate(&individual(1), 1) > weighted.evaluate(&individual(5), 3));
+    }
+
+    #[test]
+    fn it_ignores_known_signatures() {
+        let (single, weighted) = evaluators(vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        let signature = individual(5);
+
+        weighted.store.write().put(signature.clone(), 3);
+        assert_eq!(single.evaluate(&signature, 3), 0);
+        assert_eq!(weighted.evaluate(&signature, 3), 0);
+    }
+
+    #[test]
+    fn it_ignores_signers_without_weight() {
+        let (_, weighted) = evaluators(vec![1, 1, 1, 1]);
+        assert_eq!(weighted.evaluate(&individual(5), 2), 0);
     }
 }
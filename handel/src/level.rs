@@ -1,9 +1,11 @@
 use std::cmp::min;
 use std::sync::Arc;
+use std::time::Instant;
 
+use rand::Rng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
 use parking_lot::RwLock;
+use tokio::clock;
 
 use crate::partitioner::{Partitioner, PartitioningError};
 use crate::multisig::Signature;
@@ -16,6 +18,8 @@ pub struct LevelState {
     pub send_peers_pos: usize,
     pub send_signature_size: usize,
     pub send_peers_count: usize,
+    /// When the level was started by a timeout or because the previous level completed
+    pub started_at: Option<Instant>,
 }
 
 #[derive(Debug)]
@@ -38,6 +42,7 @@ impl Level {
                 send_peers_pos: 0,
                 send_signature_size: 0,
                 send_peers_count: 0,
+                started_at: None,
             })
         }
     }
@@ -46,17 +51,17 @@ impl Level {
         self.peer_ids.len()
     }
 
-    pub fn create_levels<P: Partitioner>(partitioner: Arc<P>) -> Vec<Level> {
+    /// Creates the levels of the partitioner, with the peers of every level shuffled by `rng`
+    pub fn create_levels<P: Partitioner, R: Rng>(partitioner: Arc<P>, rng: &mut R) -> Vec<Level> {
         let mut levels: Vec<Level> = Vec::new();
         let mut first_active = false;
         let mut send_expected_full_size: usize = 1;
-        let mut rng = thread_rng();
 
         for i in 0 .. partitioner.levels() {
             match partitioner.range(i) {
                 Ok(ids) => {
                     let mut ids = ids.collect::<Vec<usize>>();
-                    ids.shuffle(&mut rng);
+                    ids.shuffle(rng);
 
                     let size = ids.len();
                     trace!("Level {} peers: {:?}", i, ids);
@@ -132,6 +137,10 @@ impl Level {
     }
 
     pub fn start(&self) {
-        self.state.write().send_started = true;
+        let mut state = self.state.write();
+        state.send_started = true;
+        if state.started_at.is_none() {
+            state.started_at = Some(clock::now());
+        }
     }
 }
@@ -13,7 +13,15 @@ extern crate failure;
 #[macro_use]
 extern crate log;
 extern crate tokio;
+#[cfg(feature = "simulation")]
+extern crate tokio_current_thread;
+#[cfg(feature = "simulation")]
+extern crate tokio_executor;
+#[cfg(feature = "simulation")]
+extern crate tokio_timer;
 extern crate rand;
+#[cfg(feature = "simulation")]
+extern crate rand_chacha;
 extern crate parking_lot;
 
 extern crate beserial;
@@ -39,4 +47,6 @@ pub mod protocol;
 pub mod update;
 pub mod aggregation;
 pub mod sender;
+#[cfg(feature = "simulation")]
+pub mod simulation;
 mod todo;
@@ -16,7 +16,6 @@ pub trait Protocol: Send + Sync + 'static {
     // the protocol (i.e. `Verifier`).
     type Registry: IdentityRegistry;
     type Verifier: Verifier;
-    //type Timeouts: TimeoutStrategy;
     type Store: SignatureStore;
     type Evaluator: Evaluator + Send + Sync;
     type Partitioner: Partitioner;
@@ -24,7 +23,6 @@ pub trait Protocol: Send + Sync + 'static {
 
     fn registry(&self) -> Arc<Self::Registry>;
     fn verifier(&self) -> Arc<Self::Verifier>;
-    //fn timeouts(&self) -> Arc<Self::Timeouts>;
     fn store(&self) -> Arc<RwLock<Self::Store>>;
     fn evaluator(&self) -> Arc<Self::Evaluator>;
     fn partitioner(&self) -> Arc<Self::Partitioner>;
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tokio_executor::park::{Park, Unpark};
+use tokio_timer::clock::Now;
+
+
+/// Simulated time source.
+///
+/// Time doesn't pass while the simulation is doing work. Once all tasks are idle, the clock jumps
+/// forward to the next timer.
+#[derive(Clone, Debug)]
+pub(crate) struct SimulatedClock {
+    start: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl SimulatedClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Arc::new(Mutex::new(Duration::default())),
+        }
+    }
+
+    /// Simulated time since the start of the simulation
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock()
+    }
+
+    fn advance(&self, duration: Duration) {
+        *self.elapsed.lock() += duration;
+    }
+}
+
+impl Now for SimulatedClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+}
+
+
+/// Parks the executor of the simulation by advancing the simulated clock instead of blocking the
+/// thread.
+#[derive(Debug)]
+pub(crate) struct SimulatedPark {
+    clock: SimulatedClock,
+}
+
+impl SimulatedPark {
+    pub fn new(clock: SimulatedClock) -> Self {
+        Self {
+            clock,
+        }
+    }
+}
+
+impl Park for SimulatedPark {
+    type Unpark = SimulatedUnpark;
+    type Error = ();
+
+    fn unpark(&self) -> Self::Unpark {
+        SimulatedUnpark
+    }
+
+    fn park(&mut self) -> Result<(), Self::Error> {
+        // No timer is pending, thus nothing will happen anymore.
+        Ok(())
+    }
+
+    fn park_timeout(&mut self, duration: Duration) -> Result<(), Self::Error> {
+        self.clock.advance(duration);
+        Ok(())
+    }
+}
+
+
+/// All tasks of the simulation run on the executor's thread, so there is no thread to wake up.
+#[derive(Clone, Debug)]
+pub(crate) struct SimulatedUnpark;
+
+impl Unpark for SimulatedUnpark {
+    fn unpark(&self) {}
+}
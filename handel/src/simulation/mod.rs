@@ -0,0 +1,338 @@
+/// In-process simulation of Handel.
+///
+/// Runs an `Aggregation` for every node over an in-memory network with configurable latency,
+/// message loss, partitions and byzantine nodes. This makes it possible to compare evaluators,
+/// partitioners and timeout strategies without spinning up a devnet.
+///
+/// The network is driven by a seeded RNG per link, and the peers of every level are shuffled by a
+/// seeded RNG per node, so the same seed leads to the same latencies, losses and reports. All
+/// nodes run on a single thread and their timers run on a simulated clock, thus completion times
+/// are measured in simulated time and don't depend on the speed of the machine.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future;
+use parking_lot::Mutex;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+use tokio_current_thread::{CurrentThread, TaskExecutor};
+use tokio_timer::Timer;
+use tokio_timer::clock::Clock;
+
+use bls::SecureGenerate;
+use bls::bls12_381::KeyPair;
+use hash::{Blake2bHasher, Hasher};
+
+use crate::aggregation::{Aggregation, AggregationEvent};
+use crate::config::Config;
+use crate::identity::WeightRegistry;
+use crate::multisig::IndividualSignature;
+
+use self::clock::{SimulatedClock, SimulatedPark};
+use self::network::SimulatedNetwork;
+
+mod clock;
+mod network;
+mod protocol;
+
+pub use self::network::SimulatedSender;
+pub use self::protocol::{DefaultFactory, ProtocolFactory, SimulatedProtocol, SimulatedRegistry, SimulatedVerifier};
+
+
+/// How a byzantine node misbehaves
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ByzantineBehavior {
+    /// The node doesn't take part in the aggregation
+    Offline,
+    /// The node replaces the signatures in all its updates with signatures over another message
+    InvalidSignatures,
+    /// The node sends every update the given number of times
+    Duplicates(usize),
+}
+
+
+/// Separates `nodes` from the rest of the network
+#[derive(Clone, Debug)]
+pub struct Partition {
+    pub nodes: BTreeSet<usize>,
+
+    /// Time after which the partition is healed. If `None` the partition is never healed.
+    pub heal_after: Option<Duration>,
+}
+
+
+#[derive(Clone, Debug)]
+pub struct SimulationConfig {
+    /// Number of nodes, including byzantine nodes
+    pub num_nodes: usize,
+
+    /// Weight of every node. Default is 1 for every node.
+    pub weights: Option<Vec<usize>>,
+
+    /// Weight needed for a signature to be final. Default is 2/3 of the total weight.
+    pub threshold: Option<usize>,
+
+    /// Lower bound of the latency of a message
+    pub min_latency: Duration,
+
+    /// Upper bound of the latency of a message
+    pub max_latency: Duration,
+
+    /// Probability that a message is lost
+    pub loss: f64,
+
+    pub partitions: Vec<Partition>,
+
+    /// Maps node IDs to their byzantine behavior
+    pub byzantine: BTreeMap<usize, ByzantineBehavior>,
+
+    /// The Handel configuration used by all nodes
+    pub handel: Config,
+
+    /// The simulation is aborted after this simulated time, even if not all honest nodes are done
+    pub max_duration: Duration,
+
+    /// Seed for the keys of the nodes and the network
+    pub seed: u64,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            num_nodes: 16,
+            weights: None,
+            threshold: None,
+            min_latency: Duration::from_millis(10),
+            max_latency: Duration::from_millis(50),
+            loss: 0.0,
+            partitions: vec![],
+            byzantine: BTreeMap::new(),
+            handel: Config::default(),
+            max_duration: Duration::from_secs(30),
+            seed: 0,
+        }
+    }
+}
+
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MessageStats {
+    /// Number of messages sent, including lost messages
+    pub sent: usize,
+
+    /// Number of messages that reached their recipient
+    pub delivered: usize,
+
+    /// Number of messages that were lost, partitioned or sent to offline nodes
+    pub dropped: usize,
+
+    /// Number of messages with forged signatures sent by byzantine nodes
+    pub invalid: usize,
+
+    /// Number of bytes sent
+    pub bytes: usize,
+}
+
+
+#[derive(Clone, Debug)]
+pub struct SimulationReport {
+    /// Simulated time after which each node had a signature that reached the threshold. `None` if
+    /// the node didn't finish in time or is offline.
+    pub completion_times: Vec<Option<Duration>>,
+
+    /// IDs of the nodes that are not byzantine
+    pub honest: BTreeSet<usize>,
+
+    pub messages: MessageStats,
+}
+
+impl SimulationReport {
+    /// Completion times of the honest nodes that finished
+    fn honest_completion_times<'a>(&'a self) -> impl Iterator<Item=Duration> + 'a {
+        self.honest.iter().filter_map(move |&node_id| self.completion_times[node_id])
+    }
+
+    /// Number of honest nodes that reached the threshold
+    pub fn num_completed(&self) -> usize {
+        self.honest_completion_times().count()
+    }
+
+    /// Whether all honest nodes reached the threshold
+    pub fn all_completed(&self) -> bool {
+        self.num_completed() == self.honest.len()
+    }
+
+    /// Time until the last honest node reached the threshold
+    pub fn max_completion_time(&self) -> Option<Duration> {
+        self.honest_completion_times().max()
+    }
+
+    /// Average time until an honest node reached the threshold
+    pub fn mean_completion_time(&self) -> Option<Duration> {
+        let num_completed = self.num_completed();
+        if num_completed == 0 {
+            return None;
+        }
+        Some(self.honest_completion_times().sum::<Duration>() / num_completed as u32)
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "completed={}/{}, mean={:?}, max={:?}, sent={}, delivered={}, dropped={}, invalid={}, bytes={}",
+            self.num_completed(), self.honest.len(), self.mean_completion_time(), self.max_completion_time(),
+            self.messages.sent, self.messages.delivered, self.messages.dropped, self.messages.invalid,
+            self.messages.bytes)
+    }
+}
+
+
+pub struct Simulation<F: ProtocolFactory = DefaultFactory> {
+    config: SimulationConfig,
+    factory: F,
+}
+
+impl Simulation<DefaultFactory> {
+    pub fn new(config: SimulationConfig) -> Self {
+        Self::with_factory(config, DefaultFactory)
+    }
+}
+
+impl<F: ProtocolFactory> Simulation<F> {
+    pub fn with_factory(config: SimulationConfig, factory: F) -> Self {
+        assert!(config.num_nodes > 0, "Simulation needs at least one node");
+        Self {
+            config,
+            factory,
+        }
+    }
+
+    /// Runs the simulation until all honest nodes reached the threshold, or `max_duration` of
+    /// simulated time passed.
+    pub fn run(&self) -> SimulationReport {
+        let config = &self.config;
+        let num_nodes = config.num_nodes;
+
+        let weights = config.weights.clone().unwrap_or_else(|| vec![1; num_nodes]);
+        assert_eq!(weights.len(), num_nodes, "Number of weights must be equal to number of nodes");
+
+        let mut rng = ChaChaRng::seed_from_u64(config.seed);
+        let key_pairs: Vec<KeyPair> = (0 .. num_nodes)
+            .map(|_| KeyPair::generate(&mut rng))
+            .collect();
+        let level_seeds: Vec<u64> = (0 .. num_nodes)
+            .map(|_| rng.gen())
+            .collect();
+        let registry = Arc::new(SimulatedRegistry::new(
+            key_pairs.iter().map(|key_pair| key_pair.public).collect(),
+            weights,
+        ));
+        let threshold = config.threshold.unwrap_or_else(|| (2 * registry.total_weight() + 3) / 3);
+        let message_hash = Blake2bHasher::default().digest(format!("handel simulation {}", config.seed).as_bytes());
+        let forged_hash = Blake2bHasher::default().digest(format!("forged {}", config.seed).as_bytes());
+
+        let honest: BTreeSet<usize> = (0 .. num_nodes)
+            .filter(|node_id| !config.byzantine.contains_key(node_id))
+            .collect();
+
+        let clock = SimulatedClock::new();
+        let network = Arc::new(SimulatedNetwork::new(config, clock.clone()));
+        let completion_times = Arc::new(Mutex::new(vec![None; num_nodes]));
+
+        // The timer advances the simulated clock whenever the executor is idle.
+        let timer = Timer::new_with_now(SimulatedPark::new(clock.clone()), Clock::new_with_now(clock.clone()));
+        let timer_handle = timer.handle();
+        let mut executor = CurrentThread::new_with_park(timer);
+        {
+            let network = Arc::clone(&network);
+            let completion_times = Arc::clone(&completion_times);
+            let clock = clock.clone();
+            let factory = self.factory.clone();
+            let handel_config = config.handel.clone();
+            let byzantine = config.byzantine.clone();
+
+            executor.spawn(future::lazy(move || {
+                let mut contributions = Vec::new();
+
+                for (node_id, key_pair) in key_pairs.into_iter().enumerate() {
+                    let behavior = byzantine.get(&node_id).cloned();
+                    if behavior == Some(ByzantineBehavior::Offline) {
+                        continue;
+                    }
+
+                    let sender = SimulatedSender::new(node_id, behavior, key_pair.clone(), forged_hash.clone(), Arc::clone(&network));
+                    let protocol = SimulatedProtocol::new(&factory, node_id, Arc::clone(&registry), message_hash.clone(), threshold, sender);
+                    let mut level_rng = ChaChaRng::seed_from_u64(level_seeds[node_id]);
+                    let aggregation = Aggregation::with_rng(protocol, handel_config.clone(), &mut level_rng);
+
+                    let completion_times = Arc::clone(&completion_times);
+                    let clock = clock.clone();
+                    let registry = Arc::clone(&registry);
+                    aggregation.notifier.write().register(move |event: AggregationEvent| {
+                        match event {
+                            AggregationEvent::Complete { best } => {
+                                trace!("Node {} complete: weight={:?}", node_id, registry.signers_weight(&best.signers));
+                                completion_times.lock()[node_id] = Some(clock.elapsed());
+                            },
+                        }
+                    });
+
+                    let handler = Arc::clone(&aggregation);
+                    network.set_handler(node_id, Arc::new(move |update| handler.push_update(update)));
+
+                    let contribution = IndividualSignature::new(key_pair.sign_hash(message_hash.clone()), node_id);
+                    contributions.push((aggregation, contribution));
+                }
+
+                // Only contribute after all nodes are listening.
+                for (aggregation, contribution) in contributions {
+                    aggregation.push_contribution(contribution);
+                }
+
+                Ok(())
+            }));
+        }
+
+        // Run until all honest nodes are done
+        let clock_source = Clock::new_with_now(clock.clone());
+        let mut enter = tokio_executor::enter().expect("Multiple executors at once");
+        tokio_timer::clock::with_default(&clock_source, &mut enter, |enter| {
+            tokio_timer::with_default(&timer_handle, enter, |enter| {
+                let mut default_executor = TaskExecutor::current();
+                tokio_executor::with_default(&mut default_executor, enter, |enter| {
+                    let mut executor = executor.enter(enter);
+                    loop {
+                        let remaining = {
+                            let completion_times = completion_times.lock();
+                            honest.iter().filter(|&&node_id| completion_times[node_id].is_none()).count()
+                        };
+                        if remaining == 0 {
+                            break;
+                        }
+
+                        let elapsed = clock.elapsed();
+                        if elapsed >= config.max_duration {
+                            warn!("Simulation timed out: {} honest nodes didn't reach the threshold", remaining);
+                            break;
+                        }
+
+                        executor.turn(Some(config.max_duration - elapsed))
+                            .expect("Simulation executor failed");
+                    }
+                })
+            })
+        });
+
+        network.shutdown();
+        let completion_times = completion_times.lock().clone();
+
+        SimulationReport {
+            completion_times,
+            honest,
+            messages: network.stats(),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::io::Error as IoError;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use futures::Future;
+use parking_lot::{Mutex, RwLock};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+use tokio::clock;
+use tokio::timer::Delay;
+
+use beserial::{Deserialize, Serialize};
+use bls::bls12_381::{AggregateSignature, KeyPair};
+use hash::Blake2bHash;
+
+use crate::multisig::{IndividualSignature, MultiSignature};
+use crate::sender::Sender;
+use crate::update::{LevelUpdate, LevelUpdateMessage};
+
+use super::{ByzantineBehavior, MessageStats, Partition, SimulationConfig};
+use super::clock::SimulatedClock;
+
+
+type Handler = Arc<dyn Fn(LevelUpdate) + Send + Sync>;
+
+/// In-memory transport between the simulated nodes.
+///
+/// Every link between two nodes has its own RNG derived from the seed, which decides whether a
+/// message is lost and how long it takes to be delivered.
+pub(crate) struct SimulatedNetwork {
+    seed: u64,
+    tag: u64,
+    min_latency: Duration,
+    max_latency: Duration,
+    loss: f64,
+    partitions: Vec<Partition>,
+    clock: SimulatedClock,
+
+    links: Mutex<HashMap<(usize, usize), ChaChaRng>>,
+    nodes: RwLock<Vec<Option<Handler>>>,
+
+    sent: AtomicUsize,
+    delivered: AtomicUsize,
+    dropped: AtomicUsize,
+    invalid: AtomicUsize,
+    bytes: AtomicUsize,
+}
+
+impl SimulatedNetwork {
+    pub fn new(config: &SimulationConfig, clock: SimulatedClock) -> Self {
+        assert!(config.min_latency <= config.max_latency, "Minimum latency must not be greater than maximum latency");
+        assert!(config.loss >= 0.0 && config.loss <= 1.0, "Loss must be a probability");
+
+        Self {
+            seed: config.seed,
+            tag: config.seed,
+            min_latency: config.min_latency,
+            max_latency: config.max_latency,
+            loss: config.loss,
+            partitions: config.partitions.clone(),
+            clock,
+            links: Mutex::new(HashMap::new()),
+            nodes: RwLock::new(vec![None; config.num_nodes]),
+            sent: AtomicUsize::new(0),
+            delivered: AtomicUsize::new(0),
+            dropped: AtomicUsize::new(0),
+            invalid: AtomicUsize::new(0),
+            bytes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Sets the function that receives the updates for `node_id`.
+    pub fn set_handler(&self, node_id: usize, handler: Handler) {
+        self.nodes.write()[node_id] = Some(handler);
+    }
+
+    /// Removes all handlers. This breaks the reference cycle between the network and the
+    /// aggregations.
+    pub fn shutdown(&self) {
+        for node in self.nodes.write().iter_mut() {
+            *node = None;
+        }
+    }
+
+    pub fn stats(&self) -> MessageStats {
+        MessageStats {
+            sent: self.sent.load(Ordering::SeqCst),
+            delivered: self.delivered.load(Ordering::SeqCst),
+            dropped: self.dropped.load(Ordering::SeqCst),
+            invalid: self.invalid.load(Ordering::SeqCst),
+            bytes: self.bytes.load(Ordering::SeqCst),
+        }
+    }
+
+    fn is_partitioned(&self, from: usize, to: usize) -> bool {
+        let elapsed = self.clock.elapsed();
+        self.partitions.iter().any(|partition| {
+            partition.heal_after.map(|heal_after| elapsed < heal_after).unwrap_or(true)
+                && partition.nodes.contains(&from) != partition.nodes.contains(&to)
+        })
+    }
+
+    /// Decides whether the next message on the link is lost and how long it takes to deliver it.
+    fn next_on_link(&self, from: usize, to: usize) -> (bool, Duration) {
+        let mut links = self.links.lock();
+        let seed = self.seed;
+        let rng = links.entry((from, to))
+            .or_insert_with(|| ChaChaRng::seed_from_u64(seed ^ ((from as u64) << 32 | to as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)));
+
+        let lost = rng.gen_bool(self.loss);
+        let min = self.min_latency.as_micros() as u64;
+        let max = self.max_latency.as_micros() as u64;
+        let latency = Duration::from_micros(rng.gen_range(min, max + 1));
+        (lost, latency)
+    }
+
+    pub fn send(this: &Arc<Self>, from: usize, to: usize, data: Vec<u8>) {
+        this.sent.fetch_add(1, Ordering::SeqCst);
+        this.bytes.fetch_add(data.len(), Ordering::SeqCst);
+
+        let (lost, latency) = this.next_on_link(from, to);
+        if lost || this.is_partitioned(from, to) {
+            trace!("Dropping message from {} to {}", from, to);
+            this.dropped.fetch_add(1, Ordering::SeqCst);
+            return;
+        }
+
+        let network = Arc::clone(this);
+        tokio::spawn(Delay::new(clock::now() + latency)
+            .map(move |_| network.deliver(to, data))
+            .map_err(|e| warn!("Delivery delay failed: {}", e)));
+    }
+
+    fn deliver(&self, to: usize, data: Vec<u8>) {
+        let message: LevelUpdateMessage<u64> = match Deserialize::deserialize_from_vec(&data) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Failed to deserialize update: {}", e);
+                self.dropped.fetch_add(1, Ordering::SeqCst);
+                return;
+            },
+        };
+        if message.tag != self.tag {
+            self.dropped.fetch_add(1, Ordering::SeqCst);
+            return;
+        }
+
+        // NOTE: Clone the handler, such that we don't hold the lock while processing the update.
+        let handler = self.nodes.read().get(to).cloned().and_then(|handler| handler);
+        match handler {
+            Some(handler) => {
+                self.delivered.fetch_add(1, Ordering::SeqCst);
+                handler(message.update);
+            },
+            None => {
+                self.dropped.fetch_add(1, Ordering::SeqCst);
+            },
+        }
+    }
+}
+
+
+/// Sends the updates of a node over the simulated network. Byzantine nodes tamper with their
+/// updates here.
+pub struct SimulatedSender {
+    node_id: usize,
+    behavior: Option<ByzantineBehavior>,
+    key_pair: KeyPair,
+    forged_hash: Blake2bHash,
+    network: Arc<SimulatedNetwork>,
+}
+
+impl SimulatedSender {
+    pub(crate) fn new(node_id: usize, behavior: Option<ByzantineBehavior>, key_pair: KeyPair, forged_hash: Blake2bHash, network: Arc<SimulatedNetwork>) -> Self {
+        Self {
+            node_id,
+            behavior,
+            key_pair,
+            forged_hash,
+            network,
+        }
+    }
+
+    /// Replaces the signatures in `update` with signatures over a different message, but keeps
+    /// the signers.
+    fn forge(&self, update: LevelUpdate) -> LevelUpdate {
+        let forged = self.key_pair.sign_hash(self.forged_hash.clone());
+
+        let mut signature = AggregateSignature::new();
+        signature.aggregate(&forged);
+        let multisig = MultiSignature::new(signature, update.multisig.signers);
+        let individual = update.individual
+            .map(|individual| IndividualSignature::new(forged, individual.signer));
+
+        LevelUpdate {
+            multisig,
+            individual,
+            level: update.level,
+            origin: update.origin,
+        }
+    }
+}
+
+impl Sender for SimulatedSender {
+    type Error = IoError;
+
+    fn send_to(&self, peer_id: usize, update: LevelUpdate) {
+        let (update, copies) = match self.behavior {
+            Some(ByzantineBehavior::InvalidSignatures) => {
+                self.network.invalid.fetch_add(1, Ordering::SeqCst);
+                (self.forge(update), 1)
+            },
+            Some(ByzantineBehavior::Duplicates(copies)) => (update, copies.max(1)),
+            _ => (update, 1),
+        };
+
+        let data = update.with_tag(self.network.tag).serialize_to_vec();
+        for _ in 0 .. copies {
+            SimulatedNetwork::send(&self.network, self.node_id, peer_id, data.clone());
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::fmt;
+use std::sync::Arc;
+
+use futures::future;
+use futures::future::FutureResult;
+use parking_lot::RwLock;
+
+use bls::bls12_381::PublicKey;
+use hash::Blake2bHash;
+
+use crate::evaluator::{Evaluator, WeightedVote};
+use crate::identity::{IdentityRegistry, WeightRegistry};
+use crate::partitioner::{BinomialPartitioner, Partitioner};
+use crate::protocol::Protocol;
+use crate::store::ReplaceStore;
+use crate::multisig::Signature;
+use crate::verifier::{MultithreadedVerifier, VerificationResult, Verifier};
+
+use super::network::SimulatedSender;
+
+
+/// Public keys and weights of all simulated nodes
+#[derive(Clone, Debug)]
+pub struct SimulatedRegistry {
+    public_keys: Vec<PublicKey>,
+    weights: Vec<usize>,
+}
+
+impl SimulatedRegistry {
+    pub fn new(public_keys: Vec<PublicKey>, weights: Vec<usize>) -> Self {
+        assert_eq!(public_keys.len(), weights.len(), "Number of public keys and weights differ");
+        Self {
+            public_keys,
+            weights,
+        }
+    }
+
+    pub fn total_weight(&self) -> usize {
+        self.weights.iter().sum()
+    }
+}
+
+impl IdentityRegistry for SimulatedRegistry {
+    fn public_key(&self, id: usize) -> Option<PublicKey> {
+        self.public_keys.get(id).cloned()
+    }
+}
+
+impl WeightRegistry for SimulatedRegistry {
+    fn weight(&self, id: usize) -> Option<usize> {
+        self.weights.get(id).cloned()
+    }
+}
+
+
+/// Verifies signatures on the thread of the simulation.
+///
+/// The simulated clock advances as soon as the simulation is idle, so verifications must not run
+/// in the background.
+pub struct SimulatedVerifier {
+    message_hash: Blake2bHash,
+    registry: Arc<SimulatedRegistry>,
+}
+
+impl SimulatedVerifier {
+    pub fn new(message_hash: Blake2bHash, registry: Arc<SimulatedRegistry>) -> Self {
+        Self {
+            message_hash,
+            registry,
+        }
+    }
+}
+
+impl Verifier for SimulatedVerifier {
+    type Output = FutureResult<VerificationResult, ()>;
+
+    fn verify(&self, signature: &Signature) -> Self::Output {
+        let registry = Arc::clone(&self.registry);
+        let message_hash = self.message_hash.clone();
+        future::ok(match signature {
+            Signature::Individual(individual) => {
+                MultithreadedVerifier::verify_individual(registry, message_hash, individual)
+            },
+            Signature::Multi(multisig) => {
+                MultithreadedVerifier::verify_multisig(registry, message_hash, multisig)
+            },
+        })
+    }
+}
+
+
+/// Creates the parts of the protocol that are under test, i.e. the partitioner and evaluator.
+pub trait ProtocolFactory: Clone + Send + Sync + 'static {
+    type Partitioner: Partitioner + Send + Sync + 'static;
+    type Evaluator: Evaluator + Send + Sync + 'static;
+
+    fn partitioner(&self, node_id: usize, num_nodes: usize) -> Self::Partitioner;
+
+    fn evaluator(&self, store: Arc<RwLock<ReplaceStore<Self::Partitioner>>>, registry: Arc<SimulatedRegistry>, partitioner: Arc<Self::Partitioner>, threshold: usize) -> Self::Evaluator;
+}
+
+/// The partitioner and evaluator that are used by the validators
+#[derive(Clone, Debug, Default)]
+pub struct DefaultFactory;
+
+impl ProtocolFactory for DefaultFactory {
+    type Partitioner = BinomialPartitioner;
+    type Evaluator = WeightedVote<ReplaceStore<BinomialPartitioner>, SimulatedRegistry, BinomialPartitioner>;
+
+    fn partitioner(&self, node_id: usize, num_nodes: usize) -> Self::Partitioner {
+        BinomialPartitioner::new(node_id, num_nodes)
+    }
+
+    fn evaluator(&self, store: Arc<RwLock<ReplaceStore<Self::Partitioner>>>, registry: Arc<SimulatedRegistry>, partitioner: Arc<Self::Partitioner>, threshold: usize) -> Self::Evaluator {
+        WeightedVote::new(store, registry, partitioner, threshold)
+    }
+}
+
+
+/// Protocol of a simulated node
+pub struct SimulatedProtocol<F: ProtocolFactory> {
+    node_id: usize,
+    registry: Arc<SimulatedRegistry>,
+    verifier: Arc<SimulatedVerifier>,
+    partitioner: Arc<F::Partitioner>,
+    store: Arc<RwLock<ReplaceStore<F::Partitioner>>>,
+    evaluator: Arc<F::Evaluator>,
+    sender: Arc<SimulatedSender>,
+}
+
+impl<F: ProtocolFactory> SimulatedProtocol<F> {
+    pub(crate) fn new(factory: &F, node_id: usize, registry: Arc<SimulatedRegistry>, message_hash: Blake2bHash, threshold: usize, sender: SimulatedSender) -> Self {
+        let num_nodes = registry.public_keys.len();
+        let verifier = Arc::new(SimulatedVerifier::new(message_hash, Arc::clone(&registry)));
+        let partitioner = Arc::new(factory.partitioner(node_id, num_nodes));
+        let store = Arc::new(RwLock::new(ReplaceStore::new(Arc::clone(&partitioner))));
+        let evaluator = Arc::new(factory.evaluator(
+            Arc::clone(&store),
+            Arc::clone(&registry),
+            Arc::clone(&partitioner),
+            threshold,
+        ));
+
+        Self {
+            node_id,
+            registry,
+            verifier,
+            partitioner,
+            store,
+            evaluator,
+            sender: Arc::new(sender),
+        }
+    }
+}
+
+impl<F: ProtocolFactory> fmt::Debug for SimulatedProtocol<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "SimulatedProtocol {{ node_id: {} }}", self.node_id)
+    }
+}
+
+impl<F: ProtocolFactory> Protocol for SimulatedProtocol<F> {
+    type Registry = SimulatedRegistry;
+    type Verifier = SimulatedVerifier;
+    type Store = ReplaceStore<F::Partitioner>;
+    type Evaluator = F::Evaluator;
+    type Partitioner = F::Partitioner;
+    type Sender = SimulatedSender;
+
+    fn registry(&self) -> Arc<Self::Registry> {
+        Arc::clone(&self.registry)
+    }
+
+    fn verifier(&self) -> Arc<Self::Verifier> {
+        Arc::clone(&self.verifier)
+    }
+
+    fn store(&self) -> Arc<RwLock<Self::Store>> {
+        Arc::clone(&self.store)
+    }
+
+    fn evaluator(&self) -> Arc<Self::Evaluator> {
+        Arc::clone(&self.evaluator)
+    }
+
+    fn partitioner(&self) -> Arc<Self::Partitioner> {
+        Arc::clone(&self.partitioner)
+    }
+
+    fn sender(&self) -> Arc<Self::Sender> {
+        Arc::clone(&self.sender)
+    }
+
+    fn node_id(&self) -> usize {
+        self.node_id
+    }
+}
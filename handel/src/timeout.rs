@@ -1,22 +1,24 @@
-/// A timeout strategy defines after which period a level timeouts
-///
-/// TODO: This is not used currently
-
+/// A timeout strategy defines after which period the next level of an aggregation is started,
+/// even if the previous level is not complete yet.
 
+use std::cmp;
+use std::fmt::Debug;
 use std::time::Duration;
-use std::ops::Range;
 
-use futures::{Stream, stream};
-use tokio::util::StreamExt;
+use parking_lot::Mutex;
 
 
-pub trait TimeoutStrategy {
-    type Timeouts: Stream<Item=usize, Error=()>;
+pub trait TimeoutStrategy: Debug + Send + Sync {
+    /// Returns how long to wait after level `level - 1` was started, before starting `level`.
+    /// For level 0 this is the delay after the aggregation was created.
+    fn timeout(&self, level: usize) -> Duration;
 
-    fn timeouts(&self, num_levels: usize) -> Self::Timeouts;
+    /// Called when `level` completed, `elapsed` after it was started.
+    fn level_completed(&self, _level: usize, _elapsed: Duration) {}
 }
 
 
+/// Starts a level after every `period`.
 #[derive(Clone, Debug)]
 pub struct LinearTimeout {
     period: Duration,
@@ -37,18 +39,173 @@ impl Default for LinearTimeout {
 }
 
 impl TimeoutStrategy for LinearTimeout {
-    type Timeouts = Box<dyn Stream<Item=usize, Error=()> + Send>;
-
-    fn timeouts(&self, num_levels: usize) -> Self::Timeouts {
-        debug!("Creating timeout stream: period={:?}, levels={}", self.period, num_levels);
-        Box::new(stream::iter_ok::<Range<usize>, ()>(0..num_levels)
-            .throttle(self.period.clone())
-            .map(|level| {
-                debug!("Timeout for level {}", level);
-                level
-            })
-            .map_err(|e| {
-                warn!("Throttle error: {:?}", e);
-            }))
+    fn timeout(&self, _level: usize) -> Duration {
+        self.period
+    }
+}
+
+
+/// Waits `base * factor^level` before starting a level, but never longer than `max`.
+///
+/// Higher levels contain exponentially more peers, so it takes longer until we received all of
+/// their signatures.
+#[derive(Clone, Debug)]
+pub struct ExponentialTimeout {
+    base: Duration,
+    factor: f64,
+    max: Duration,
+}
+
+impl ExponentialTimeout {
+    pub fn new(base: Duration, factor: f64, max: Duration) -> Self {
+        assert!(factor >= 1.0, "Factor must be at least 1");
+        ExponentialTimeout {
+            base,
+            factor,
+            max,
+        }
+    }
+}
+
+impl Default for ExponentialTimeout {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(100), 2.0, Duration::from_secs(5))
+    }
+}
+
+impl TimeoutStrategy for ExponentialTimeout {
+    fn timeout(&self, level: usize) -> Duration {
+        let secs = self.base.as_secs_f64() * self.factor.powi(level as i32);
+        if secs >= self.max.as_secs_f64() {
+            self.max
+        }
+        else {
+            Duration::from_secs_f64(secs)
+        }
+    }
+}
+
+
+/// Smoothed completion time of a level and its variation, estimated like the round-trip time in
+/// TCP (RFC 6298).
+#[derive(Clone, Copy, Debug)]
+struct LatencyEstimate {
+    smoothed: Duration,
+    variation: Duration,
+}
+
+impl LatencyEstimate {
+    fn new(sample: Duration) -> Self {
+        LatencyEstimate {
+            smoothed: sample,
+            variation: sample / 2,
+        }
+    }
+
+    fn update(&mut self, sample: Duration) {
+        let deviation = if sample > self.smoothed { sample - self.smoothed } else { self.smoothed - sample };
+        self.variation = (self.variation * 3 + deviation) / 4;
+        self.smoothed = (self.smoothed * 7 + sample) / 8;
+    }
+
+    fn timeout(&self) -> Duration {
+        self.smoothed + self.variation * 4
+    }
+}
+
+/// Adapts the timeouts to the latency of the network. The timeout for a level is derived from how
+/// long the previous level took to complete in earlier aggregations, bounded by `min` and `max`.
+///
+/// Share one instance between aggregations (i.e. clone the `Config`) to keep the estimates.
+#[derive(Debug)]
+pub struct AdaptiveTimeout {
+    /// Timeout used for levels that never completed so far
+    initial: Duration,
+    min: Duration,
+    max: Duration,
+    estimates: Mutex<Vec<Option<LatencyEstimate>>>,
+}
+
+impl AdaptiveTimeout {
+    pub fn new(initial: Duration, min: Duration, max: Duration) -> Self {
+        assert!(min <= max, "Minimum timeout must not be greater than maximum timeout");
+        AdaptiveTimeout {
+            initial,
+            min,
+            max,
+            estimates: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl Default for AdaptiveTimeout {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(500), Duration::from_millis(100), Duration::from_secs(5))
+    }
+}
+
+impl TimeoutStrategy for AdaptiveTimeout {
+    fn timeout(&self, level: usize) -> Duration {
+        // Level 0 only contains ourselves and completes as soon as we contributed.
+        if level <= 1 {
+            return self.min;
+        }
+
+        let timeout = self.estimates.lock()
+            .get(level - 1)
+            .and_then(|estimate| estimate.map(|estimate| estimate.timeout()))
+            .unwrap_or(self.initial);
+        cmp::max(self.min, cmp::min(timeout, self.max))
+    }
+
+    fn level_completed(&self, level: usize, elapsed: Duration) {
+        if level == 0 {
+            return;
+        }
+
+        let mut estimates = self.estimates.lock();
+        if estimates.len() <= level {
+            estimates.resize(level + 1, None);
+        }
+        match &mut estimates[level] {
+            Some(estimate) => estimate.update(elapsed),
+            estimate => *estimate = Some(LatencyEstimate::new(elapsed)),
+        }
+        trace!("Level {} completed after {:?}, estimate: {:?}", level, elapsed, estimates[level]);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_computes_exponential_timeouts() {
+        let timeouts = ExponentialTimeout::new(Duration::from_millis(100), 2.0, Duration::from_secs(1));
+        assert_eq!(timeouts.timeout(0), Duration::from_millis(100));
+        assert_eq!(timeouts.timeout(1), Duration::from_millis(200));
+        assert_eq!(timeouts.timeout(3), Duration::from_millis(800));
+        assert_eq!(timeouts.timeout(4), Duration::from_secs(1));
+        assert_eq!(timeouts.timeout(100), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn it_adapts_timeouts_to_latency() {
+        let timeouts = AdaptiveTimeout::new(Duration::from_millis(500), Duration::from_millis(100), Duration::from_secs(5));
+        assert_eq!(timeouts.timeout(1), Duration::from_millis(100));
+        assert_eq!(timeouts.timeout(3), Duration::from_millis(500));
+
+        // The timeout of level 3 depends on how long level 2 took.
+        timeouts.level_completed(2, Duration::from_millis(1000));
+        assert_eq!(timeouts.timeout(3), Duration::from_millis(3000));
+
+        for _ in 0..100 {
+            timeouts.level_completed(2, Duration::from_millis(200));
+        }
+        assert!(timeouts.timeout(3) < Duration::from_millis(300));
+
+        timeouts.level_completed(2, Duration::from_secs(60));
+        assert_eq!(timeouts.timeout(3), Duration::from_secs(5));
     }
 }
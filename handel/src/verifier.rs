@@ -73,7 +73,7 @@ impl<I: IdentityRegistry> MultithreadedVerifier<I> {
         Self::new(message_hash, identity_registry, Arc::clone(&SHARED_CPU_POOL))
     }
 
-    fn verify_individual(identity_registry: Arc<I>, message_hash: Blake2bHash, individual: &IndividualSignature) -> VerificationResult {
+    pub(crate) fn verify_individual(identity_registry: Arc<I>, message_hash: Blake2bHash, individual: &IndividualSignature) -> VerificationResult {
         if let Some(public_key) = identity_registry.public_key(individual.signer) {
             if public_key.verify_hash(message_hash, &individual.signature) {
                 VerificationResult::Ok
@@ -87,7 +87,7 @@ impl<I: IdentityRegistry> MultithreadedVerifier<I> {
         }
     }
 
-    fn verify_multisig(identity_registry: Arc<I>, message_hash: Blake2bHash, multisig: &MultiSignature) -> VerificationResult {
+    pub(crate) fn verify_multisig(identity_registry: Arc<I>, message_hash: Blake2bHash, multisig: &MultiSignature) -> VerificationResult {
         let mut aggregated_public_key = AggregatePublicKey::new();
         for signer in multisig.signers.iter() {
             if let Some(public_key) = identity_registry.public_key(signer) {
@@ -0,0 +1,126 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use nimiq_handel::config::Config;
+use nimiq_handel::simulation::{ByzantineBehavior, Partition, Simulation, SimulationConfig};
+use nimiq_handel::timeout::{ExponentialTimeout, LinearTimeout};
+
+fn config(num_nodes: usize) -> SimulationConfig {
+    SimulationConfig {
+        num_nodes,
+        handel: Config {
+            timeout_strategy: Arc::new(LinearTimeout::new(Duration::from_millis(100))),
+            ..Config::default()
+        },
+        max_duration: Duration::from_secs(20),
+        ..SimulationConfig::default()
+    }
+}
+
+#[test]
+fn it_aggregates_with_honest_nodes() {
+    let report = Simulation::new(config(8)).run();
+    assert!(report.all_completed(), "{}", report);
+    assert_eq!(report.messages.dropped, 0);
+    assert_eq!(report.messages.invalid, 0);
+}
+
+#[test]
+fn it_aggregates_with_exponential_timeouts() {
+    let mut config = config(8);
+    config.handel.timeout_strategy = Arc::new(ExponentialTimeout::new(Duration::from_millis(50), 2.0, Duration::from_secs(1)));
+
+    let report = Simulation::new(config).run();
+    assert!(report.all_completed(), "{}", report);
+}
+
+#[test]
+fn it_aggregates_with_byzantine_nodes_and_loss() {
+    let mut config = config(8);
+    config.loss = 0.1;
+    config.byzantine = vec![
+        (1, ByzantineBehavior::InvalidSignatures),
+        (5, ByzantineBehavior::Duplicates(3)),
+    ].into_iter().collect::<BTreeMap<_, _>>();
+
+    let report = Simulation::new(config).run();
+    assert!(report.all_completed(), "{}", report);
+    assert_eq!(report.honest.len(), 6);
+    assert!(report.messages.invalid > 0);
+    assert!(report.messages.dropped > 0);
+}
+
+#[test]
+fn it_aggregates_with_offline_nodes() {
+    let mut config = config(8);
+    config.byzantine.insert(2, ByzantineBehavior::Offline);
+
+    let report = Simulation::new(config).run();
+    assert!(report.all_completed(), "{}", report);
+    assert_eq!(report.completion_times[2], None);
+}
+
+#[test]
+fn it_aggregates_after_partition_heals() {
+    let mut config = config(8);
+    config.partitions.push(Partition {
+        nodes: (0 .. 4).collect::<BTreeSet<_>>(),
+        heal_after: Some(Duration::from_millis(500)),
+    });
+
+    let report = Simulation::new(config).run();
+    assert!(report.all_completed(), "{}", report);
+    assert!(report.messages.dropped > 0);
+    // Completion times are simulated time, so no node can finish before the partition heals.
+    assert!(report.max_completion_time().unwrap() >= Duration::from_millis(500));
+}
+
+#[test]
+fn it_measures_simulated_time() {
+    let mut config = config(8);
+    config.min_latency = Duration::from_secs(2);
+    config.max_latency = Duration::from_secs(2);
+    config.max_duration = Duration::from_secs(120);
+
+    let report = Simulation::new(config).run();
+    assert!(report.all_completed(), "{}", report);
+    // Every node needs at least one message from another node.
+    assert!(report.max_completion_time().unwrap() >= Duration::from_secs(2));
+}
+
+#[test]
+fn it_does_not_aggregate_in_permanent_partition() {
+    let mut config = config(8);
+    config.max_duration = Duration::from_secs(2);
+    config.partitions.push(Partition {
+        nodes: (0 .. 4).collect::<BTreeSet<_>>(),
+        heal_after: None,
+    });
+
+    let report = Simulation::new(config).run();
+    assert_eq!(report.num_completed(), 0, "{}", report);
+}
+
+#[test]
+fn it_aggregates_with_unequal_weights() {
+    let mut config = config(8);
+    config.weights = Some(vec![1, 20, 1, 5, 1, 1, 30, 2]);
+
+    let report = Simulation::new(config).run();
+    assert!(report.all_completed(), "{}", report);
+}
+
+#[test]
+fn it_produces_the_same_report_for_the_same_seed() {
+    let mut config = config(16);
+    config.loss = 0.1;
+    config.seed = 42;
+    config.byzantine.insert(3, ByzantineBehavior::Duplicates(2));
+
+    let first = Simulation::new(config.clone()).run();
+    let second = Simulation::new(config).run();
+    assert!(first.all_completed(), "{}", first);
+    assert_eq!(first.completion_times, second.completion_times);
+    assert_eq!(first.messages, second.messages);
+}
@@ -1,6 +1,9 @@
 use std::convert::TryFrom;
+use std::path::Path;
 use std::sync::{Arc, Weak};
 
+#[cfg(feature="validator")]
+use signer::{LocalSigner, RemoteSigner, Signer};
 #[cfg(feature="validator")]
 use validator::validator::Validator;
 use consensus::{
@@ -10,8 +13,11 @@ use consensus::{
 use database::Environment;
 use network::{NetworkConfig, Network as GenericNetwork};
 use mempool::Mempool as GenericMempool;
+use network_primitives::networks::{NetworkId, NetworkInfo};
 use network_primitives::services::ServiceFlags;
 use blockchain::Blockchain;
+use build_tools::genesis::albatross::{GenesisBuilder, GenesisInfo};
+use primitives::slot;
 
 use crate::error::Error;
 use crate::config::config::{ClientConfig, ProtocolConfig};
@@ -23,6 +29,35 @@ pub type Mempool = GenericMempool<Blockchain>;
 pub type Network = GenericNetwork<Blockchain>;
 
 
+/// Generates the genesis block from the genesis config file and registers it as the custom
+/// Albatross network with the network ID set in that file.
+fn register_custom_network(genesis_file: &Path) -> Result<NetworkId, Error> {
+    let mut builder = GenesisBuilder::default();
+    builder.with_config_file(genesis_file)?;
+
+    // Otherwise every node would generate a different genesis block.
+    if builder.timestamp.is_none() {
+        return Err(Error::config_error("Genesis file of a custom network must set a timestamp."));
+    }
+
+    // Transactions are signed for their network ID, so each custom network needs its own.
+    let network_id = builder.network_id
+        .ok_or_else(|| Error::config_error("Genesis file of a custom network must set a network ID."))?;
+
+    // The genesis block already encodes slot allocations, so the number of slots must be set first.
+    let policy = builder.policy.unwrap_or_default();
+    slot::set_num_slots(policy.slots);
+
+    let GenesisInfo { block, hash, accounts } = builder.generate()?;
+    let validator_registry = builder.staking_contract_address.clone()
+        .ok_or_else(|| Error::config_error("Genesis file must set the staking contract address."))?;
+    info!("Custom network {} genesis block: {} ({:?})", network_id, hash, policy);
+
+    NetworkInfo::register_custom_albatross(network_id, &block, hash, accounts, validator_registry, policy)?;
+    Ok(network_id)
+}
+
+
 /// Holds references to the relevant structs. This is then Arc'd in `Client` and a nice API is
 /// exposed.
 ///
@@ -82,10 +117,18 @@ impl TryFrom<ClientConfig> for ClientInner {
         // Initialize peer key
         config.storage.init_key_store(&mut network_config)?;
 
-        // Load validator key (before we give away ownership of the storage config
+        // Load validator key or connect to the remote signer (before we give away ownership of
+        // the storage config)
         #[cfg(feature="validator")]
-        let validator_key = config.storage.validator_key()
-            .expect("Failed to load validator key");
+        let validator_signer = config.validator.as_ref()
+            .map(|validator_config| -> Result<Arc<dyn Signer>, Error> {
+                let signer: Arc<dyn Signer> = match &validator_config.signer {
+                    Some(address) => Arc::new(RemoteSigner::connect(address.clone(), validator_config.signer_token.clone())?),
+                    None => Arc::new(LocalSigner::new(config.storage.validator_key()?)),
+                };
+                Ok(signer)
+            })
+            .transpose()?;
 
         // Add validator service flag, if necessary
         #[cfg(feature="validator")]
@@ -98,23 +141,40 @@ impl TryFrom<ClientConfig> for ClientInner {
             }
         }
 
+        // Generate the genesis block of a custom network, which also determines its network ID
+        let network_id = match (&config.genesis_file, config.network) {
+            (Some(genesis_file), network) => {
+                let network_id = register_custom_network(genesis_file)?;
+                if let NetworkId::CustomAlbatross(_) = network {
+                    if network != network_id {
+                        return Err(Error::config_error(&format!("Genesis file is for {}, but {} is configured", network_id, network)));
+                    }
+                }
+                network_id
+            },
+            (None, NetworkId::CustomAlbatross(_)) => {
+                return Err(Error::config_error("Genesis file is required for custom networks."));
+            },
+            (None, network) => network,
+        };
+
         // Open database
-        let environment = config.storage.database(config.network, config.consensus, config.database)?;
+        let environment = config.storage.database(network_id, config.consensus, config.database)?;
 
         // Create Nimiq consensus
-        if !config.network.is_albatross() {
-            return Err(Error::config_error(&format!("{} is not compatible with Albatross", config.network)));
+        if !network_id.is_albatross() {
+            return Err(Error::config_error(&format!("{} is not compatible with Albatross", network_id)));
         }
         let consensus = Consensus::new(
             environment.clone(),
-            config.network,
+            network_id,
             network_config,
             config.mempool,
         )?;
 
         #[cfg(feature="validator")]
-        let validator = config.validator.map(|_config| {
-            Validator::new(Arc::clone(&consensus), validator_key)
+        let validator = validator_signer.map(|signer| {
+            Validator::new(Arc::clone(&consensus), signer)
         }).transpose()?;
 
         Ok(ClientInner {
@@ -89,6 +89,15 @@ pub struct CommandLine {
     ///
     #[structopt(long)]
     pub network: Option<NetworkId>,
+
+    /// Genesis config file of a custom Albatross network. The network ID is set in this file.
+    ///
+    /// # Examples
+    ///
+    /// * `nimiq-client --genesis-file my-network.toml`
+    ///
+    #[structopt(long = "genesis-file")]
+    pub genesis_file: Option<String>,
 }
 
 impl CommandLine {
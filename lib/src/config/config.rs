@@ -10,7 +10,7 @@ use url::Url;
 #[cfg(feature="validator")]
 use bls::SecureGenerate;
 #[cfg(feature="validator")]
-use bls::bls12_381::KeyPair as BlsKeyPair;
+use bls::bls12_381::{KeyPair as BlsKeyPair, SecretKey as BlsSecretKey};
 use database::Environment;
 use database::lmdb::{LmdbEnvironment, open as LmdbFlags};
 use database::volatile::VolatileEnvironment;
@@ -18,7 +18,13 @@ use mempool::filter::Rules as MempoolRules;
 use mempool::MempoolConfig;
 use network::network_config::{NetworkConfig, ReverseProxyConfig, Seed};
 use network_primitives::address::{NetAddress, SeedList, PeerUri};
+#[cfg(feature="validator")]
+use signer::SignerAddress;
 use primitives::networks::NetworkId;
+#[cfg(feature="rpc-server")]
+use rpc_server::access::Subnet;
+#[cfg(feature="rpc-server")]
+use rpc_server::auth::ApiToken;
 use utils::key_store::Error as KeyStoreError;
 use utils::key_store::KeyStore;
 use keys::PublicKey;
@@ -28,6 +34,7 @@ use crate::config::command_line::CommandLine;
 use crate::config::config_file;
 use crate::config::config_file::ConfigFile;
 use crate::config::consts;
+use crate::config::passphrase::PassphraseSource;
 use crate::config::paths;
 use crate::config::user_agent::UserAgent;
 use crate::error::Error;
@@ -117,9 +124,13 @@ pub enum ProtocolConfig {
 }
 
 #[cfg(feature="validator")]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ValidatorConfig {
-    // TODO
+    /// Address of a remote signer that holds the validator key. If not set, the validator key is
+    /// loaded from the key file.
+    pub signer: Option<SignerAddress>,
+    /// Token to authenticate with the remote signer. Required for signers reachable over TCP.
+    pub signer_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -132,8 +143,14 @@ pub struct FileStorageConfig {
     /// Path to peer key
     peer_key: PathBuf,
 
+    /// Passphrase of the peer key, if it is encrypted
+    peer_key_passphrase: PassphraseSource,
+
     /// Path to validator key
     validator_key: Option<PathBuf>,
+
+    /// Passphrase of the validator key, if it is encrypted
+    validator_key_passphrase: PassphraseSource,
 }
 
 impl FileStorageConfig {
@@ -144,7 +161,9 @@ impl FileStorageConfig {
         Self {
             database_parent: path.to_path_buf(),
             peer_key: path.join("peer_key.dat"),
+            peer_key_passphrase: PassphraseSource::new("peer key", "NIMIQ_PEER_KEY_PASSPHRASE"),
             validator_key: Some(path.join("validator_key.dat")),
+            validator_key_passphrase: PassphraseSource::new("validator key", "NIMIQ_VALIDATOR_KEY_PASSPHRASE"),
         }
     }
 
@@ -174,8 +193,8 @@ pub struct DatabaseConfig {
     #[builder(default="50 * 1024 * 1024")]
     size: usize,
 
-    /// Max number of DBs. Recommended: 10
-    #[builder(default="10")]
+    /// Max number of DBs. Default: `consts::DATABASE_DEFAULT_MAX_DBS`
+    #[builder(default="consts::DATABASE_DEFAULT_MAX_DBS")]
     max_dbs: u32,
 
     /// Additional LMDB flags
@@ -187,7 +206,7 @@ impl Default for DatabaseConfig {
     fn default() -> Self {
         Self {
             size: 50 * 1024 * 1024,
-            max_dbs: 10,
+            max_dbs: consts::DATABASE_DEFAULT_MAX_DBS,
             flags: LmdbFlags::NOMETASYNC,
         }
     }
@@ -281,7 +300,8 @@ impl StorageConfig {
                     .ok_or_else(|| Error::config_error(format!("Failed to convert path of peer key to string: {}", file_storage.peer_key.display())))?
                     .to_string();
                 let key_store = KeyStore::new(key_path.clone());
-                network_config.init_persistent(&key_store)
+                let passphrase = Self::key_passphrase(&key_store, &file_storage.peer_key_passphrase)?;
+                network_config.init_persistent(&key_store, passphrase.as_ref().map(String::as_bytes))
                     .map_err(|e| {
                         warn!("Failed to initialize network config: {}", e);
                         warn!("Does the peer key file exist? {}", key_path);
@@ -306,19 +326,34 @@ impl StorageConfig {
                     .ok_or_else(|| Error::config_error(format!("Failed to convert path of validator key to string: {}", key_path.display())))?
                     .to_string();
                 let key_store = KeyStore::new(key_path);
-                match key_store.load_key() {
+                let passphrase = Self::key_passphrase(&key_store, &file_storage.validator_key_passphrase)?;
+                let passphrase = passphrase.as_ref().map(String::as_bytes);
+                match key_store.load_key_with_passphrase(passphrase) {
                     Err(KeyStoreError::IoError(_)) => {
-                        let validator_key = BlsKeyPair::generate_default_csprng();
-                        key_store.save_key(&validator_key)?;
-                        Ok(validator_key)
+                        let secret_key = BlsSecretKey::generate_default_csprng();
+                        match passphrase {
+                            Some(passphrase) => key_store.save_encrypted_key(secret_key, passphrase)?,
+                            None => key_store.save_key(&secret_key)?,
+                        }
+                        Ok(BlsKeyPair::from(secret_key))
                     },
-                    res => res,
+                    res => res.map(BlsKeyPair::from),
                 }?
             },
             _ => return Err(self.not_available()),
         })
     }
 
+    /// Returns the passphrase for a key file. The user is only prompted for it, if the key file is
+    /// encrypted.
+    fn key_passphrase(key_store: &KeyStore, source: &PassphraseSource) -> Result<Option<String>, Error> {
+        if key_store.exists() && key_store.is_encrypted()? {
+            Ok(Some(source.passphrase()?))
+        } else {
+            source.configured()
+        }
+    }
+
     fn not_available(&self) -> Error {
         Error::Config(format!("Storage backend not implemented: {:?}", self))
     }
@@ -380,10 +415,21 @@ pub struct RpcServerConfig {
     #[builder(setter(strip_option))]
     pub corsdomain: Option<Vec<String>>,
 
-    /// If specified, only allow connections from these IP addresses
+    /// If specified, only allow connections from these IP addresses or subnets
     ///
     #[builder(setter(strip_option))]
-    pub allow_ips: Option<Vec<IpAddr>>,
+    pub allow_ips: Option<Vec<Subnet>>,
+
+    /// If specified, deny connections from these IP addresses or subnets, even if they are
+    /// allowed by `allow_ips`
+    ///
+    #[builder(setter(strip_option))]
+    pub deny_ips: Option<Vec<Subnet>>,
+
+    /// If specified, limit the number of requests per minute from a single IP address
+    ///
+    #[builder(setter(strip_option))]
+    pub rate_limit: Option<usize>,
 
     /// If specified, only allow these RPC methods
     ///
@@ -393,6 +439,11 @@ pub struct RpcServerConfig {
     /// If specified, require HTTP basic auth with these credentials
     #[builder(setter(strip_option))]
     pub credentials: Option<Credentials>,
+
+    /// Named API tokens, each allowed to call a set of methods. Clients authenticate with the
+    /// token name as username and the token as password.
+    #[builder(default)]
+    pub tokens: Vec<ApiToken>,
 }
 
 #[cfg(feature="ws-rpc-server")]
@@ -413,9 +464,30 @@ pub struct WsRpcServerConfig {
     #[builder(default="consts::WS_RPC_DEFAULT_PORT")]
     pub port: u16,
 
+    /// If specified, only allow connections from these IP addresses or subnets
+    ///
+    #[builder(setter(strip_option))]
+    pub allow_ips: Option<Vec<Subnet>>,
+
+    /// If specified, deny connections from these IP addresses or subnets, even if they are
+    /// allowed by `allow_ips`
+    ///
+    #[builder(setter(strip_option))]
+    pub deny_ips: Option<Vec<Subnet>>,
+
+    /// If specified, limit the number of requests per minute from a single IP address
+    ///
+    #[builder(setter(strip_option))]
+    pub rate_limit: Option<usize>,
+
     /// If specified, require HTTP basic auth with these credentials
     #[builder(setter(strip_option))]
     pub credentials: Option<Credentials>,
+
+    /// Named API tokens, each allowed to call a set of methods. Clients authenticate with the
+    /// token name as username and the token as password.
+    #[builder(default)]
+    pub tokens: Vec<ApiToken>,
 }
 
 #[cfg(feature="metrics-server")]
@@ -482,6 +554,12 @@ pub struct ClientConfig {
     #[builder(default="NetworkId::DevAlbatross")]
     pub network: NetworkId,
 
+    /// The genesis config file of a custom Albatross network. If set, the client connects to that
+    /// network, with the network ID from the genesis file, instead of `network`.
+    ///
+    #[builder(setter(strip_option), default)]
+    pub genesis_file: Option<PathBuf>,
+
     /// This configuration is needed if your node runs behind a reverse proxy.
     ///
     #[builder(setter(custom), default)]
@@ -577,6 +655,13 @@ impl ClientConfigBuilder {
         self.network(NetworkId::TestAlbatross)
     }
 
+    /// Sets the network to a custom Albatross network, with the network ID, genesis block and
+    /// policy generated from `genesis_file`
+    ///
+    pub fn custom<P: AsRef<Path>>(&mut self, genesis_file: P) -> &mut Self {
+        self.genesis_file(genesis_file.as_ref())
+    }
+
     /// Sets the client to sync the full block chain.
     ///
     pub fn full(&mut self) -> &mut Self {
@@ -668,7 +753,23 @@ impl ClientConfigBuilder {
 
     /// Sets the mempool filter rules
     pub fn mempool(&mut self, filter_rules: MempoolRules, filter_limit: usize) -> &mut Self {
-        self.mempool = Some(MempoolConfig { filter_rules, filter_limit });
+        let mempool = self.mempool.get_or_insert_with(MempoolConfig::default);
+        mempool.filter_rules = filter_rules;
+        mempool.filter_limit = filter_limit;
+        self
+    }
+
+    /// Stores the mempool in the database, such that pending transactions survive a restart.
+    pub fn persistent_mempool(&mut self, persistent: bool) -> &mut Self {
+        let mempool = self.mempool.get_or_insert_with(MempoolConfig::default);
+        mempool.persistent = persistent;
+        self
+    }
+
+    /// Sets the maximum total size of the transactions in the mempool in bytes
+    pub fn mempool_size_limit(&mut self, size_limit: usize) -> &mut Self {
+        let mempool = self.mempool.get_or_insert_with(MempoolConfig::default);
+        mempool.size_limit = size_limit;
         self
     }
 
@@ -689,11 +790,21 @@ impl ClientConfigBuilder {
         self.seed(Seed::new_list(SeedList::new(url, public_key_opt)))
     }
 
-    /// Sets the validator config. Since there is no configuration for validators (except key file)
-    /// yet, this will just enable the validator.
+    /// Enables the validator. The validator key is loaded from the key file.
     #[cfg(feature="validator")]
     pub fn validator(&mut self) -> &mut Self {
-        self.validator = Some(Some(ValidatorConfig {}));
+        self.validator = Some(Some(ValidatorConfig::default()));
+        self
+    }
+
+    /// Enables the validator and lets the remote signer at `address` sign with the validator key.
+    /// The validator authenticates with `token`, if given.
+    #[cfg(feature="validator")]
+    pub fn validator_with_signer(&mut self, address: SignerAddress, token: Option<String>) -> &mut Self {
+        self.validator = Some(Some(ValidatorConfig {
+            signer: Some(address),
+            signer_token: token,
+        }));
         self
     }
 
@@ -734,6 +845,8 @@ impl ClientConfigBuilder {
 
         // Configure network
         self.network(config_file.consensus.network);
+        config_file.consensus.genesis_file.as_ref()
+            .map(|path| self.genesis_file(PathBuf::from(path)));
 
         // Configure storage config.
         let mut file_storage = FileStorageConfig::default();
@@ -745,16 +858,28 @@ impl ClientConfigBuilder {
             .map(|path| {
                 file_storage.peer_key = PathBuf::from(path);
             });
+        config_file.peer_key_passphrase_file.as_ref()
+            .map(|path| {
+                file_storage.peer_key_passphrase.file = Some(PathBuf::from(path));
+            });
         config_file.validator.as_ref()
             .map(|validator_config| {
                 validator_config.key_file.as_ref()
                     .map(|key_path| file_storage.validator_key = Some(PathBuf::from(key_path)));
+                validator_config.key_passphrase_file.as_ref()
+                    .map(|path| file_storage.validator_key_passphrase.file = Some(PathBuf::from(path)));
             });
         self.storage = Some(file_storage.into());
 
         // Configure database
         self.database(config_file.database.clone());
 
+        // Configure mempool
+        config_file.mempool.as_ref()
+            .map(|mempool| {
+                self.mempool = Some(mempool.clone().into());
+            });
+
         // Configure reverse proxy config
         config_file.reverse_proxy.as_ref()
             .map(|reverse_proxy| {
@@ -767,17 +892,8 @@ impl ClientConfigBuilder {
                 let bind_to = rpc_config.bind.as_ref()
                     .and_then(|addr| addr.into_ip_address());
 
-                let allow_ips = if rpc_config.allowip.is_empty() {
-                    None
-                }
-                else {
-                    let result = rpc_config.allowip.iter().map(|s| {
-                        s.parse::<IpAddr>().map_err({
-                            |e| Error::config_error(format!("Invalid IP: {}", e))
-                        })
-                    }).collect::<Result<Vec<IpAddr>, Error>>();
-                    Some(result?)
-                };
+                let allow_ips = parse_subnets(&rpc_config.allowip)?;
+                let deny_ips = parse_subnets(&rpc_config.denyip)?;
 
                 let credentials = match (&rpc_config.username, &rpc_config.password) {
                     (Some(u), Some(p)) => {
@@ -792,8 +908,11 @@ impl ClientConfigBuilder {
                     port: rpc_config.port.unwrap_or(consts::RPC_DEFAULT_PORT),
                     corsdomain: Some(rpc_config.corsdomain.clone()),
                     allow_ips,
+                    deny_ips,
+                    rate_limit: rpc_config.ratelimit,
                     allowed_methods: Some(rpc_config.methods.clone()),
                     credentials,
+                    tokens: api_tokens(&rpc_config.tokens),
                 }));
             }
         }
@@ -815,7 +934,11 @@ impl ClientConfigBuilder {
                 self.ws_rpc_server = Some(Some(WsRpcServerConfig {
                     bind_to,
                     port: ws_rpc_config.port.unwrap_or(consts::WS_RPC_DEFAULT_PORT),
+                    allow_ips: parse_subnets(&ws_rpc_config.allowip)?,
+                    deny_ips: parse_subnets(&ws_rpc_config.denyip)?,
+                    rate_limit: ws_rpc_config.ratelimit,
                     credentials,
+                    tokens: api_tokens(&ws_rpc_config.tokens),
                 }));
             }
         }
@@ -846,8 +969,22 @@ impl ClientConfigBuilder {
 
         // Configure validator
         #[cfg(feature="validator")] {
-            if config_file.validator.is_some() {
-                self.validator = Some(Some(ValidatorConfig {}));
+            if let Some(validator_settings) = &config_file.validator {
+                let signer = validator_settings.signer.as_ref()
+                    .map(|address| address.parse::<SignerAddress>())
+                    .transpose()
+                    .map_err(|e| Error::config_error(e.to_string()))?;
+                let signer_token = validator_settings.signer_token_file.as_ref()
+                    .map(|file| -> Result<String, Error> {
+                        let content = std::fs::read_to_string(file)
+                            .map_err(|e| Error::config_error(format!("Can't read signer token file {}: {}", file, e)))?;
+                        Ok(content.lines().next().unwrap_or_default().trim().to_string())
+                    })
+                    .transpose()?;
+                self.validator = Some(Some(ValidatorConfig {
+                    signer,
+                    signer_token,
+                }));
             }
         }
 
@@ -879,8 +1016,34 @@ impl ClientConfigBuilder {
 
         // Set network ID
         command_line.network.map(|network| self.network(network));
+        command_line.genesis_file.map(|path| self.genesis_file(PathBuf::from(path)));
 
         // NOTE: We're always return `Ok(_)`, but we might want to introduce errors later.
         Ok(self)
     }
 }
+
+/// Parses a list of IP addresses or subnets in CIDR notation. Returns `None` for an empty list.
+#[cfg(feature="rpc-server")]
+fn parse_subnets(subnets: &[String]) -> Result<Option<Vec<Subnet>>, Error> {
+    if subnets.is_empty() {
+        return Ok(None);
+    }
+
+    subnets.iter()
+        .map(|s| s.parse::<Subnet>()
+            .map_err(|e| Error::config_error(format!("Invalid IP or subnet {}: {}", s, e))))
+        .collect::<Result<Vec<Subnet>, Error>>()
+        .map(Some)
+}
+
+#[cfg(feature="rpc-server")]
+fn api_tokens(tokens: &[config_file::ApiTokenSettings]) -> Vec<ApiToken> {
+    tokens.iter()
+        .map(|token| ApiToken {
+            name: token.name.clone(),
+            token: token.token.clone(),
+            methods: token.methods.iter().cloned().collect(),
+        })
+        .collect()
+}
@@ -52,6 +52,8 @@ pub struct ConfigFile {
     #[serde(default)]
     pub peer_key_file: Option<String>,
     #[serde(default)]
+    pub peer_key_passphrase_file: Option<String>,
+    #[serde(default)]
     pub validator: Option<ValidatorSettings>,
 }
 
@@ -250,6 +252,7 @@ pub struct ConsensusSettings {
     pub consensus_type: ConsensusType,
     #[serde(default)]
     pub network: Network,
+    pub genesis_file: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
@@ -347,9 +350,14 @@ pub struct RpcServerSettings {
     #[serde(default)]
     pub allowip: Vec<String>,
     #[serde(default)]
+    pub denyip: Vec<String>,
+    pub ratelimit: Option<usize>,
+    #[serde(default)]
     pub methods: Vec<String>,
     pub username: Option<String>,
     pub password: Option<String>,
+    #[serde(default)]
+    pub tokens: Vec<ApiTokenSettings>,
 }
 
 
@@ -360,8 +368,26 @@ pub struct WsRpcServerSettings {
     #[serde(default)]
     pub bind: Option<address::NetAddress>,
     pub port: Option<u16>,
+    #[serde(default)]
+    pub allowip: Vec<String>,
+    #[serde(default)]
+    pub denyip: Vec<String>,
+    pub ratelimit: Option<usize>,
     pub username: Option<String>,
     pub password: Option<String>,
+    #[serde(default)]
+    pub tokens: Vec<ApiTokenSettings>,
+}
+
+
+/// A named API token for the RPC servers, restricted to a set of methods.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiTokenSettings {
+    pub name: String,
+    pub token: String,
+    #[serde(default)]
+    pub methods: Vec<String>,
 }
 
 
@@ -447,7 +473,7 @@ impl Default for DatabaseSettings {
         DatabaseSettings {
             path: None,
             size: Some(1024 * 1024 * 50),
-            max_dbs: Some(10),
+            max_dbs: Some(consts::DATABASE_DEFAULT_MAX_DBS),
             no_lmdb_sync: None,
         }
     }
@@ -458,6 +484,13 @@ impl Default for DatabaseSettings {
 pub struct MempoolSettings {
     pub filter: Option<MempoolFilterSettings>,
     pub blacklist_limit: Option<usize>,
+    pub persistent: Option<bool>,
+    pub replacement_fee_margin: Option<f64>,
+    pub size_limit: Option<usize>,
+    pub transactions_limit: Option<usize>,
+    pub transactions_per_sender_limit: Option<u32>,
+    pub free_transactions_per_sender_limit: Option<u32>,
+    pub relay_fee_min: Option<f64>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -500,12 +533,22 @@ pub struct MempoolFilterSettings {
 
 impl From<MempoolSettings> for MempoolConfig {
     fn from(mempool: MempoolSettings) -> Self {
+        let default = MempoolConfig::default();
         Self {
             filter_limit: mempool.blacklist_limit
                 .unwrap_or(MempoolFilter::DEFAULT_BLACKLIST_SIZE),
             filter_rules: mempool.filter
                 .map(MempoolRules::from)
                 .unwrap_or_default(),
+            persistent: mempool.persistent.unwrap_or(default.persistent),
+            replacement_fee_margin: mempool.replacement_fee_margin.unwrap_or(default.replacement_fee_margin),
+            size_limit: mempool.size_limit.unwrap_or(default.size_limit),
+            transactions_limit: mempool.transactions_limit.unwrap_or(default.transactions_limit),
+            transactions_per_sender_limit: mempool.transactions_per_sender_limit
+                .unwrap_or(default.transactions_per_sender_limit),
+            free_transactions_per_sender_limit: mempool.free_transactions_per_sender_limit
+                .unwrap_or(default.free_transactions_per_sender_limit),
+            relay_fee_min: mempool.relay_fee_min.unwrap_or(default.relay_fee_min),
         }
     }
 }
@@ -534,4 +577,7 @@ impl From<MempoolFilterSettings> for MempoolRules {
 #[serde(deny_unknown_fields)]
 pub struct ValidatorSettings {
     pub key_file: Option<String>,
+    pub key_passphrase_file: Option<String>,
+    pub signer: Option<String>,
+    pub signer_token_file: Option<String>,
 }
@@ -16,6 +16,11 @@ pub const WS_RPC_DEFAULT_PORT: u16 = 8650;
 /// The default port for the metrics server
 pub const METRICS_DEFAULT_PORT: u16 = 8649;
 
+/// The default maximum number of databases. A validator with the mempool and wallet stores
+/// opens 16 databases: 12 for the Albatross chain and accounts tree, plus SlashingProtection,
+/// Mempool, Wallet and HdWallet. The rest is headroom for new stores.
+pub const DATABASE_DEFAULT_MAX_DBS: u32 = 32;
+
 /// Returns the default bind, i.e. localhost
 pub fn default_bind() -> IpAddr {
     IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))
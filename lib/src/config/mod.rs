@@ -7,6 +7,7 @@
 ///   write our own derive macro, that will also use FromStr to parse the variables.
 
 pub mod config;
+pub mod passphrase;
 pub mod paths;
 pub mod user_agent;
 pub mod config_file;
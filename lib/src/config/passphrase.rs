@@ -0,0 +1,57 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::Error;
+
+
+/// Where to get the passphrase of an encrypted key file from.
+///
+/// The passphrase is read from the passphrase file, if one is configured. Otherwise it is taken
+/// from the environment variable. If neither is available, the user is prompted on the terminal.
+///
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PassphraseSource {
+    /// Name of the key, as shown to the user
+    name: &'static str,
+
+    /// File that contains the passphrase in its first line
+    pub file: Option<PathBuf>,
+
+    /// Environment variable that contains the passphrase
+    pub env_var: &'static str,
+}
+
+impl PassphraseSource {
+    pub fn new(name: &'static str, env_var: &'static str) -> Self {
+        Self {
+            name,
+            file: None,
+            env_var,
+        }
+    }
+
+    /// Returns the passphrase from the passphrase file or the environment, if available.
+    ///
+    pub fn configured(&self) -> Result<Option<String>, Error> {
+        if let Some(file) = &self.file {
+            let content = fs::read_to_string(file)
+                .map_err(|e| Error::config_error(format!("Failed to read passphrase file {}: {}", file.display(), e)))?;
+            let passphrase = content.lines().next().unwrap_or_default().to_string();
+            return Ok(Some(passphrase));
+        }
+
+        Ok(env::var(self.env_var).ok())
+    }
+
+    /// Returns the passphrase from the passphrase file or the environment, or asks the user for it.
+    ///
+    pub fn passphrase(&self) -> Result<String, Error> {
+        if let Some(passphrase) = self.configured()? {
+            return Ok(passphrase);
+        }
+
+        rpassword::read_password_from_tty(Some(&format!("Passphrase for {}: ", self.name)))
+            .map_err(|e| Error::config_error(format!("No passphrase for {} available: {}", self.name, e)))
+    }
+}
@@ -7,10 +7,14 @@ use log::SetLoggerError;
 use database::lmdb::LmdbError;
 use database::volatile::VolatileDatabaseError;
 use network::error::Error as NetworkError;
+use network_primitives::networks::NetworkInfoError;
 use utils::key_store::Error as KeyStoreError;
 use consensus::Error as ConsensusError;
+use build_tools::genesis::albatross::GenesisBuilderError;
 #[cfg(feature="validator")]
 use validator::error::Error as ValidatorError;
+#[cfg(feature="validator")]
+use signer::SignerError;
 #[cfg(feature="rpc-server")]
 use rpc_server::error::Error as RpcServerError;
 
@@ -38,10 +42,20 @@ pub enum Error {
     #[fail(display = "Config file parsing error: {}", _0)]
     Toml(#[cause] TomlError),
 
+    #[fail(display = "Genesis error: {}", _0)]
+    Genesis(#[cause] GenesisBuilderError),
+
+    #[fail(display = "Network info error: {}", _0)]
+    NetworkInfo(#[cause] NetworkInfoError),
+
     #[cfg(feature="validator")]
     #[fail(display = "Validator error: {}", _0)]
     Validator(#[cause] ValidatorError),
 
+    #[cfg(feature="validator")]
+    #[fail(display = "Signer error: {}", _0)]
+    Signer(#[cause] SignerError),
+
     #[cfg(feature="rpc-server")]
     #[fail(display = "RPC server error: {}", _0)]
     RpcServer(#[cause] RpcServerError),
@@ -107,6 +121,18 @@ impl From<TomlError> for Error {
     }
 }
 
+impl From<GenesisBuilderError> for Error {
+    fn from(e: GenesisBuilderError) -> Self {
+        Self::Genesis(e)
+    }
+}
+
+impl From<NetworkInfoError> for Error {
+    fn from(e: NetworkInfoError) -> Self {
+        Self::NetworkInfo(e)
+    }
+}
+
 #[cfg(feature="validator")]
 impl From<ValidatorError> for Error {
     fn from(e: ValidatorError) -> Self {
@@ -114,6 +140,13 @@ impl From<ValidatorError> for Error {
     }
 }
 
+#[cfg(feature="validator")]
+impl From<SignerError> for Error {
+    fn from(e: SignerError) -> Self {
+        Self::Signer(e)
+    }
+}
+
 #[cfg(feature="rpc-server")]
 impl From<RpcServerError> for Error {
     fn from(e: RpcServerError) -> Self {
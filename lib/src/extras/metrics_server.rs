@@ -1,6 +1,11 @@
+use std::sync::Arc;
+
 use metrics_server::MetricsServer;
+use metrics_server::server::Metrics;
 use metrics_server::error::Error;
 use metrics_server::AlbatrossChainMetrics;
+#[cfg(feature="validator")]
+use metrics_server::ValidatorMetrics;
 
 use crate::config::config::MetricsServerConfig;
 use crate::client::Client;
@@ -18,13 +23,22 @@ pub fn initialize_metrics_server(client: &Client, config: MetricsServerConfig, p
         (None, None)
     };
 
-     Ok(MetricsServer::new::<_, AlbatrossChainMetrics>(
+    #[allow(unused_mut)]
+    let mut additional_metrics = Vec::new();
+    #[cfg(feature="validator")] {
+        if let Some(validator) = client.validator() {
+            additional_metrics.push(Arc::new(ValidatorMetrics::new(validator)) as Arc<dyn Metrics>);
+        }
+    }
+
+    Ok(MetricsServer::new::<_, AlbatrossChainMetrics>(
         ip,
         config.port,
         username,
         password,
         pkcs12_key_file,
         pkcs12_passphrase,
-        client.consensus()
+        client.consensus(),
+        additional_metrics,
     )?)
 }
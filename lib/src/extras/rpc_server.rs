@@ -2,7 +2,8 @@ use std::collections::HashSet;
 use std::sync::Arc;
 use std::iter::FromIterator;
 
-use rpc_server::{RpcServer, JsonRpcConfig};
+use rpc_server::{Handler, RpcServer, JsonRpcConfig};
+use rpc_server::access::IpFilter;
 use rpc_server::handlers::*;
 
 use crate::client::Client;
@@ -20,7 +21,9 @@ pub fn initialize_rpc_server(client: &Client, config: RpcServerConfig) -> Result
         (Some(credentials.username), Some(credentials.password))
     }
     else {
-        warn!("No password set for RPC server!");
+        if config.tokens.is_empty() {
+            warn!("No password set for RPC server!");
+        }
         (None, None)
     };
 
@@ -33,16 +36,22 @@ pub fn initialize_rpc_server(client: &Client, config: RpcServerConfig) -> Result
     let json_rpc_config = JsonRpcConfig {
         username,
         password,
+        tokens: config.tokens,
         methods,
-        allowip: (), // TODO: config.allow_ips,
+        ip_filter: IpFilter::new(config.allow_ips.unwrap_or_default(), config.deny_ips.unwrap_or_default()),
+        rate_limit: config.rate_limit,
         corsdomain,
     };
 
     // Initialize RPC server
     let rpc_server = RpcServer::new(ip, config.port, json_rpc_config)?;
-    let handler = Arc::clone(&rpc_server.handler);
+    install_modules(client, &rpc_server.handler);
+
+    Ok(rpc_server)
+}
 
-    // Install RPC modules
+/// Installs the RPC modules into the handler. This is shared with the websocket RPC server.
+pub fn install_modules(client: &Client, handler: &Handler) {
     #[cfg(feature="validator")] {
         if let Some(validator) = client.validator() {
             let block_production_handler = BlockProductionAlbatrossHandler::new(validator);
@@ -65,6 +74,4 @@ pub fn initialize_rpc_server(client: &Client, config: RpcServerConfig) -> Result
 
     let mempool_handler = MempoolAlbatrossHandler::new(client.mempool(), Some(wallet_manager));
     handler.add_module(mempool_handler);
-
-    Ok(rpc_server)
 }
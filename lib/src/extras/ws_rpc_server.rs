@@ -1,17 +1,43 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use rpc_server::{Handler, JsonRpcConfig};
+use rpc_server::access::IpFilter;
 use ws_rpc_server::WsRpcServer;
 
 use crate::error::Error;
 use crate::client::Client;
 use crate::config::config::WsRpcServerConfig;
 use crate::config::consts::default_bind;
+use crate::extras::rpc_server::install_modules;
 
 pub fn initialize_ws_rcp_server(client: &Client, config: WsRpcServerConfig) -> Result<WsRpcServer, Error> {
     let ip = config.bind_to.unwrap_or_else(default_bind);
 
     info!("Initializing websocket RPC server: {}:{}", ip, config.port);
 
-    let server = WsRpcServer::new(ip, config.port)?;
+    let (username, password) = if let Some(credentials) = config.credentials {
+        (Some(credentials.username), Some(credentials.password))
+    }
+    else {
+        (None, None)
+    };
+
+    // The websocket server serves the same RPC methods as the HTTP server.
+    let handler = Arc::new(Handler::new(JsonRpcConfig {
+        username,
+        password,
+        tokens: config.tokens,
+        methods: HashSet::new(),
+        ip_filter: IpFilter::new(config.allow_ips.unwrap_or_default(), config.deny_ips.unwrap_or_default()),
+        rate_limit: config.rate_limit,
+        corsdomain: Vec::new(),
+    }));
+    install_modules(client, &handler);
+
+    let server = WsRpcServer::new(ip, config.port, handler)?;
     server.register_blockchain(client.consensus());
+    server.register_mempool(client.mempool());
     #[cfg(feature="validator")] {
         if let Some(validator) = client.validator() {
             server.register_validator(validator)
@@ -12,11 +12,14 @@ extern crate nimiq_mempool as mempool;
 extern crate nimiq_utils as utils;
 extern crate nimiq_keys as keys;
 extern crate nimiq_blockchain_albatross as blockchain;
+extern crate nimiq_build_tools as build_tools;
 
 #[cfg(feature="validator")]
 extern crate nimiq_validator as validator;
 #[cfg(feature="validator")]
 extern crate nimiq_bls as bls;
+#[cfg(feature="validator")]
+extern crate nimiq_signer as signer;
 
 #[cfg(feature="rpc-server")]
 extern crate nimiq_rpc_server as rpc_server;
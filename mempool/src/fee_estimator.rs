@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+
+use beserial::Serialize;
+use transaction::Transaction;
+
+/// Lower bounds of the fee/byte buckets of the fee histogram.
+pub const FEE_PER_BYTE_BUCKETS: [u64; 14] = [0, 1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000, 10000];
+
+/// Transactions in a fee/byte range `[fee_per_byte_min, next bucket)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeeHistogramBucket {
+    pub fee_per_byte_min: u64,
+    /// Number of transactions
+    pub count: usize,
+    /// Total serialized size of the transactions in bytes
+    pub size: usize,
+}
+
+/// Sorts `transactions` into the buckets of `FEE_PER_BYTE_BUCKETS`.
+pub fn fee_histogram<'a, I: IntoIterator<Item=&'a Transaction>>(transactions: I) -> Vec<FeeHistogramBucket> {
+    let mut buckets: Vec<FeeHistogramBucket> = FEE_PER_BYTE_BUCKETS.iter()
+        .map(|&fee_per_byte_min| FeeHistogramBucket { fee_per_byte_min, ..Default::default() })
+        .collect();
+
+    for transaction in transactions {
+        let fee_per_byte = transaction.fee_per_byte() as u64;
+        let i = FEE_PER_BYTE_BUCKETS.iter()
+            .rposition(|&fee_per_byte_min| fee_per_byte >= fee_per_byte_min)
+            .unwrap_or(0);
+        buckets[i].count += 1;
+        buckets[i].size += transaction.serialized_size();
+    }
+
+    buckets
+}
+
+/// Fee/byte a transaction needed to be included in a recent block.
+#[derive(Debug, Clone, Copy)]
+struct BlockFee {
+    fee_per_byte_min: f64,
+}
+
+/// Estimates the fee/byte a transaction has to pay to be included within a number of blocks.
+///
+/// The estimate is based on the fees of the transactions in recent blocks and on the pending
+/// transactions that would be included before the new transaction.
+#[derive(Debug, Clone)]
+pub struct FeeEstimator {
+    blocks: VecDeque<BlockFee>,
+    history_size: usize,
+    block_size: usize,
+}
+
+impl FeeEstimator {
+    /// Number of recent blocks to base the estimate on by default.
+    pub const DEFAULT_HISTORY_SIZE: usize = 100;
+
+    /// Probability with which a transaction paying the estimated fee should be included within
+    /// the target number of blocks.
+    const CONFIDENCE: f64 = 0.95;
+
+    /// Share of the maximum block size above which a block is considered full. Blocks are never
+    /// completely filled with transactions because of the header and other block content.
+    const FULL_BLOCK_RATIO: f64 = 0.9;
+
+    /// Creates an estimator for blocks with space for `block_size` bytes of transactions.
+    pub fn new(history_size: usize, block_size: usize) -> Self {
+        FeeEstimator {
+            blocks: VecDeque::with_capacity(history_size),
+            history_size,
+            block_size: (block_size as f64 * Self::FULL_BLOCK_RATIO) as usize,
+        }
+    }
+
+    /// Records the transactions of a new block.
+    pub fn push_block(&mut self, transactions: &[Transaction]) {
+        let size: usize = transactions.iter().map(Serialize::serialized_size).sum();
+
+        // If the block was not full, it would have included any transaction.
+        let fee_per_byte_min = if !transactions.is_empty() && size + Transaction::MIN_SIZE > self.block_size {
+            transactions.iter()
+                .map(Transaction::fee_per_byte)
+                .fold(std::f64::INFINITY, f64::min)
+        } else {
+            0f64
+        };
+
+        if self.blocks.len() >= self.history_size {
+            self.blocks.pop_front();
+        }
+        self.blocks.push_back(BlockFee { fee_per_byte_min });
+    }
+
+    /// Forgets the `num_blocks` most recent blocks, e.g. because they were reverted.
+    pub fn revert_blocks(&mut self, num_blocks: usize) {
+        let len = self.blocks.len();
+        self.blocks.truncate(len.saturating_sub(num_blocks));
+    }
+
+    /// Returns the number of blocks the estimate is based on.
+    pub fn num_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Estimates the fee/byte for a transaction to be included within `target_blocks` blocks.
+    /// `pending` are the transactions in the mempool sorted by fee/byte, ascending.
+    pub fn estimate<'a, I>(&self, target_blocks: u32, pending: I) -> f64
+        where I: IntoIterator<Item=&'a Transaction>, I::IntoIter: DoubleEndedIterator {
+        let target_blocks = target_blocks.max(1);
+        self.estimate_from_blocks(target_blocks).max(self.estimate_from_pending(target_blocks, pending))
+    }
+
+    /// A transaction is included within `target_blocks` blocks with probability `CONFIDENCE` if
+    /// it pays enough to be included in a share `1 - (1 - CONFIDENCE)^(1 / target_blocks)` of
+    /// the blocks.
+    fn estimate_from_blocks(&self, target_blocks: u32) -> f64 {
+        if self.blocks.is_empty() {
+            return 0f64;
+        }
+
+        let share = 1f64 - (1f64 - Self::CONFIDENCE).powf(1f64 / f64::from(target_blocks));
+
+        let mut fees: Vec<f64> = self.blocks.iter().map(|block| block.fee_per_byte_min).collect();
+        fees.sort_by(|a, b| a.partial_cmp(b).expect("Fee per byte is never NaN"));
+
+        let i = ((share * fees.len() as f64).ceil() as usize).max(1) - 1;
+        fees[i.min(fees.len() - 1)]
+    }
+
+    /// A transaction must outbid the pending transactions that fill the next `target_blocks`
+    /// blocks.
+    fn estimate_from_pending<'a, I>(&self, target_blocks: u32, pending: I) -> f64
+        where I: IntoIterator<Item=&'a Transaction>, I::IntoIter: DoubleEndedIterator {
+        let space = self.block_size.saturating_mul(target_blocks as usize);
+
+        let mut size = 0;
+        for transaction in pending.into_iter().rev() {
+            size += transaction.serialized_size();
+            if size > space {
+                return transaction.fee_per_byte();
+            }
+        }
+        0f64
+    }
+}
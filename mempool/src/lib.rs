@@ -4,6 +4,7 @@ extern crate nimiq_account as account;
 extern crate nimiq_block_base as block_base;
 extern crate nimiq_blockchain_base as blockchain_base;
 extern crate nimiq_collections as collections;
+extern crate nimiq_database as database;
 extern crate nimiq_hash as hash;
 extern crate nimiq_keys as keys;
 extern crate nimiq_primitives as primitives;
@@ -12,29 +13,40 @@ extern crate nimiq_utils as utils;
 
 use std::cmp::Ordering;
 use std::collections::{BTreeSet, HashMap, HashSet};
+use std::iter;
 use std::sync::Arc;
 
 use parking_lot::{Mutex, RwLock, RwLockUpgradableReadGuard};
 
-use account::{Account, AccountTransactionInteraction};
+use account::{Account, AccountError, AccountTransactionInteraction};
 use beserial::Serialize;
 use block_base::Block;
 use blockchain_base::{AbstractBlockchain, BlockchainEvent};
 use hash::{Blake2bHash, Hash};
 use keys::Address;
+use nimiq_network_primitives::networks::NetworkInfo;
 use transaction::{Transaction, TransactionFlags};
 use utils::observer::{Notifier, weak_listener};
 use primitives::networks::NetworkId;
+use primitives::policy::AlbatrossPolicy;
 
+use crate::fee_estimator::{fee_histogram, FeeEstimator, FeeHistogramBucket};
 use crate::filter::{MempoolFilter, Rules};
+use crate::store::MempoolStore;
 
+pub mod fee_estimator;
 pub mod filter;
+pub mod store;
 
 pub struct Mempool<B: AbstractBlockchain> {
     blockchain: Arc<B>,
     pub notifier: RwLock<Notifier<'static, MempoolEvent>>,
     state: RwLock<MempoolState>,
     mut_lock: Mutex<()>,
+    config: MempoolConfig,
+    fee_estimator: RwLock<FeeEstimator>,
+    /// Keeps the transactions across restarts, if the mempool is persistent
+    store: Option<MempoolStore>,
 }
 
 struct MempoolState {
@@ -43,6 +55,11 @@ struct MempoolState {
     transactions_by_recipient: HashMap<Address, BTreeSet<Arc<Transaction>>>,
     transactions_sorted_fee: BTreeSet<Arc<Transaction>>, // sorted by fee, ascending
     filter: MempoolFilter,
+    /// Total serialized size of all transactions in bytes
+    size: usize,
+    /// Minimum fee/byte for new transactions. Rises when transactions are evicted because the
+    /// mempool is full and decays again once there is enough space.
+    fee_per_byte_min: f64,
 }
 
 #[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
@@ -51,25 +68,58 @@ pub enum MempoolEvent {
     TransactionRestored(Arc<Transaction>),
     TransactionMined(Arc<Transaction>),
     TransactionEvicted(Arc<Transaction>),
+    /// The first transaction was replaced by the second one, which pays a higher fee. The new
+    /// transaction is also announced with `TransactionAdded`.
+    TransactionReplaced(Arc<Transaction>, Arc<Transaction>),
 }
 
 #[derive(Debug, Clone)]
 pub struct MempoolConfig {
     pub filter_rules: Rules,
     pub filter_limit: usize,
+    /// Store the transactions in the database, such that they are restored after a restart
+    pub persistent: bool,
+    /// Minimum relative increase of the fee per byte for a transaction to replace a pending
+    /// transaction with the same sender, recipient, value and validity start height.
+    pub replacement_fee_margin: f64,
+    /// Maximum total serialized size of all transactions in bytes
+    pub size_limit: usize,
+    /// Maximum number of transactions
+    pub transactions_limit: usize,
+    /// Maximum number of transactions per sender
+    pub transactions_per_sender_limit: u32,
+    /// Maximum number of "free" transactions per sender
+    pub free_transactions_per_sender_limit: u32,
+    /// Fee threshold in sat/byte below which transactions are considered "free". When the
+    /// mempool is full, new transactions must pay at least this much more than the transactions
+    /// that were evicted.
+    pub relay_fee_min: f64,
 }
 
 impl Default for MempoolConfig {
     fn default() -> MempoolConfig {
         MempoolConfig {
             filter_rules: Rules::default(),
-            filter_limit: MempoolFilter::DEFAULT_BLACKLIST_SIZE
+            filter_limit: MempoolFilter::DEFAULT_BLACKLIST_SIZE,
+            persistent: false,
+            replacement_fee_margin: 0.1,
+            size_limit: SIZE_BYTES_MAX,
+            transactions_limit: SIZE_MAX,
+            transactions_per_sender_limit: TRANSACTIONS_PER_SENDER_MAX,
+            free_transactions_per_sender_limit: FREE_TRANSACTIONS_PER_SENDER_MAX,
+            relay_fee_min: TRANSACTION_RELAY_FEE_MIN,
         }
     }
 }
 
 impl<B: AbstractBlockchain + 'static> Mempool<B> {
     pub fn new(blockchain: Arc<B>, config: MempoolConfig) -> Arc<Self> {
+        Self::with_store(blockchain, config, None)
+    }
+
+    /// Creates a mempool that keeps its transactions in `store`. Transactions that are already in
+    /// the store are re-validated and added to the mempool.
+    pub fn with_store(blockchain: Arc<B>, config: MempoolConfig, store: Option<MempoolStore>) -> Arc<Self> {
         let arc = Arc::new(Self {
             blockchain: blockchain.clone(),
             notifier: RwLock::new(Notifier::new()),
@@ -78,9 +128,14 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
                 transactions_by_sender: HashMap::new(),
                 transactions_by_recipient: HashMap::new(),
                 transactions_sorted_fee: BTreeSet::new(),
-                filter: MempoolFilter::new(config.filter_rules, config.filter_limit),
+                filter: MempoolFilter::new(config.filter_rules.clone(), config.filter_limit),
+                size: 0,
+                fee_per_byte_min: 0f64,
             }),
             mut_lock: Mutex::new(()),
+            config,
+            fee_estimator: RwLock::new(FeeEstimator::new(FeeEstimator::DEFAULT_HISTORY_SIZE, <B::Block as Block>::MAX_SIZE)),
+            store,
         });
 
         // register listener to blockchain through weak reference
@@ -89,9 +144,49 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
             this.on_blockchain_event(event)
         }));
 
+        arc.restore_from_store();
+
         arc
     }
 
+    /// Pushes the transactions from the store into the mempool. Stored transactions that are not
+    /// accepted anymore are removed from the store.
+    fn restore_from_store(&self) {
+        let store = match &self.store {
+            Some(store) => store,
+            None => return,
+        };
+        let mut transactions = store.load();
+
+        // Push transactions with the highest fee/byte first, such that they are preferred if
+        // limits are hit.
+        transactions.sort();
+        let num_stored = transactions.len();
+        let mut rejected = Vec::new();
+        for tx in transactions.into_iter().rev() {
+            match self.push_transaction(tx.clone()) {
+                ReturnCode::Accepted => {},
+                return_code => {
+                    debug!("Dropping stored transaction {}: {:?}", tx.hash::<Blake2bHash>(), return_code);
+                    rejected.push(tx);
+                },
+            }
+        }
+        store.update(iter::empty(), rejected.iter());
+
+        if num_stored > 0 {
+            info!("Restored {} of {} stored transactions", num_stored - rejected.len(), num_stored);
+        }
+    }
+
+    /// Writes the changes of one operation to the store, if the mempool is persistent.
+    fn update_store<'a, A, R>(&self, added: A, removed: R)
+        where A: IntoIterator<Item=&'a Arc<Transaction>>, R: IntoIterator<Item=&'a Arc<Transaction>> {
+        if let Some(store) = &self.store {
+            store.update(added.into_iter().map(AsRef::as_ref), removed.into_iter().map(AsRef::as_ref));
+        }
+    }
+
     pub fn is_filtered(&self, hash: &Blake2bHash) -> bool {
         self.state.read().filter.blacklisted(hash)
     }
@@ -108,6 +203,9 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
         // Transactions that are invalidated by the new transaction are stored here.
         let mut txs_to_remove = Vec::new();
 
+        // The transaction that is replaced by the new transaction.
+        let replaced_tx;
+
         {
             let state = self.state.upgradable_read();
 
@@ -129,15 +227,40 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
                 return ReturnCode::Invalid;
             }
 
+            // Check if the transaction replaces a pending transaction and pays enough for that.
+            replaced_tx = Self::find_replaceable(&state, &transaction);
+            if let Some(replaced_tx) = &replaced_tx {
+                if transaction.fee_per_byte() < self.replacement_fee_per_byte_min(replaced_tx) {
+                    trace!("Fee too low to replace transaction {}", replaced_tx.hash::<Blake2bHash>());
+                    return ReturnCode::FeeTooLow;
+                }
+            }
+
+            // Check the minimum fee, which rises if the mempool is full.
+            if transaction.fee_per_byte() < state.fee_per_byte_min {
+                trace!("Fee too low while mempool is full: {}", hash);
+                return ReturnCode::FeeTooLow;
+            }
+
+            // If the mempool is full, the transaction must pay more than the transactions it would displace.
+            if replaced_tx.is_none() && self.is_full(&state, transaction.serialized_size()) {
+                if let Some(tx) = state.transactions_sorted_fee.iter().next() {
+                    if transaction.fee_per_byte() <= tx.fee_per_byte() {
+                        trace!("Fee too low while mempool is full: {}", hash);
+                        return ReturnCode::FeeTooLow;
+                    }
+                }
+            }
+
             // Check limit for free transactions.
             let txs_by_sender_opt = state.transactions_by_sender.get(&transaction.sender);
-            if transaction.fee_per_byte() < TRANSACTION_RELAY_FEE_MIN {
+            if transaction.fee_per_byte() < self.config.relay_fee_min {
                 let mut num_free_tx = 0;
                 if let Some(transactions) = txs_by_sender_opt {
                     for tx in transactions {
-                        if tx.fee_per_byte() < TRANSACTION_RELAY_FEE_MIN {
+                        if tx.fee_per_byte() < self.config.relay_fee_min {
                             num_free_tx += 1;
-                            if num_free_tx >= FREE_TRANSACTIONS_PER_SENDER_MAX {
+                            if num_free_tx >= self.config.free_transactions_per_sender_limit {
                                 return ReturnCode::FeeTooLow;
                             }
                         } else {
@@ -150,7 +273,8 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
 
             // Check if transaction is valid at the next block height.
             let block_height = self.blockchain.head_height() + 1;
-            if !transaction.is_valid_at(block_height) {
+            let policy = self.blockchain.albatross_policy();
+            if !transaction.is_valid_at(block_height, self.blockchain.transaction_validity_window()) {
                 return ReturnCode::Invalid;
             }
 
@@ -169,7 +293,7 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
             }
 
             // Test incoming transaction.
-            match recipient_account.check_incoming_transaction(&transaction, block_height) {
+            match Self::check_incoming(&recipient_account, &transaction, block_height, policy.as_ref()) {
                 Err(_) => return ReturnCode::Invalid,
                 Ok(_) => {
                     // Check recipient account against filter rules.
@@ -203,7 +327,7 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
                     empty_btree = BTreeSet::new();
                     empty_btree.iter()
                 }
-            };
+            }.filter(|tx| replaced_tx.as_ref().map_or(true, |replaced_tx| !Arc::ptr_eq(tx, replaced_tx)));
 
             // First apply all transactions with a higher fee/byte.
             // These are not affected by the new transaction and should never fail to apply.
@@ -214,7 +338,7 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
                     break;
                 }
                 // Reject the transaction, if after the intrinsic check, the balance went too low
-                if sender_account.commit_outgoing_transaction(tx, block_height).is_err() {
+                if Self::commit_outgoing(&mut sender_account, tx, block_height, policy.as_ref()).is_err() {
                     return ReturnCode::Invalid
                 }
                 tx_count += 1;
@@ -222,13 +346,13 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
             }
 
             // If we are already at the transaction limit, reject the new transaction.
-            if tx_count >= TRANSACTIONS_PER_SENDER_MAX {
+            if tx_count >= self.config.transactions_per_sender_limit {
                 return ReturnCode::FeeTooLow;
             }
 
             // Now, check the new transaction.
             let old_sender_balance = sender_account.balance();
-            if sender_account.commit_outgoing_transaction(&transaction, block_height).is_err() {
+            if Self::commit_outgoing(&mut sender_account, &transaction, block_height, policy.as_ref()).is_err() {
                 return ReturnCode::Invalid // XXX More specific return code here?
             };
 
@@ -243,8 +367,8 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
             // Finally, check the remaining transactions with lower fee/byte and evict them if necessary.
             // tx_opt already contains the first lower/fee byte transaction to check (if there is one remaining).
             while let Some(tx) = tx_opt {
-                if tx_count < TRANSACTIONS_PER_SENDER_MAX {
-                    if sender_account.commit_outgoing_transaction(tx, block_height).is_ok() {
+                if tx_count < self.config.transactions_per_sender_limit {
+                    if Self::commit_outgoing(&mut sender_account, tx, block_height, policy.as_ref()).is_ok() {
                         tx_count += 1;
                     } else {
                         txs_to_remove.push(tx.clone())
@@ -262,6 +386,9 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
         {
             // Transaction is valid, add it to the mempool.
             let mut state = self.state.write();
+            if let Some(tx) = &replaced_tx {
+                Self::remove_transaction(&mut state, tx);
+            }
             Self::add_transaction(&mut state, hash.clone(), tx_arc.clone());
 
             // Evict transactions that were invalidated by the new transaction.
@@ -272,19 +399,22 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
             // Rename variable.
             removed_transactions = txs_to_remove;
 
-            // Remove the lowest fee transaction if mempool max size is reached.
-            if state.transactions_sorted_fee.len() > SIZE_MAX {
-                let tx = state.transactions_sorted_fee.iter().next().unwrap().clone();
-                Self::remove_transaction(&mut state, &tx);
-                removed_transactions.push(tx);
-            }
+            // Remove the lowest fee transactions if the mempool is full.
+            removed_transactions.extend(self.enforce_limits(&mut state));
         }
 
+        self.update_store(iter::once(&tx_arc), replaced_tx.iter().chain(removed_transactions.iter()));
+
         // Drop the lock on blockchain::push
         drop(_push_lock);
 
         // Tell listeners about the new transaction we received.
-        self.notifier.read().notify(MempoolEvent::TransactionAdded(hash, tx_arc));
+        self.notifier.read().notify(MempoolEvent::TransactionAdded(hash, Arc::clone(&tx_arc)));
+
+        // Tell listeners about the transaction we replaced.
+        if let Some(tx) = replaced_tx {
+            self.notifier.read().notify(MempoolEvent::TransactionReplaced(tx, tx_arc));
+        }
 
         // Tell listeners about the transactions we evicted.
         for tx in removed_transactions {
@@ -294,6 +424,48 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
         ReturnCode::Accepted
     }
 
+    /// Returns the minimum fee per byte a transaction must pay to replace `transaction`.
+    pub fn replacement_fee_per_byte_min(&self, transaction: &Transaction) -> f64 {
+        let fee_per_byte = transaction.fee_per_byte();
+        // Replacements of free transactions must at least pay the relay fee. Otherwise they
+        // could be replaced forever without paying anything.
+        if fee_per_byte < self.config.relay_fee_min {
+            self.config.relay_fee_min
+        } else {
+            fee_per_byte * (1f64 + self.config.replacement_fee_margin)
+        }
+    }
+
+    /// Returns the minimum fee per byte new transactions currently have to pay. This is zero
+    /// unless transactions were recently evicted because the mempool was full.
+    pub fn min_fee_per_byte(&self) -> f64 {
+        self.state.read().fee_per_byte_min
+    }
+
+    /// Returns the total serialized size of the transactions in the mempool in bytes.
+    pub fn size(&self) -> usize {
+        self.state.read().size
+    }
+
+    /// Returns the number of transactions in the mempool.
+    pub fn num_transactions(&self) -> usize {
+        self.state.read().transactions_sorted_fee.len()
+    }
+
+    /// Estimates the fee/byte a transaction has to pay to be included within `target_blocks`
+    /// blocks, based on recent blocks and the transactions in the mempool.
+    pub fn estimate_fee(&self, target_blocks: u32) -> f64 {
+        let state = self.state.read();
+        let estimate = self.fee_estimator.read()
+            .estimate(target_blocks, state.transactions_sorted_fee.iter().map(AsRef::as_ref));
+        estimate.max(state.fee_per_byte_min)
+    }
+
+    /// Returns the number and size of the transactions in the mempool by fee/byte.
+    pub fn fee_histogram(&self) -> Vec<FeeHistogramBucket> {
+        fee_histogram(self.state.read().transactions_sorted_fee.iter().map(AsRef::as_ref))
+    }
+
     pub fn contains(&self, hash: &Blake2bHash) -> bool {
         self.state.read().transactions_by_hash.contains_key(hash)
     }
@@ -360,36 +532,70 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
         self.blockchain.network_id()
     }
 
+    pub fn network_info(&self) -> &'static NetworkInfo {
+        self.blockchain.network_info()
+    }
+
     fn on_blockchain_event(&self, event: &BlockchainEvent<B::Block>) {
         match event {
-            BlockchainEvent::Extended(_) | BlockchainEvent::Finalized(_) => {
-                self.evict_transactions()
+            BlockchainEvent::Extended(hash) | BlockchainEvent::Finalized(hash) => {
+                // The head might have moved on already, so look up the block of this event.
+                let block = self.blockchain.get_block(hash, true);
+                if block.is_none() {
+                    warn!("Block {} of blockchain event not found", hash);
+                }
+                let mined_transactions = block.as_ref().and_then(Block::transactions);
+                if let Some(transactions) = mined_transactions {
+                    self.fee_estimator.write().push_block(transactions);
+                }
+                self.evict_transactions(mined_transactions.into_iter().flatten())
             },
-            BlockchainEvent::Rebranched(reverted_blocks, _) => {
+            BlockchainEvent::Rebranched(reverted_blocks, adopted_blocks) => {
+                {
+                    let mut fee_estimator = self.fee_estimator.write();
+                    fee_estimator.revert_blocks(reverted_blocks.iter()
+                        .filter(|(_, block)| block.transactions().is_some())
+                        .count());
+                    for (_, block) in adopted_blocks {
+                        if let Some(transactions) = block.transactions() {
+                            fee_estimator.push_block(transactions);
+                        }
+                    }
+                }
                 self.restore_transactions(reverted_blocks);
-                self.evict_transactions();
+                self.evict_transactions(adopted_blocks.iter()
+                    .filter_map(|(_, block)| block.transactions())
+                    .flatten());
             },
         }
     }
 
     /// Evict all transactions from the pool that have become invalid due to changes in the
     /// account state (i.e. typically because the were included in a newly mined block). No need to re-check signatures.
-    fn evict_transactions(&self) {
+    /// Transactions that replace one of the `mined_transactions` are evicted as well.
+    fn evict_transactions<'a, I: IntoIterator<Item=&'a Transaction>>(&self, mined_transactions: I) {
         // Only one mutating operation at a time.
         let _lock = self.mut_lock.lock();
 
+        let mut mined_by_sender: HashMap<&Address, Vec<&Transaction>> = HashMap::new();
+        for tx in mined_transactions {
+            mined_by_sender.entry(&tx.sender).or_insert_with(Vec::new).push(tx);
+        }
+
         let mut txs_mined = Vec::new();
         let mut txs_evicted = Vec::new();
         {
             let state = self.state.read();
             let block_height = self.blockchain.head_height() + 1;
+            let policy = self.blockchain.albatross_policy();
 
             for (address, transactions) in state.transactions_by_sender.iter() {
                 // TODO Eliminate copy
                 let mut sender_account = self.blockchain.get_account(&address);
+                let mined_txs = mined_by_sender.get(address);
                 for tx in transactions.iter().rev() {
                     // Check if the transaction has expired.
-                    if !tx.is_valid_at(block_height) {
+                    if !tx.is_valid_at(block_height, self.blockchain.transaction_validity_window()) {
                         txs_evicted.push(tx.clone());
                         continue;
                     }
@@ -400,16 +606,22 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
                         continue;
                     }
 
+                    // Check if a transaction that this transaction replaces has been mined.
+                    if mined_txs.map_or(false, |mined_txs| mined_txs.iter().any(|mined_tx| Self::is_replacement(tx, mined_tx))) {
+                        txs_evicted.push(tx.clone());
+                        continue;
+                    }
+
                     // Check if transaction is still valid for recipient.
                     // TODO Eliminate copy
                     let recipient_account = self.blockchain.get_account(&tx.recipient);
-                    if recipient_account.check_incoming_transaction(&tx, block_height).is_err() {
+                    if Self::check_incoming(&recipient_account, &tx, block_height, policy.as_ref()).is_err() {
                         txs_evicted.push(tx.clone());
                         continue;
                     }
 
                     // Check if transaction is still valid for sender.
-                    if sender_account.commit_outgoing_transaction(&tx, block_height).is_err() {
+                    if Self::commit_outgoing(&mut sender_account, &tx, block_height, policy.as_ref()).is_err() {
                         txs_evicted.push(tx.clone());
                     }
                 }
@@ -425,8 +637,18 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
             for tx in txs_evicted.iter() {
                 Self::remove_transaction(&mut state, tx);
             }
+
+            // Let the minimum fee decay once the mempool has enough space again.
+            if state.size <= self.config.size_limit / 2 && state.transactions_sorted_fee.len() <= self.config.transactions_limit / 2 {
+                state.fee_per_byte_min /= 2f64;
+                if state.fee_per_byte_min < self.config.relay_fee_min {
+                    state.fee_per_byte_min = 0f64;
+                }
+            }
         }
 
+        self.update_store(iter::empty(), txs_mined.iter().chain(txs_evicted.iter()));
+
         // Notify listeners.
         for tx in txs_mined {
             trace!("Transaction minded: {:?}", tx);
@@ -446,6 +668,7 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
         let mut removed_transactions = Vec::new();
         let mut restored_transactions = Vec::new();
         let block_height = self.blockchain.head_height() + 1;
+        let policy = self.blockchain.albatross_policy();
 
         // Collect all transactions from reverted blocks that are still valid.
         // Track them by sender and sort them by fee/byte.
@@ -457,7 +680,7 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
             }
 
             for tx in transactions.unwrap().iter() {
-                if !tx.is_valid_at(block_height) {
+                if !tx.is_valid_at(block_height, self.blockchain.transaction_validity_window()) {
                     // This transaction has expired (or is not valid yet) on the new chain.
                     // XXX The transaction is lost!
                     continue;
@@ -470,7 +693,7 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
 
                 // TODO Eliminate copy
                 let recipient_account = self.blockchain.get_account(&tx.recipient);
-                if recipient_account.check_incoming_transaction(&tx, block_height).is_err() {
+                if Self::check_incoming(&recipient_account, &tx, block_height, policy.as_ref()).is_err() {
                     // This transaction cannot be accepted by the recipient anymore.
                     // XXX The transaction is lost!
                     continue;
@@ -499,7 +722,7 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
 
                 // TODO Eliminate copy.
                 let sender_account = self.blockchain.get_account(&sender);
-                let (txs_to_add, txs_to_remove) = Self::merge_transactions(sender_account, block_height, existing_txs, &restored_txs);
+                let (txs_to_add, txs_to_remove) = Self::merge_transactions(sender_account, block_height, policy.as_ref(), existing_txs, &restored_txs, self.config.transactions_per_sender_limit);
                 for tx in txs_to_add {
                     let transaction = Arc::new(tx.clone());
                     Self::add_transaction(&mut state, tx.hash(), transaction.clone());
@@ -512,20 +735,11 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
             }
 
             // Evict lowest fee transactions if the mempool has grown too large.
-            let size = state.transactions_sorted_fee.len();
-            if size > SIZE_MAX {
-                let mut txs_to_remove = Vec::with_capacity(size - SIZE_MAX);
-                let mut iter = state.transactions_sorted_fee.iter();
-                for _ in 0..size - SIZE_MAX {
-                    txs_to_remove.push(iter.next().unwrap().clone());
-                }
-                for tx in txs_to_remove.iter() {
-                    Self::remove_transaction(&mut state, tx);
-                }
-                removed_transactions.extend(txs_to_remove);
-            }
+            removed_transactions.extend(self.enforce_limits(&mut state));
         }
 
+        self.update_store(restored_transactions.iter(), removed_transactions.iter());
+
         // Notify listeners.
         for tx in removed_transactions {
             self.notifier.read().notify(MempoolEvent::TransactionEvicted(tx));
@@ -536,7 +750,53 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
         }
     }
 
+    /// Returns whether adding a transaction of `tx_size` bytes would exceed the limits of the mempool.
+    fn is_full(&self, state: &MempoolState, tx_size: usize) -> bool {
+        state.size + tx_size > self.config.size_limit
+            || state.transactions_sorted_fee.len() >= self.config.transactions_limit
+    }
+
+    /// Evicts the transactions with the lowest fee/byte until the mempool is within its limits.
+    /// New transactions then have to pay more than the evicted ones, such that they can't be
+    /// pushed out again by transactions paying the same fee.
+    fn enforce_limits(&self, state: &mut MempoolState) -> Vec<Arc<Transaction>> {
+        let mut evicted = Vec::new();
+        while state.size > self.config.size_limit || state.transactions_sorted_fee.len() > self.config.transactions_limit {
+            let tx = match state.transactions_sorted_fee.iter().next() {
+                Some(tx) => Arc::clone(tx),
+                None => break,
+            };
+            Self::remove_transaction(state, &tx);
+
+            let fee_per_byte_min = tx.fee_per_byte() + self.config.relay_fee_min;
+            if fee_per_byte_min > state.fee_per_byte_min {
+                state.fee_per_byte_min = fee_per_byte_min;
+            }
+            evicted.push(tx);
+        }
+        evicted
+    }
+
+    /// Finds the pending transaction that `transaction` can replace, i.e. a transaction with the
+    /// same sender, recipient, value and validity start height.
+    fn find_replaceable(state: &MempoolState, transaction: &Transaction) -> Option<Arc<Transaction>> {
+        state.transactions_by_sender.get(&transaction.sender)?
+            .iter()
+            .find(|tx| Self::is_replacement(transaction, tx))
+            .cloned()
+    }
+
+    /// Returns whether `transaction` replaces `other`, i.e. both have the same sender, recipient,
+    /// value and validity start height. Only one of them can be mined.
+    fn is_replacement(transaction: &Transaction, other: &Transaction) -> bool {
+        transaction.sender == other.sender
+            && transaction.recipient == other.recipient
+            && transaction.value == other.value
+            && transaction.validity_start_height == other.validity_start_height
+    }
+
     fn add_transaction(state: &mut MempoolState, hash: Blake2bHash, tx: Arc<Transaction>) {
+        state.size += tx.serialized_size();
         state.transactions_by_hash.insert(hash, tx.clone());
         state.transactions_sorted_fee.insert(tx.clone());
 
@@ -552,7 +812,9 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
     }
 
     fn remove_transaction(state: &mut MempoolState, tx: &Transaction) {
-        state.transactions_by_hash.remove(&tx.hash());
+        if state.transactions_by_hash.remove(&tx.hash()).is_some() {
+            state.size -= tx.serialized_size();
+        }
         state.transactions_sorted_fee.remove(tx);
 
         let mut remove_key = false;
@@ -574,7 +836,27 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
         }
     }
 
-    fn merge_transactions<'a>(mut sender_account: Account, block_height: u32, old_txs: &BTreeSet<Arc<Transaction>>, new_txs: &BTreeSet<&'a Transaction>) -> (Vec<&'a Transaction>, Vec<Arc<Transaction>>) {
+    /// Checks `transaction` against its recipient account, including the parts that depend on
+    /// the Albatross policy of the network.
+    fn check_incoming(recipient_account: &Account, transaction: &Transaction, block_height: u32, policy: Option<&AlbatrossPolicy>) -> Result<(), AccountError> {
+        recipient_account.check_incoming_transaction(transaction, block_height)?;
+        if let Some(policy) = policy {
+            recipient_account.check_incoming_policy(transaction, policy)?;
+        }
+        Ok(())
+    }
+
+    /// Applies `transaction` to its sender account, including the checks that depend on the
+    /// Albatross policy of the network.
+    fn commit_outgoing(sender_account: &mut Account, transaction: &Transaction, block_height: u32, policy: Option<&AlbatrossPolicy>) -> Result<(), AccountError> {
+        if let Some(policy) = policy {
+            sender_account.check_outgoing_policy(transaction, block_height, policy)?;
+        }
+        sender_account.commit_outgoing_transaction(transaction, block_height)?;
+        Ok(())
+    }
+
+    fn merge_transactions<'a>(mut sender_account: Account, block_height: u32, policy: Option<&AlbatrossPolicy>, old_txs: &BTreeSet<Arc<Transaction>>, new_txs: &BTreeSet<&'a Transaction>, max_per_sender: u32) -> (Vec<&'a Transaction>, Vec<Arc<Transaction>>) {
         let mut txs_to_add = Vec::new();
         let mut txs_to_remove = Vec::new();
 
@@ -595,9 +877,9 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
             };
 
             if new_is_next {
-                if tx_count < TRANSACTIONS_PER_SENDER_MAX {
+                if tx_count < max_per_sender {
                     let tx = new_tx.unwrap();
-                    if sender_account.commit_outgoing_transaction(*tx, block_height).is_ok() {
+                    if Self::commit_outgoing(&mut sender_account, *tx, block_height, policy).is_ok() {
                         tx_count += 1;
                         txs_to_add.push(*tx)
                     }
@@ -605,8 +887,8 @@ impl<B: AbstractBlockchain + 'static> Mempool<B> {
                 new_tx = iter_new.next_back();
             } else {
                 let tx = old_tx.unwrap();
-                if tx_count < TRANSACTIONS_PER_SENDER_MAX {
-                    if sender_account.commit_outgoing_transaction(tx, block_height).is_ok() {
+                if tx_count < max_per_sender {
+                    if Self::commit_outgoing(&mut sender_account, tx, block_height, policy).is_ok() {
                         tx_count += 1;
                     } else {
                         txs_to_remove.push(tx.clone())
@@ -631,14 +913,17 @@ pub enum ReturnCode {
     Filtered,
 }
 
-/// Fee threshold in sat/byte below which transactions are considered "free".
-const TRANSACTION_RELAY_FEE_MIN : f64 = 1f64;
+/// Default fee threshold in sat/byte below which transactions are considered "free".
+pub const TRANSACTION_RELAY_FEE_MIN : f64 = 1f64;
 
-/// Maximum number of transactions per sender.
-const TRANSACTIONS_PER_SENDER_MAX : u32 = 500;
+/// Default maximum number of transactions per sender.
+pub const TRANSACTIONS_PER_SENDER_MAX : u32 = 500;
 
-/// Maximum number of "free" transactions per sender.
-const FREE_TRANSACTIONS_PER_SENDER_MAX : u32 = 10;
+/// Default maximum number of "free" transactions per sender.
+pub const FREE_TRANSACTIONS_PER_SENDER_MAX : u32 = 10;
 
-/// Maximum number of transactions in the mempool.
+/// Default maximum number of transactions in the mempool.
 pub const SIZE_MAX : usize = 100_000;
+
+/// Default maximum total size of the transactions in the mempool in bytes.
+pub const SIZE_BYTES_MAX : usize = 16 * 1024 * 1024;
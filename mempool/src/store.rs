@@ -0,0 +1,81 @@
+use std::io;
+
+use beserial::{Deserialize, Serialize};
+use database::{Database, Environment, ReadTransaction, WriteTransaction};
+use database::{FromDatabaseValue, IntoDatabaseValue};
+use database::cursor::ReadCursor;
+use hash::{Blake2bHash, Hash};
+use transaction::Transaction;
+
+/// Wrapper to store transactions in the database.
+struct StoredTransaction(Transaction);
+
+impl IntoDatabaseValue for StoredTransaction {
+    fn database_byte_size(&self) -> usize {
+        self.0.serialized_size()
+    }
+
+    fn copy_into_database(&self, mut bytes: &mut [u8]) {
+        Serialize::serialize(&self.0, &mut bytes).unwrap();
+    }
+}
+
+impl FromDatabaseValue for StoredTransaction {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self> where Self: Sized {
+        let mut cursor = io::Cursor::new(bytes);
+        let transaction = Deserialize::deserialize(&mut cursor)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        Ok(StoredTransaction(transaction))
+    }
+}
+
+/// Journal of the transactions in the mempool, such that they survive a restart of the node.
+///
+/// The mempool puts every transaction it accepts or restores and removes every transaction
+/// that is mined or evicted. The changes of a push or a block are written at once.
+pub struct MempoolStore {
+    env: Environment,
+    db: Database,
+}
+
+impl MempoolStore {
+    const DB_NAME: &'static str = "Mempool";
+
+    pub fn new(env: Environment) -> Self {
+        let db = env.open_database(Self::DB_NAME.to_string());
+        MempoolStore { env, db }
+    }
+
+    /// Puts the `added` and removes the `removed` transactions in a single database transaction.
+    pub fn update<'a, A, R>(&self, added: A, removed: R)
+        where A: IntoIterator<Item=&'a Transaction>, R: IntoIterator<Item=&'a Transaction> {
+        let mut added = added.into_iter().peekable();
+        let mut removed = removed.into_iter().peekable();
+        if added.peek().is_none() && removed.peek().is_none() {
+            return;
+        }
+
+        let mut txn = WriteTransaction::new(&self.env);
+        for transaction in added {
+            txn.put_reserve(&self.db, &transaction.hash::<Blake2bHash>(), &StoredTransaction(transaction.clone()));
+        }
+        for transaction in removed {
+            txn.remove(&self.db, &transaction.hash::<Blake2bHash>());
+        }
+        txn.commit();
+    }
+
+    /// Returns all stored transactions.
+    pub fn load(&self) -> Vec<Transaction> {
+        let txn = ReadTransaction::new(&self.env);
+        let mut cursor = txn.cursor(&self.db);
+
+        let mut transactions = Vec::new();
+        let mut entry: Option<(Blake2bHash, StoredTransaction)> = cursor.first();
+        while let Some((_, StoredTransaction(transaction))) = entry {
+            transactions.push(transaction);
+            entry = cursor.next();
+        }
+        transactions
+    }
+}
@@ -0,0 +1,88 @@
+use std::convert::TryFrom;
+
+use beserial::Serialize;
+use nimiq_keys::Address;
+use nimiq_mempool::fee_estimator::{fee_histogram, FeeEstimator, FEE_PER_BYTE_BUCKETS};
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::networks::NetworkId;
+use nimiq_transaction::Transaction;
+
+fn transaction(value: u64, fee_per_byte: u64) -> Transaction {
+    let mut tx = Transaction::new_basic(
+        Address::from([32u8; Address::SIZE]),
+        Address::from([213u8; Address::SIZE]),
+        Coin::try_from(value).unwrap(),
+        Coin::try_from(0).unwrap(),
+        1,
+        NetworkId::Main,
+    );
+    tx.fee = Coin::try_from(fee_per_byte * tx.serialized_size() as u64).unwrap();
+    tx
+}
+
+/// Size of the transactions above, which all have the same size.
+fn tx_size() -> usize {
+    transaction(1, 0).serialized_size()
+}
+
+#[test]
+fn it_sorts_transactions_into_buckets() {
+    let transactions = vec![transaction(1, 0), transaction(2, 3), transaction(3, 4), transaction(4, 20000)];
+    let histogram = fee_histogram(&transactions);
+
+    assert_eq!(histogram.len(), FEE_PER_BYTE_BUCKETS.len());
+    assert_eq!(histogram[0].fee_per_byte_min, 0);
+    assert_eq!(histogram[0].count, 1);
+    assert_eq!(histogram[2].fee_per_byte_min, 2);
+    assert_eq!(histogram[2].count, 2);
+    assert_eq!(histogram[2].size, 2 * tx_size());
+    assert_eq!(histogram.last().unwrap().count, 1);
+    assert_eq!(histogram.iter().map(|bucket| bucket.count).sum::<usize>(), 4);
+}
+
+#[test]
+fn it_estimates_nothing_without_competition() {
+    let mut estimator = FeeEstimator::new(10, 10 * tx_size());
+    estimator.push_block(&[transaction(1, 5)]);
+    estimator.push_block(&[]);
+
+    assert_eq!(estimator.num_blocks(), 2);
+    assert_eq!(estimator.estimate(1, &Vec::<Transaction>::new()), 0f64);
+}
+
+#[test]
+fn it_estimates_from_full_blocks() {
+    let mut estimator = FeeEstimator::new(10, 4 * tx_size());
+    for i in 0..10 {
+        // Full blocks with a minimum fee/byte of 1 to 10
+        let fee_per_byte = i + 1;
+        let transactions: Vec<Transaction> = (0..4).map(|j| transaction(j + 1, fee_per_byte + j)).collect();
+        estimator.push_block(&transactions);
+    }
+
+    // Only the most expensive blocks include a transaction for sure
+    assert_eq!(estimator.estimate(1, &Vec::<Transaction>::new()), 10f64);
+    // Waiting longer is cheaper
+    let estimate = estimator.estimate(5, &Vec::<Transaction>::new());
+    assert!(estimate < 10f64 && estimate > 1f64);
+    assert!(estimator.estimate(10, &Vec::<Transaction>::new()) <= estimate);
+
+    // The history is limited
+    estimator.push_block(&[]);
+    assert_eq!(estimator.num_blocks(), 10);
+    estimator.revert_blocks(3);
+    assert_eq!(estimator.num_blocks(), 7);
+}
+
+#[test]
+fn it_estimates_from_pending_transactions() {
+    let estimator = FeeEstimator::new(10, 4 * tx_size());
+
+    // Sorted by fee/byte, ascending
+    let pending: Vec<Transaction> = (1..=10).map(|i| transaction(i, i)).collect();
+
+    // A transaction has to outbid the transactions that fill the next block.
+    assert_eq!(estimator.estimate(1, &pending), 7f64);
+    assert_eq!(estimator.estimate(2, &pending), 3f64);
+    assert_eq!(estimator.estimate(3, &pending), 0f64);
+}
@@ -1,6 +1,8 @@
 use std::convert::TryFrom;
 use std::sync::Arc;
 
+use parking_lot::Mutex;
+
 use hex;
 
 use beserial::{Deserialize, Serialize};
@@ -9,10 +11,11 @@ use nimiq_block::BlockBody;
 use nimiq_blockchain::Blockchain;
 use nimiq_database::volatile::VolatileEnvironment;
 use nimiq_database::WriteTransaction;
-use nimiq_hash::Hash;
+use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_keys::{KeyPair, SecureGenerate};
 use nimiq_keys::Address;
-use nimiq_mempool::{Mempool, MempoolConfig, ReturnCode};
+use nimiq_mempool::{Mempool, MempoolConfig, MempoolEvent, ReturnCode};
+use nimiq_mempool::store::MempoolStore;
 use nimiq_network_primitives::time::NetworkTime;
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::networks::NetworkId;
@@ -161,3 +164,175 @@ fn reject_free_tx_beyond_limit() {
         }
     }
 }
+
+#[test]
+fn restore_persisted_transactions() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::Main, Arc::new(NetworkTime::new())).unwrap());
+    let mempool = Mempool::with_store(blockchain.clone(), MempoolConfig::default(), Some(MempoolStore::new(env.clone())));
+
+    let keypair_a = KeyPair::generate_default_csprng();
+    let address_a = Address::from(&keypair_a.public);
+    let address_b = Address::from([2u8; Address::SIZE]);
+
+    // Give address_a balance
+    let body = BlockBody { miner: address_a.clone(), extra_data: Vec::new(), transactions: Vec::new(), receipts: Receipts::default() };
+    let mut txn = WriteTransaction::new(&env);
+    blockchain.state().accounts().commit(&mut txn, &body.transactions, &vec![body.get_reward_inherent(1)], 1).unwrap();
+    txn.commit();
+
+    // Generate, sign and push transaction from address_a
+    let mut tx = Transaction::new_basic( address_a.clone(), address_b.clone(), Coin::try_from(10).unwrap(), Coin::try_from(0).unwrap(), 1, NetworkId::Main );
+    let signature_proof = SignatureProof::from(keypair_a.public.clone(), keypair_a.sign(&tx.serialize_content()));
+    tx.proof = signature_proof.serialize_to_vec();
+    let hash = tx.hash();
+    assert_eq!(mempool.push_transaction(tx.clone()), ReturnCode::Accepted);
+    drop(mempool);
+
+    // Store a transaction that became invalid while the node was offline
+    let v: Vec<u8> = hex::decode(BASIC_TRANSACTION).unwrap();
+    let invalid_tx: Transaction = Deserialize::deserialize(&mut &v[..]).unwrap();
+    MempoolStore::new(env.clone()).update(Some(&invalid_tx), None);
+
+    // Restart the mempool
+    let mempool = Mempool::with_store(blockchain.clone(), MempoolConfig::default(), Some(MempoolStore::new(env.clone())));
+    assert_eq!(Arc::new(tx.clone()), mempool.get_transaction(&hash).unwrap());
+    assert!(!mempool.contains(&invalid_tx.hash()));
+    assert_eq!(MempoolStore::new(env.clone()).load(), vec![tx]);
+}
+
+#[test]
+fn replace_tx_with_higher_fee() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::Main, Arc::new(NetworkTime::new())).unwrap());
+    let mempool = Mempool::new(blockchain.clone(), MempoolConfig::default());
+
+    let replaced = Arc::new(Mutex::new(Vec::new()));
+    let replaced_listener = Arc::clone(&replaced);
+    mempool.notifier.write().register(move |event: &MempoolEvent| {
+        if let MempoolEvent::TransactionReplaced(old_tx, new_tx) = event {
+            replaced_listener.lock().push((old_tx.hash::<Blake2bHash>(), new_tx.hash::<Blake2bHash>()));
+        }
+    });
+
+    let keypair_a = KeyPair::generate_default_csprng();
+    let address_a = Address::from(&keypair_a.public);
+    let address_b = Address::from([2u8; Address::SIZE]);
+
+    // Give address_a balance
+    let body = BlockBody { miner: address_a.clone(), extra_data: Vec::new(), transactions: Vec::new(), receipts: Receipts::default() };
+    let mut txn = WriteTransaction::new(&env);
+    blockchain.state().accounts().commit(&mut txn, &body.transactions, &vec![body.get_reward_inherent(1)], 1).unwrap();
+    txn.commit();
+
+    let create_tx = |fee: u64| {
+        let mut tx = Transaction::new_basic( address_a.clone(), address_b.clone(), Coin::try_from(10).unwrap(), Coin::try_from(fee).unwrap(), 1, NetworkId::Main );
+        let signature_proof = SignatureProof::from(keypair_a.public.clone(), keypair_a.sign(&tx.serialize_content()));
+        tx.proof = signature_proof.serialize_to_vec();
+        tx
+    };
+
+    let tx1 = create_tx(200);
+    let hash1: Blake2bHash = tx1.hash();
+    assert_eq!(mempool.push_transaction(tx1), ReturnCode::Accepted);
+
+    // The fee is not high enough to replace the first transaction
+    assert_eq!(mempool.push_transaction(create_tx(210)), ReturnCode::FeeTooLow);
+    assert!(mempool.contains(&hash1));
+
+    let tx2 = create_tx(300);
+    let hash2: Blake2bHash = tx2.hash();
+    assert_eq!(mempool.push_transaction(tx2), ReturnCode::Accepted);
+    assert!(!mempool.contains(&hash1));
+    assert!(mempool.contains(&hash2));
+    assert_eq!(*replaced.lock(), vec![(hash1, hash2)]);
+}
+
+#[test]
+fn replace_free_tx_only_with_relay_fee() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::Main, Arc::new(NetworkTime::new())).unwrap());
+    let mempool = Mempool::new(blockchain.clone(), MempoolConfig::default());
+
+    let keypair_a = KeyPair::generate_default_csprng();
+    let address_a = Address::from(&keypair_a.public);
+    let address_b = Address::from([2u8; Address::SIZE]);
+
+    // Give address_a balance
+    let body = BlockBody { miner: address_a.clone(), extra_data: Vec::new(), transactions: Vec::new(), receipts: Receipts::default() };
+    let mut txn = WriteTransaction::new(&env);
+    blockchain.state().accounts().commit(&mut txn, &body.transactions, &vec![body.get_reward_inherent(1)], 1).unwrap();
+    txn.commit();
+
+    let create_tx = |fee: u64| {
+        let mut tx = Transaction::new_basic( address_a.clone(), address_b.clone(), Coin::try_from(10).unwrap(), Coin::try_from(fee).unwrap(), 1, NetworkId::Main );
+        let signature_proof = SignatureProof::from(keypair_a.public.clone(), keypair_a.sign(&tx.serialize_content()));
+        tx.proof = signature_proof.serialize_to_vec();
+        tx
+    };
+
+    let tx1 = create_tx(0);
+    assert_eq!(mempool.push_transaction(tx1.clone()), ReturnCode::Accepted);
+    assert_eq!(mempool.replacement_fee_per_byte_min(&tx1), 1f64);
+
+    assert_eq!(mempool.push_transaction(create_tx(10)), ReturnCode::FeeTooLow);
+    assert_eq!(mempool.push_transaction(create_tx(tx1.serialized_size() as u64)), ReturnCode::Accepted);
+    assert!(!mempool.contains(&tx1.hash()));
+}
+
+#[test]
+fn evict_lowest_fee_per_byte_when_full() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::Main, Arc::new(NetworkTime::new())).unwrap());
+
+    let keypair_a = KeyPair::generate_default_csprng();
+    let address_a = Address::from(&keypair_a.public);
+    let address_b = Address::from([2u8; Address::SIZE]);
+
+    // Give address_a balance
+    let body = BlockBody { miner: address_a.clone(), extra_data: Vec::new(), transactions: Vec::new(), receipts: Receipts::default() };
+    let mut txn = WriteTransaction::new(&env);
+    blockchain.state().accounts().commit(&mut txn, &body.transactions, &vec![body.get_reward_inherent(1)], 1).unwrap();
+    txn.commit();
+
+    let create_tx = |value: u64, fee: u64| {
+        let mut tx = Transaction::new_basic( address_a.clone(), address_b.clone(), Coin::try_from(value).unwrap(), Coin::try_from(fee).unwrap(), 1, NetworkId::Main );
+        let signature_proof = SignatureProof::from(keypair_a.public.clone(), keypair_a.sign(&tx.serialize_content()));
+        tx.proof = signature_proof.serialize_to_vec();
+        tx
+    };
+
+    // The mempool has space for three basic transactions
+    let tx_size = create_tx(1, 0).serialized_size();
+    let config = MempoolConfig {
+        size_limit: 3 * tx_size,
+        ..MempoolConfig::default()
+    };
+    let mempool = Mempool::new(blockchain.clone(), config);
+
+    let free_tx = create_tx(1, 0);
+    assert_eq!(mempool.push_transaction(free_tx.clone()), ReturnCode::Accepted);
+    assert_eq!(mempool.push_transaction(create_tx(2, 200)), ReturnCode::Accepted);
+    assert_eq!(mempool.push_transaction(create_tx(3, 300)), ReturnCode::Accepted);
+    assert_eq!(mempool.size(), 3 * tx_size);
+    assert_eq!(mempool.min_fee_per_byte(), 0f64);
+
+    // A transaction paying less than the cheapest one in the full mempool is rejected
+    assert_eq!(mempool.push_transaction(create_tx(4, 0)), ReturnCode::FeeTooLow);
+
+    // A transaction paying more evicts the cheapest one and raises the minimum fee
+    let tx = create_tx(4, 100);
+    assert_eq!(mempool.push_transaction(tx.clone()), ReturnCode::Accepted);
+    assert!(mempool.contains(&tx.hash()));
+    assert!(!mempool.contains(&free_tx.hash()));
+    assert_eq!(mempool.num_transactions(), 3);
+    assert_eq!(mempool.size(), 3 * tx_size);
+    assert_eq!(mempool.min_fee_per_byte(), 1f64);
+
+    // New transactions now have to pay the raised minimum fee
+    assert_eq!(mempool.push_transaction(create_tx(5, 110)), ReturnCode::FeeTooLow);
+    assert_eq!(mempool.push_transaction(create_tx(5, 2 * tx_size as u64)), ReturnCode::Accepted);
+    assert!(!mempool.contains(&tx.hash()));
+    assert_eq!(mempool.size(), 3 * tx_size);
+    assert!(mempool.min_fee_per_byte() > 1f64);
+}
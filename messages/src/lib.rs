@@ -30,7 +30,7 @@ use beserial::{Deserialize, DeserializeWithLength, ReadBytesExt, Serialize, Seri
 use bitflags::bitflags;
 use block::{Block, BlockHeader};
 use block::proof::ChainProof;
-use block_albatross::{Block as BlockAlbatross, BlockHeader as BlockHeaderAlbatross, ForkProof, PbftCommitMessage, PbftPrepareMessage, SignedPbftProposal, ViewChange, ViewChangeProof};
+use block_albatross::{Block as BlockAlbatross, BlockHeader as BlockHeaderAlbatross, EpochChainProof, ForkProof, PbftCommitMessage, PbftPrepareMessage, SignedPbftProposal, ViewChange, ViewChangeProof};
 use handel::update::LevelUpdateMessage;
 use hash::Blake2bHash;
 use keys::{Address, KeyPair, PublicKey, Signature};
@@ -103,6 +103,8 @@ pub enum MessageType {
     GetMacroBlocks = 123,
     GetEpochTransactions = 124,
     EpochTransactions = 125,
+    GetEpochProof = 126,
+    EpochProof = 127,
 }
 
 impl Display for MessageType {
@@ -158,6 +160,8 @@ impl Display for MessageType {
             Self::GetMacroBlocks  => write!(f, "get-macro-blocks"),
             Self::GetEpochTransactions  => write!(f, "get-epoch-transactions"),
             Self::EpochTransactions  => write!(f, "epoch-transactions"),
+            Self::GetEpochProof  => write!(f, "get-epoch-proof"),
+            Self::EpochProof  => write!(f, "epoch-proof"),
         }
     }
 }
@@ -215,6 +219,8 @@ pub enum Message {
     GetMacroBlocks(Box<GetBlocksMessage>),
     GetEpochTransactions(Box<GetEpochTransactionsMessage>),
     EpochTransactions(Box<EpochTransactionsMessage>),
+    GetEpochProof(Box<GetEpochProofMessage>),
+    EpochProof(Box<EpochProofMessage>),
 }
 
 impl Message {
@@ -265,6 +271,8 @@ impl Message {
             Message::GetMacroBlocks(_) => MessageType::GetMacroBlocks,
             Message::GetEpochTransactions(_) => MessageType::GetEpochTransactions,
             Message::EpochTransactions(_) => MessageType::EpochTransactions,
+            Message::GetEpochProof(_) => MessageType::GetEpochProof,
+            Message::EpochProof(_) => MessageType::EpochProof,
         }
     }
 
@@ -378,6 +386,8 @@ impl Deserialize for Message {
             MessageType::GetMacroBlocks => Message::GetMacroBlocks(Deserialize::deserialize(&mut crc32_reader)?),
             MessageType::GetEpochTransactions => Message::GetEpochTransactions(Deserialize::deserialize(&mut crc32_reader)?),
             MessageType::EpochTransactions => Message::EpochTransactions(Deserialize::deserialize(&mut crc32_reader)?),
+            MessageType::GetEpochProof => Message::GetEpochProof(Deserialize::deserialize(&mut crc32_reader)?),
+            MessageType::EpochProof => Message::EpochProof(Deserialize::deserialize(&mut crc32_reader)?),
         };
 
         // XXX Consume any leftover bytes in the message before computing the checksum.
@@ -450,6 +460,8 @@ impl Serialize for Message {
             Message::GetMacroBlocks(get_blocks_message) => get_blocks_message.serialize(&mut v)?,
             Message::GetEpochTransactions(get_epoch_transactions) => get_epoch_transactions.serialize(&mut v)?,
             Message::EpochTransactions(epoch_transactions) => epoch_transactions.serialize(&mut v)?,
+            Message::GetEpochProof(get_epoch_proof) => get_epoch_proof.serialize(&mut v)?,
+            Message::EpochProof(epoch_proof) => epoch_proof.serialize(&mut v)?,
         };
 
         // write checksum to placeholder
@@ -511,6 +523,8 @@ impl Serialize for Message {
             Message::GetMacroBlocks(get_blocks_message) => get_blocks_message.serialized_size(),
             Message::GetEpochTransactions(get_epoch_transactions) => get_epoch_transactions.serialized_size(),
             Message::EpochTransactions(epoch_transactions) => epoch_transactions.serialized_size(),
+            Message::GetEpochProof(get_epoch_proof) => get_epoch_proof.serialized_size(),
+            Message::EpochProof(epoch_proof) => epoch_proof.serialized_size(),
         };
         size
     }
@@ -563,6 +577,8 @@ pub struct MessageNotifier {
     pub get_macro_blocks: RwLock<PassThroughNotifier<'static, GetBlocksMessage>>,
     pub get_epoch_transactions: RwLock<PassThroughNotifier<'static, GetEpochTransactionsMessage>>,
     pub epoch_transactions: RwLock<PassThroughNotifier<'static, EpochTransactionsMessage>>,
+    pub get_epoch_proof: RwLock<PassThroughNotifier<'static, GetEpochProofMessage>>,
+    pub epoch_proof: RwLock<PassThroughNotifier<'static, EpochProofMessage>>,
 }
 
 impl MessageNotifier {
@@ -617,6 +633,8 @@ impl MessageNotifier {
             Message::GetMacroBlocks(msg) => self.get_macro_blocks.read().notify(*msg),
             Message::GetEpochTransactions(msg) => self.get_epoch_transactions.read().notify(*msg),
             Message::EpochTransactions(msg) => self.epoch_transactions.read().notify(*msg),
+            Message::GetEpochProof(msg) => self.get_epoch_proof.read().notify(*msg),
+            Message::EpochProof(msg) => self.epoch_proof.read().notify(*msg),
         }
     }
 }
@@ -1210,3 +1228,33 @@ impl EpochTransactionsMessage {
         }))
     }
 }
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetEpochProofMessage {
+    /// Hash of the latest macro block the requester knows.
+    pub known_macro_hash: Blake2bHash,
+}
+impl GetEpochProofMessage {
+    pub fn new(known_macro_hash: Blake2bHash) -> Message {
+        Message::GetEpochProof(Box::new(Self {
+            known_macro_hash,
+        }))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EpochProofMessage {
+    pub known_macro_hash: Blake2bHash,
+    pub proof: Option<EpochChainProof>,
+}
+impl EpochProofMessage {
+    /// Maximum number of epochs in a single proof. Requesters ask for more if the limit was hit.
+    pub const MAX_EPOCHS: usize = 50;
+
+    pub fn new(known_macro_hash: Blake2bHash, proof: Option<EpochChainProof>) -> Message {
+        Message::EpochProof(Box::new(Self {
+            known_macro_hash,
+            proof,
+        }))
+    }
+}
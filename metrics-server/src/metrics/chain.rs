@@ -5,6 +5,7 @@ use blockchain::Blockchain;
 use blockchain_base::AbstractBlockchain;
 use blockchain_albatross::Blockchain as AlbatrossBlockchain;
 use block::Difficulty;
+use block_albatross::Block as AlbatrossBlock;
 use consensus::{ConsensusProtocol, AlbatrossConsensusProtocol, NimiqConsensusProtocol};
 
 use crate::server;
@@ -79,20 +80,56 @@ impl Metrics for AlbatrossChainMetrics {
             serializer.metric("chain_head_height", head.block_number())?;
             serializer.metric("chain_head_view_number", head.view_number())?;
 
-            /*match head {
-                AlbatrossBlock::Macro(ref macro_block) => {
-                    // TODO metrics about macro block?
-                    // e.g.: number of signatures in prepare & commit
-                }
+            match *head {
+                AlbatrossBlock::Macro(_) => {
+                    serializer.metric_with_attributes("chain_head_transactions", 0, attributes!{"type" => "macro"})?;
+                },
                 AlbatrossBlock::Micro(ref micro_block) => {
-                    // TODO: number of transactions in micro block
-                    // other metrics?
+                    let num_transactions = micro_block.extrinsics.as_ref().map(|extrinsics| extrinsics.transactions.len()).unwrap_or(0);
+                    serializer.metric_with_attributes("chain_head_transactions", num_transactions, attributes!{"type" => "micro"})?;
+                    serializer.metric("chain_head_view_change", micro_block.justification.view_change_proof.is_some() as u8)?;
+                },
+            }
+        }
+
+        // The justification of the last macro block is signed by the validators of the previous epoch.
+        let (macro_head_number, justification) = {
+            let macro_head = self.blockchain.macro_head();
+            (macro_head.header.block_number, macro_head.justification.clone())
+        };
+        serializer.metric("chain_macro_head_height", macro_head_number)?;
+        if let Some(justification) = justification {
+            serializer.metric_with_attributes("chain_macro_head_pbft_signers", justification.prepare.signers.len(), attributes!{"phase" => "prepare"})?;
+            serializer.metric_with_attributes("chain_macro_head_pbft_signers", justification.commit.signers.len(), attributes!{"phase" => "commit"})?;
+
+            let state = self.blockchain.state();
+            if let Some(validators) = state.last_validators() {
+                if let Ok(votes) = justification.prepare.votes(validators) {
+                    serializer.metric_with_attributes("chain_macro_head_pbft_votes", votes, attributes!{"phase" => "prepare"})?;
                 }
-            }*/
+                if let Ok(votes) = justification.commit.votes(validators) {
+                    serializer.metric_with_attributes("chain_macro_head_pbft_votes", votes, attributes!{"phase" => "commit"})?;
+                }
+            }
         }
 
+        {
+            let state = self.blockchain.state();
+
+            serializer.metric_with_attributes("chain_slashed_slots", state.current_slashed_set().len(), attributes!{"epoch" => "current"})?;
+            if self.blockchain.policy.epoch_at(state.block_number()) > 0 {
+                serializer.metric_with_attributes("chain_slashed_slots", state.last_slashed_set().len(), attributes!{"epoch" => "last"})?;
+            }
+
+            let reward_registry = state.reward_registry();
+            serializer.metric_with_attributes("chain_reward_pot", u64::from(reward_registry.current_reward_pot()), attributes!{"epoch" => "current"})?;
+            serializer.metric_with_attributes("chain_reward_pot", u64::from(reward_registry.previous_reward_pot()), attributes!{"epoch" => "last"})?;
+        }
+
+        serializer.metric("chain_view_changes", self.blockchain.metrics().view_change_count())?;
+
         self.serialize_blockchain_metrics(Arc::clone(&self.blockchain), serializer)?;
 
         Ok(())
     }
-}
\ No newline at end of file
+}
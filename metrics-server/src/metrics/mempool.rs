@@ -1,9 +1,8 @@
 use std::io;
 use std::sync::Arc;
 
-use beserial::Serialize;
 use blockchain_base::AbstractBlockchain;
-use mempool::{Mempool, SIZE_MAX};
+use mempool::Mempool;
 
 use crate::server;
 use crate::server::SerializationType;
@@ -20,29 +19,45 @@ impl<B: AbstractBlockchain + 'static> MempoolMetrics<B> {
     }
 }
 
+impl<B: AbstractBlockchain + 'static> MempoolMetrics<B> {
+    /// Target numbers of blocks for which the fee estimate is reported.
+    const FEE_ESTIMATE_TARGETS: [u32; 4] = [1, 2, 5, 10];
+}
+
 impl<B: AbstractBlockchain + 'static> server::Metrics for MempoolMetrics<B> {
     fn metrics(&self, serializer: &mut server::MetricsSerializer<SerializationType>) -> Result<(), io::Error> {
-        let txs = self.mempool.get_transactions(SIZE_MAX, 0f64);
-        let group = [0usize, 1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000, 10000];
-        for i in 1..group.len() {
-            let lower_bound = group[i - 1];
-            let upper_bound = group[i];
+        let histogram = self.mempool.fee_histogram();
+        for (i, bucket) in histogram.iter().enumerate() {
+            let fee_per_byte = match histogram.get(i + 1) {
+                Some(next_bucket) => format!("<{}", next_bucket.fee_per_byte_min),
+                None => format!(">={}", bucket.fee_per_byte_min),
+            };
             serializer.metric_with_attributes(
                 "mempool_transactions",
-                txs.iter().filter(|tx| (tx.fee_per_byte() as usize) >= lower_bound && (tx.fee_per_byte() as usize) < upper_bound).count(),
-                attributes!{"fee_per_byte" => format!("<{}", group[i])}
+                bucket.count,
+                attributes!{"fee_per_byte" => &fee_per_byte}
+            )?;
+            serializer.metric_with_attributes(
+                "mempool_transactions_size",
+                bucket.size,
+                attributes!{"fee_per_byte" => &fee_per_byte}
             )?;
         }
-        let lower_bound = *group.last().unwrap();
-        serializer.metric_with_attributes(
-            "mempool_transactions",
-            txs.iter().filter(|tx| (tx.fee_per_byte() as usize) >= lower_bound).count(),
-            attributes!{"fee_per_byte" => format!(">={}", lower_bound)}
-        )?;
         serializer.metric(
             "mempool_size",
-            txs.iter().map(|tx| tx.serialized_size()).sum::<usize>(),
+            self.mempool.size(),
         )?;
+        serializer.metric(
+            "mempool_min_fee_per_byte",
+            self.mempool.min_fee_per_byte(),
+        )?;
+        for &target_blocks in Self::FEE_ESTIMATE_TARGETS.iter() {
+            serializer.metric_with_attributes(
+                "mempool_fee_per_byte_estimate",
+                self.mempool.estimate_fee(target_blocks),
+                attributes!{"target_blocks" => target_blocks}
+            )?;
+        }
 
         Ok(())
     }
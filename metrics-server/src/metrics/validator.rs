@@ -0,0 +1,36 @@
+use std::io;
+use std::sync::Arc;
+
+use block_albatross::BlockType;
+use validator::validator::{Validator, ValidatorStatus};
+
+use crate::server;
+use crate::server::{SerializationType, Metrics};
+
+pub struct ValidatorMetrics {
+    validator: Arc<Validator>,
+}
+
+impl ValidatorMetrics {
+    pub fn new(validator: Arc<Validator>) -> Self {
+        ValidatorMetrics {
+            validator,
+        }
+    }
+}
+
+impl Metrics for ValidatorMetrics {
+    fn metrics(&self, serializer: &mut server::MetricsSerializer<SerializationType>) -> Result<(), io::Error> {
+        let status = self.validator.status();
+        serializer.metric("validator_active", (status == ValidatorStatus::Active) as u8)?;
+        serializer.metric("validator_slots", self.validator.num_slots().unwrap_or(0))?;
+
+        let metrics = &self.validator.metrics;
+        serializer.metric_with_attributes("validator_blocks_produced", metrics.blocks_produced(BlockType::Micro), attributes!{"type" => "micro"})?;
+        serializer.metric_with_attributes("validator_blocks_produced", metrics.blocks_produced(BlockType::Macro), attributes!{"type" => "macro"})?;
+        serializer.metric("validator_missed_slots", metrics.missed_slots())?;
+        serializer.metric("validator_view_changes_started", metrics.view_changes_started())?;
+
+        Ok(())
+    }
+}
@@ -11,20 +11,29 @@ use nimiq_build_tools::genesis::albatross::GenesisBuilder;
 use nimiq_build_tools::genesis::powchain::PowChainGenesis;
 use nimiq_hash::Blake2bHash;
 use nimiq_keys::Address;
+use nimiq_primitives::policy::AlbatrossPolicy;
 
-fn write_genesis_rs(directory: &PathBuf, name: &str, genesis_hash: &Blake2bHash, validator_registry: Option<Address>) {
+fn write_genesis_rs(directory: &PathBuf, name: &str, genesis_hash: &Blake2bHash, validator_registry: Option<Address>, policy: Option<AlbatrossPolicy>) {
     let validator_registry_str;
     if let Some(address) = validator_registry {
         validator_registry_str = format!("Some(\"{}\".into())", address);
     } else {
         validator_registry_str = "None".to_string();
     }
+    let policy_str;
+    if let Some(policy) = policy {
+        policy_str = format!("Some(AlbatrossPolicy {{ epoch_length: {}, slots: {}, min_stake: {}, unstaking_delay: {}, transaction_validity_window: {} }})",
+            policy.epoch_length, policy.slots, policy.min_stake, policy.unstaking_delay, policy.transaction_validity_window);
+    } else {
+        policy_str = "None".to_string();
+    }
     let genesis_rs = format!(r#"GenesisData {{
-            block: include_bytes!(concat!(env!("OUT_DIR"), "/genesis/{}/block.dat")),
+            block: Cow::Borrowed(include_bytes!(concat!(env!("OUT_DIR"), "/genesis/{}/block.dat"))),
             hash: "{}".into(),
-            accounts: include_bytes!(concat!(env!("OUT_DIR"), "/genesis/{}/accounts.dat")),
+            accounts: Cow::Borrowed(include_bytes!(concat!(env!("OUT_DIR"), "/genesis/{}/accounts.dat"))),
             validator_registry: {},
-    }}"#, name, genesis_hash, name, validator_registry_str);
+            policy: {},
+    }}"#, name, genesis_hash, name, validator_registry_str, policy_str);
     debug!("Writing genesis source code: {}", &genesis_rs);
     fs::write(directory.join("genesis.rs"), genesis_rs.as_bytes()).unwrap();
 }
@@ -35,7 +44,7 @@ fn generate_powchain(name: &str, out_dir: &PathBuf, powchain: PowChainGenesis) {
     let genesis_hash = powchain.generate_genesis_hash().unwrap();
 
     powchain.write_to_files(&directory).unwrap();
-    write_genesis_rs(&directory, name, &genesis_hash, None);
+    write_genesis_rs(&directory, name, &genesis_hash, None, None);
 }
 
 fn generate_albatross(name: &str, out_dir: &PathBuf, src_dir: &PathBuf, config_override: Option<PathBuf>) {
@@ -56,7 +65,7 @@ fn generate_albatross(name: &str, out_dir: &PathBuf, src_dir: &PathBuf, config_o
     builder.with_config_file(genesis_config).unwrap();
     let staking_contract_address = builder.staking_contract_address.clone().expect("Missing staking contract address");
     let genesis_hash = builder.write_to_files(&directory).unwrap();
-    write_genesis_rs(&directory, name, &genesis_hash, Some(staking_contract_address));
+    write_genesis_rs(&directory, name, &genesis_hash, Some(staking_contract_address), Some(builder.policy.unwrap_or_default()));
 }
 
 
@@ -5,29 +5,36 @@ use crate::address::peer_address::PeerAddressType;
 use crate::address::PeerId;
 use crate::services::ServiceFlags;
 use crate::address::seed_list::SeedList;
-use beserial::Deserialize;
+use beserial::{Deserialize, Serialize};
+use failure::Fail;
 use hash::Blake2bHash;
 use keys::Address;
 use account::Account;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use keys::PublicKey;
 use hex::FromHex;
 use account::AccountsList;
+use block_albatross::Block as AlbatrossBlock;
 use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use primitives::policy::AlbatrossPolicy;
+use primitives::slot;
 
 
 #[derive(Clone, Debug)]
 struct GenesisData {
-    block: &'static [u8],
+    block: Cow<'static, [u8]>,
     hash: Blake2bHash,
-    accounts: &'static [u8],
+    accounts: Cow<'static, [u8]>,
     validator_registry: Option<Address>,
+    policy: Option<AlbatrossPolicy>,
 }
 
 #[derive(Clone, Debug)]
 pub struct NetworkInfo {
     network_id: NetworkId,
-    name: &'static str,
+    name: Cow<'static, str>,
 
     seed_peers: Vec<PeerAddress>,
     seed_lists: Vec<SeedList>,
@@ -43,7 +50,7 @@ impl NetworkInfo {
 
     #[inline]
     pub fn name(&self) -> String {
-        self.name.into()
+        self.name.to_string()
     }
 
     #[inline]
@@ -58,7 +65,7 @@ impl NetworkInfo {
 
     #[inline]
     pub fn genesis_block<B: Deserialize>(&self) -> B {
-        let block: B = Deserialize::deserialize_from_vec(&self.genesis.block.to_vec())
+        let block: B = Deserialize::deserialize_from_vec(&self.genesis.block)
             .expect("Failed to deserialize genesis block.");
         block
     }
@@ -70,7 +77,7 @@ impl NetworkInfo {
 
     #[inline]
     pub fn genesis_accounts(&self) -> Vec<(Address, Account)> {
-        let accounts: AccountsList = Deserialize::deserialize_from_vec(&self.genesis.accounts.to_vec())
+        let accounts: AccountsList = Deserialize::deserialize_from_vec(&self.genesis.accounts)
             .expect("Failed to deserialize genesis accounts.");
         accounts.0
     }
@@ -80,14 +87,75 @@ impl NetworkInfo {
         self.genesis.validator_registry.as_ref()
     }
 
-    pub fn from_network_id(network_id: NetworkId) -> &'static Self {
+    /// The consensus parameters of an Albatross network.
+    #[inline]
+    pub fn albatross_policy(&self) -> AlbatrossPolicy {
+        self.genesis.policy.unwrap_or_default()
+    }
+
+    pub fn from_network_id(network_id: NetworkId) -> Result<&'static Self, NetworkInfoError> {
+        if let NetworkId::CustomAlbatross(_) = network_id {
+            return CUSTOM_NETWORKS.read().get(&network_id)
+                .cloned()
+                .ok_or(NetworkInfoError::CustomNetworkNotRegistered(network_id));
+        }
         NETWORK_MAP.get(&network_id)
-            .unwrap_or_else(|| panic!("No such network ID: {}", network_id))
+            .ok_or(NetworkInfoError::UnknownNetwork(network_id))
     }
+
+    /// Registers the genesis data of the custom Albatross network `network_id`, e.g. after
+    /// generating it from a genesis config file. This must happen before the network is used.
+    /// This also sets the number of slots that slot allocations are deserialized with.
+    pub fn register_custom_albatross(network_id: NetworkId, block: &AlbatrossBlock, hash: Blake2bHash, accounts: Vec<(Address, Account)>, validator_registry: Address, policy: AlbatrossPolicy) -> Result<&'static Self, NetworkInfoError> {
+        let id = match network_id {
+            NetworkId::CustomAlbatross(id) => id,
+            _ => return Err(NetworkInfoError::NotCustom(network_id)),
+        };
+
+        let mut custom_networks = CUSTOM_NETWORKS.write();
+        if custom_networks.contains_key(&network_id) {
+            return Err(NetworkInfoError::AlreadyRegistered(network_id));
+        }
+
+        // Slot allocations of this network are encoded with its number of slots.
+        slot::set_num_slots(policy.slots);
+
+        let info: &'static NetworkInfo = Box::leak(Box::new(NetworkInfo {
+            network_id,
+            name: Cow::Owned(format!("custom-albatross-{}", id)),
+            seed_peers: vec![],
+            seed_lists: vec![],
+            genesis: GenesisData {
+                block: Cow::Owned(block.serialize_to_vec()),
+                hash,
+                accounts: Cow::Owned(AccountsList(accounts).serialize_to_vec()),
+                validator_registry: Some(validator_registry),
+                policy: Some(policy),
+            },
+        }));
+        custom_networks.insert(network_id, info);
+        Ok(info)
+    }
+}
+
+#[derive(Clone, Debug, Fail, PartialEq, Eq)]
+pub enum NetworkInfoError {
+    #[fail(display = "No such network ID: {}", _0)]
+    UnknownNetwork(NetworkId),
+    #[fail(display = "Custom network was not registered: {}", _0)]
+    CustomNetworkNotRegistered(NetworkId),
+    #[fail(display = "Custom network was already registered: {}", _0)]
+    AlreadyRegistered(NetworkId),
+    #[fail(display = "Not a custom network: {}", _0)]
+    NotCustom(NetworkId),
 }
 
 
 
+lazy_static! {
+    static ref CUSTOM_NETWORKS: RwLock<HashMap<NetworkId, &'static NetworkInfo>> = RwLock::new(HashMap::new());
+}
+
 lazy_static! {
     static ref NETWORK_MAP: HashMap<NetworkId, NetworkInfo> = {
         let mut m = HashMap::new();
@@ -97,7 +165,7 @@ lazy_static! {
 
         add(&mut m, NetworkInfo {
             network_id: NetworkId::Main,
-            name: "main",
+            name: Cow::Borrowed("main"),
             seed_peers: vec![
                 create_seed_peer_addr("seed-1.nimiq.com", 8443, "b70d0c3e6cdf95485cac0688b086597a5139bc4237173023c83411331ef90507"),
                 create_seed_peer_addr("seed-2.nimiq.com", 8443, "8580275aef426981a04ee5ea948ca3c95944ef1597ad78db9839f810d6c5b461"),
@@ -128,7 +196,7 @@ lazy_static! {
 
         add(&mut m, NetworkInfo {
             network_id: NetworkId::Test,
-            name: "test",
+            name: Cow::Borrowed("test"),
             seed_peers: vec![
                 create_seed_peer_addr("seed1.nimiqtest.net", 8080, "175d5f01af8a5911c240a78df689a76eef782d793ca15d073bdc913edd07c74b"),
                 create_seed_peer_addr("seed2.nimiqtest.net", 8080, "2c950d2afad1aa7ad12f01a56527f709b7687b1b00c94da6e0bd8ae4d263d47c"),
@@ -141,7 +209,7 @@ lazy_static! {
 
         add(&mut m, NetworkInfo {
             network_id: NetworkId::Dev,
-            name: "dev",
+            name: Cow::Borrowed("dev"),
             seed_peers: vec![
                 create_seed_peer_addr_ws("dev.nimiq-network.com", 8080, "e65e39616662f2c16d62dc08915e5a1d104619db8c2b9cf9b389f96c8dce9837")
             ],
@@ -151,7 +219,7 @@ lazy_static! {
 
         add(&mut m, NetworkInfo {
             network_id: NetworkId::DevAlbatross,
-            name: "dev-albatross",
+            name: Cow::Borrowed("dev-albatross"),
             seed_peers: vec![
                 //create_seed_peer_addr_ws("albatross.nimiq.dev", 8444, "5af4c3f30998573e8d3476cd0e0543bf7adba576ef321342e41c2bccc246c377"),
             ],
@@ -161,7 +229,7 @@ lazy_static! {
 
         add(&mut m, NetworkInfo {
             network_id: NetworkId::UnitAlbatross,
-            name: "unit-albatross",
+            name: Cow::Borrowed("unit-albatross"),
             seed_peers: vec![],
             seed_lists: vec![],
             genesis: include!(concat!(env!("OUT_DIR"), "/genesis/unit-albatross/genesis.rs")),
@@ -1,3 +1,4 @@
+use beserial::{Deserialize, Serialize};
 use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_block::Block;
 use nimiq_network_primitives::networks::*;
@@ -6,7 +7,31 @@ use hex;
 #[test]
 fn it_has_expected_main_hash() {
     assert_eq!(
-        NetworkInfo::from_network_id(NetworkId::Main).genesis_block::<Block>().header.hash::<Blake2bHash>().as_bytes(),
+        NetworkInfo::from_network_id(NetworkId::Main).unwrap().genesis_block::<Block>().header.hash::<Blake2bHash>().as_bytes(),
         &hex::decode("264AAF8A4F9828A76C550635DA078EB466306A189FCC03710BEE9F649C869D12").unwrap()[..]
     )
 }
+
+#[test]
+fn it_returns_an_error_for_unregistered_custom_networks() {
+    let network_id = NetworkId::CustomAlbatross(200);
+    assert_eq!(NetworkInfo::from_network_id(network_id).unwrap_err(), NetworkInfoError::CustomNetworkNotRegistered(network_id));
+    assert_eq!(NetworkInfo::from_network_id(NetworkId::Bounty).unwrap_err(), NetworkInfoError::UnknownNetwork(NetworkId::Bounty));
+}
+
+#[test]
+fn it_serializes_custom_network_ids() {
+    assert_eq!(NetworkId::Main.serialize_to_vec(), vec![42]);
+    assert_eq!(NetworkId::CustomAlbatross(200).serialize_to_vec(), vec![200]);
+    assert_eq!(NetworkId::deserialize_from_vec(&[200]).unwrap(), NetworkId::CustomAlbatross(200));
+    assert_eq!(NetworkId::deserialize_from_vec(&[6]).unwrap(), NetworkId::DevAlbatross);
+
+    // IDs below the custom range are only valid for the built-in networks.
+    assert!(NetworkId::deserialize_from_vec(&[100]).is_err());
+    assert_eq!(NetworkId::custom_albatross(100), None);
+
+    assert_ne!(NetworkId::CustomAlbatross(200), NetworkId::CustomAlbatross(201));
+    assert_eq!("customalbatross-200".parse::<NetworkId>().unwrap(), NetworkId::CustomAlbatross(200));
+    assert_eq!(NetworkId::CustomAlbatross(200).to_string(), "CustomAlbatross-200");
+    assert!("customalbatross-100".parse::<NetworkId>().is_err());
+}
@@ -264,7 +264,7 @@ pub struct PeerAddressBook {
     state: RwLock<PeerAddressBookState>,
     seeded: AtomicBool,
     network_config: Arc<NetworkConfig>,
-    network_id: NetworkId,
+    network_info: &'static NetworkInfo,
     timers: Timers<PeerAddressBookTimer>,
     change_lock: Mutex<()>,
     pub notifier: Notifier<'static, PeerAddressBookEvent>,
@@ -283,6 +283,7 @@ pub enum PeerAddressBookEvent {
 
 impl PeerAddressBook {
     pub fn new(network_config: Arc<NetworkConfig>, network_id: NetworkId) -> Result<Self, Error> {
+        let network_info = NetworkInfo::from_network_id(network_id)?;
         let this = Self {
             state: RwLock::new(PeerAddressBookState {
                 info_by_address: HashMap::new(),
@@ -293,7 +294,7 @@ impl PeerAddressBook {
                 addresses_by_net_address: HashMap::new(),
             }),
             seeded: AtomicBool::new(false),
-            network_id,
+            network_info,
             network_config,
             timers: Timers::new(),
             change_lock: Mutex::new(()),
@@ -301,7 +302,7 @@ impl PeerAddressBook {
         };
 
         // Init hardcoded seed peers.
-        this.add(None, NetworkInfo::from_network_id(network_id).seed_peers().clone());
+        this.add(None, network_info.seed_peers().clone());
 
         // Init seed peers from config file.
         let additional_seeds: Vec<PeerAddress> = this.network_config.additional_seeds().iter()
@@ -348,7 +349,7 @@ impl PeerAddressBook {
                 },
             }
         });
-        seeder.collect(this.network_id, this.network_config.clone());
+        seeder.collect(this.network_info, this.network_config.clone());
 
         Ok(())
     }
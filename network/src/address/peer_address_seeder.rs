@@ -13,7 +13,7 @@ use keys::Signature;
 use crate::network_config::{NetworkConfig, Seed};
 use network_primitives::address::peer_address::PeerAddress;
 use network_primitives::address::peer_uri::{PeerUri, PeerUriError};
-use network_primitives::networks::{NetworkInfo, NetworkId};
+use network_primitives::networks::NetworkInfo;
 
 use utils::observer::Notifier;
 
@@ -65,9 +65,7 @@ impl PeerAddressSeeder {
         }
     }
 
-    pub fn collect(&self, network_id: NetworkId, network_config: Arc<NetworkConfig>) {
-        let network_info = NetworkInfo::from_network_id(network_id);
-
+    pub fn collect(&self, network_info: &NetworkInfo, network_config: Arc<NetworkConfig>) {
         // Get additional seed lists from the config file (in Iterator form)
         let additional_seedlists = network_config.additional_seeds().iter()
         .filter_map(|seed| {
@@ -44,6 +44,7 @@ pub enum CloseType {
     InvalidConnectionState = 40,
 
     GetEpochTransactionsTimeout = 41,
+    GetEpochProofTimeout = 42,
 
     ManualPeerDisconnect = 90,
 
@@ -70,8 +71,10 @@ pub enum CloseType {
 
     UnexpectedEpochTransactions = 117,
     InvalidEpochTransactions = 118,
+    UnexpectedEpochProof = 119,
 
     RateLimitExceeded = 120,
+    InvalidEpochProof = 121,
 
     ManualPeerBan = 190,
 
@@ -14,7 +14,6 @@ use macros::upgrade_weak;
 use network_messages::*;
 use network_primitives::address::peer_address::PeerAddress;
 use network_primitives::address::PeerId;
-use network_primitives::networks::NetworkInfo;
 use network_primitives::protocol::Protocol;
 use network_primitives::version;
 use utils::observer::{Notifier, weak_listener, weak_passthru_listener};
@@ -164,7 +163,7 @@ impl<B: AbstractBlockchain + 'static> NetworkAgent<B> {
         // Kick off the handshake by telling the peer our version, network address & blockchain head hash.
         // Firefox sends the data-channel-open event too early, so sending the version message might fail.
         // Try again in this case.
-        let network_info = NetworkInfo::from_network_id(self.blockchain.network_id());
+        let network_info = self.blockchain.network_info();
         let msg = VersionMessage::new(
             self.network_config.peer_address(),
             self.blockchain.head_hash(),
@@ -257,7 +256,7 @@ impl<B: AbstractBlockchain + 'static> NetworkAgent<B> {
         }
 
         // Check if the peer is working on the same genesis block.
-        let network_info = NetworkInfo::from_network_id(self.blockchain.network_id());
+        let network_info = self.blockchain.network_info();
         if *network_info.genesis_hash() != msg.genesis_hash {
             self.channel.close(CloseType::DifferentGenesisBlock);
             return;
@@ -1,7 +1,7 @@
 use failure::Fail;
 use utils::key_store::Error as KeyStoreError;
 
-use network_primitives::networks::NetworkId;
+use network_primitives::networks::NetworkInfoError;
 use network_primitives::address::peer_uri::PeerUriError;
 
 use crate::websocket::error::ServerStartError;
@@ -15,8 +15,8 @@ pub enum Error {
     KeyStoreError(#[cause] KeyStoreError),
     #[fail(display = "{}", _0)]
     ServerStartError(#[cause] ServerStartError),
-    #[fail(display = "Could not load network info for id {:?}", _0)]
-    InvalidNetworkInfo(NetworkId),
+    #[fail(display = "Could not load network info: {}", _0)]
+    InvalidNetworkInfo(#[cause] NetworkInfoError),
     #[fail(display = "Could not add seed node {}", _0)]
     InvalidSeed(#[cause] SeedError)
 }
@@ -33,6 +33,12 @@ impl From<ServerStartError> for Error {
     }
 }
 
+impl From<NetworkInfoError> for Error {
+    fn from(e: NetworkInfoError) -> Self {
+        Error::InvalidNetworkInfo(e)
+    }
+}
+
 impl From<SeedError> for Error {
     fn from(e: SeedError) -> Self {
         Error::InvalidSeed(e)
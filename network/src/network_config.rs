@@ -92,15 +92,20 @@ impl NetworkConfig {
         }
     }
 
-    pub fn init_persistent(&mut self, peer_key_store: &KeyStore) -> Result<(), Error> {
+    /// Loads the peer key from `peer_key_store` or generates a new one. If a passphrase is given,
+    /// it is used to decrypt the key file or to encrypt a newly generated key.
+    pub fn init_persistent(&mut self, peer_key_store: &KeyStore, passphrase: Option<&[u8]>) -> Result<(), Error> {
         if self.key_pair.is_some() {
             return Ok(());
         }
 
-        let private_key = match peer_key_store.load_key() {
+        let private_key = match peer_key_store.load_key_with_passphrase(passphrase) {
             Err(KeyStoreError::IoError(_)) => {
                 let private_key = PrivateKey::generate_default_csprng();
-                peer_key_store.save_key(&private_key)?;
+                match passphrase {
+                    Some(passphrase) => peer_key_store.save_encrypted_key(private_key.clone(), passphrase)?,
+                    None => peer_key_store.save_key(&private_key)?,
+                }
                 Ok(private_key)
             },
             res => res,
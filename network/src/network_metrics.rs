@@ -60,7 +60,7 @@ pub struct MessageMetrics {
 
 impl MessageMetrics {
     // New message types need to be added here to occur in the metrics!
-    const MESSAGE_TYPES: [MessageType; 43] = [
+    const MESSAGE_TYPES: [MessageType; 45] = [
         MessageType::Version,
         MessageType::Inv,
         MessageType::GetData,
@@ -104,6 +104,8 @@ impl MessageMetrics {
         MessageType::PbftProposal,
         MessageType::PbftPrepare,
         MessageType::PbftCommit,
+        MessageType::GetEpochProof,
+        MessageType::EpochProof,
     ];
 
     pub fn new() -> Self {
@@ -11,6 +11,7 @@ use parking_lot::RwLock;
 
 use network_messages::{Message, MessageNotifier};
 use utils::observer::Notifier;
+use utils::unique_id::UniqueId;
 
 use crate::connection::close_type::CloseType;
 use crate::connection::network_connection::AddressInfo;
@@ -102,6 +103,24 @@ impl PeerChannel {
         }
     }
 
+    /// Creates a channel that isn't backed by a network connection. Messages sent to the peer
+    /// are passed to `sink`, messages from the peer have to be passed to `msg_notifier`.
+    pub fn from_sink(sink: UnboundedSender<WebSocketMessage>, address_info: AddressInfo) -> Self {
+        let closed_flag = ClosedFlag::new();
+        PeerChannel {
+            msg_notifier: Arc::new(MessageNotifier::new()),
+            close_notifier: Arc::new(RwLock::new(Notifier::new())),
+            peer_sink: PeerSink::new(sink, UniqueId::new(), closed_flag.clone()),
+            address_info,
+            closed_flag,
+            last_message_received: Arc::new(Atomic::new(Instant::now())),
+            close_event_sent: Arc::new(AtomicBool::new(false)),
+
+            #[cfg(feature = "metrics")]
+            message_metrics: Arc::new(MessageMetrics::new()),
+        }
+    }
+
     pub fn send(&self, msg: Message) -> Result<(), SendError<WebSocketMessage>> {
         self.peer_sink.send(msg)
     }
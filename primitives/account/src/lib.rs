@@ -22,6 +22,7 @@ use hash::{Hash, Hasher, HashOutput, SerializeContent};
 use keys::Address;
 pub use primitives::account::AccountType;
 use primitives::coin::{Coin, CoinParseError};
+use primitives::policy::AlbatrossPolicy;
 use transaction::{Transaction, TransactionError};
 
 use crate::inherent::{AccountInherentInteraction};
@@ -142,6 +143,24 @@ impl Account {
             Account::Staking(_) => StakingContract::check_incoming_transaction(transaction, block_height),
         }
     }
+
+    /// Checks the parts of an incoming transaction that depend on the Albatross policy of the
+    /// network, i.e. the minimum stake.
+    pub fn check_incoming_policy(&self, transaction: &Transaction, policy: &AlbatrossPolicy) -> Result<(), AccountError> {
+        match self {
+            Account::Staking(_) => StakingContract::check_min_stake(transaction, policy),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the parts of an outgoing transaction that depend on the Albatross policy of the
+    /// network, i.e. the unstaking delay.
+    pub fn check_outgoing_policy(&self, transaction: &Transaction, block_height: u32, policy: &AlbatrossPolicy) -> Result<(), AccountError> {
+        match self {
+            Account::Staking(ref contract) => contract.check_unstaking_delay(transaction, block_height, policy),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl AccountsTreeLeave for Account {
@@ -1,5 +1,5 @@
 use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::collections::btree_set::BTreeSet;
 use std::mem;
 use std::sync::Arc;
@@ -7,57 +7,96 @@ use std::sync::Arc;
 use beserial::{Deserialize, DeserializeWithLength, ReadBytesExt, Serialize, SerializeWithLength, SerializingError, WriteBytesExt};
 use bls::bls12_381::CompressedPublicKey as BlsPublicKey;
 use keys::Address;
-use primitives::{policy, coin::Coin};
+use primitives::coin::Coin;
+use primitives::policy::AlbatrossPolicy;
 use primitives::slot::{Slots, SlotsBuilder};
 use transaction::{SignatureProof, Transaction};
-use transaction::account::staking_contract::{StakingTransactionData, StakingTransactionType};
+use transaction::account::staking_contract::{MAX_COMMISSION, StakingTransactionData, StakingTransactionType, UpdateValidatorData};
 use vrf::{VrfSeed, VrfUseCase, AliasMethod};
 
 use crate::{Account, AccountError, AccountTransactionInteraction, AccountType};
 use crate::inherent::{AccountInherentInteraction, Inherent, InherentType};
 
+/// A validator registered in the staking contract. Its balance is the total stake delegated to it.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct ActiveStake {
-    pub staker_address: Address,
+pub struct Validator {
+    pub validator_address: Address,
     pub balance: Coin,
-    pub validator_key: BlsPublicKey, // TODO Share validator keys eventually and if required
+    pub validator_key: BlsPublicKey,
     pub reward_address: Option<Address>,
+    pub commission: u16,
+    pub active: bool,
 }
 
-impl PartialEq for ActiveStake {
-    fn eq(&self, other: &ActiveStake) -> bool {
+impl PartialEq for Validator {
+    fn eq(&self, other: &Validator) -> bool {
         self.balance == other.balance
-            && self.staker_address == other.staker_address
+            && self.validator_address == other.validator_address
     }
 }
 
-impl Eq for ActiveStake {}
+impl Eq for Validator {}
 
-impl PartialOrd for ActiveStake {
-    fn partial_cmp(&self, other: &ActiveStake) -> Option<Ordering> {
+impl PartialOrd for Validator {
+    fn partial_cmp(&self, other: &Validator) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl Ord for ActiveStake {
+impl Ord for Validator {
     // Highest to low balances
     fn cmp(&self, other: &Self) -> Ordering {
         other.balance.cmp(&self.balance)
-            .then_with(|| self.staker_address.cmp(&other.staker_address))
+            .then_with(|| self.validator_address.cmp(&other.validator_address))
     }
 }
 
+impl Validator {
+    pub fn with_balance(&self, balance: Coin) -> Self {
+        Validator {
+            validator_address: self.validator_address.clone(),
+            balance,
+            validator_key: self.validator_key.clone(),
+            reward_address: self.reward_address.clone(),
+            commission: self.commission,
+            active: self.active,
+        }
+    }
+
+    pub fn reward_address(&self) -> &Address {
+        self.reward_address.as_ref()
+            .unwrap_or(&self.validator_address)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct ActiveStake {
+    pub staker_address: Address,
+    pub balance: Coin,
+    pub validator_address: Address,
+}
+
 impl ActiveStake {
     pub fn with_balance(&self, balance: Coin) -> Self {
         ActiveStake {
             staker_address: self.staker_address.clone(),
             balance,
-            validator_key: self.validator_key.clone(),
-            reward_address: self.reward_address.clone(),
+            validator_address: self.validator_address.clone(),
         }
     }
 }
 
+/// The stake of an active validator at the time validators were selected. The rewards of the
+/// selected epoch are split with the delegators according to it.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct DelegationSnapshot {
+    pub balance: Coin,
+    pub commission: u16,
+    /// The stake delegated by others, ordered by staker address.
+    #[beserial(len_type(u32))]
+    pub delegations: Vec<Arc<ActiveStake>>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct InactiveStake {
     pub balance: Coin,
@@ -66,8 +105,20 @@ pub struct InactiveStake {
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct ActiveStakeReceipt {
+    validator_address: Address,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct ValidatorReceipt {
     validator_key: BlsPublicKey,
     reward_address: Option<Address>,
+    commission: u16,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+struct RegisterValidatorReceipt {
+    previous_validator: Option<ValidatorReceipt>,
+    stake: Option<ActiveStakeReceipt>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
@@ -88,12 +139,19 @@ struct SlashReceipt {
 
 /**
  Here's an explanation of how the different transactions work.
- 1. Stake:
+ 1. Register validator:
+    - Transaction from validator address to contract
+    - Registers the sender as a validator with a validator_key, optional reward_address and
+      commission. The sender address identifies the validator from now on.
+    - Transfers value into the stake of the sender, delegated to the new validator.
+    - A validator that has been deactivated may register again with new parameters.
+    - Normal transaction, signed by validator/sender address
+ 2. Stake:
     - Transaction from staking address to contract
     - Transfers value into a new or existing entry in the active_stake list
-    - Existing entries are updated with potentially new validator_key and reward_address
+    - The whole entry is delegated to the validator given in the transaction, which must be active
     - Normal transaction, signed by staking/sender address
- 2. Retire:
+ 3. Retire:
     - Transaction from staking contract to itself
     - Removes a balance (the transaction value) from the active stake of a staker
       (may remove staker from active stake list entirely)
@@ -101,11 +159,19 @@ struct SlashReceipt {
     - If a staker retires multiple times, balance is added to the existing entry and
       retire_time is reset.
     - Signed by staking/sender address
- 3. Unstake:
+ 4. Unstake:
     - Transaction from the contract to an external address
     - If condition of block_height ≥ next_macro_block_after(retire_time) + UNSTAKE_DELAY is met,
       transfers value from inactive_validators entry/entries
     - Signed by staking/sender address
+ 5. Update validator:
+    - Transaction from staking contract to itself
+    - Replaces the validator_key and/or reward_address of the signing validator. The new
+      parameters are used from the next validator selection on.
+    - The new validator_key must not be used by any registered validator.
+    - The value is returned to the contract, only the fee is deducted from the validator's own
+      active stake.
+    - Signed by validator address
 
   Reverting transactions:
   Since transactions need to be revertable, the with_{incoming,outgoing}_transaction functions
@@ -113,27 +179,50 @@ struct SlashReceipt {
   Internally, this data can be serialized/deserialized.
 
   Objects:
+  Validator: A registered validator, characterized by the tuple
+    (validator_address, balance, validator_key, optional reward_address, commission, active).
+    Its balance is the sum of all active stake delegated to it.
   ActiveStake: Stake considered for validator selection, characterized by the tuple
-    (staker_address, balance, validator_key, optional reward_address).
+    (staker_address, balance, validator_address).
   InactiveStake: Stake ignored for validator selection, represented by the tuple
     (balance, retire_time).
+  DelegationSnapshot: The stake of an active validator when validators were selected,
+    represented by the tuple (balance, commission, delegations).
+
+  Slashing and parking apply to validators, i.e. the parking lists contain validator addresses.
+  A validator that is still parked when its epoch is finalized has its own stake retired and is
+  deactivated. Its delegators keep their stake, but have to retire or delegate it elsewhere.
+
+  Rewards are split with the delegators according to the stake at the time the validators of an
+  epoch were selected, such that stake delegated during an epoch doesn't earn rewards for it.
+  Like the parking lists, the delegation snapshots are rotated whenever an epoch is finalized:
+  The rewards of an epoch are paid out when the following epoch is finalized, using the snapshot
+  that was taken before that epoch's validators were selected.
+
+  The number of slots, the minimum stake and the unstaking delay are given by the policy of the
+  network. The policy isn't part of the contract's state and is passed in by the caller instead.
 
   Internal lookups required:
+  - Register validator requires a way to get from a validator address to a Validator object
   - Stake requires a way to get from a staker address to an ActiveStake object
+    and from a validator address to a Validator object
   - Retire requires a way to get from a staker address to an ActiveStake object
     and from a staker address to the list of InactiveStake objects.
   - Unstake requires a way to get from a staker address to the list of InactiveStake objects.
-  - Retrieving the list of active stakes that are actually considered for the selection
-    requires a list of ActiveStake objects ordered by its balance.
+  - Retrieving the list of validators that are actually considered for the selection
+    requires a list of active Validator objects ordered by their balance.
  */
 #[derive(Clone, Debug)]
 pub struct StakingContract {
     pub balance: Coin,
-    pub active_stake_sorted: BTreeSet<Arc<ActiveStake>>, // A list might be sufficient.
+    pub validators_by_address: HashMap<Address, Arc<Validator>>,
+    pub active_validators_sorted: BTreeSet<Arc<Validator>>, // A list might be sufficient.
     pub active_stake_by_address: HashMap<Address, Arc<ActiveStake>>,
     pub inactive_stake_by_address: HashMap<Address, InactiveStake>,
     pub current_epoch_parking: HashSet<Address>,
     pub previous_epoch_parking: HashSet<Address>,
+    pub current_epoch_delegations: BTreeMap<Address, DelegationSnapshot>,
+    pub previous_epoch_delegations: BTreeMap<Address, DelegationSnapshot>,
 }
 
 impl StakingContract {
@@ -149,35 +238,212 @@ impl StakingContract {
         self.inactive_stake_by_address.get(staker_address).map(|stake| stake.balance).unwrap_or(Coin::ZERO)
     }
 
-    /// Adds funds to stake of `address`.
+    pub fn get_validator(&self, validator_address: &Address) -> Option<&Arc<Validator>> {
+        self.validators_by_address.get(validator_address)
+    }
+
+    /// Checks that a stake transaction stakes at least the minimum stake of the policy.
+    pub fn check_min_stake(transaction: &Transaction, policy: &AlbatrossPolicy) -> Result<(), AccountError> {
+        if transaction.sender != transaction.recipient
+            && transaction.value < Coin::from_u64_unchecked(policy.min_stake) {
+            warn!("Stake value below minimum");
+            return Err(AccountError::InvalidForRecipient);
+        }
+        Ok(())
+    }
+
+    /// Checks that the stake of an unstake transaction was retired at least the unstaking delay of
+    /// the policy after the next macro block.
+    pub fn check_unstaking_delay(&self, transaction: &Transaction, block_height: u32, policy: &AlbatrossPolicy) -> Result<(), AccountError> {
+        if transaction.sender != transaction.recipient {
+            let staker_address = Self::get_signer(transaction)?;
+            let inactive_stake = self.inactive_stake_by_address.get(&staker_address)
+                .ok_or(AccountError::InvalidForSender)?;
+
+            if block_height < policy.macro_block_after(inactive_stake.retire_time) + policy.unstaking_delay {
+                return Err(AccountError::InvalidForSender);
+            }
+        }
+        Ok(())
+    }
+
+    /// Registers `validator_address` as a validator. A deactivated validator is reactivated with
+    /// the new parameters.
+    /// XXX This is public to fill the genesis staking contract
+    pub fn register_validator(&mut self, validator_address: &Address, validator_key: BlsPublicKey, reward_address: Option<Address>, commission: u16) -> Result<Option<ValidatorReceipt>, AccountError> {
+        let (balance, receipt) = match self.validators_by_address.get(validator_address) {
+            Some(validator) if validator.active => return Err(AccountError::InvalidForRecipient),
+            Some(validator) => (validator.balance, Some(ValidatorReceipt {
+                validator_key: validator.validator_key.clone(),
+                reward_address: validator.reward_address.clone(),
+                commission: validator.commission,
+            })),
+            None => (Coin::ZERO, None),
+        };
+
+        self.replace_validator(Arc::new(Validator {
+            validator_address: validator_address.clone(),
+            balance,
+            validator_key,
+            reward_address,
+            commission,
+            active: true,
+        }));
+
+        Ok(receipt)
+    }
+
+    /// Reverts a validator registration.
+    fn revert_register_validator(&mut self, validator_address: &Address, receipt: Option<ValidatorReceipt>) -> Result<(), AccountError> {
+        let validator = self.validators_by_address.remove(validator_address)
+            .ok_or(AccountError::InvalidForRecipient)?;
+
+        self.active_validators_sorted.remove(&validator);
+
+        if let Some(receipt) = receipt {
+            self.replace_validator(Arc::new(Validator {
+                validator_address: validator_address.clone(),
+                balance: validator.balance,
+                validator_key: receipt.validator_key,
+                reward_address: receipt.reward_address,
+                commission: receipt.commission,
+                active: false,
+            }));
+        } else if validator.balance != Coin::ZERO {
+            return Err(AccountError::InvalidReceipt);
+        }
+        Ok(())
+    }
+
+    /// Replaces the key and/or reward address of a validator. The change takes effect with the
+    /// next validator selection.
+    fn update_validator(&mut self, validator_address: &Address, value: Coin, data: UpdateValidatorData) -> Result<ValidatorReceipt, AccountError> {
+        // Another update in the same block might have claimed the key already.
+        if let Some(ref new_key) = data.new_key {
+            if self.is_validator_key_used(&new_key.validator_key) {
+                return Err(AccountError::InvalidForRecipient);
+            }
+        }
+
+        self.balance = Account::balance_add(self.balance, value)?;
+
+        let validator = self.validators_by_address.get(validator_address)
+            .ok_or(AccountError::InvalidForRecipient)?;
+
+        let receipt = ValidatorReceipt {
+            validator_key: validator.validator_key.clone(),
+            reward_address: validator.reward_address.clone(),
+            commission: validator.commission,
+        };
+
+        let mut new_validator = validator.with_balance(validator.balance);
+        if let Some(new_key) = data.new_key {
+            new_validator.validator_key = new_key.validator_key;
+        }
+        if let Some(new_reward_address) = data.new_reward_address {
+            new_validator.reward_address = Some(new_reward_address);
+        }
+        self.replace_validator(Arc::new(new_validator));
+
+        Ok(receipt)
+    }
+
+    /// Reverts a validator update.
+    fn revert_update_validator(&mut self, validator_address: &Address, value: Coin, receipt: ValidatorReceipt) -> Result<(), AccountError> {
+        self.balance = Account::balance_sub(self.balance, value)?;
+
+        let validator = self.validators_by_address.get(validator_address)
+            .ok_or(AccountError::InvalidForRecipient)?;
+
+        let mut new_validator = validator.with_balance(validator.balance);
+        new_validator.validator_key = receipt.validator_key;
+        new_validator.reward_address = receipt.reward_address;
+        new_validator.commission = receipt.commission;
+        self.replace_validator(Arc::new(new_validator));
+
+        Ok(())
+    }
+
+    /// Returns whether a registered validator uses the given key.
+    fn is_validator_key_used(&self, validator_key: &BlsPublicKey) -> bool {
+        self.validators_by_address.values()
+            .any(|validator| &validator.validator_key == validator_key)
+    }
+
+    /// Removes a validator from the selection. Its stake remains delegated to it.
+    fn deactivate_validator(&mut self, validator_address: &Address) {
+        if let Some(validator) = self.validators_by_address.get(validator_address) {
+            if validator.active {
+                let mut new_validator = validator.with_balance(validator.balance);
+                new_validator.active = false;
+                self.replace_validator(Arc::new(new_validator));
+            }
+        }
+    }
+
+    /// Inserts or updates a validator, keeping the list of active validators sorted.
+    fn replace_validator(&mut self, validator: Arc<Validator>) {
+        if let Some(old_validator) = self.validators_by_address.insert(validator.validator_address.clone(), Arc::clone(&validator)) {
+            self.active_validators_sorted.remove(&old_validator);
+        }
+
+        if validator.active {
+            self.active_validators_sorted.insert(validator);
+        }
+    }
+
+    /// Adds `value` to the stake delegated to a validator.
+    fn add_validator_stake(&mut self, validator_address: &Address, value: Coin) -> Result<(), AccountError> {
+        let validator = self.validators_by_address.get(validator_address)
+            .ok_or(AccountError::InvalidForTarget)?;
+
+        let new_validator = Arc::new(validator.with_balance(Account::balance_add(validator.balance, value)?));
+        self.replace_validator(new_validator);
+        Ok(())
+    }
+
+    /// Removes `value` from the stake delegated to a validator.
+    fn sub_validator_stake(&mut self, validator_address: &Address, value: Coin) -> Result<(), AccountError> {
+        let validator = self.validators_by_address.get(validator_address)
+            .ok_or(AccountError::InvalidForTarget)?;
+
+        let new_validator = Arc::new(validator.with_balance(Account::balance_sub(validator.balance, value)?));
+        self.replace_validator(new_validator);
+        Ok(())
+    }
+
+    /// Adds funds to stake of `address` and delegates all of its stake to `validator_address`.
     /// XXX This is public to fill the genesis staking contract
-    pub fn stake(&mut self, staker_address: &Address, value: Coin, validator_key: BlsPublicKey, reward_address: Option<Address>) -> Result<Option<ActiveStakeReceipt>, AccountError> {
+    pub fn stake(&mut self, staker_address: &Address, value: Coin, validator_address: &Address) -> Result<Option<ActiveStakeReceipt>, AccountError> {
+        match self.validators_by_address.get(validator_address) {
+            Some(validator) if validator.active => {},
+            _ => return Err(AccountError::InvalidForRecipient),
+        }
+
         self.balance = Account::balance_add(self.balance, value)?;
 
         if let Some(active_stake) = self.active_stake_by_address.remove(staker_address) {
             let new_active_stake = Arc::new(ActiveStake {
                 staker_address: active_stake.staker_address.clone(),
                 balance: Account::balance_add(active_stake.balance, value)?,
-                validator_key,
-                reward_address
+                validator_address: validator_address.clone(),
             });
 
-            self.active_stake_sorted.remove(&active_stake);
-            self.active_stake_sorted.insert(Arc::clone(&new_active_stake));
+            // The existing stake moves along to the new validator.
+            self.sub_validator_stake(&active_stake.validator_address, active_stake.balance)?;
+            self.add_validator_stake(validator_address, new_active_stake.balance)?;
             self.active_stake_by_address.insert(staker_address.clone(), new_active_stake);
 
             Ok(Some(ActiveStakeReceipt {
-                validator_key: active_stake.validator_key.clone(),
-                reward_address: active_stake.reward_address.clone(),
+                validator_address: active_stake.validator_address.clone(),
             }))
         } else {
             let stake = Arc::new(ActiveStake {
                 staker_address: staker_address.clone(),
                 balance: value,
-                validator_key,
-                reward_address,
+                validator_address: validator_address.clone(),
             });
-            self.active_stake_sorted.insert(Arc::clone(&stake));
+            self.add_validator_stake(validator_address, value)?;
             self.active_stake_by_address.insert(staker_address.clone(), stake);
 
             Ok(None)
@@ -188,29 +454,26 @@ impl StakingContract {
     fn revert_stake(&mut self, staker_address: &Address, value: Coin, receipt: Option<ActiveStakeReceipt>) -> Result<(), AccountError> {
         self.balance = Account::balance_sub(self.balance, value)?;
 
-        let active_stake = self.active_stake_by_address.get(&staker_address)
+        let active_stake = self.active_stake_by_address.remove(staker_address)
             .ok_or(AccountError::InvalidForRecipient)?;
 
+        self.sub_validator_stake(&active_stake.validator_address, active_stake.balance)?;
+
         if active_stake.balance > value {
             let receipt = receipt.ok_or(AccountError::InvalidReceipt)?;
             let new_active_stake = Arc::new(ActiveStake {
                 staker_address: active_stake.staker_address.clone(),
                 balance: Account::balance_sub(active_stake.balance, value)?,
-                validator_key: receipt.validator_key,
-                reward_address: receipt.reward_address,
+                validator_address: receipt.validator_address,
             });
 
-            self.active_stake_sorted.remove(active_stake);
-            self.active_stake_sorted.insert(Arc::clone(&new_active_stake));
+            self.add_validator_stake(&new_active_stake.validator_address, new_active_stake.balance)?;
             self.active_stake_by_address.insert(staker_address.clone(), new_active_stake);
         } else {
             assert_eq!(active_stake.balance, value);
             if receipt.is_some() {
                 return Err(AccountError::InvalidReceipt);
             }
-
-            self.active_stake_sorted.remove(active_stake);
-            self.active_stake_by_address.remove(staker_address);
         }
         Ok(())
     }
@@ -222,8 +485,6 @@ impl StakingContract {
         let active_stake = self.active_stake_by_address.remove(staker_address)
             .ok_or(AccountError::InvalidForSender)?;
 
-        self.active_stake_sorted.remove(&active_stake);
-
         // Check total value.
         if active_stake.balance != total_value {
             return Err(AccountError::InvalidForSender);
@@ -232,7 +493,7 @@ impl StakingContract {
         // Then deduct fee.
         let new_active_stake = Arc::new(active_stake.with_balance(Account::balance_sub(active_stake.balance, fee)?));
 
-        self.active_stake_sorted.insert(Arc::clone(&new_active_stake));
+        self.sub_validator_stake(&active_stake.validator_address, fee)?;
         self.active_stake_by_address.insert(staker_address.clone(), new_active_stake);
 
         Ok(())
@@ -245,8 +506,6 @@ impl StakingContract {
         let active_stake = self.active_stake_by_address.remove(staker_address)
             .ok_or(AccountError::InvalidForSender)?;
 
-        self.active_stake_sorted.remove(&active_stake);
-
         // Then deduct fee.
         let new_active_stake = Arc::new(active_stake.with_balance(Account::balance_add(active_stake.balance, fee)?));
 
@@ -255,7 +514,37 @@ impl StakingContract {
             return Err(AccountError::InvalidForSender);
         }
 
-        self.active_stake_sorted.insert(Arc::clone(&new_active_stake));
+        self.add_validator_stake(&active_stake.validator_address, fee)?;
+        self.active_stake_by_address.insert(staker_address.clone(), new_active_stake);
+
+        Ok(())
+    }
+
+    /// Deducts the fee of a transaction that doesn't move any stake from the sender's active stake.
+    /// The value is returned to the contract by the recipient side of the transaction.
+    fn pay_fee_sender(&mut self, staker_address: &Address, total_value: Coin, fee: Coin) -> Result<(), AccountError> {
+        self.balance = Account::balance_sub(self.balance, total_value)?;
+
+        let active_stake = self.active_stake_by_address.remove(staker_address)
+            .ok_or(AccountError::InvalidForSender)?;
+
+        let new_active_stake = Arc::new(active_stake.with_balance(Account::balance_sub(active_stake.balance, fee)?));
+
+        self.sub_validator_stake(&active_stake.validator_address, fee)?;
+        self.active_stake_by_address.insert(staker_address.clone(), new_active_stake);
+
+        Ok(())
+    }
+
+    fn revert_pay_fee_sender(&mut self, staker_address: &Address, total_value: Coin, fee: Coin) -> Result<(), AccountError> {
+        self.balance = Account::balance_add(self.balance, total_value)?;
+
+        let active_stake = self.active_stake_by_address.remove(staker_address)
+            .ok_or(AccountError::InvalidForSender)?;
+
+        let new_active_stake = Arc::new(active_stake.with_balance(Account::balance_add(active_stake.balance, fee)?));
+
+        self.add_validator_stake(&active_stake.validator_address, fee)?;
         self.active_stake_by_address.insert(staker_address.clone(), new_active_stake);
 
         Ok(())
@@ -300,25 +589,18 @@ impl StakingContract {
         let active_stake = self.active_stake_by_address.remove(staker_address)
             .ok_or(AccountError::InvalidForSender)?;
 
-        self.active_stake_sorted.remove(&active_stake);
+        self.sub_validator_stake(&active_stake.validator_address, total_value)?;
 
         if active_stake.balance > total_value {
-            let new_active_stake = Arc::new(ActiveStake {
-                staker_address: staker_address.clone(),
-                balance: Account::balance_sub(active_stake.balance, total_value)?,
-                validator_key: active_stake.validator_key.clone(),
-                reward_address: active_stake.reward_address.clone(),
-            });
+            let new_active_stake = Arc::new(active_stake.with_balance(Account::balance_sub(active_stake.balance, total_value)?));
 
-            self.active_stake_sorted.insert(Arc::clone(&new_active_stake));
             self.active_stake_by_address.insert(staker_address.clone(), new_active_stake);
 
             Ok(None)
         } else {
             assert_eq!(active_stake.balance, total_value);
             Ok(Some(ActiveStakeReceipt {
-                validator_key: active_stake.validator_key.clone(),
-                reward_address: active_stake.reward_address.clone(),
+                validator_address: active_stake.validator_address.clone(),
             }))
         }
     }
@@ -332,26 +614,19 @@ impl StakingContract {
                 return Err(AccountError::InvalidReceipt);
             }
 
-            let new_active_stake = Arc::new(ActiveStake {
-                staker_address: staker_address.clone(),
-                balance: Account::balance_add(active_stake.balance, total_value)?,
-                validator_key: active_stake.validator_key.clone(),
-                reward_address: active_stake.reward_address.clone(),
-            });
+            let new_active_stake = Arc::new(active_stake.with_balance(Account::balance_add(active_stake.balance, total_value)?));
 
-            self.active_stake_sorted.remove(&active_stake);
-            self.active_stake_sorted.insert(Arc::clone(&new_active_stake));
+            self.add_validator_stake(&new_active_stake.validator_address, total_value)?;
             self.active_stake_by_address.insert(staker_address.clone(), new_active_stake);
         } else {
             let receipt = receipt.ok_or(AccountError::InvalidReceipt)?;
             let new_active_stake = Arc::new(ActiveStake {
                 staker_address: staker_address.clone(),
                 balance: total_value,
-                validator_key: receipt.validator_key,
-                reward_address: receipt.reward_address,
+                validator_address: receipt.validator_address,
             });
 
-            self.active_stake_sorted.insert(Arc::clone(&new_active_stake));
+            self.add_validator_stake(&new_active_stake.validator_address, total_value)?;
             self.active_stake_by_address.insert(staker_address.clone(), new_active_stake);
         }
         Ok(())
@@ -450,18 +725,23 @@ impl StakingContract {
         Ok(())
     }
 
-    pub fn select_validators(&self, seed: &VrfSeed) -> Slots {
+    pub fn select_validators(&self, seed: &VrfSeed, policy: &AlbatrossPolicy) -> Slots {
         // TODO: Depending on the circumstances and parameters, it might be more efficient to store active stake in an unsorted Vec.
         // Then, we would not need to create the Vec here. But then, removal of stake is a O(n) operation.
         // Assuming that validator selection happens less frequently than stake removal, the current implementation might be ok.
-        let mut potential_validators = Vec::with_capacity(self.active_stake_sorted.len());
-        let mut weights: Vec<u64> = Vec::with_capacity(self.active_stake_sorted.len());
+        let mut potential_validators = Vec::with_capacity(self.active_validators_sorted.len());
+        let mut weights: Vec<u64> = Vec::with_capacity(self.active_validators_sorted.len());
 
-        debug!("Select validators: num_slots = {}", policy::SLOTS);
+        debug!("Select validators: num_slots = {}", policy.slots);
 
-        // NOTE: `active_stake_sorted` is sorted from highest to lowest stake. `LookupTable`
+        // NOTE: `active_validators_sorted` is sorted from highest to lowest stake. `LookupTable`
         // expects the reverse ordering.
-        for validator in self.active_stake_sorted.iter() {
+        for validator in self.active_validators_sorted.iter() {
+            // Validators without any stake delegated to them can't be selected.
+            if validator.balance == Coin::ZERO {
+                continue;
+            }
+
             potential_validators.push(Arc::clone(validator));
             weights.push(validator.balance.into());
         }
@@ -470,21 +750,89 @@ impl StakingContract {
         let lookup = AliasMethod::new(weights);
         let mut rng = seed.rng(VrfUseCase::ValidatorSelection, 0);
 
-        for _ in 0 .. policy::SLOTS {
+        for _ in 0 .. policy.slots {
             let index = lookup.sample(&mut rng);
 
-            let active_stake = &potential_validators[index];
+            let validator = &potential_validators[index];
 
             slots_builder.push(
-                active_stake.validator_key.clone(),
-                active_stake.staker_address.clone(),
-                active_stake.reward_address.clone()
+                validator.validator_key.clone(),
+                validator.validator_address.clone(),
+                validator.reward_address.clone()
             );
         }
 
         slots_builder.build()
     }
 
+    /// Takes a snapshot of the stake of the active validators, which is used to split the rewards
+    /// of the epoch selected from the current state. The previous snapshot is kept until the
+    /// rewards of the running epoch have been paid out.
+    ///
+    /// This happens whenever an epoch is finalized and has to be done once for the genesis
+    /// validators.
+    pub fn snapshot_delegations(&mut self) {
+        let mut snapshots: BTreeMap<Address, DelegationSnapshot> = self.active_validators_sorted.iter()
+            .filter(|validator| validator.balance != Coin::ZERO)
+            .map(|validator| {
+                let snapshot = DelegationSnapshot {
+                    balance: validator.balance,
+                    commission: validator.commission,
+                    delegations: Vec::new(),
+                };
+                (validator.validator_address.clone(), snapshot)
+            })
+            .collect();
+
+        for active_stake in self.active_stake_by_address.values() {
+            if active_stake.staker_address != active_stake.validator_address {
+                if let Some(snapshot) = snapshots.get_mut(&active_stake.validator_address) {
+                    snapshot.delegations.push(Arc::clone(active_stake));
+                }
+            }
+        }
+        for snapshot in snapshots.values_mut() {
+            snapshot.delegations.sort_by(|a, b| a.staker_address.cmp(&b.staker_address));
+        }
+
+        let current_epoch = mem::replace(&mut self.current_epoch_delegations, snapshots);
+        self.previous_epoch_delegations = current_epoch;
+    }
+
+    /// Splits the rewards of validators between them and their delegators, proportional to the
+    /// stake delegated to the validator after deducting its commission.
+    ///
+    /// The rewards are those of the epoch preceding the running one, so the stake is taken from
+    /// the snapshot that was used for its validator selection. Stake delegated or retired since
+    /// then doesn't change the split.
+    ///
+    /// For each pair of validator address and reward, the shares of the delegators are returned
+    /// ordered by staker address. The validator itself receives the rest of its reward, i.e. its
+    /// commission, the share of its own stake and any rounding remainder.
+    pub fn delegator_rewards(&self, validator_rewards: &[(Address, Coin)]) -> Vec<Vec<(Address, Coin)>> {
+        validator_rewards.iter()
+            .map(|(validator_address, reward)| {
+                let snapshot = match self.previous_epoch_delegations.get(validator_address) {
+                    Some(snapshot) => snapshot,
+                    None => return Vec::new(),
+                };
+
+                // Use 128 bit arithmetic, the products below don't fit into 64 bits.
+                let reward = u128::from(u64::from(*reward));
+                let commission = reward * u128::from(snapshot.commission) / u128::from(MAX_COMMISSION);
+                let distributable = reward - commission;
+                let total_stake = u128::from(u64::from(snapshot.balance));
+
+                snapshot.delegations.iter()
+                    .map(|active_stake| {
+                        let share = distributable * u128::from(u64::from(active_stake.balance)) / total_stake;
+                        (active_stake.staker_address.clone(), Coin::from_u64_unchecked(share as u64))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
     fn get_signer(transaction: &Transaction) -> Result<Address, AccountError> {
         let signature_proof: SignatureProof = Deserialize::deserialize(&mut &transaction.proof[..])?;
         Ok(signature_proof.compute_signer())
@@ -509,7 +857,9 @@ impl AccountTransactionInteraction for StakingContract {
             // For retire & unpark transactions, we need to check a valid flag in the data field.
             let ty: StakingTransactionType = Deserialize::deserialize(&mut &transaction.data[..])?;
 
-            if transaction.data.len() != ty.serialized_size() {
+            if ty == StakingTransactionType::UpdateValidator {
+                UpdateValidatorData::parse(transaction)?;
+            } else if transaction.data.len() != ty.serialized_size() {
                 return Err(AccountError::InvalidForTarget);
             }
         }
@@ -518,10 +868,26 @@ impl AccountTransactionInteraction for StakingContract {
 
     fn commit_incoming_transaction(&mut self, transaction: &Transaction, block_height: u32) -> Result<Option<Vec<u8>>, AccountError> {
         if transaction.sender != transaction.recipient {
-            // Stake transaction
-            let data = StakingTransactionData::parse(transaction)?;
-            Ok(self.stake(&transaction.sender, transaction.value, data.validator_key, data.reward_address)?
-                .map(|receipt| receipt.serialize_to_vec()))
+            match StakingTransactionData::parse(transaction)? {
+                StakingTransactionData::RegisterValidator { validator_key, reward_address, commission, .. } => {
+                    // Register validator transaction, the sender stakes with its own validator.
+                    let receipt = RegisterValidatorReceipt {
+                        previous_validator: self.register_validator(&transaction.sender, validator_key, reward_address, commission)?,
+                        stake: self.stake(&transaction.sender, transaction.value, &transaction.sender)?,
+                    };
+
+                    if receipt == RegisterValidatorReceipt::default() {
+                        Ok(None)
+                    } else {
+                        Ok(Some(receipt.serialize_to_vec()))
+                    }
+                },
+                StakingTransactionData::Stake { validator_address } => {
+                    // Stake transaction
+                    Ok(self.stake(&transaction.sender, transaction.value, &validator_address)?
+                        .map(|receipt| receipt.serialize_to_vec()))
+                },
+            }
         } else {
             let ty: StakingTransactionType = Deserialize::deserialize(&mut &transaction.data[..])?;
             // XXX Get staker address from transaction proof. This violates the model that only the
@@ -538,18 +904,35 @@ impl AccountTransactionInteraction for StakingContract {
                 StakingTransactionType::Unpark => {
                     Ok(Some(self.unpark_recipient(&staker_address, transaction.value)?.serialize_to_vec()))
                 },
+                StakingTransactionType::UpdateValidator => {
+                    let data = UpdateValidatorData::parse(transaction)?;
+                    Ok(Some(self.update_validator(&staker_address, transaction.value, data)?.serialize_to_vec()))
+                },
             }
         }
     }
 
     fn revert_incoming_transaction(&mut self, transaction: &Transaction, _block_height: u32, receipt: Option<&Vec<u8>>) -> Result<(), AccountError> {
         if transaction.sender != transaction.recipient {
-            // Stake transaction
-            let receipt = match receipt {
-                Some(v) => Some(Deserialize::deserialize_from_vec(v)?),
-                _ => None
-            };
-            self.revert_stake(&transaction.sender, transaction.value, receipt)
+            match StakingTransactionData::parse(transaction)? {
+                StakingTransactionData::RegisterValidator { .. } => {
+                    // Register validator transaction
+                    let receipt: RegisterValidatorReceipt = match receipt {
+                        Some(v) => Deserialize::deserialize_from_vec(v)?,
+                        _ => Default::default(),
+                    };
+                    self.revert_stake(&transaction.sender, transaction.value, receipt.stake)?;
+                    self.revert_register_validator(&transaction.sender, receipt.previous_validator)
+                },
+                StakingTransactionData::Stake { .. } => {
+                    // Stake transaction
+                    let receipt = match receipt {
+                        Some(v) => Some(Deserialize::deserialize_from_vec(v)?),
+                        _ => None
+                    };
+                    self.revert_stake(&transaction.sender, transaction.value, receipt)
+                },
+            }
         } else {
             let ty: StakingTransactionType = Deserialize::deserialize(&mut &transaction.data[..])?;
             let staker_address = Self::get_signer(transaction)?;
@@ -567,22 +950,22 @@ impl AccountTransactionInteraction for StakingContract {
                     let receipt = Deserialize::deserialize_from_vec(receipt.ok_or(AccountError::InvalidReceipt)?)?;
                     self.revert_unpark_recipient(&staker_address, transaction.value, receipt)
                 },
+                StakingTransactionType::UpdateValidator => {
+                    let receipt = Deserialize::deserialize_from_vec(receipt.ok_or(AccountError::InvalidReceipt)?)?;
+                    self.revert_update_validator(&staker_address, transaction.value, receipt)
+                },
             }
         }
     }
 
-    fn check_outgoing_transaction(&self, transaction: &Transaction, block_height: u32) -> Result<(), AccountError> {
+    fn check_outgoing_transaction(&self, transaction: &Transaction, _block_height: u32) -> Result<(), AccountError> {
         let staker_address = Self::get_signer(transaction)?;
         if transaction.sender != transaction.recipient {
             // Unstake transaction
             let inactive_stake = self.inactive_stake_by_address.get(&staker_address)
                 .ok_or(AccountError::InvalidForSender)?;
 
-            // Check unstake delay.
-            if block_height < policy::macro_block_after(inactive_stake.retire_time) + policy::UNSTAKING_DELAY {
-                return Err(AccountError::InvalidForSender);
-            }
-
+            // The unstaking delay depends on the policy and is checked by `check_unstaking_delay`.
             Account::balance_sufficient(inactive_stake.balance, transaction.total_value()?)
         } else {
             let ty: StakingTransactionType = Deserialize::deserialize(&mut &transaction.data[..])?;
@@ -605,6 +988,24 @@ impl AccountTransactionInteraction for StakingContract {
                     }
                     Ok(())
                 },
+                StakingTransactionType::UpdateValidator => {
+                    // Only the validator itself may update its parameters.
+                    if !self.validators_by_address.contains_key(&staker_address) {
+                        return Err(AccountError::InvalidForSender);
+                    }
+
+                    // The fee is paid from the validator's own stake, the value is only passed through.
+                    Account::balance_sufficient(active_stake.balance, transaction.fee)?;
+                    Account::balance_sufficient(self.balance, transaction.total_value()?)?;
+
+                    let data = UpdateValidatorData::parse(transaction)?;
+                    if let Some(new_key) = data.new_key {
+                        if self.is_validator_key_used(&new_key.validator_key) {
+                            return Err(AccountError::InvalidForRecipient);
+                        }
+                    }
+                    Ok(())
+                },
             }
         }
     }
@@ -630,6 +1031,10 @@ impl AccountTransactionInteraction for StakingContract {
                     self.unpark_sender(&staker_address, transaction.total_value()?, transaction.fee)?;
                     Ok(None)
                 },
+                StakingTransactionType::UpdateValidator => {
+                    self.pay_fee_sender(&staker_address, transaction.total_value()?, transaction.fee)?;
+                    Ok(None)
+                },
             }
         }
     }
@@ -659,6 +1064,9 @@ impl AccountTransactionInteraction for StakingContract {
                 StakingTransactionType::Unpark => {
                     self.revert_unpark_sender(&staker_address, transaction.total_value()?, transaction.fee)
                 },
+                StakingTransactionType::UpdateValidator => {
+                    self.revert_pay_fee_sender(&staker_address, transaction.total_value()?, transaction.fee)
+                },
             }
         }
     }
@@ -679,9 +1087,9 @@ impl AccountInherentInteraction for StakingContract {
                     return Err(AccountError::InvalidInherent);
                 }
 
-                // Address doesn't exist in contract
-                let staker_address: Address = Deserialize::deserialize(&mut &inherent.data[..])?;
-                if !self.active_stake_by_address.contains_key(&staker_address) && !self.inactive_stake_by_address.contains_key(&staker_address) {
+                // Validator doesn't exist in contract
+                let validator_address: Address = Deserialize::deserialize(&mut &inherent.data[..])?;
+                if !self.validators_by_address.contains_key(&validator_address) {
                     return Err(AccountError::InvalidInherent);
                 }
 
@@ -704,11 +1112,11 @@ impl AccountInherentInteraction for StakingContract {
 
         match &inherent.ty {
             InherentType::Slash => {
-                // Simply add staker address to parking.
-                let staker_address: Address = Deserialize::deserialize(&mut &inherent.data[..])?;
+                // Simply add validator address to parking.
+                let validator_address: Address = Deserialize::deserialize(&mut &inherent.data[..])?;
                 // TODO: The inherent might have originated from a fork proof for the previous epoch.
                 // Right now, we don't care and start the parking period in the epoch the proof has been submitted.
-                let newly_slashed = self.current_epoch_parking.insert(staker_address);
+                let newly_slashed = self.current_epoch_parking.insert(validator_address);
                 let receipt = SlashReceipt { newly_slashed };
                 Ok(Some(receipt.serialize_to_vec()))
             },
@@ -717,19 +1125,25 @@ impl AccountInherentInteraction for StakingContract {
                 let current_epoch = mem::replace(&mut self.current_epoch_parking, HashSet::new());
                 let old_epoch = mem::replace(&mut self.previous_epoch_parking, current_epoch);
 
-                // Remove all parked stakers.
+                // Remove all parked validators.
                 for address in old_epoch {
                     let balance = self.get_active_balance(&address);
-                    // We do not remove stakers from the parking list if they send a retire transaction.
+                    // We do not remove validators from the parking list if they send a retire transaction.
                     // Instead, we simply skip these here.
                     // This saves space in the receipts of retire transactions as they happen much more often
-                    // than stakers are added to the parking lists.
+                    // than validators are added to the parking lists.
                     if balance > Coin::ZERO {
                         self.retire_sender(&address, balance, block_height)?;
                         self.retire_recipient(&address, balance, block_height)?;
                     }
+
+                    // Stake delegated by others stays with the validator, but it won't be selected anymore.
+                    self.deactivate_validator(&address);
                 }
 
+                // The next validators are selected from the state after this inherent.
+                self.snapshot_delegations();
+
                 // Since finalized epochs cannot be reverted, we don't need any receipts.
                 Ok(None)
             },
@@ -741,12 +1155,12 @@ impl AccountInherentInteraction for StakingContract {
         match &inherent.ty {
             InherentType::Slash => {
                 let receipt: SlashReceipt = Deserialize::deserialize_from_vec(&receipt.ok_or(AccountError::InvalidReceipt)?)?;
-                let staker_address: Address = Deserialize::deserialize(&mut &inherent.data[..])?;
+                let validator_address: Address = Deserialize::deserialize(&mut &inherent.data[..])?;
 
                 // Only remove if it was not already slashed.
                 // I kept this in two nested if's for clarity.
                 if receipt.newly_slashed {
-                    let has_been_removed = self.current_epoch_parking.remove(&staker_address);
+                    let has_been_removed = self.current_epoch_parking.remove(&validator_address);
                     if !has_been_removed {
                         return Err(AccountError::InvalidInherent);
                     }
@@ -768,8 +1182,19 @@ impl Serialize for StakingContract {
         let mut size = 0;
         size += Serialize::serialize(&self.balance, writer)?;
 
-        size += Serialize::serialize(&(self.active_stake_sorted.len() as u32), writer)?;
-        for active_stake in self.active_stake_sorted.iter() {
+        let mut validators: Vec<&Arc<Validator>> = self.validators_by_address.values().collect();
+        validators.sort_by(|a, b| a.validator_address.cmp(&b.validator_address));
+
+        size += Serialize::serialize(&(validators.len() as u32), writer)?;
+        for validator in validators {
+            size += Serialize::serialize(validator, writer)?;
+        }
+
+        let mut active_stakes: Vec<&Arc<ActiveStake>> = self.active_stake_by_address.values().collect();
+        active_stakes.sort_by(|a, b| a.staker_address.cmp(&b.staker_address));
+
+        size += Serialize::serialize(&(active_stakes.len() as u32), writer)?;
+        for active_stake in active_stakes {
             let inactive_stake = self.inactive_stake_by_address.get(&active_stake.staker_address);
             size += Serialize::serialize(active_stake, writer)?;
             size += Serialize::serialize(&inactive_stake, writer)?;
@@ -794,6 +1219,8 @@ impl Serialize for StakingContract {
 
         size += SerializeWithLength::serialize::<u32, _>(&self.current_epoch_parking, writer)?;
         size += SerializeWithLength::serialize::<u32, _>(&self.previous_epoch_parking, writer)?;
+        size += SerializeWithLength::serialize::<u32, _>(&self.current_epoch_delegations, writer)?;
+        size += SerializeWithLength::serialize::<u32, _>(&self.previous_epoch_delegations, writer)?;
 
         Ok(size)
     }
@@ -803,7 +1230,12 @@ impl Serialize for StakingContract {
         size += Serialize::serialized_size(&self.balance);
 
         size += Serialize::serialized_size(&0u32);
-        for active_stake in self.active_stake_sorted.iter() {
+        for validator in self.validators_by_address.values() {
+            size += Serialize::serialized_size(validator);
+        }
+
+        size += Serialize::serialized_size(&0u32);
+        for active_stake in self.active_stake_by_address.values() {
             let inactive_stake = self.inactive_stake_by_address.get(&active_stake.staker_address);
             size += Serialize::serialized_size(active_stake);
             size += Serialize::serialized_size(&inactive_stake);
@@ -819,6 +1251,8 @@ impl Serialize for StakingContract {
 
         size += SerializeWithLength::serialized_size::<u32>(&self.current_epoch_parking);
         size += SerializeWithLength::serialized_size::<u32>(&self.previous_epoch_parking);
+        size += SerializeWithLength::serialized_size::<u32>(&self.current_epoch_delegations);
+        size += SerializeWithLength::serialized_size::<u32>(&self.previous_epoch_delegations);
 
         size
     }
@@ -828,16 +1262,26 @@ impl Deserialize for StakingContract {
     fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
         let balance = Deserialize::deserialize(reader)?;
 
-        let mut active_stake_sorted = BTreeSet::new();
+        let mut validators_by_address = HashMap::new();
+        let mut active_validators_sorted = BTreeSet::new();
         let mut active_stake_by_address = HashMap::new();
         let mut inactive_stake_by_address = HashMap::new();
 
+        let num_validators: u32 = Deserialize::deserialize(reader)?;
+        for _ in 0..num_validators {
+            let validator: Arc<Validator> = Deserialize::deserialize(reader)?;
+
+            if validator.active {
+                active_validators_sorted.insert(Arc::clone(&validator));
+            }
+            validators_by_address.insert(validator.validator_address.clone(), validator);
+        }
+
         let num_active_stakes: u32 = Deserialize::deserialize(reader)?;
         for _ in 0..num_active_stakes {
             let active_stake: Arc<ActiveStake> = Deserialize::deserialize(reader)?;
             let inactive_stake: Option<InactiveStake> = Deserialize::deserialize(reader)?;
 
-            active_stake_sorted.insert(Arc::clone(&active_stake));
             active_stake_by_address.insert(active_stake.staker_address.clone(), Arc::clone(&active_stake));
 
             if let Some(stake) = inactive_stake {
@@ -854,14 +1298,19 @@ impl Deserialize for StakingContract {
 
         let current_epoch_parking: HashSet<Address> = DeserializeWithLength::deserialize::<u32, _>(reader)?;
         let last_epoch_parking: HashSet<Address> = DeserializeWithLength::deserialize::<u32, _>(reader)?;
+        let current_epoch_delegations: BTreeMap<Address, DelegationSnapshot> = DeserializeWithLength::deserialize::<u32, _>(reader)?;
+        let previous_epoch_delegations: BTreeMap<Address, DelegationSnapshot> = DeserializeWithLength::deserialize::<u32, _>(reader)?;
 
         Ok(StakingContract {
             balance,
-            active_stake_sorted,
+            validators_by_address,
+            active_validators_sorted,
             active_stake_by_address,
             inactive_stake_by_address,
             current_epoch_parking,
-            previous_epoch_parking: last_epoch_parking
+            previous_epoch_parking: last_epoch_parking,
+            current_epoch_delegations,
+            previous_epoch_delegations,
         })
     }
 }
@@ -892,11 +1341,14 @@ impl Default for StakingContract {
     fn default() -> Self {
         StakingContract {
             balance: Coin::ZERO,
-            active_stake_sorted: BTreeSet::new(),
+            validators_by_address: HashMap::new(),
+            active_validators_sorted: BTreeSet::new(),
             active_stake_by_address: HashMap::new(),
             inactive_stake_by_address: HashMap::new(),
             current_epoch_parking: HashSet::new(),
             previous_epoch_parking: HashSet::new(),
+            current_epoch_delegations: BTreeMap::new(),
+            previous_epoch_delegations: BTreeMap::new(),
         }
     }
 }
@@ -904,14 +1356,11 @@ impl Default for StakingContract {
 
 #[test]
 fn it_can_de_serialize_an_active_stake_receipt() {
-    const ACTIVE_STAKE_RECEIPT: &str = "96b94e8a2fa79cb3d96bfde5ed2fa693aa6bec225e944b23c96b1c83dda67b34b62d105763bdf3cd378de9e4d8809fb00f815e309ec94126f22d77ef81fe00fa3a51a6c750349efda2133ca2f0e1b04094c4e2ce08b73c72fccedc33e127259f010303030303030303030303030303030303030303";
-    const BLS_PUBLIC_KEY: &str = "96b94e8a2fa79cb3d96bfde5ed2fa693aa6bec225e944b23c96b1c83dda67b34b62d105763bdf3cd378de9e4d8809fb00f815e309ec94126f22d77ef81fe00fa3a51a6c750349efda2133ca2f0e1b04094c4e2ce08b73c72fccedc33e127259f";
+    const ACTIVE_STAKE_RECEIPT: &str = "0303030303030303030303030303030303030303";
 
     let bytes: Vec<u8> = hex::decode(ACTIVE_STAKE_RECEIPT).unwrap();
     let asr: ActiveStakeReceipt = Deserialize::deserialize(&mut &bytes[..]).unwrap();
-    let bls_bytes: Vec<u8> = hex::decode(BLS_PUBLIC_KEY).unwrap();
-    let bls_pubkey: BlsPublicKey = Deserialize::deserialize(&mut &bls_bytes[..]).unwrap();
-    assert_eq!(asr.validator_key, bls_pubkey);
+    assert_eq!(asr.validator_address, Address::from([3u8; 20]));
 
     assert_eq!(hex::encode(asr.serialize_to_vec()), ACTIVE_STAKE_RECEIPT);
 }
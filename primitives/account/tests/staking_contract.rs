@@ -3,7 +3,7 @@ use std::convert::{TryFrom, TryInto};
 use rand::thread_rng;
 
 use beserial::{Deserialize, Serialize};
-use nimiq_account::{AccountError, AccountTransactionInteraction, AccountType, StakingContract};
+use nimiq_account::{Account, AccountError, AccountTransactionInteraction, AccountType, StakingContract};
 use nimiq_account::inherent::{AccountInherentInteraction, Inherent, InherentType};
 use nimiq_bls::bls12_381::KeyPair as BlsKeyPair;
 use nimiq_bls::bls12_381::Signature as BlsSignature;
@@ -11,24 +11,28 @@ use nimiq_bls::SecureGenerate;
 use nimiq_keys::{Address, KeyPair, PrivateKey};
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::networks::NetworkId;
+use nimiq_primitives::policy::AlbatrossPolicy;
 use nimiq_primitives::slot::{SlotCollection, SlotIndex};
 use nimiq_transaction::{SignatureProof, Transaction, TransactionError};
 use nimiq_transaction::account::AccountTransactionVerification;
-use nimiq_transaction::account::staking_contract::{StakingTransactionData, StakingTransactionType};
+use nimiq_transaction::account::staking_contract::{StakingTransactionData, StakingTransactionType, UpdateValidatorData, ValidatorKeyUpdate};
 
-const CONTRACT_1: &str = "000000000000000000000000000000000000000000000000";
-const CONTRACT_2: &str = "0000000023c34600000000020202020202020202020202020202020202020202000000001ad27480a2f7d485efe6fabad3d780d1ea5ad690bd027a5328f44b612cad1f33347c8df5bde90a340c30877a21861e2173f6cfda0715d35ac2941437bf7e73d7e48fcf6e1901249134532ad1826ad1e396caed2d4d1d11e82d79f93946b21800a00971f000005e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e0000000008f0d180a9edd1613b714ec6107f4ffd532e52727c4f3a2897b3000e9ebccf076e8ffdf4b424f7e798d31dc67bbf9b3776096f101740b3f992ba8a5d0e20860f8d3466b7b58fb6b918eebb3c014bf6bb1cbdcb045c184d673c3db6435f454a1c530b9dfc012a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a00000000000000000000000000";
+const CONTRACT_1: &str = "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000";
+const CONTRACT_2: &str = "0000000023c34600000000020202020202020202020202020202020202020202000000001ad27480a2f7d485efe6fabad3d780d1ea5ad690bd027a5328f44b612cad1f33347c8df5bde90a340c30877a21861e2173f6cfda0715d35ac2941437bf7e73d7e48fcf6e1901249134532ad1826ad1e396caed2d4d1d11e82d79f93946b21800a00971f0000000015e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e0000000008f0d180a9edd1613b714ec6107f4ffd532e52727c4f3a2897b3000e9ebccf076e8ffdf4b424f7e798d31dc67bbf9b3776096f101740b3f992ba8a5d0e20860f8d3466b7b58fb6b918eebb3c014bf6bb1cbdcb045c184d673c3db6435f454a1c530b9dfc012a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a01f4010000000302020202020202020202020202020202020202020000000011e1a30002020202020202020202020202020202020202020003030303030303030303030303030303030303030000000008f0d1800202020202020202020202020202020202020202005e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e0000000008f0d1805e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e000000000000000000000000000000000000000000";
 
 #[test]
 fn it_can_de_serialize_a_staking_contract() {
     let bytes_1: Vec<u8> = hex::decode(CONTRACT_1).unwrap();
     let contract_1: StakingContract = Deserialize::deserialize(&mut &bytes_1[..]).unwrap();
     assert_eq!(contract_1.balance, 0.try_into().unwrap());
+    assert_eq!(contract_1.validators_by_address.len(), 0);
+    assert_eq!(contract_1.active_validators_sorted.len(), 0);
     assert_eq!(contract_1.active_stake_by_address.len(), 0);
-    assert_eq!(contract_1.active_stake_sorted.len(), 0);
     assert_eq!(contract_1.inactive_stake_by_address.len(), 0);
     assert_eq!(contract_1.current_epoch_parking.len(), 0);
     assert_eq!(contract_1.previous_epoch_parking.len(), 0);
+    assert_eq!(contract_1.current_epoch_delegations.len(), 0);
+    assert_eq!(contract_1.previous_epoch_delegations.len(), 0);
     let mut bytes_1_out = Vec::<u8>::with_capacity(contract_1.serialized_size());
     let size_1_out = contract_1.serialize(&mut bytes_1_out).unwrap();
     assert_eq!(size_1_out, contract_1.serialized_size());
@@ -37,13 +41,19 @@ fn it_can_de_serialize_a_staking_contract() {
     let bytes_2: Vec<u8> = hex::decode(CONTRACT_2).unwrap();
     let contract_2: StakingContract = Deserialize::deserialize(&mut &bytes_2[..]).unwrap();
     assert_eq!(contract_2.balance, 600_000_000.try_into().unwrap());
-    assert_eq!(contract_2.get_balance(&Address::from([2u8; 20])), Coin::from_u64_unchecked(450_000_000u64));
+    assert_eq!(contract_2.get_balance(&Address::from([2u8; 20])), Coin::from_u64_unchecked(300_000_000u64));
+    assert_eq!(contract_2.get_balance(&Address::from([3u8; 20])), Coin::from_u64_unchecked(150_000_000u64));
     assert_eq!(contract_2.get_balance(&Address::from([0x5eu8; 20])), Coin::from_u64_unchecked(150_000_000u64));
-    assert_eq!(contract_2.active_stake_by_address.len(), 2);
-    assert_eq!(contract_2.active_stake_sorted.len(), 2);
+    assert_eq!(contract_2.validators_by_address.len(), 2);
+    assert_eq!(contract_2.active_validators_sorted.len(), 2);
+    assert_eq!(contract_2.get_validator(&Address::from([2u8; 20])).unwrap().balance, Coin::from_u64_unchecked(450_000_000u64));
+    assert_eq!(contract_2.get_validator(&Address::from([0x5eu8; 20])).unwrap().commission, 500);
+    assert_eq!(contract_2.active_stake_by_address.len(), 3);
     assert_eq!(contract_2.inactive_stake_by_address.len(), 0);
     assert_eq!(contract_2.current_epoch_parking.len(), 0);
     assert_eq!(contract_2.previous_epoch_parking.len(), 0);
+    assert_eq!(contract_2.current_epoch_delegations.len(), 0);
+    assert_eq!(contract_2.previous_epoch_delegations.len(), 0);
     let mut bytes_2_out = Vec::<u8>::with_capacity(contract_2.serialized_size());
     let size_2_out = contract_2.serialize(&mut bytes_2_out).unwrap();
     assert_eq!(size_2_out, contract_2.serialized_size());
@@ -75,97 +85,132 @@ fn it_can_verify_staking_transaction() {
 
     let proof_of_knowledge = bls_pair.sign(&bls_pair.public).compress();
 
-    let mut data = StakingTransactionData {
+    let make_data = |commission: u16, proof_of_knowledge| StakingTransactionData::RegisterValidator {
         validator_key: bls_pair.public.compress(),
         reward_address: Some(Address::from([3u8; 20])),
+        commission,
         proof_of_knowledge,
     };
-    tx.data = data.serialize_to_vec();
+    tx.data = make_data(500, proof_of_knowledge.clone()).serialize_to_vec();
 
     // Valid
     assert_eq!(AccountType::verify_incoming_transaction(&tx), Ok(()));
 
+    // Valid delegation
+    let mut tx_stake = tx.clone();
+    tx_stake.data = StakingTransactionData::Stake { validator_address: Address::from([3u8; 20]) }.serialize_to_vec();
+    assert_eq!(AccountType::verify_incoming_transaction(&tx_stake), Ok(()));
+
     // Below minimum stake
     tx.value = 123.try_into().unwrap();
     assert_eq!(AccountType::verify_incoming_transaction(&tx), Err(TransactionError::InvalidForRecipient));
+    tx.value = 150_000_000.try_into().unwrap();
+
+    // Commission above 100%
+    tx.data = make_data(10_001, proof_of_knowledge).serialize_to_vec();
+    assert_eq!(AccountType::verify_incoming_transaction(&tx), Err(TransactionError::InvalidData));
 
     // Invalid proof of knowledge
     let other_pair = BlsKeyPair::generate(&mut thread_rng());
     let invalid_pok = other_pair.sign(&bls_pair.public);
-    data.proof_of_knowledge = invalid_pok.compress();
-    tx.data = data.serialize_to_vec();
+    tx.data = make_data(500, invalid_pok.compress()).serialize_to_vec();
     assert_eq!(AccountType::verify_incoming_transaction(&tx), Err(TransactionError::InvalidData));
 }
 
 #[test]
 fn it_can_apply_staking_transaction() {
     let mut contract = make_empty_contract();
+    let validator_address = Address::from([2u8; 20]);
 
     // Default transaction data
     let bls_pair = BlsKeyPair::generate(&mut thread_rng());
     let proof_of_knowledge = bls_pair.sign(&bls_pair.public).compress();
-    let stake_data = StakingTransactionData {
+    let register_data = StakingTransactionData::RegisterValidator {
         validator_key: bls_pair.public.compress(),
         reward_address: None,
+        commission: 1_000,
         proof_of_knowledge,
     };
+    let stake_data = StakingTransactionData::Stake {
+        validator_address: validator_address.clone(),
+    };
 
-    // Default stake
+    // Delegate to unknown validator
+    let mut tx_0 = make_incoming_transaction();
+    tx_0.data = stake_data.serialize_to_vec();
+    assert_eq!(StakingContract::check_incoming_transaction(&tx_0, 1), Ok(()));
+    assert_eq!(contract.commit_incoming_transaction(&tx_0, 1), Err(AccountError::InvalidForRecipient));
+
+    // Register validator
     let mut tx_1 = make_incoming_transaction();
-    tx_1.data = stake_data.serialize_to_vec();
+    tx_1.data = register_data.serialize_to_vec();
     assert_eq!(StakingContract::check_incoming_transaction(&tx_1, 2), Ok(()));
     assert_eq!(contract.commit_incoming_transaction(&tx_1, 2), Ok(None));
+    assert_eq!(contract.validators_by_address.len(), 1);
+    assert_eq!(contract.active_validators_sorted.len(), 1);
     assert_eq!(contract.active_stake_by_address.len(), 1);
-    assert_eq!(contract.get_balance(&Address::from([2u8; 20])), Coin::from_u64_unchecked(150_000_000u64));
+    assert_eq!(contract.get_balance(&validator_address), Coin::from_u64_unchecked(150_000_000u64));
+    assert_eq!(contract.get_validator(&validator_address).unwrap().balance, Coin::from_u64_unchecked(150_000_000u64));
+    assert_eq!(contract.get_validator(&validator_address).unwrap().commission, 1_000);
     assert_eq!(contract.balance, 150_000_000.try_into().unwrap());
 
-    // Same stake again
+    // Register the same validator again
+    assert_eq!(contract.commit_incoming_transaction(&tx_1, 3), Err(AccountError::InvalidForRecipient));
+
+    // Validator stakes again
     let mut tx_2 = make_incoming_transaction();
     tx_2.data = stake_data.serialize_to_vec();
     assert_eq!(StakingContract::check_incoming_transaction(&tx_2, 3), Ok(()));
     let receipt_2 = contract.commit_incoming_transaction(&tx_2, 3).unwrap().unwrap();
     assert_eq!(contract.active_stake_by_address.len(), 1);
-    assert_eq!(contract.get_balance(&Address::from([2u8; 20])), Coin::from_u64_unchecked(300_000_000u64));
+    assert_eq!(contract.get_balance(&validator_address), Coin::from_u64_unchecked(300_000_000u64));
+    assert_eq!(contract.get_validator(&validator_address).unwrap().balance, Coin::from_u64_unchecked(300_000_000u64));
     assert_eq!(contract.balance, 300_000_000.try_into().unwrap());
 
-    // Stake again, changing validator key
+    // Delegate from new account
     let mut tx_3 = make_incoming_transaction();
-    let bls_other = BlsKeyPair::generate(&mut thread_rng());
-    let pok_other = bls_other.sign(&bls_other.public);
-    tx_3.data = StakingTransactionData {
-        validator_key: bls_other.public.compress(),
-        reward_address: None,
-        proof_of_knowledge: pok_other.compress(),
-    }.serialize_to_vec();
+    tx_3.sender = Address::from([94u8; 20]);
+    tx_3.data = stake_data.serialize_to_vec();
     assert_eq!(StakingContract::check_incoming_transaction(&tx_3, 4), Ok(()));
-    let receipt_3 = contract.commit_incoming_transaction(&tx_3, 4).unwrap().unwrap();
-    assert_eq!(contract.active_stake_by_address.len(), 1);
-    assert_eq!(contract.get_balance(&Address::from([2u8; 20])), Coin::from_u64_unchecked(450_000_000u64));
+    assert_eq!(contract.commit_incoming_transaction(&tx_3, 4), Ok(None));
+    assert_eq!(contract.active_stake_by_address.len(), 2);
+    assert_eq!(contract.get_balance(&validator_address), Coin::from_u64_unchecked(300_000_000u64));
+    assert_eq!(contract.get_balance(&Address::from([94u8; 20])), Coin::from_u64_unchecked(150_000_000u64));
+    assert_eq!(contract.get_validator(&validator_address).unwrap().balance, Coin::from_u64_unchecked(450_000_000u64));
     assert_eq!(contract.balance, 450_000_000.try_into().unwrap());
 
-    // Stake on new account with reward address
+    // Register another validator and move the delegation over to it
     let mut tx_4 = make_incoming_transaction();
-    tx_4.sender = Address::from([94u8; 20]);
-    let mut stake_data_4 = stake_data.clone();
-    stake_data_4.reward_address = Some(Address::from([42u8; 20]));
-    tx_4.data = stake_data_4.serialize_to_vec();
-    assert_eq!(StakingContract::check_incoming_transaction(&tx_4, 5), Ok(()));
+    tx_4.sender = Address::from([95u8; 20]);
+    tx_4.data = register_data.serialize_to_vec();
     assert_eq!(contract.commit_incoming_transaction(&tx_4, 5), Ok(None));
-    assert_eq!(contract.active_stake_by_address.len(), 2);
-    assert_eq!(contract.get_balance(&Address::from([2u8; 20])), Coin::from_u64_unchecked(450_000_000u64));
-    assert_eq!(contract.get_balance(&Address::from([94u8; 20])), Coin::from_u64_unchecked(150_000_000u64));
-    assert_eq!(contract.balance, 600_000_000.try_into().unwrap());
+    let mut tx_5 = make_incoming_transaction();
+    tx_5.sender = Address::from([94u8; 20]);
+    tx_5.data = StakingTransactionData::Stake { validator_address: Address::from([95u8; 20]) }.serialize_to_vec();
+    let receipt_5 = contract.commit_incoming_transaction(&tx_5, 5).unwrap().unwrap();
+    assert_eq!(contract.get_balance(&Address::from([94u8; 20])), Coin::from_u64_unchecked(300_000_000u64));
+    assert_eq!(contract.get_validator(&validator_address).unwrap().balance, Coin::from_u64_unchecked(300_000_000u64));
+    assert_eq!(contract.get_validator(&Address::from([95u8; 20])).unwrap().balance, Coin::from_u64_unchecked(450_000_000u64));
+    assert_eq!(contract.balance, 750_000_000.try_into().unwrap());
 
     // Revert everything
+    assert_eq!(contract.revert_incoming_transaction(&tx_5, 5, Some(&receipt_5)), Ok(()));
+    assert_eq!(contract.get_validator(&validator_address).unwrap().balance, Coin::from_u64_unchecked(450_000_000u64));
+    assert_eq!(contract.get_validator(&Address::from([95u8; 20])).unwrap().balance, Coin::from_u64_unchecked(150_000_000u64));
     assert_eq!(contract.revert_incoming_transaction(&tx_4, 5, None), Ok(()));
+    assert_eq!(contract.validators_by_address.len(), 1);
+    assert_eq!(contract.active_validators_sorted.len(), 1);
     assert_eq!(contract.balance, 450_000_000.try_into().unwrap());
-    assert_eq!(contract.revert_incoming_transaction(&tx_3, 4, Some(&receipt_3)), Ok(()));
+    assert_eq!(contract.revert_incoming_transaction(&tx_3, 4, None), Ok(()));
     assert_eq!(contract.balance, 300_000_000.try_into().unwrap());
     assert_eq!(contract.revert_incoming_transaction(&tx_2, 3, Some(&receipt_2)), Ok(()));
     assert_eq!(contract.active_stake_by_address.len(), 1);
+    assert_eq!(contract.get_validator(&validator_address).unwrap().balance, Coin::from_u64_unchecked(150_000_000u64));
     assert_eq!(contract.balance, 150_000_000.try_into().unwrap());
     assert_eq!(contract.revert_incoming_transaction(&tx_1, 2, None), Ok(()));
     assert_eq!(contract.active_stake_by_address.len(), 0);
+    assert_eq!(contract.validators_by_address.len(), 0);
+    assert_eq!(contract.active_validators_sorted.len(), 0);
     assert_eq!(contract.balance, 0.try_into().unwrap());
 }
 
@@ -223,7 +268,7 @@ fn it_can_apply_retiring_transaction() {
     assert_eq!(contract.commit_incoming_transaction(&tx_1, 2).unwrap(), None);
 
     assert_eq!(contract.active_stake_by_address.len(), 1);
-    assert_eq!(contract.active_stake_sorted.len(), 1);
+    assert_eq!(contract.get_validator(&Address::from(&key_pair.public)).unwrap().balance, Coin::from_u64_unchecked(150_000_000));
     assert_eq!(contract.inactive_stake_by_address.len(), 1);
     assert_eq!(contract.get_balance(&Address::from(&key_pair.public)), 299_999_766.try_into().unwrap());
     assert_eq!(contract.balance, 299_999_766.try_into().unwrap());
@@ -257,7 +302,7 @@ fn it_can_apply_retiring_transaction() {
     let receipt_incoming_2 = contract.commit_incoming_transaction(&tx_3, 3).unwrap().unwrap();
 
     assert_eq!(contract.active_stake_by_address.len(), 0);
-    assert_eq!(contract.active_stake_sorted.len(), 0);
+    assert_eq!(contract.get_validator(&Address::from(&key_pair.public)).unwrap().balance, Coin::from_u64_unchecked(0));
     assert_eq!(contract.inactive_stake_by_address.len(), 1);
     assert_eq!(contract.get_balance(&Address::from(&key_pair.public)), 299_999_298.try_into().unwrap());
     assert_eq!(contract.balance, 299_999_298.try_into().unwrap());
@@ -275,7 +320,7 @@ fn it_can_apply_retiring_transaction() {
     assert_eq!(contract.revert_outgoing_transaction(&tx_1, 2, None), Ok(()));
 
     assert_eq!(contract.active_stake_by_address.len(), 1);
-    assert_eq!(contract.active_stake_sorted.len(), 1);
+    assert_eq!(contract.get_validator(&Address::from(&key_pair.public)).unwrap().balance, Coin::from_u64_unchecked(300_000_000));
     assert_eq!(contract.inactive_stake_by_address.len(), 0);
     assert_eq!(contract.balance, 300_000_000.try_into().unwrap());
     assert_eq!(contract.get_balance(&Address::from(&key_pair.public)), 300_000_000.try_into().unwrap());
@@ -336,8 +381,10 @@ fn it_can_apply_unstaking_transaction() {
     assert_eq!(contract.check_outgoing_transaction(&tx_2, 40003), Err(funds_error.clone()));
     assert_eq!(contract.commit_outgoing_transaction(&tx_2, 40003), Err(funds_error.clone()));
 
-    // Block 40003: Unstake quarter
+    // Block 40003: Unstake quarter, after the unstaking delay of the policy
+    let policy = AlbatrossPolicy::default();
     let tx_3 = make_unstake(75_000_000 - 234);
+    assert_eq!(contract.check_unstaking_delay(&tx_3, 40003, &policy), Ok(()));
     assert_eq!(contract.check_outgoing_transaction(&tx_3, 40003), Ok(()));
     assert_eq!(contract.commit_outgoing_transaction(&tx_3, 40003), Ok(None));
     assert_balance(&contract, 225_000_000);
@@ -356,20 +403,21 @@ fn it_can_apply_unstaking_transaction() {
     let receipt_5_outgoing = contract.commit_outgoing_transaction(&tx_5, 40004).unwrap().unwrap();
     let receipt_5_incoming = contract.commit_incoming_transaction(&tx_5, 40004).unwrap().unwrap();
 
-    // Try to replay reverted unstaking, should fail
+    // Try to replay reverted unstaking, should fail because of the unstaking delay
     let tx_6 = tx_3.clone();
-    assert_eq!(contract.check_outgoing_transaction(&tx_6, 40005), Err(AccountError::InvalidForSender));
-    assert_eq!(contract.commit_outgoing_transaction(&tx_6, 40005), Err(AccountError::InvalidForSender));
+    assert_eq!(contract.check_unstaking_delay(&tx_6, 40005, &policy), Err(AccountError::InvalidForSender));
+    assert_eq!(Account::Staking(contract.clone()).check_outgoing_policy(&tx_6, 40005, &policy), Err(AccountError::InvalidForSender));
 
     // Unstake rest
     let tx_7 = make_unstake(225_000_000 - 234);
+    assert_eq!(contract.check_unstaking_delay(&tx_7, 100000, &policy), Ok(()));
     assert_eq!(contract.check_outgoing_transaction(&tx_7, 100000), Ok(()));
     let receipt_7 = contract.commit_outgoing_transaction(&tx_7, 100000).unwrap().unwrap();
     assert_eq!(contract.balance, 0.try_into().unwrap());
 
     // Contract is empty at this point
     assert_eq!(contract.active_stake_by_address.len(), 0);
-    assert_eq!(contract.active_stake_sorted.len(), 0);
+    assert_eq!(contract.get_validator(&Address::from(&key_pair.public)).unwrap().balance, Coin::from_u64_unchecked(0));
     assert_eq!(contract.inactive_stake_by_address.len(), 0);
     assert_eq!(contract.balance, 0.try_into().unwrap());
 
@@ -388,7 +436,7 @@ fn it_can_apply_unstaking_transaction() {
 
     // Initial contract state
     assert_eq!(contract.active_stake_by_address.len(), 1);
-    assert_eq!(contract.active_stake_sorted.len(), 1);
+    assert_eq!(contract.get_validator(&Address::from(&key_pair.public)).unwrap().balance, Coin::from_u64_unchecked(300_000_000));
     assert_eq!(contract.inactive_stake_by_address.len(), 0);
     assert_balance(&contract, 300_000_000);
 }
@@ -555,6 +603,8 @@ fn it_can_apply_slash_and_finalize_epoch_inherent() {
     assert_eq!(contract.current_epoch_parking.len(), 0);
     assert_eq!(contract.previous_epoch_parking.len(), 1);
     assert!(contract.previous_epoch_parking.contains(&address));
+    assert!(!contract.get_validator(&address).unwrap().active);
+    assert_eq!(contract.active_validators_sorted.len(), 0);
 
     // Another finalize
     assert_eq!(contract.check_inherent(&finalize, 0), Ok(()));
@@ -593,7 +643,7 @@ fn it_can_apply_slashes_after_retire() {
     assert_eq!(contract.commit_incoming_transaction(&tx_1, 2).unwrap(), None);
 
     assert_eq!(contract.active_stake_by_address.len(), 0);
-    assert_eq!(contract.active_stake_sorted.len(), 0);
+    assert_eq!(contract.get_validator(&Address::from(&key_pair.public)).unwrap().balance, Coin::from_u64_unchecked(0));
     assert_eq!(contract.inactive_stake_by_address.len(), 1);
     assert_eq!(contract.get_balance(&Address::from(&key_pair.public)), 299_999_766.try_into().unwrap());
     assert_eq!(contract.balance, 299_999_766.try_into().unwrap());
@@ -974,7 +1024,7 @@ fn it_can_revert_slash_inherent() {
 
 #[test]
 fn it_can_build_a_validator_set() {
-    // Helper function for building a staking transaction.
+    // Helper function for building a validator registration transaction.
     // `order` sets the first byte of the address as a marker.
     // It also controls the secondary index when building the potential validator list.
     let stake = |amount: u64, order: u16| {
@@ -985,9 +1035,10 @@ fn it_can_build_a_validator_set() {
         let mut address_buf = [0u8; 20];
         address_buf[0] = (order & 0xFF) as u8;
         tx.sender = Address::from(address_buf);
-        tx.data = StakingTransactionData {
+        tx.data = StakingTransactionData::RegisterValidator {
             validator_key: bls_pair.public.compress(),
             reward_address: None,
+            commission: 0,
             proof_of_knowledge: bls_pair.sign(&bls_pair.public).compress(),
         }.serialize_to_vec();
         tx
@@ -997,14 +1048,14 @@ fn it_can_build_a_validator_set() {
     let seed_vec = hex::decode("ac22bbbf6a315f9e9eb23eca98918a0a5a35e31219b8c3c8b3bd5b71bc7a33371aad8588007e89e95ffe63bd9dce4c27").unwrap();
     let seed = BlsSignature::deserialize_from_vec(&seed_vec).unwrap();
 
-    // Fill contract with same stakes
+    // Fill contract with stakes below the minimum stake, which is checked outside of the contract
     let mut contract = make_empty_contract();
     contract.commit_incoming_transaction(&stake(10_000,  0xFE), 2).unwrap();
     contract.commit_incoming_transaction(&stake(130_000, 0x00), 2).unwrap();
     contract.commit_incoming_transaction(&stake(12,      0xFF), 2).unwrap();
 
     // Test potential validator selection by stake
-    let slots = contract.select_validators(&seed.compress().into());
+    let slots = contract.select_validators(&seed.compress().into(), &AlbatrossPolicy::default());
     assert_eq!(slots.stake_slots.len(), 2);
     assert_eq!(slots.get(SlotIndex::Slot(0)).unwrap().staker_address().as_bytes()[0], 0x00);
 
@@ -1015,13 +1066,320 @@ fn it_can_build_a_validator_set() {
     contract.commit_incoming_transaction(&stake(100_000_000, 0x04), 2).unwrap();
 
     // Test potential validator selection by secondary index
-    let slots = contract.select_validators(&seed.compress().into());
+    let slots = contract.select_validators(&seed.compress().into(), &AlbatrossPolicy::default());
     assert_eq!(slots.stake_slots.len(), 3);
     assert_eq!(slots.get(SlotIndex::Slot(0)).unwrap().staker_address().as_bytes()[0], 0x03);
 
     // TODO More tests
 }
 
+#[test]
+fn it_rejects_stake_below_the_minimum_stake() {
+    let contract = Account::Staking(make_empty_contract());
+    let policy = AlbatrossPolicy { min_stake: 150_000_001, ..Default::default() };
+
+    let bls_pair = bls_key_pair();
+    let mut tx = make_incoming_transaction();
+    tx.data = StakingTransactionData::RegisterValidator {
+        validator_key: bls_pair.public.compress(),
+        reward_address: None,
+        commission: 0,
+        proof_of_knowledge: bls_pair.sign(&bls_pair.public).compress(),
+    }.serialize_to_vec();
+    assert_eq!(StakingContract::check_min_stake(&tx, &policy), Err(AccountError::InvalidForRecipient));
+    assert_eq!(contract.check_incoming_policy(&tx, &policy), Err(AccountError::InvalidForRecipient));
+
+    tx.value = Coin::from_u64_unchecked(150_000_001);
+    assert_eq!(StakingContract::check_min_stake(&tx, &policy), Ok(()));
+    assert_eq!(contract.check_incoming_policy(&tx, &policy), Ok(()));
+
+    // Retire and unpark transactions don't stake anything
+    tx.value = Coin::from_u64_unchecked(1);
+    tx.recipient = tx.sender.clone();
+    assert_eq!(StakingContract::check_min_stake(&tx, &policy), Ok(()));
+}
+
+#[test]
+fn it_can_reactivate_a_validator() {
+    let bls_pair = bls_key_pair();
+    let key_pair = ed25519_key_pair();
+    let mut contract = make_sample_contract(&key_pair, &bls_pair);
+    let address = Address::from(&key_pair.public);
+
+    // Another staker delegates to the validator.
+    let mut stake = make_incoming_transaction();
+    stake.data = StakingTransactionData::Stake { validator_address: address.clone() }.serialize_to_vec();
+    assert_eq!(contract.commit_incoming_transaction(&stake, 2), Ok(None));
+
+    // Park the validator for two epochs to deactivate it.
+    let slash = Inherent {
+        ty: InherentType::Slash,
+        target: Default::default(),
+        value: Coin::ZERO,
+        data: address.serialize_to_vec(),
+    };
+    let finalize = Inherent {
+        ty: InherentType::FinalizeEpoch,
+        target: Default::default(),
+        value: Coin::ZERO,
+        data: vec![]
+    };
+    assert_eq!(contract.commit_inherent(&slash, 3), Ok(Some(vec![1])));
+    assert_eq!(contract.commit_inherent(&finalize, 3), Ok(None));
+    assert_eq!(contract.commit_inherent(&finalize, 4), Ok(None));
+    assert!(!contract.get_validator(&address).unwrap().active);
+    assert_eq!(contract.active_validators_sorted.len(), 0);
+
+    // The delegated stake stays with the validator, but it can't receive new stake.
+    assert_eq!(contract.get_active_balance(&Address::from([2u8; 20])), Coin::from_u64_unchecked(150_000_000));
+    assert_eq!(contract.get_validator(&address).unwrap().balance, Coin::from_u64_unchecked(150_000_000));
+    assert_eq!(contract.commit_incoming_transaction(&stake, 5), Err(AccountError::InvalidForRecipient));
+
+    // Register again with a new key.
+    let other_pair = BlsKeyPair::generate(&mut thread_rng());
+    let mut register = make_incoming_transaction();
+    register.sender = address.clone();
+    register.data = StakingTransactionData::RegisterValidator {
+        validator_key: other_pair.public.compress(),
+        reward_address: None,
+        commission: 200,
+        proof_of_knowledge: other_pair.sign(&other_pair.public).compress(),
+    }.serialize_to_vec();
+    let receipt = contract.commit_incoming_transaction(&register, 5).unwrap();
+    assert!(receipt.is_some());
+    let validator = contract.get_validator(&address).unwrap().clone();
+    assert!(validator.active);
+    assert_eq!(validator.validator_key, other_pair.public.compress());
+    assert_eq!(validator.commission, 200);
+    assert_eq!(validator.balance, Coin::from_u64_unchecked(300_000_000));
+    assert_eq!(contract.active_validators_sorted.len(), 1);
+
+    // Revert the registration.
+    assert_eq!(contract.revert_incoming_transaction(&register, 5, receipt.as_ref()), Ok(()));
+    let validator = contract.get_validator(&address).unwrap().clone();
+    assert!(!validator.active);
+    assert_eq!(validator.validator_key, bls_pair.public.compress());
+    assert_eq!(validator.commission, 0);
+    assert_eq!(validator.balance, Coin::from_u64_unchecked(150_000_000));
+    assert_eq!(contract.active_validators_sorted.len(), 0);
+}
+
+#[test]
+fn it_can_split_rewards_with_delegators() {
+    let bls_pair = bls_key_pair();
+    let validator_address = Address::from([1u8; 20]);
+    let mut contract = make_empty_contract();
+
+    // Validator with 10% commission and 100 NIM of its own stake.
+    contract.register_validator(&validator_address, bls_pair.public.compress(), Some(Address::from([9u8; 20])), 1_000).unwrap();
+    contract.stake(&validator_address, Coin::from_u64_unchecked(100_000), &validator_address).unwrap();
+
+    // Two delegators with 200 NIM and 100 NIM.
+    contract.stake(&Address::from([3u8; 20]), Coin::from_u64_unchecked(200_000), &validator_address).unwrap();
+    contract.stake(&Address::from([2u8; 20]), Coin::from_u64_unchecked(100_000), &validator_address).unwrap();
+
+    // The validators are selected when the epoch is finalized and rewarded when the next one is.
+    finalize_epoch(&mut contract);
+    finalize_epoch(&mut contract);
+
+    let rewards = contract.delegator_rewards(&[
+        (validator_address.clone(), Coin::from_u64_unchecked(1_000_003)),
+        (Address::from([7u8; 20]), Coin::from_u64_unchecked(1_000)),
+    ]);
+    assert_eq!(rewards.len(), 2);
+
+    // 900_003 Luna are split after commission, ordered by staker address.
+    assert_eq!(rewards[0], vec![
+        (Address::from([2u8; 20]), Coin::from_u64_unchecked(225_000)),
+        (Address::from([3u8; 20]), Coin::from_u64_unchecked(450_001)),
+    ]);
+
+    // Unknown validators have no delegators.
+    assert!(rewards[1].is_empty());
+
+    // The whole stake counts towards the validator's weight in the selection.
+    let seed_vec = hex::decode("ac22bbbf6a315f9e9eb23eca98918a0a5a35e31219b8c3c8b3bd5b71bc7a33371aad8588007e89e95ffe63bd9dce4c27").unwrap();
+    let seed = BlsSignature::deserialize_from_vec(&seed_vec).unwrap();
+    let slots = contract.select_validators(&seed.compress().into(), &AlbatrossPolicy::default());
+    assert_eq!(slots.stake_slots.len(), 1);
+    assert_eq!(slots.get(SlotIndex::Slot(0)).unwrap().staker_address(), &validator_address);
+    assert_eq!(slots.get(SlotIndex::Slot(0)).unwrap().reward_address(), &Address::from([9u8; 20]));
+}
+
+#[test]
+fn it_does_not_reward_delegations_made_during_the_epoch() {
+    let bls_pair = bls_key_pair();
+    let validator_address = Address::from([1u8; 20]);
+    let delegator_1 = Address::from([2u8; 20]);
+    let delegator_2 = Address::from([3u8; 20]);
+    let mut contract = make_empty_contract();
+
+    contract.register_validator(&validator_address, bls_pair.public.compress(), None, 0).unwrap();
+    contract.stake(&validator_address, Coin::from_u64_unchecked(100_000), &validator_address).unwrap();
+    contract.stake(&delegator_1, Coin::from_u64_unchecked(100_000), &validator_address).unwrap();
+
+    // The validators of epoch 1 are selected.
+    finalize_epoch(&mut contract);
+
+    // During epoch 1, a second delegator joins and the first one delegates more.
+    contract.stake(&delegator_2, Coin::from_u64_unchecked(200_000), &validator_address).unwrap();
+    contract.stake(&delegator_1, Coin::from_u64_unchecked(100_000), &validator_address).unwrap();
+    assert_eq!(contract.get_validator(&validator_address).unwrap().balance, Coin::from_u64_unchecked(500_000));
+
+    // Epoch 1 is rewarded when epoch 2 is finalized, by the stake at its selection.
+    finalize_epoch(&mut contract);
+    let rewards = contract.delegator_rewards(&[(validator_address.clone(), Coin::from_u64_unchecked(1_000))]);
+    assert_eq!(rewards, vec![vec![(delegator_1.clone(), Coin::from_u64_unchecked(500))]]);
+
+    // The new stake counts for epoch 2, which was selected after it had been delegated.
+    finalize_epoch(&mut contract);
+    let rewards = contract.delegator_rewards(&[(validator_address.clone(), Coin::from_u64_unchecked(1_000))]);
+    assert_eq!(rewards, vec![vec![
+        (delegator_1, Coin::from_u64_unchecked(400)),
+        (delegator_2, Coin::from_u64_unchecked(400)),
+    ]]);
+
+    // The snapshots survive serialization.
+    let contract: StakingContract = Deserialize::deserialize_from_vec(&contract.serialize_to_vec()).unwrap();
+    assert_eq!(contract.previous_epoch_delegations.get(&validator_address).unwrap().balance, Coin::from_u64_unchecked(500_000));
+    assert_eq!(contract.current_epoch_delegations.get(&validator_address).unwrap().delegations.len(), 2);
+}
+
+#[test]
+fn it_can_verify_update_validator_transaction() {
+    let new_pair = BlsKeyPair::generate(&mut thread_rng());
+    let key_pair = ed25519_key_pair();
+
+    let mut tx = make_outgoing_transaction();
+    tx.recipient = tx.sender.clone();
+    tx.recipient_type = AccountType::Staking;
+    tx.data = UpdateValidatorData {
+        new_key: Some(ValidatorKeyUpdate {
+            validator_key: new_pair.public.compress(),
+            proof_of_knowledge: new_pair.sign(&new_pair.public).compress(),
+        }),
+        new_reward_address: None,
+    }.to_data();
+    tx.proof = SignatureProof::from(key_pair.public, key_pair.sign(&tx.serialize_content())).serialize_to_vec();
+
+    // Valid
+    assert_eq!(AccountType::verify_incoming_transaction(&tx), Ok(()));
+    assert_eq!(StakingContract::check_incoming_transaction(&tx, 2), Ok(()));
+
+    // Invalid proof of knowledge
+    let other_pair = BlsKeyPair::generate(&mut thread_rng());
+    tx.data = UpdateValidatorData {
+        new_key: Some(ValidatorKeyUpdate {
+            validator_key: new_pair.public.compress(),
+            proof_of_knowledge: other_pair.sign(&other_pair.public).compress(),
+        }),
+        new_reward_address: None,
+    }.to_data();
+    assert_eq!(AccountType::verify_incoming_transaction(&tx), Err(TransactionError::InvalidData));
+
+    // Nothing to update
+    tx.data = UpdateValidatorData {
+        new_key: None,
+        new_reward_address: None,
+    }.to_data();
+    assert_eq!(AccountType::verify_incoming_transaction(&tx), Err(TransactionError::InvalidData));
+
+    // Trailing data
+    let mut data = UpdateValidatorData {
+        new_key: None,
+        new_reward_address: Some(Address::from([9u8; 20])),
+    }.to_data();
+    data.push(0);
+    tx.data = data;
+    assert_eq!(AccountType::verify_incoming_transaction(&tx), Err(TransactionError::InvalidData));
+    assert!(StakingContract::check_incoming_transaction(&tx, 2).is_err());
+}
+
+#[test]
+fn it_can_apply_update_validator_transaction() {
+    let bls_pair = bls_key_pair();
+    let key_pair = ed25519_key_pair();
+    let mut contract = make_sample_contract(&key_pair, &bls_pair);
+    let address = Address::from(&key_pair.public);
+    let new_pair = BlsKeyPair::generate(&mut thread_rng());
+
+    let make_update_with_key = |key_pair: &KeyPair, new_pair: &BlsKeyPair, fee: u64| -> Transaction {
+        let mut tx = make_outgoing_transaction();
+        tx.recipient = tx.sender.clone();
+        tx.value = Coin::from_u64_unchecked(1);
+        tx.fee = Coin::try_from(fee).unwrap();
+        tx.data = UpdateValidatorData {
+            new_key: Some(ValidatorKeyUpdate {
+                validator_key: new_pair.public.compress(),
+                proof_of_knowledge: new_pair.sign(&new_pair.public).compress(),
+            }),
+            new_reward_address: Some(Address::from([9u8; 20])),
+        }.to_data();
+        tx.proof = SignatureProof::from(key_pair.public, key_pair.sign(&tx.serialize_content())).serialize_to_vec();
+        tx
+    };
+    let make_update = |key_pair: &KeyPair, fee: u64| make_update_with_key(key_pair, &new_pair, fee);
+
+    // Fee exceeds the validator's own stake
+    let update = make_update(&key_pair, 300_000_001);
+    assert_eq!(contract.check_outgoing_transaction(&update, 2), Err(AccountError::InsufficientFunds {
+        needed: Coin::from_u64_unchecked(300_000_001),
+        balance: Coin::from_u64_unchecked(300_000_000),
+    }));
+
+    // Signer is a delegator, not a validator
+    let priv_key: PrivateKey = Deserialize::deserialize(&mut &hex::decode("aa9b15259bf42d3e74efc25a41db8e3223280bffa7ffbe5903a5537ac9b43275").unwrap()[..]).unwrap();
+    let key_pair2: KeyPair = priv_key.into();
+    contract.stake(&Address::from(&key_pair2.public), Coin::from_u64_unchecked(150_000_000), &address).unwrap();
+    let update = make_update(&key_pair2, 2);
+    assert_eq!(contract.check_outgoing_transaction(&update, 2), Err(AccountError::InvalidForSender));
+
+    // Key is already used by a validator
+    let update = make_update_with_key(&key_pair, &bls_pair, 2);
+    assert_eq!(contract.check_outgoing_transaction(&update, 2), Err(AccountError::InvalidForRecipient));
+    let other_pair = BlsKeyPair::generate(&mut thread_rng());
+    contract.register_validator(&Address::from([7u8; 20]), other_pair.public.compress(), None, 0).unwrap();
+    let update = make_update_with_key(&key_pair, &other_pair, 2);
+    assert_eq!(contract.check_outgoing_transaction(&update, 2), Err(AccountError::InvalidForRecipient));
+    assert_eq!(contract.commit_incoming_transaction(&update, 2), Err(AccountError::InvalidForRecipient));
+
+    // Update, only the fee is deducted from the validator's stake
+    let update = make_update(&key_pair, 2);
+    assert_eq!(contract.check_outgoing_transaction(&update, 2), Ok(()));
+    assert_eq!(StakingContract::check_incoming_transaction(&update, 2), Ok(()));
+    let outgoing_receipt = contract.commit_outgoing_transaction(&update, 2).unwrap();
+    let incoming_receipt = contract.commit_incoming_transaction(&update, 2).unwrap();
+    assert!(incoming_receipt.is_some());
+    assert_eq!(contract.balance, Coin::from_u64_unchecked(449_999_998));
+    assert_eq!(contract.get_active_balance(&address), Coin::from_u64_unchecked(299_999_998));
+    let validator = contract.get_validator(&address).unwrap().clone();
+    assert_eq!(validator.validator_key, new_pair.public.compress());
+    assert_eq!(validator.reward_address(), &Address::from([9u8; 20]));
+    assert_eq!(validator.balance, Coin::from_u64_unchecked(449_999_998));
+    assert_eq!(contract.active_validators_sorted.iter().next().unwrap().validator_key, new_pair.public.compress());
+
+    // Revert update
+    assert_eq!(contract.revert_incoming_transaction(&update, 2, incoming_receipt.as_ref()), Ok(()));
+    assert_eq!(contract.revert_outgoing_transaction(&update, 2, outgoing_receipt.as_ref()), Ok(()));
+    assert_eq!(contract.balance, Coin::from_u64_unchecked(450_000_000));
+    assert_eq!(contract.get_active_balance(&address), Coin::from_u64_unchecked(300_000_000));
+    let validator = contract.get_validator(&address).unwrap().clone();
+    assert_eq!(validator.validator_key, bls_pair.public.compress());
+    assert_eq!(validator.reward_address(), &Address::from([3u8; 20]));
+    assert_eq!(validator.balance, Coin::from_u64_unchecked(450_000_000));
+    assert_eq!(contract.active_validators_sorted.iter().next().unwrap().validator_key, bls_pair.public.compress());
+}
+
+fn finalize_epoch(contract: &mut StakingContract) {
+    let inherent = Inherent {
+        ty: InherentType::FinalizeEpoch,
+        target: Default::default(),
+        value: Coin::ZERO,
+        data: vec![],
+    };
+    assert_eq!(contract.commit_inherent(&inherent, 0), Ok(None));
+}
+
 fn make_empty_contract() -> StakingContract {
     StakingContract::default()
 }
@@ -1034,9 +1392,10 @@ fn make_sample_contract(key_pair: &KeyPair, bls_pair: &BlsKeyPair) -> StakingCon
 
     let proof_of_knowledge = bls_pair.sign_hash(Deserialize::deserialize_from_vec(&[0x41u8; 32].to_vec()).unwrap());
 
-    let data = StakingTransactionData {
+    let data = StakingTransactionData::RegisterValidator {
         validator_key: bls_pair.public.compress(),
         reward_address: Some(Address::from([3u8; 20])),
+        commission: 0,
         proof_of_knowledge: proof_of_knowledge.compress(),
     };
     tx.data = data.serialize_to_vec();
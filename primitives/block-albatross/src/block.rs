@@ -5,6 +5,7 @@ use block_base;
 use hash::{Blake2bHash, Hash, SerializeContent};
 use hash_derive::SerializeContent;
 use primitives::networks::NetworkId;
+use primitives::policy::AlbatrossPolicy;
 use transaction::Transaction;
 use vrf::VrfSeed;
 
@@ -28,10 +29,10 @@ pub enum Block {
 impl Block {
     pub const VERSION: u16 = 1;
 
-    pub fn verify(&self, network_id: NetworkId) -> Result<(), BlockError> {
+    pub fn verify(&self, network_id: NetworkId, policy: &AlbatrossPolicy) -> Result<(), BlockError> {
         match self {
             Block::Macro(ref block) => block.verify(),
-            Block::Micro(ref block) => block.verify(network_id),
+            Block::Micro(ref block) => block.verify(network_id, policy),
         }
     }
 
@@ -330,6 +331,9 @@ impl block_base::Block for Block {
     type Header = BlockHeader;
     type Error = BlockError;
 
+    // Only micro blocks contain transactions.
+    const MAX_SIZE: usize = MicroBlock::MAX_SIZE;
+
     fn hash(&self) -> Blake2bHash {
         self.hash()
     }
@@ -0,0 +1,95 @@
+use std::convert::TryFrom;
+
+use beserial::{Deserialize, Serialize};
+use hash::{Blake2bHash, Hash};
+use primitives::policy::AlbatrossPolicy;
+use primitives::slot::SlotCollection;
+
+use crate::{BlockError, MacroBlock, MacroHeader};
+use crate::pbft::PbftProof;
+use crate::signed::AggregateProofError;
+
+/// Proves the validator handoff into an epoch: The header of the macro block that concludes the
+/// epoch and the pBFT proof by which the previous validator set finalized it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EpochProof {
+    pub header: MacroHeader,
+    pub justification: PbftProof,
+}
+
+impl TryFrom<MacroBlock> for EpochProof {
+    type Error = BlockError;
+
+    fn try_from(macro_block: MacroBlock) -> Result<Self, Self::Error> {
+        let justification = macro_block.justification
+            .ok_or(BlockError::NoJustification)?;
+
+        Ok(EpochProof {
+            header: macro_block.header,
+            justification,
+        })
+    }
+}
+
+/// A chain of consecutive epoch proofs, starting with the epoch after a macro block known to
+/// the verifier. This allows to reach the current validator set without downloading micro blocks.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EpochChainProof {
+    #[beserial(len_type(u16))]
+    pub epochs: Vec<EpochProof>,
+}
+
+impl EpochChainProof {
+    pub fn new(epochs: Vec<EpochProof>) -> Self {
+        EpochChainProof { epochs }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.epochs.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.epochs.len()
+    }
+
+    /// Verifies the proof on top of the trusted macro header `start`.
+    /// Every macro header must directly follow its predecessor, elect validators with the number of
+    /// slots of the policy and be signed by the validators that its predecessor elected. Returns
+    /// the last macro header of the proof.
+    pub fn verify<'a>(&'a self, start: &'a MacroHeader, policy: &AlbatrossPolicy) -> Result<&'a MacroHeader, EpochProofError> {
+        let mut previous = start;
+        for epoch in self.epochs.iter() {
+            let header = &epoch.header;
+            if header.block_number != policy.macro_block_after(previous.block_number) {
+                return Err(EpochProofError::InvalidBlockNumber(header.block_number));
+            }
+
+            let previous_hash: Blake2bHash = previous.hash();
+            if header.parent_macro_hash != previous_hash {
+                return Err(EpochProofError::InvalidParentMacroHash(header.block_number));
+            }
+
+            if header.validators.total_slots() != policy.slots {
+                return Err(EpochProofError::InvalidSlotCount(header.block_number));
+            }
+
+            epoch.justification.verify(header.hash(), &previous.validators, policy.two_third_slots())
+                .map_err(|e| EpochProofError::InvalidJustification(header.block_number, e))?;
+
+            previous = header;
+        }
+        Ok(previous)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Fail)]
+pub enum EpochProofError {
+    #[fail(display = "Macro block #{} doesn't follow its predecessor", _0)]
+    InvalidBlockNumber(u32),
+    #[fail(display = "Macro block #{} has an invalid parent macro hash", _0)]
+    InvalidParentMacroHash(u32),
+    #[fail(display = "Macro block #{} doesn't have the number of slots of the policy", _0)]
+    InvalidSlotCount(u32),
+    #[fail(display = "Macro block #{} has an invalid justification: {}", _0, _1)]
+    InvalidJustification(u32, AggregateProofError),
+}
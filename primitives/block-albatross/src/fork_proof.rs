@@ -4,7 +4,7 @@ use std::io;
 use beserial::{Deserialize, Serialize};
 use hash::{Blake2bHash, Hash, SerializeContent};
 use nimiq_bls::bls12_381::{CompressedSignature, PublicKey};
-use primitives::policy;
+use primitives::policy::AlbatrossPolicy;
 
 use crate::MicroHeader;
 
@@ -39,9 +39,9 @@ impl ForkProof {
         Ok(())
     }
 
-    pub fn is_valid_at(&self, block_number: u32) -> bool {
-        let given_epoch = policy::epoch_at(block_number);
-        let proof_epoch = policy::epoch_at(self.header1.block_number);
+    pub fn is_valid_at(&self, block_number: u32, policy: &AlbatrossPolicy) -> bool {
+        let given_epoch = policy.epoch_at(block_number);
+        let proof_epoch = policy.epoch_at(self.header1.block_number);
         self.header1.block_number == self.header2.block_number
             && self.header1.view_number == self.header2.view_number
             // XXX Should this be checked at a higher layer?
@@ -18,6 +18,7 @@ extern crate nimiq_vrf as vrf;
 
 mod block;
 mod macro_block;
+mod epoch_proof;
 mod micro_block;
 mod pbft;
 mod fork_proof;
@@ -29,6 +30,7 @@ pub use macro_block::{MacroBlock, MacroHeader, MacroExtrinsics};
 pub use micro_block::{MicroBlock, MicroHeader, MicroJustification, MicroExtrinsics};
 pub use view_change::{ViewChange, SignedViewChange, ViewChangeProof, ViewChangeProofBuilder, ViewChanges};
 pub use fork_proof::ForkProof;
+pub use epoch_proof::{EpochProof, EpochChainProof, EpochProofError};
 pub use pbft::{PbftPrepareMessage, PbftCommitMessage, PbftProofBuilder, PbftProof, SignedPbftPrepareMessage, SignedPbftCommitMessage, SignedPbftProposal, PbftProposal};
 
 use crate::transaction::TransactionError;
@@ -5,6 +5,7 @@ use beserial::{Deserialize, Serialize};
 use hash::{Hash, Blake2bHash, SerializeContent};
 use hash_derive::SerializeContent;
 use primitives::networks::NetworkId;
+use primitives::policy::AlbatrossPolicy;
 use bls::bls12_381::CompressedSignature;
 use transaction::Transaction;
 use vrf::VrfSeed;
@@ -55,9 +56,9 @@ pub struct MicroExtrinsics {
 impl MicroBlock {
     pub const MAX_SIZE: usize = 100_000; // 100 KB
 
-    pub fn verify(&self, network_id: NetworkId) -> Result<(), BlockError> {
+    pub fn verify(&self, network_id: NetworkId, policy: &AlbatrossPolicy) -> Result<(), BlockError> {
         if let Some(ref extrinsics) = self.extrinsics {
-            extrinsics.verify(self.header.block_number, network_id)?;
+            extrinsics.verify(self.header.block_number, network_id, policy)?;
 
             if self.header.extrinsics_root != extrinsics.hash() {
                 return Err(BlockError::BodyHashMismatch);
@@ -78,7 +79,7 @@ impl MicroHeader {
 }
 
 impl MicroExtrinsics {
-    pub fn verify(&self, block_height: u32, network_id: NetworkId) -> Result<(), BlockError> {
+    pub fn verify(&self, block_height: u32, network_id: NetworkId, policy: &AlbatrossPolicy) -> Result<(), BlockError> {
         // Verify fork proofs.
         let mut previous_proof: Option<&ForkProof> = None;
         for proof in &self.fork_proofs {
@@ -97,7 +98,7 @@ impl MicroExtrinsics {
             previous_proof = Some(proof);
 
             // Check that the proof is within the reporting window.
-            if !proof.is_valid_at(block_height) {
+            if !proof.is_valid_at(block_height, policy) {
                 return Err(BlockError::InvalidForkProof);
             }
         }
@@ -120,7 +121,7 @@ impl MicroExtrinsics {
             previous_tx = Some(tx);
 
             // Check that the transaction is within its validity window.
-            if !tx.is_valid_at(block_height) {
+            if !tx.is_valid_at(block_height, policy.transaction_validity_window) {
                 return Err(BlockError::ExpiredTransaction);
             }
 
@@ -21,6 +21,8 @@ impl signed::Message for ViewChange {
     const PREFIX: u8 = signed::PREFIX_VIEW_CHANGE;
 }
 
+impl hash::Hash for ViewChange {}
+
 pub type SignedViewChange = signed::SignedMessage<ViewChange>;
 pub type ViewChangeProof = signed::AggregateProof<ViewChange>;
 pub type ViewChangeProofBuilder = signed::AggregateProofBuilder<ViewChange>;
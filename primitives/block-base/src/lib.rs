@@ -13,6 +13,9 @@ pub trait Block: Serialize + Deserialize {
     type Header: BlockHeader;
     type Error: BlockError;
 
+    /// Maximum serialized size of a block that contains transactions
+    const MAX_SIZE: usize;
+
     fn hash(&self) -> Blake2bHash;
 
     fn height(&self) -> u32;
@@ -157,6 +157,8 @@ impl block_base::Block for Block {
     type Header = BlockHeader;
     type Error = BlockError;
 
+    const MAX_SIZE: usize = Block::MAX_SIZE;
+
     fn hash(&self) -> Blake2bHash {
         self.header.hash()
     }
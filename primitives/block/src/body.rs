@@ -58,7 +58,7 @@ impl BlockBody {
             previous_tx = Some(tx);
 
             // Check that the transaction is within its validity window.
-            if !tx.is_valid_at(block_height) {
+            if !tx.is_valid_at(block_height, policy::TRANSACTION_VALIDITY_WINDOW) {
                 return Err(BlockError::ExpiredTransaction);
             }
 
@@ -1,31 +1,91 @@
-use beserial::{Deserialize, Serialize};
+use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteBytesExt};
 use std::fmt::{Display, Formatter, Error};
 use std::str::FromStr;
 
-#[derive(Serialize, Deserialize, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
-#[repr(u8)]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
 pub enum NetworkId {
-    Test = 1,
-    Dev = 2,
-    Bounty = 3,
-    Dummy = 4,
-    Main = 42,
-
-    TestAlbatross = 5,
-    DevAlbatross = 6,
-    UnitAlbatross = 7,
+    Test,
+    Dev,
+    Bounty,
+    Dummy,
+    Main,
+
+    TestAlbatross,
+    DevAlbatross,
+    UnitAlbatross,
+    /// Albatross network with a genesis block and policy that are loaded at runtime. Its ID is set
+    /// in the genesis config file, so that transactions can't be replayed on other custom networks.
+    CustomAlbatross(u8),
 }
 
 
 impl NetworkId {
+    /// Smallest ID of a custom Albatross network. All IDs from here on are reserved for them.
+    pub const CUSTOM_ALBATROSS_MIN: u8 = 128;
+
+    /// Returns the custom Albatross network with ID `id`, or `None` if the ID isn't reserved for
+    /// custom networks.
+    pub fn custom_albatross(id: u8) -> Option<NetworkId> {
+        if id >= Self::CUSTOM_ALBATROSS_MIN {
+            Some(NetworkId::CustomAlbatross(id))
+        } else {
+            None
+        }
+    }
+
+    pub fn from_int(x: u8) -> Option<NetworkId> {
+        match x {
+            1 => Some(NetworkId::Test),
+            2 => Some(NetworkId::Dev),
+            3 => Some(NetworkId::Bounty),
+            4 => Some(NetworkId::Dummy),
+            42 => Some(NetworkId::Main),
+            5 => Some(NetworkId::TestAlbatross),
+            6 => Some(NetworkId::DevAlbatross),
+            7 => Some(NetworkId::UnitAlbatross),
+            x => Self::custom_albatross(x),
+        }
+    }
+
+    pub fn to_int(self) -> u8 {
+        match self {
+            NetworkId::Test => 1,
+            NetworkId::Dev => 2,
+            NetworkId::Bounty => 3,
+            NetworkId::Dummy => 4,
+            NetworkId::Main => 42,
+            NetworkId::TestAlbatross => 5,
+            NetworkId::DevAlbatross => 6,
+            NetworkId::UnitAlbatross => 7,
+            NetworkId::CustomAlbatross(id) => id,
+        }
+    }
+
     pub fn is_albatross(self) -> bool {
         match self {
-            NetworkId::TestAlbatross | NetworkId::DevAlbatross => true,
+            NetworkId::TestAlbatross | NetworkId::DevAlbatross | NetworkId::CustomAlbatross(_) => true,
             _ => false,
         }
     }
 }
 
+impl Serialize for NetworkId {
+    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
+        Serialize::serialize(&self.to_int(), writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        Serialize::serialized_size(&self.to_int())
+    }
+}
+
+impl Deserialize for NetworkId {
+    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        let id: u8 = Deserialize::deserialize(reader)?;
+        NetworkId::from_int(id).ok_or(SerializingError::InvalidValue)
+    }
+}
+
 #[derive(Fail, Debug)]
 #[fail(display = "Input is not a valid network name: {}", _0)]
 pub struct NetworkIdParseError(String);
@@ -34,7 +94,8 @@ impl FromStr for NetworkId {
     type Err = NetworkIdParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
+        let name = s.to_lowercase();
+        match name.as_str() {
             "test" => Ok(NetworkId::Test),
             "dev" => Ok(NetworkId::Dev),
             "bounty" => Ok(NetworkId::Bounty),
@@ -42,7 +103,15 @@ impl FromStr for NetworkId {
             "main" => Ok(NetworkId::Main),
             "testalbatross" => Ok(NetworkId::TestAlbatross),
             "devalbatross" => Ok(NetworkId::DevAlbatross),
-            _ => Err(NetworkIdParseError(String::from(s)))
+            _ => {
+                // Custom networks are named by their ID, e.g. `customalbatross-200`.
+                if name.starts_with("customalbatross-") {
+                    if let Some(network_id) = name["customalbatross-".len()..].parse().ok().and_then(NetworkId::custom_albatross) {
+                        return Ok(network_id);
+                    }
+                }
+                Err(NetworkIdParseError(String::from(s)))
+            }
         }
     }
 }
@@ -58,6 +127,7 @@ impl Display for NetworkId {
             NetworkId::TestAlbatross => "TestAlbatross",
             NetworkId::DevAlbatross => "DevAlbatross",
             NetworkId::UnitAlbatross => "UnitAlbatross",
+            NetworkId::CustomAlbatross(id) => return write!(f, "CustomAlbatross-{}", id),
         })
     }
 }
@@ -1,5 +1,6 @@
 use std::convert::TryInto;
 
+use beserial::{Deserialize, Serialize};
 use lazy_static::lazy_static;
 use num_bigint::BigUint;
 use num_traits::pow;
@@ -28,9 +29,6 @@ pub const DIFFICULTY_MAX_ADJUSTMENT_FACTOR: f64 = 2f64;
 /// Number of blocks a transaction is valid.
 pub const TRANSACTION_VALIDITY_WINDOW: u32 = 120;
 
-/// Number of blocks a transaction is valid with Albatross consensus.
-pub const TRANSACTION_VALIDITY_WINDOW_ALBATROSS: u32 = 7200;
-
 /// Total supply in units.
 pub const TOTAL_SUPPLY: u64 = 2_100_000_000_000_000;
 
@@ -109,85 +107,119 @@ pub fn block_reward_at(block_height: u32) -> Coin {
 
 /* Albatross */
 
-/// Number of micro blocks to wait for unstaking after next macro block.
-pub const UNSTAKING_DELAY: u32 = 100; // TODO: Set.
-
-/// Number of available slots
-pub const SLOTS: u16 = 512;
+/// Consensus parameters of Albatross. Unlike the parameters above, these can be chosen per
+/// network, e.g. to run a private network with short epochs and only a few slots. The policy of a
+/// network is provided by its `NetworkInfo` and stored in the staking contract of its genesis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlbatrossPolicy {
+    /// Length of epoch including macro block
+    pub epoch_length: u32,
 
-/// ceil(2/3) of active validators
-// (2 * n + 3) / 3 = ceil(2f + 1) where n = 3f + 1
-pub const TWO_THIRD_SLOTS: u16 = (2 * SLOTS + 3) / 3;
+    /// Number of available slots
+    pub slots: u16,
 
-// Length of epoch including macro block
-pub const EPOCH_LENGTH: u32 = 128;
+    /// Minimum stake in units
+    pub min_stake: u64,
 
-/// Minimum stake in units
-pub const MIN_STAKE: u64 = 100_000_000;
+    /// Number of micro blocks to wait for unstaking after next macro block.
+    pub unstaking_delay: u32,
 
-/// Returns the height of the next macro block after given `block_height`
-#[inline]
-pub fn macro_block_after(block_number: u32) -> u32 {
-    (block_number / EPOCH_LENGTH + 1) * EPOCH_LENGTH
+    /// Number of blocks a transaction is valid.
+    pub transaction_validity_window: u32,
 }
 
-/// Returns the height of the preceding macro block before given `block_number`
-#[inline]
-pub fn macro_block_before(block_number: u32) -> u32 {
-    if block_number == 0 {
-        panic!("Called macro_block_before with block_number 0");
+impl AlbatrossPolicy {
+    /// ceil(2/3) of active validators
+    // (2 * n + 3) / 3 = ceil(2f + 1) where n = 3f + 1
+    #[inline]
+    pub fn two_third_slots(&self) -> u16 {
+        ((2 * u32::from(self.slots) + 3) / 3) as u16
     }
-    (block_number - 1) / EPOCH_LENGTH * EPOCH_LENGTH
-}
 
-#[inline]
-pub fn epoch_at(block_number: u32) -> u32 {
-    (block_number + EPOCH_LENGTH - 1) / EPOCH_LENGTH
-}
+    /// Checks that the parameters can be used to run a chain.
+    pub fn is_valid(&self) -> bool {
+        // An epoch consists of at least one micro block and the macro block.
+        self.epoch_length >= 2
+            && self.slots >= 1
+            && self.transaction_validity_window >= 1
+    }
 
-#[inline]
-pub fn epoch_index_at(block_number: u32) -> u32 {
-    (block_number + EPOCH_LENGTH - 1) % EPOCH_LENGTH
-}
+    /// Returns the height of the next macro block after given `block_height`
+    #[inline]
+    pub fn macro_block_after(&self, block_number: u32) -> u32 {
+        (block_number / self.epoch_length + 1) * self.epoch_length
+    }
 
-#[inline]
-pub fn is_macro_block_at(block_number: u32) -> bool {
-    epoch_index_at(block_number) == EPOCH_LENGTH - 1
-}
+    /// Returns the height of the preceding macro block before given `block_number`
+    #[inline]
+    pub fn macro_block_before(&self, block_number: u32) -> u32 {
+        if block_number == 0 {
+            panic!("Called macro_block_before with block_number 0");
+        }
+        (block_number - 1) / self.epoch_length * self.epoch_length
+    }
 
-#[inline]
-pub fn last_macro_block(block_number: u32) -> u32 {
-    block_number / EPOCH_LENGTH * EPOCH_LENGTH
-}
+    #[inline]
+    pub fn epoch_at(&self, block_number: u32) -> u32 {
+        (block_number + self.epoch_length - 1) / self.epoch_length
+    }
 
-#[inline]
-pub fn is_micro_block_at(block_height: u32) -> bool {
-    !is_macro_block_at(block_height)
-}
+    #[inline]
+    pub fn epoch_index_at(&self, block_number: u32) -> u32 {
+        (block_number + self.epoch_length - 1) % self.epoch_length
+    }
 
-pub fn successive_micro_blocks(a: u32, b: u32) -> bool {
-    a + 1 == b || (a + 2 == b && is_macro_block_at(a + 1))
-}
+    #[inline]
+    pub fn is_macro_block_at(&self, block_number: u32) -> bool {
+        self.epoch_index_at(block_number) == self.epoch_length - 1
+    }
 
-pub fn first_block_of(epoch: u32) -> u32 {
-    if epoch == 0 {
-        panic!("Called first_block_of for epoch 0");
+    #[inline]
+    pub fn last_macro_block(&self, block_number: u32) -> u32 {
+        block_number / self.epoch_length * self.epoch_length
     }
-    epoch * EPOCH_LENGTH - EPOCH_LENGTH + 1
-}
 
-/// First block in reward registry (first block of previous epoch)
-/// Returns `0u32` during epoch 0 (genesis) and 1.
-pub fn first_block_of_registry(epoch: u32) -> u32 {
-    if epoch <= 1 {
-        0u32
-    } else {
-        first_block_of(epoch - 1)
+    #[inline]
+    pub fn is_micro_block_at(&self, block_height: u32) -> bool {
+        !self.is_macro_block_at(block_height)
+    }
+
+    pub fn successive_micro_blocks(&self, a: u32, b: u32) -> bool {
+        a + 1 == b || (a + 2 == b && self.is_macro_block_at(a + 1))
+    }
+
+    pub fn first_block_of(&self, epoch: u32) -> u32 {
+        if epoch == 0 {
+            panic!("Called first_block_of for epoch 0");
+        }
+        epoch * self.epoch_length - self.epoch_length + 1
+    }
+
+    /// First block in reward registry (first block of previous epoch)
+    /// Returns `0u32` during epoch 0 (genesis) and 1.
+    pub fn first_block_of_registry(&self, epoch: u32) -> u32 {
+        if epoch <= 1 {
+            0u32
+        } else {
+            self.first_block_of(epoch - 1)
+        }
+    }
+
+    pub fn macro_block_of(&self, epoch: u32) -> u32 {
+        epoch * self.epoch_length
     }
 }
 
-pub fn macro_block_of(epoch: u32) -> u32 {
-    epoch * EPOCH_LENGTH
+impl Default for AlbatrossPolicy {
+    fn default() -> Self {
+        AlbatrossPolicy {
+            epoch_length: 128,
+            slots: 512,
+            min_stake: 100_000_000,
+            unstaking_delay: 100, // TODO: Set.
+            transaction_validity_window: 7200,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -224,49 +256,63 @@ mod tests {
 
     #[test]
     fn it_correctly_computes_epoch() {
-        assert_eq!(epoch_at(0), 0);
-        assert_eq!(epoch_at(1), 1);
-        assert_eq!(epoch_at(128), 1);
-        assert_eq!(epoch_at(129), 2);
+        let policy = AlbatrossPolicy::default();
+        assert_eq!(policy.epoch_at(0), 0);
+        assert_eq!(policy.epoch_at(1), 1);
+        assert_eq!(policy.epoch_at(128), 1);
+        assert_eq!(policy.epoch_at(129), 2);
     }
 
     #[test]
     fn it_correctly_computes_epoch_index() {
-        assert_eq!(epoch_index_at(1), 0);
-        assert_eq!(epoch_index_at(2), 1);
-        assert_eq!(epoch_index_at(128), 127);
-        assert_eq!(epoch_index_at(129), 0);
+        let policy = AlbatrossPolicy::default();
+        assert_eq!(policy.epoch_index_at(1), 0);
+        assert_eq!(policy.epoch_index_at(2), 1);
+        assert_eq!(policy.epoch_index_at(128), 127);
+        assert_eq!(policy.epoch_index_at(129), 0);
     }
 
     #[test]
     fn it_correctly_computes_macro_block_position() {
-        assert_eq!( is_macro_block_at(0), true);
-        assert_eq!(!is_micro_block_at(0), true);
-        assert_eq!( is_macro_block_at(1), false);
-        assert_eq!(!is_micro_block_at(1), false);
-        assert_eq!( is_macro_block_at(2), false);
-        assert_eq!(!is_micro_block_at(2), false);
-        assert_eq!( is_macro_block_at(127), false);
-        assert_eq!(!is_micro_block_at(127), false);
-        assert_eq!( is_macro_block_at(128), true);
-        assert_eq!(!is_micro_block_at(128), true);
-        assert_eq!( is_macro_block_at(129), false);
-        assert_eq!(!is_micro_block_at(129), false);
+        let policy = AlbatrossPolicy::default();
+        assert_eq!( policy.is_macro_block_at(0), true);
+        assert_eq!(!policy.is_micro_block_at(0), true);
+        assert_eq!( policy.is_macro_block_at(1), false);
+        assert_eq!(!policy.is_micro_block_at(1), false);
+        assert_eq!( policy.is_macro_block_at(2), false);
+        assert_eq!(!policy.is_micro_block_at(2), false);
+        assert_eq!( policy.is_macro_block_at(127), false);
+        assert_eq!(!policy.is_micro_block_at(127), false);
+        assert_eq!( policy.is_macro_block_at(128), true);
+        assert_eq!(!policy.is_micro_block_at(128), true);
+        assert_eq!( policy.is_macro_block_at(129), false);
+        assert_eq!(!policy.is_micro_block_at(129), false);
     }
 
     #[test]
     fn it_correctly_computes_macro_numbers() {
-        assert_eq!(macro_block_after(0), 128);
-        assert_eq!(macro_block_after(1), 128);
-        assert_eq!(macro_block_after(127), 128);
-        assert_eq!(macro_block_after(128), 256);
-        assert_eq!(macro_block_after(129), 256);
-
-        assert_eq!(macro_block_before(1), 0);
-        assert_eq!(macro_block_before(2), 0);
-        assert_eq!(macro_block_before(127), 0);
-        assert_eq!(macro_block_before(128), 0);
-        assert_eq!(macro_block_before(129), 128);
-        assert_eq!(macro_block_before(130), 128);
+        let policy = AlbatrossPolicy::default();
+        assert_eq!(policy.macro_block_after(0), 128);
+        assert_eq!(policy.macro_block_after(1), 128);
+        assert_eq!(policy.macro_block_after(127), 128);
+        assert_eq!(policy.macro_block_after(128), 256);
+        assert_eq!(policy.macro_block_after(129), 256);
+
+        assert_eq!(policy.macro_block_before(1), 0);
+        assert_eq!(policy.macro_block_before(2), 0);
+        assert_eq!(policy.macro_block_before(127), 0);
+        assert_eq!(policy.macro_block_before(128), 0);
+        assert_eq!(policy.macro_block_before(129), 128);
+        assert_eq!(policy.macro_block_before(130), 128);
+    }
+
+    #[test]
+    fn it_correctly_computes_two_third_slots() {
+        assert_eq!(AlbatrossPolicy::default().two_third_slots(), 342);
+
+        let policy = AlbatrossPolicy { slots: 4, ..Default::default() };
+        assert_eq!(policy.two_third_slots(), 3);
+        assert!(policy.is_valid());
+        assert!(!AlbatrossPolicy { epoch_length: 1, ..Default::default() }.is_valid());
     }
 }
@@ -53,13 +53,15 @@ use std::fmt;
 use bitvec::prelude::BitVec;
 use bitvec::cursor::BigEndian;
 use itertools::Itertools;
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
 
 use beserial::{Deserialize, Serialize, ReadBytesExt, WriteBytesExt, SerializingError, SerializeWithLength, DeserializeWithLength, uvar};
 use bls::bls12_381::lazy::LazyPublicKey;
 use bls::bls12_381::CompressedPublicKey;
 use keys::Address;
 
-use crate::policy::SLOTS;
+use crate::policy::AlbatrossPolicy;
 
 
 /// Enum to index a slot.
@@ -189,8 +191,8 @@ pub trait SlotCollection {
     /// The kind of [SlotBand] that is stored in this collection.
     type SlotBand: SlotBand;
 
-    /// The total number of slots. This is contant is should always be [policy::SLOTS]
-    const TOTAL_SLOTS: u16;
+    /// The total number of slots. This is the number of slots of the network's policy.
+    fn total_slots(&self) -> u16;
 
     fn get_band_number_by_slot_number(&self, slot_number: u16) -> Option<u16>;
 
@@ -333,7 +335,10 @@ impl ValidatorSlots {
 
 impl SlotCollection for ValidatorSlots {
     type SlotBand = ValidatorSlotBand;
-    const TOTAL_SLOTS: u16 = SLOTS;
+
+    fn total_slots(&self) -> u16 {
+        self.index.len()
+    }
 
     fn get_band_number_by_slot_number(&self, slot_number: u16) -> Option<u16> {
         self.index.get(slot_number)
@@ -391,7 +396,7 @@ impl Serialize for ValidatorSlots {
     }
 
     fn serialized_size(&self) -> usize {
-        CompressedPublicKey::SIZE * self.bands.len() + SlotAllocation::SIZE
+        CompressedPublicKey::SIZE * self.bands.len() + SlotAllocation::size(self.total_slots())
     }
 }
 
@@ -483,7 +488,10 @@ impl StakeSlots {
 
 impl SlotCollection for StakeSlots {
     type SlotBand = StakeSlotBand;
-    const TOTAL_SLOTS: u16 = SLOTS;
+
+    fn total_slots(&self) -> u16 {
+        self.index.len()
+    }
 
     fn get_band_number_by_slot_number(&self, slot_number: u16) -> Option<u16> {
         self.index.get(slot_number)
@@ -556,7 +564,7 @@ impl Serialize for StakeSlots {
 
     fn serialized_size(&self) -> usize {
         // Size of slot allocation
-        let mut size = SlotAllocation::SIZE;
+        let mut size = SlotAllocation::size(self.total_slots());
 
         // Size of BitVec that encodes which reward addresses are custom
         // TODO: Replace with fixed-size BitVec
@@ -630,7 +638,8 @@ struct SlotIndexTable {
 
 impl SlotIndexTable {
     pub fn new<B: SlotBand>(bands: &Vec<B>) -> Self {
-        let mut index = Vec::with_capacity(SLOTS as usize);
+        let slots: usize = bands.iter().map(|band| band.num_slots() as usize).sum();
+        let mut index = Vec::with_capacity(slots);
 
         for (i, band) in bands.iter().enumerate() {
             for _ in 0 .. band.num_slots() {
@@ -638,13 +647,16 @@ impl SlotIndexTable {
             }
         }
 
-        assert_eq!(index.len(), SLOTS as usize);
-
         Self {
             index,
         }
     }
 
+    /// Returns the total number of slots.
+    pub fn len(&self) -> u16 {
+        self.index.len() as u16
+    }
+
     pub fn get(&self, slot_number: u16) -> Option<u16> {
         self.index.get(slot_number as usize).copied()
     }
@@ -665,6 +677,26 @@ impl fmt::Debug for SlotIndexTable {
 }
 
 
+lazy_static! {
+    /// Number of slots of the network this process runs. A slot allocation is encoded with one bit
+    /// per slot and doesn't contain the number of slots, so it must be known when deserializing.
+    static ref NUM_SLOTS: RwLock<u16> = RwLock::new(AlbatrossPolicy::default().slots);
+}
+
+/// Sets the number of slots from the policy of the network this process runs. This must happen
+/// before any slot allocations of that network are deserialized.
+pub fn set_num_slots(slots: u16) {
+    assert!(slots >= 1, "Number of slots must be positive");
+    *NUM_SLOTS.write() = slots;
+}
+
+/// Returns the number of slots of the network this process runs.
+#[inline]
+pub fn num_slots() -> u16 {
+    *NUM_SLOTS.read()
+}
+
+
 /// A compressed representation of repeated objects. This is used to encode the slot allocation
 /// of something that is a `NumSlotsCollection`
 struct SlotAllocation {
@@ -673,11 +705,14 @@ struct SlotAllocation {
 
 impl SlotAllocation {
     /// Size in bytes - number of slots divided by 8 and rounded up.
-    const SIZE: usize = ((SLOTS as usize) + 8 - 1) / 8;
+    fn size(slots: u16) -> usize {
+        ((slots as usize) + 8 - 1) / 8
+    }
 
     pub fn from_iter<I: Iterator<Item=u16>>(iter: I) -> Result<Self, SerializingError> {
-        let mut bits = BitVec::with_capacity(SLOTS as usize);
-        bits.resize(SLOTS as usize, false);
+        let slots = num_slots() as usize;
+        let mut bits = BitVec::with_capacity(slots);
+        bits.resize(slots, false);
 
         let mut i = 0;
 
@@ -687,6 +722,11 @@ impl SlotAllocation {
                 return Err(SerializingError::InvalidValue);
             }
 
+            // The slots must add up to the number of slots of the network
+            if i + num_slots as usize > slots {
+                return Err(SerializingError::InvalidValue);
+            }
+
             // Set bit to encode a new validator and 1 slot
             bits.set(i, true);
 
@@ -695,7 +735,9 @@ impl SlotAllocation {
             i += num_slots as usize;
         }
 
-        assert_eq!(i, SLOTS as usize);
+        if i != slots {
+            return Err(SerializingError::InvalidValue);
+        }
 
         Ok(Self { bits })
     }
@@ -719,10 +761,10 @@ impl SlotAllocation {
         total += count;
         num_slots.push(count);
 
-        // Verify that the cumulative number of slots is `policy::SLOTS`.
+        // Verify that the cumulative number of slots is the total number of slots.
         // NOTE: Because of the nature of the encoding this always holds if the implementation of
         //       `SlotAllocation` is correct.
-        assert_eq!(total, SLOTS);
+        assert_eq!(total as usize, self.bits.len());
 
         num_slots
     }
@@ -740,15 +782,18 @@ impl Serialize for SlotAllocation {
     }
 
     fn serialized_size(&self) -> usize {
-        Self::SIZE
+        Self::size(self.bits.len() as u16)
     }
 }
 
 impl Deserialize for SlotAllocation {
     fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        let slots = num_slots();
+
         // Allocate buffer for BitVec
-        let mut buf = Vec::with_capacity(Self::SIZE);
-        buf.resize(Self::SIZE, 0_u8);
+        let size = Self::size(slots);
+        let mut buf = Vec::with_capacity(size);
+        buf.resize(size, 0_u8);
 
         // Read and create BitVec
         reader.read_exact(buf.as_mut_slice())?;
@@ -756,8 +801,9 @@ impl Deserialize for SlotAllocation {
 
         // We can end up with a BitVec that is slightly larger, if the original BitVec's length
         // wasn't a multiple of 8. Check that and resize to exact length
-        assert!(bits.len() >= SLOTS as usize);
-        bits.resize(SLOTS as usize, false);
+        let slots = slots as usize;
+        assert!(bits.len() >= slots);
+        bits.resize(slots, false);
 
         // Parse into slot allocation
         let allocation = Self { bits };
@@ -1,9 +1,7 @@
-use beserial::{Deserialize, ReadBytesExt, Serialize};
+use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteBytesExt};
 use bls::bls12_381::{CompressedPublicKey as BlsPublicKey, CompressedSignature as BlsSignature};
 use keys::Address;
 use primitives::account::AccountType;
-use primitives::coin::Coin;
-use primitives::policy;
 
 use crate::{Transaction, TransactionError, TransactionFlags};
 use crate::account::AccountTransactionVerification;
@@ -22,11 +20,13 @@ impl AccountTransactionVerification for StakingContractVerifier {
 
         if transaction.sender != transaction.recipient {
             // Staking transaction
+            // The minimum stake depends on the policy of the network and is checked by the
+            // staking contract.
             StakingTransactionData::parse(transaction)?.verify()?;
-
-            if transaction.value < Coin::from_u64_unchecked(policy::MIN_STAKE) {
-                warn!("Stake value below minimum");
-                return Err(TransactionError::InvalidForRecipient);
+        } else {
+            let ty: StakingTransactionType = Deserialize::deserialize(&mut &transaction.data[..])?;
+            if ty == StakingTransactionType::UpdateValidator {
+                UpdateValidatorData::parse(transaction)?.verify()?;
             }
         }
 
@@ -52,18 +52,144 @@ impl AccountTransactionVerification for StakingContractVerifier {
 pub enum StakingTransactionType {
     Retire = 0,
     Unpark = 1,
+    UpdateValidator = 2,
+}
+
+/// Commissions are given in basis points, i.e. in 1/100 of a percent.
+pub const MAX_COMMISSION: u16 = 10_000;
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum IncomingStakingTransactionType {
+    RegisterValidator = 0,
+    Stake = 1,
+}
+
+/// Data of transactions into the staking contract.
+#[derive(Clone, Debug)]
+pub enum StakingTransactionData {
+    /// Registers the sender as a validator and stakes the transaction value with it.
+    RegisterValidator {
+        validator_key: BlsPublicKey,
+        reward_address: Option<Address>,
+        /// The share of the rewards the validator keeps before splitting them with its
+        /// delegators, in basis points.
+        commission: u16,
+        proof_of_knowledge: BlsSignature,
+    },
+    /// Delegates the transaction value to the validator registered by `validator_address`.
+    Stake {
+        validator_address: Address,
+    },
 }
 
+impl StakingTransactionData {
+    pub fn parse(transaction: &Transaction) -> Result<Self, TransactionError> {
+        let reader = &mut &transaction.data[..];
+        let data = Deserialize::deserialize(reader)?;
+
+        // Ensure that transaction data has been fully read.
+        if reader.read_u8().is_ok() {
+            return Err(TransactionError::InvalidData);
+        }
+
+        Ok(data)
+    }
+
+    pub fn ty(&self) -> IncomingStakingTransactionType {
+        match self {
+            StakingTransactionData::RegisterValidator { .. } => IncomingStakingTransactionType::RegisterValidator,
+            StakingTransactionData::Stake { .. } => IncomingStakingTransactionType::Stake,
+        }
+    }
+
+    pub fn verify(&self) -> Result<(), TransactionError> {
+        match self {
+            StakingTransactionData::RegisterValidator { validator_key, commission, proof_of_knowledge, .. } => {
+                if *commission > MAX_COMMISSION {
+                    warn!("Commission above maximum");
+                    return Err(TransactionError::InvalidData);
+                }
+
+                verify_proof_of_knowledge(validator_key, proof_of_knowledge)
+            },
+            StakingTransactionData::Stake { .. } => Ok(()),
+        }
+    }
+}
+
+impl Serialize for StakingTransactionData {
+    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
+        let mut size = 0;
+        size += self.ty().serialize(writer)?;
+        size += match self {
+            StakingTransactionData::RegisterValidator { validator_key, reward_address, commission, proof_of_knowledge } => {
+                validator_key.serialize(writer)?
+                    + reward_address.serialize(writer)?
+                    + commission.serialize(writer)?
+                    + proof_of_knowledge.serialize(writer)?
+            },
+            StakingTransactionData::Stake { validator_address } => validator_address.serialize(writer)?,
+        };
+        Ok(size)
+    }
+
+    fn serialized_size(&self) -> usize {
+        let mut size = 0;
+        size += self.ty().serialized_size();
+        size += match self {
+            StakingTransactionData::RegisterValidator { validator_key, reward_address, commission, proof_of_knowledge } => {
+                validator_key.serialized_size()
+                    + reward_address.serialized_size()
+                    + commission.serialized_size()
+                    + proof_of_knowledge.serialized_size()
+            },
+            StakingTransactionData::Stake { validator_address } => validator_address.serialized_size(),
+        };
+        size
+    }
+}
+
+impl Deserialize for StakingTransactionData {
+    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        let ty: IncomingStakingTransactionType = Deserialize::deserialize(reader)?;
+        match ty {
+            IncomingStakingTransactionType::RegisterValidator => Ok(StakingTransactionData::RegisterValidator {
+                validator_key: Deserialize::deserialize(reader)?,
+                reward_address: Deserialize::deserialize(reader)?,
+                commission: Deserialize::deserialize(reader)?,
+                proof_of_knowledge: Deserialize::deserialize(reader)?,
+            }),
+            IncomingStakingTransactionType::Stake => Ok(StakingTransactionData::Stake {
+                validator_address: Deserialize::deserialize(reader)?,
+            }),
+        }
+    }
+}
+
+/// A new validator key together with a proof of knowledge of its secret key.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct StakingTransactionData {
+pub struct ValidatorKeyUpdate {
     pub validator_key: BlsPublicKey,
-    pub reward_address: Option<Address>,
     pub proof_of_knowledge: BlsSignature,
 }
 
-impl StakingTransactionData {
+/// Data of an `UpdateValidator` transaction, following its type byte. Fields that are `None`
+/// are left unchanged.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateValidatorData {
+    pub new_key: Option<ValidatorKeyUpdate>,
+    pub new_reward_address: Option<Address>,
+}
+
+impl UpdateValidatorData {
     pub fn parse(transaction: &Transaction) -> Result<Self, TransactionError> {
         let reader = &mut &transaction.data[..];
+        let ty: StakingTransactionType = Deserialize::deserialize(reader)?;
+        if ty != StakingTransactionType::UpdateValidator {
+            return Err(TransactionError::InvalidData);
+        }
+
         let data = Deserialize::deserialize(reader)?;
 
         // Ensure that transaction data has been fully read.
@@ -74,17 +200,36 @@ impl StakingTransactionData {
         Ok(data)
     }
 
-    /// Important: Currently, the proof of knowledge of the secret key is a signature of the public key.
-    /// If an attacker A ever tricks a validator B into signing a message with content `pk_A - pk_B`,
-    /// where `pk_X` is X's BLS public key, A will be able to sign aggregate messages that are valid for
-    /// public keys `pk_B + (pk_A - pk_B) = pk_B`.
-    /// Alternatives would be to replace the proof of knowledge by a zero-knowledge proof.
+    /// Serializes the data including the transaction type.
+    pub fn to_data(&self) -> Vec<u8> {
+        let mut data = StakingTransactionType::UpdateValidator.serialize_to_vec();
+        data.extend(self.serialize_to_vec());
+        data
+    }
+
     pub fn verify(&self) -> Result<(), TransactionError> {
-        if !self.validator_key.uncompress().map_err(|_| TransactionError::InvalidData)?
-            .verify(&self.validator_key,
-                    &self.proof_of_knowledge.uncompress().map_err(|_| TransactionError::InvalidData)?) {
-            return Err(TransactionError::InvalidData)
+        if self.new_key.is_none() && self.new_reward_address.is_none() {
+            warn!("Validator update doesn't change anything");
+            return Err(TransactionError::InvalidData);
+        }
+
+        if let Some(ref new_key) = self.new_key {
+            verify_proof_of_knowledge(&new_key.validator_key, &new_key.proof_of_knowledge)?;
         }
         Ok(())
     }
 }
+
+/// Important: Currently, the proof of knowledge of the secret key is a signature of the public key.
+/// If an attacker A ever tricks a validator B into signing a message with content `pk_A - pk_B`,
+/// where `pk_X` is X's BLS public key, A will be able to sign aggregate messages that are valid for
+/// public keys `pk_B + (pk_A - pk_B) = pk_B`.
+/// Alternatives would be to replace the proof of knowledge by a zero-knowledge proof.
+fn verify_proof_of_knowledge(validator_key: &BlsPublicKey, proof_of_knowledge: &BlsSignature) -> Result<(), TransactionError> {
+    if !validator_key.uncompress().map_err(|_| TransactionError::InvalidData)?
+        .verify(validator_key,
+                &proof_of_knowledge.uncompress().map_err(|_| TransactionError::InvalidData)?) {
+        return Err(TransactionError::InvalidData)
+    }
+    Ok(())
+}
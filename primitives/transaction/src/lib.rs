@@ -264,15 +264,11 @@ impl Transaction {
         }
     }
 
-    pub fn is_valid_at(&self, block_height: u32) -> bool {
-        let window = if self.network_id.is_albatross() {
-            policy::TRANSACTION_VALIDITY_WINDOW_ALBATROSS
-        }
-        else {
-            policy::TRANSACTION_VALIDITY_WINDOW
-        };
+    /// Checks whether the transaction can be included at `block_height`. The validity window
+    /// depends on the blockchain, see `AbstractBlockchain::transaction_validity_window`.
+    pub fn is_valid_at(&self, block_height: u32, validity_window: u32) -> bool {
         block_height >= self.validity_start_height
-            && block_height < self.validity_start_height + window
+            && block_height < self.validity_start_height + validity_window
     }
 
     pub fn contract_creation_address(&self) -> Address {
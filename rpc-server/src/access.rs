@@ -0,0 +1,262 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+use failure::Fail;
+use parking_lot::Mutex;
+
+use utils::rate_limit::RateLimit;
+
+#[derive(Debug, Fail, Clone, PartialEq, Eq)]
+pub enum SubnetParseError {
+    #[fail(display = "Invalid IP address")]
+    InvalidIp,
+    #[fail(display = "Invalid prefix length")]
+    InvalidPrefixLength,
+}
+
+/// An IP network in CIDR notation, e.g. `192.168.0.0/24`. A plain IP address is a network
+/// containing only this address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Subnet {
+    ip: IpAddr,
+    prefix_len: u8,
+}
+
+impl Subnet {
+    pub fn new(ip: IpAddr, prefix_len: u8) -> Result<Self, SubnetParseError> {
+        let max_len = match ip {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix_len > max_len {
+            return Err(SubnetParseError::InvalidPrefixLength);
+        }
+        Ok(Subnet { ip, prefix_len })
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.ip, Self::canonical(ip)) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                Self::prefix_matches(&net.octets(), &ip.octets(), self.prefix_len)
+            },
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                Self::prefix_matches(&net.octets(), &ip.octets(), self.prefix_len)
+            },
+            _ => false,
+        }
+    }
+
+    /// IPv4 clients connecting to a dual-stack socket show up as IPv4-mapped IPv6 addresses.
+    fn canonical(ip: &IpAddr) -> IpAddr {
+        match ip {
+            IpAddr::V6(ipv6) => match ipv6.segments() {
+                [0, 0, 0, 0, 0, 0xffff, _, _] => IpAddr::V4(Self::mapped_ipv4(ipv6)),
+                _ => *ip,
+            },
+            _ => *ip,
+        }
+    }
+
+    fn mapped_ipv4(ip: &Ipv6Addr) -> Ipv4Addr {
+        let octets = ip.octets();
+        Ipv4Addr::new(octets[12], octets[13], octets[14], octets[15])
+    }
+
+    fn prefix_matches(net: &[u8], ip: &[u8], prefix_len: u8) -> bool {
+        let full_bytes = (prefix_len / 8) as usize;
+        if net[..full_bytes] != ip[..full_bytes] {
+            return false;
+        }
+
+        let remaining_bits = prefix_len % 8;
+        if remaining_bits == 0 {
+            return true;
+        }
+        let mask = 0xffu8 << (8 - remaining_bits);
+        net[full_bytes] & mask == ip[full_bytes] & mask
+    }
+}
+
+impl FromStr for Subnet {
+    type Err = SubnetParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '/');
+        let ip = parts.next().unwrap_or_default()
+            .parse::<IpAddr>()
+            .map_err(|_| SubnetParseError::InvalidIp)?;
+        let prefix_len = match parts.next() {
+            Some(prefix_len) => prefix_len.parse::<u8>()
+                .map_err(|_| SubnetParseError::InvalidPrefixLength)?,
+            None => match ip {
+                IpAddr::V4(_) => 32,
+                IpAddr::V6(_) => 128,
+            },
+        };
+        Subnet::new(ip, prefix_len)
+    }
+}
+
+impl fmt::Display for Subnet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.ip, self.prefix_len)
+    }
+}
+
+/// Decides which IP addresses may access an RPC server. Denied networks take precedence over
+/// allowed ones. If no networks are allowed explicitly, all addresses that are not denied are
+/// allowed.
+#[derive(Clone, Debug, Default)]
+pub struct IpFilter {
+    pub allow: Vec<Subnet>,
+    pub deny: Vec<Subnet>,
+}
+
+impl IpFilter {
+    pub fn new(allow: Vec<Subnet>, deny: Vec<Subnet>) -> Self {
+        IpFilter { allow, deny }
+    }
+
+    pub fn is_allowed(&self, ip: &IpAddr) -> bool {
+        if self.deny.iter().any(|subnet| subnet.contains(ip)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|subnet| subnet.contains(ip))
+    }
+}
+
+/// Limits the number of requests per minute for each IP address.
+pub struct IpRateLimiter {
+    requests_per_minute: usize,
+    max_entries: usize,
+    state: Mutex<IpRateLimiterState>,
+}
+
+#[derive(Default)]
+struct IpRateLimiterState {
+    limits: HashMap<IpAddr, RateLimit>,
+    /// Tracked IP addresses, oldest first.
+    order: VecDeque<IpAddr>,
+}
+
+impl IpRateLimiter {
+    /// Default maximum number of tracked IP addresses.
+    pub const DEFAULT_MAX_ENTRIES: usize = 1024;
+
+    pub fn new(requests_per_minute: usize) -> Self {
+        Self::with_max_entries(requests_per_minute, Self::DEFAULT_MAX_ENTRIES)
+    }
+
+    /// Creates a rate limiter that tracks at most `max_entries` IP addresses. If the limit is
+    /// reached, idle entries are removed first and then the oldest ones.
+    pub fn with_max_entries(requests_per_minute: usize, max_entries: usize) -> Self {
+        assert!(max_entries > 0, "The rate limiter must track at least one IP address");
+        IpRateLimiter {
+            requests_per_minute,
+            max_entries,
+            state: Mutex::new(IpRateLimiterState::default()),
+        }
+    }
+
+    /// Notes a request from `ip` and returns whether it is within the rate limit.
+    pub fn note_request(&self, ip: &IpAddr) -> bool {
+        let requests_per_minute = self.requests_per_minute;
+        let mut state = self.state.lock();
+        let state = &mut *state;
+
+        if !state.limits.contains_key(ip) {
+            if state.limits.len() >= self.max_entries {
+                // Entries whose time period has passed without requests are not needed anymore.
+                state.limits.retain(|_, limit| limit.num_allowed() < requests_per_minute);
+                let limits = &state.limits;
+                state.order.retain(|tracked| limits.contains_key(tracked));
+            }
+            while state.limits.len() >= self.max_entries {
+                let oldest = state.order.pop_front().expect("Tracked IP addresses without order");
+                state.limits.remove(&oldest);
+            }
+            state.order.push_back(*ip);
+        }
+
+        state.limits.entry(*ip)
+            .or_insert_with(|| RateLimit::new_per_minute(requests_per_minute))
+            .note_single()
+    }
+
+    /// Returns the number of tracked IP addresses.
+    pub fn num_entries(&self) -> usize {
+        self.state.lock().limits.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_can_parse_subnets() {
+        assert_eq!("192.168.0.0/24".parse::<Subnet>().unwrap().to_string(), "192.168.0.0/24");
+        assert_eq!("10.0.0.1".parse::<Subnet>().unwrap().to_string(), "10.0.0.1/32");
+        assert_eq!("fd00::/8".parse::<Subnet>().unwrap().to_string(), "fd00::/8");
+        assert_eq!("10.0.0.0/33".parse::<Subnet>(), Err(SubnetParseError::InvalidPrefixLength));
+        assert_eq!("10.0.0/8".parse::<Subnet>(), Err(SubnetParseError::InvalidIp));
+    }
+
+    #[test]
+    fn it_matches_addresses() {
+        let subnet: Subnet = "192.168.0.0/23".parse().unwrap();
+        assert!(subnet.contains(&"192.168.1.17".parse().unwrap()));
+        assert!(!subnet.contains(&"192.168.2.1".parse().unwrap()));
+        assert!(subnet.contains(&"::ffff:192.168.0.1".parse().unwrap()));
+        assert!(!subnet.contains(&"fd00::1".parse().unwrap()));
+
+        let any: Subnet = "0.0.0.0/0".parse().unwrap();
+        assert!(any.contains(&"8.8.8.8".parse().unwrap()));
+    }
+
+    #[test]
+    fn deny_takes_precedence() {
+        let filter = IpFilter::new(
+            vec!["10.0.0.0/8".parse().unwrap()],
+            vec!["10.0.1.0/24".parse().unwrap()],
+        );
+        assert!(filter.is_allowed(&"10.0.0.1".parse().unwrap()));
+        assert!(!filter.is_allowed(&"10.0.1.1".parse().unwrap()));
+        assert!(!filter.is_allowed(&"127.0.0.1".parse().unwrap()));
+
+        let filter = IpFilter::new(vec![], vec!["127.0.0.1".parse().unwrap()]);
+        assert!(filter.is_allowed(&"10.0.0.1".parse().unwrap()));
+        assert!(!filter.is_allowed(&"127.0.0.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn it_limits_requests_per_ip() {
+        let limiter = IpRateLimiter::new(2);
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        assert!(limiter.note_request(&ip));
+        assert!(limiter.note_request(&ip));
+        assert!(!limiter.note_request(&ip));
+        assert!(limiter.note_request(&"10.0.0.2".parse().unwrap()));
+    }
+
+    #[test]
+    fn it_evicts_the_oldest_ip_when_full() {
+        let limiter = IpRateLimiter::with_max_entries(1, 2);
+        let ip1: IpAddr = "10.0.0.1".parse().unwrap();
+        let ip2: IpAddr = "10.0.0.2".parse().unwrap();
+        let ip3: IpAddr = "10.0.0.3".parse().unwrap();
+        assert!(limiter.note_request(&ip1));
+        assert!(limiter.note_request(&ip2));
+        assert!(!limiter.note_request(&ip1));
+
+        // All entries are in use, so the oldest one is evicted.
+        assert!(limiter.note_request(&ip3));
+        assert_eq!(limiter.num_entries(), 2);
+        assert!(!limiter.note_request(&ip2));
+        assert!(!limiter.note_request(&ip3));
+        assert!(limiter.note_request(&ip1));
+        assert_eq!(limiter.num_entries(), 2);
+    }
+}
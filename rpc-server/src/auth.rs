@@ -0,0 +1,71 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// A named API token. Clients authenticate with the name as username and the token as password.
+#[derive(Clone, Debug)]
+pub struct ApiToken {
+    pub name: String,
+    pub token: String,
+    /// The methods this token may call. All methods are allowed if this is empty.
+    pub methods: HashSet<String>,
+}
+
+impl ApiToken {
+    pub fn permissions(&self) -> Permissions {
+        if self.methods.is_empty() {
+            Permissions::all()
+        } else {
+            Permissions::methods(self.methods.clone())
+        }
+    }
+}
+
+/// The methods an authenticated client is allowed to call.
+#[derive(Clone, Debug)]
+pub struct Permissions {
+    methods: Option<Arc<HashSet<String>>>,
+}
+
+impl Permissions {
+    pub fn all() -> Self {
+        Permissions { methods: None }
+    }
+
+    pub fn methods(methods: HashSet<String>) -> Self {
+        Permissions { methods: Some(Arc::new(methods)) }
+    }
+
+    pub fn allows(&self, method: &str) -> bool {
+        match self.methods {
+            Some(ref methods) => methods.contains(method),
+            None => true,
+        }
+    }
+}
+
+/// Compares a secret provided by a client with the configured one in time that only depends on
+/// their lengths, such that the secret can't be guessed byte by byte from response times.
+pub fn secrets_equal(provided: &str, expected: &str) -> bool {
+    let provided = provided.as_bytes();
+    let expected = expected.as_bytes();
+    if provided.len() != expected.len() {
+        return false;
+    }
+    provided.iter().zip(expected)
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_compares_secrets() {
+        assert!(secrets_equal("secret", "secret"));
+        assert!(secrets_equal("", ""));
+        assert!(!secrets_equal("secreT", "secret"));
+        assert!(!secrets_equal("secre", "secret"));
+        assert!(!secrets_equal("secrets", "secret"));
+        assert!(!secrets_equal("", "secret"));
+    }
+}
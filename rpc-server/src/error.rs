@@ -20,3 +20,11 @@ pub enum AuthenticationError {
     #[fail(display = "Incorrect credentials.")]
     IncorrectCredentials,
 }
+
+#[derive(Debug, Fail)]
+pub enum AccessError {
+    #[fail(display = "IP address is not allowed.")]
+    IpNotAllowed,
+    #[fail(display = "Rate limit exceeded.")]
+    RateLimitExceeded,
+}
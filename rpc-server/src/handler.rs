@@ -1,11 +1,14 @@
 use std::collections::HashMap;
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use parking_lot::RwLock;
-use json::{Array, JsonValue};
+use json::{Array, JsonValue, object};
 
 use crate::JsonRpcConfig;
-use crate::error::AuthenticationError;
+use crate::access::IpRateLimiter;
+use crate::auth::{Permissions, secrets_equal};
+use crate::error::{AccessError, AuthenticationError};
 use crate::jsonrpc;
 use crate::handlers::Module;
 
@@ -28,12 +31,14 @@ impl Method {
 pub struct Handler {
     pub methods: RwLock<HashMap<&'static str, Method>>,
     pub config: Arc<JsonRpcConfig>,
+    rate_limiter: Option<IpRateLimiter>,
 }
 
 impl Handler {
     pub fn new(config: JsonRpcConfig) -> Self {
         Handler {
             methods: RwLock::new(HashMap::new()),
+            rate_limiter: config.rate_limit.map(IpRateLimiter::new),
             config: Arc::new(config),
         }
     }
@@ -51,7 +56,7 @@ impl Handler {
 }
 
 impl jsonrpc::Handler for Handler {
-    fn call_method(&self, name: &str, params: Array) -> Option<Result<JsonValue, JsonValue>> {
+    fn call_method(&self, name: &str, params: Array, permissions: &Permissions) -> Option<Result<JsonValue, JsonValue>> {
         trace!("RPC method called: {}", name);
 
         if !self.config.methods.is_empty() && !self.config.methods.contains(name) {
@@ -60,22 +65,113 @@ impl jsonrpc::Handler for Handler {
             return None
         }
 
-        self.methods.read().get(name).map(|h| h.call(&params))
+        let methods = self.methods.read();
+        let method = methods.get(name)?;
+
+        if !permissions.allows(name) {
+            info!("RPC call to method without permission: {}", name);
+            return Some(Err(object!{"message" => "Method is not allowed."}));
+        }
+
+        Some(method.call(&params))
     }
 
-    fn authorize(&self, username: &str, password: &str) -> Result<(), AuthenticationError> {
-        let ok = match (&self.config.username, &self.config.password) {
-            (Some(u), Some(p)) => u == username && p == password,
-            (None, None) => true,
-            _ => false, // Configuration error
+    fn authorize(&self, username: &str, password: &str) -> Result<Permissions, AuthenticationError> {
+        // Credentials with full access.
+        let credentials_configured = match (&self.config.username, &self.config.password) {
+            (Some(u), Some(p)) => {
+                if u == username && secrets_equal(password, p) {
+                    return Ok(Permissions::all());
+                }
+                true
+            },
+            (None, None) => false,
+            _ => true, // Configuration error
         };
 
-        if ok {
-            Ok(())
+        // Named API tokens.
+        if let Some(token) = self.config.tokens.iter().find(|token| token.name == username) {
+            if secrets_equal(password, &token.token) {
+                return Ok(token.permissions());
+            }
         }
-        else {
+
+        if credentials_configured || !self.config.tokens.is_empty() {
             Err(AuthenticationError::IncorrectCredentials)
         }
+        else {
+            Ok(Permissions::all())
+        }
+    }
+
+    fn check_access(&self, ip: &IpAddr) -> Result<(), AccessError> {
+        if !self.config.ip_filter.is_allowed(ip) {
+            return Err(AccessError::IpNotAllowed);
+        }
+
+        match self.rate_limiter {
+            Some(ref rate_limiter) if !rate_limiter.note_request(ip) => Err(AccessError::RateLimitExceeded),
+            _ => Ok(()),
+        }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::access::IpFilter;
+    use crate::auth::ApiToken;
+    use crate::jsonrpc::Handler as _;
+
+    use super::*;
+
+    fn handler() -> Handler {
+        let mut methods = HashSet::new();
+        methods.insert("blockNumber".to_string());
+        let config = JsonRpcConfig {
+            username: Some("admin".to_string()),
+            password: Some("password".to_string()),
+            tokens: vec![ApiToken {
+                name: "reader".to_string(),
+                token: "secret".to_string(),
+                methods,
+            }],
+            methods: HashSet::new(),
+            ip_filter: IpFilter::default(),
+            rate_limit: None,
+            corsdomain: vec![],
+        };
+
+        let handler = Handler::new(config);
+        handler.register_method("blockNumber", Method::new(|_| Ok(1.into())));
+        handler.register_method("sendTransaction", Method::new(|_| Ok(true.into())));
+        handler
+    }
+
+    #[test]
+    fn it_rejects_incorrect_credentials() {
+        let handler = handler();
+        assert!(handler.authorize("admin", "password").is_ok());
+        assert!(handler.authorize("reader", "secret").is_ok());
+
+        assert!(handler.authorize("admin", "passwort").is_err());
+        assert!(handler.authorize("reader", "secre").is_err());
+        assert!(handler.authorize("reader", "password").is_err());
+        assert!(handler.authorize("unknown", "secret").is_err());
+        assert!(handler.authorize("", "").is_err());
+    }
+
+    #[test]
+    fn tokens_only_allow_their_methods() {
+        let handler = handler();
+
+        let permissions = handler.authorize("reader", "secret").unwrap();
+        assert_eq!(handler.call_method("blockNumber", vec![], &permissions), Some(Ok(1.into())));
+        assert!(handler.call_method("sendTransaction", vec![], &permissions).unwrap().is_err());
+        assert!(handler.call_method("unknownMethod", vec![], &permissions).is_none());
+
+        let permissions = handler.authorize("admin", "password").unwrap();
+        assert_eq!(handler.call_method("sendTransaction", vec![], &permissions), Some(Ok(true.into())));
+    }
+}
@@ -23,40 +23,53 @@ impl BlockProductionAlbatrossHandler {
     }
 
     fn validator_key(&self, _params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
-        let key_pair = &self.validator.validator_key;
-
         // Compute proof of knowledge.
         // TODO: Do we need this at all? This is only needed to sign staking transactions, and
         // that can be done with the mempool module.
-        let proof_of_knowledge = key_pair
-            .sign(&key_pair.public)
-            .compress();
+        let proof_of_knowledge = self.validator.signer.proof_of_knowledge()
+            .map_err(|e| object!{"message" => e.to_string()})?;
 
         Ok(object! {
-            "validatorKey" => self.validator.validator_key.public.to_string(),
+            "validatorKey" => self.validator.public_key().to_string(),
             "proofOfKnowledge" => proof_of_knowledge.to_string(),
         })
     }
 
     fn proof_of_knowledge(&self, _params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
-        let key_pair = &self.validator.validator_key;
-
         // Compute proof of knowledge.
         // TODO: Do we need this at all? This is only needed to sign staking transactions, and
         // that can be done with the mempool module.
-        let proof_of_knowledge = key_pair
-            .sign(&key_pair.public)
-            .compress();
+        let proof_of_knowledge = self.validator.signer.proof_of_knowledge()
+            .map_err(|e| object!{"message" => e.to_string()})?;
 
         Ok(object! {
             "proofOfKnowledge" => proof_of_knowledge.to_string(),
         })
     }
+
+    /// Returns the slashing protection records of the validator in the interchange format.
+    fn export_slashing_protection(&self, _params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        Ok(self.validator.slashing_protection.export(&self.validator.public_key().compress()))
+    }
+
+    /// Imports slashing protection records in the interchange format.
+    /// Parameters:
+    /// - interchange (object): The exported records of this validator key.
+    ///
+    /// Returns the number of imported records.
+    fn import_slashing_protection(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let interchange = params.get(0).unwrap_or(&JsonValue::Null);
+        let count = self.validator.slashing_protection.import(interchange, &self.validator.public_key().compress())
+            .map_err(|e| object!{"message" => e.to_string()})?;
+        Ok(count.into())
+    }
 }
 
 impl Module for BlockProductionAlbatrossHandler {
     rpc_module_methods! {
         "validatorKey" => validator_key,
         "proofOfKnowledge" => proof_of_knowledge,
+        "exportSlashingProtection" => export_slashing_protection,
+        "importSlashingProtection" => import_slashing_protection,
     }
 }
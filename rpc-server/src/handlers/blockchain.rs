@@ -124,9 +124,12 @@ impl<B: AbstractBlockchain + 'static> BlockchainHandler<B> {
         self.get_transaction_by_block_and_index(&block, index)
     }
 
-    /// Retrieves transaction receipts for an address.
+    /// Retrieves transaction receipts for an address, most recent first.
     /// Parameters:
     /// - address (string)
+    /// - limit (number, optional): Default is 1000. Split evenly between sent and received transactions.
+    /// - recipientLimit (number, optional): If given, `limit` only applies to sent transactions
+    ///     and this limit to received transactions.
     ///
     /// Returns a list of receipts:
     /// ```text
@@ -145,11 +148,12 @@ impl<B: AbstractBlockchain + 'static> BlockchainHandler<B> {
             .and_then(|s| Address::from_any_str(s)
                 .map_err(|_| object!{"message" => "Invalid address"}))?;
 
-        // TODO: Accept two limit parameters?
-        let limit = params.get(0).and_then(JsonValue::as_usize)
+        let limit = params.get(1).and_then(JsonValue::as_usize)
             .unwrap_or(1000);
-        let sender_limit = limit / 2;
-        let recipient_limit = limit / 2;
+        let (sender_limit, recipient_limit) = match params.get(2).and_then(JsonValue::as_usize) {
+            Some(recipient_limit) => (limit, recipient_limit),
+            None => (limit / 2, limit / 2),
+        };
 
         Ok(JsonValue::Array(self.blockchain
             .get_transaction_receipts_by_address(&address, sender_limit, recipient_limit)
@@ -202,7 +206,7 @@ impl<B: AbstractBlockchain + 'static> BlockchainHandler<B> {
         Ok(transaction_to_obj(&transaction, Some(&TransactionContext {
             block_hash: &block.hash().to_hex(),
             block_number: block.height(),
-            index,
+            index: index.into(),
             timestamp: block.header().timestamp(),
         }), Some(self.blockchain.head_height())))
     }
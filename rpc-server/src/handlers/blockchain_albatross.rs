@@ -4,22 +4,22 @@ use std::sync::Arc;
 
 use json::{JsonValue, Null, object};
 
+use beserial::Deserialize;
 use block_albatross::{Block, ForkProof, signed};
 use account::Account;
-use account::staking_contract::{ActiveStake, InactiveStake};
+use account::staking_contract::{ActiveStake, InactiveStake, Validator};
 use blockchain_base::AbstractBlockchain;
 use blockchain_albatross::Blockchain;
+use blockchain_albatross::transaction_store::TransactionInfo;
 use hash::{Blake2bHash, Hash};
 use keys::Address;
-use network_primitives::networks::NetworkInfo;
-use primitives::policy;
 use primitives::slot::{Slot, Slots, SlotBand};
+use transaction::Transaction;
 
 use crate::handler::Method;
 use crate::handlers::Module;
-use crate::handlers::blockchain::BlockchainHandler;
+use crate::handlers::blockchain::{parse_hash, BlockchainHandler};
 use crate::handlers::mempool::{transaction_to_obj, TransactionContext};
-use crate::rpc_not_implemented;
 
 pub struct BlockchainAlbatrossHandler {
     pub blockchain: Arc<Blockchain>,
@@ -38,7 +38,7 @@ impl BlockchainAlbatrossHandler {
 
     /// Returns the current epoch number.
     pub(crate) fn epoch_number(&self, _params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
-        Ok(policy::epoch_at(self.blockchain.height()).into())
+        Ok(self.blockchain.policy.epoch_at(self.blockchain.height()).into())
     }
 
     /// Returns a block object for a block hash.
@@ -126,7 +126,7 @@ impl BlockchainAlbatrossHandler {
         // Check if it's not a macro block
         //
         // TODO: Macro blocks have a slot too. It's just only for the proposal.
-        if policy::is_macro_block_at(block_number) {
+        if self.blockchain.policy.is_macro_block_at(block_number) {
             // TODO: Macro blocks have a proposer
             return Err(object!{"message" => "Block is a macro block"});
         }
@@ -196,8 +196,32 @@ impl BlockchainAlbatrossHandler {
     /// - transaction (string): Hex encoded transaction.
     ///
     /// Returns an info object:
-    pub(crate) fn get_raw_transaction_info(&self, _params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
-        /*
+    /// ```text
+    /// {
+    ///     hash: string,
+    ///     from: string, // hex encoded
+    ///     fromAddress: string, // user friendly address
+    ///     fromType: number,
+    ///     to: string, // hex encoded
+    ///     toAddress: string, // user friendly address
+    ///     toType: number,
+    ///     value: number,
+    ///     fee: number,
+    ///     data: string,
+    ///     flags: number,
+    ///     validityStartHeight: number,
+    ///
+    ///     blockHash: string|null,
+    ///     blockNumber: number|null,
+    ///     timestamp: number|null, // in milliseconds
+    ///     confirmations: number|null,
+    ///     transactionIndex: number|null,
+    ///
+    ///     valid: boolean,
+    ///     inMempool: boolean,
+    /// }
+    /// ```
+    pub(crate) fn get_raw_transaction_info(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
         let transaction: Transaction = params.get(0).unwrap_or(&Null).as_str()
             .ok_or_else(|| object!{"message" => "Raw transaction data must be a string"}) // Result<&str, Err>
             .and_then(|s| hex::decode(s)
@@ -205,29 +229,21 @@ impl BlockchainAlbatrossHandler {
             .and_then(|b| Deserialize::deserialize_from_vec(&b)
                 .map_err(|_| object!{"message" => "Invalid transaction data"}))?;
 
-        let (transaction, valid, in_mempool) =
+        // This handler has no access to the mempool, so we can only tell whether the transaction
+        // was already included in the chain.
+        let (mut transaction, valid, in_mempool) =
             if let Ok(live_transaction) = self.get_transaction_by_hash_helper(&transaction.hash::<Blake2bHash>()) {
-                let confirmations = live_transaction["confirmations"].as_u32()
-                    .expect("Function didn't return transaction with confirmation number");
-                (live_transaction, true, confirmations == 0)
+                (live_transaction, true, false)
             }
             else {
                 (transaction_to_obj(&transaction, None, None),
                  transaction.verify(self.blockchain.network_id).is_ok(), false)
             };
 
-        // Insert `valid` and `in_mempool` into `transaction` object.
-        match transaction {
-            // This should always be an object
-            JsonValue::Object(mut o) => {
-                o.insert("valid", JsonValue::Boolean(valid));
-                o.insert("inMempool", JsonValue::Boolean(in_mempool));
-            }
-            _ => unreachable!()
-        };
-        */
+        transaction["valid"] = valid.into();
+        transaction["inMempool"] = in_mempool.into();
 
-        rpc_not_implemented()
+        Ok(transaction)
     }
 
     /// Retrieves information about a transaction by its hash.
@@ -252,14 +268,19 @@ impl BlockchainAlbatrossHandler {
     ///
     ///     blockHash: string,
     ///     blockNumber: number,
-    ///     timestamp: number,
-    ///     timestampMillis: number,
+    ///     timestamp: number, // in milliseconds
     ///     confirmations: number,
     ///     transactionIndex: number,
     /// }
     /// ```
-    pub(crate) fn get_transaction_by_hash(&self, _params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
-        rpc_not_implemented()
+    ///
+    /// Transactions that were synced through macro blocks are reported with the hash and number
+    /// of the macro block of their epoch and their index within the epoch.
+    pub(crate) fn get_transaction_by_hash(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        params.get(0)
+            .ok_or(object!{"message" => "First argument must be hash"})
+            .and_then(parse_hash)
+            .and_then(|h| self.get_transaction_by_hash_helper(&h))
     }
 
     /// Retrieves a transaction receipt by its hash.
@@ -272,45 +293,37 @@ impl BlockchainAlbatrossHandler {
     ///     transactionHash: string,
     ///     blockHash: string,
     ///     blockNumber: number,
-    ///     timestamp: number,
-    ///     timestampMillis: number,
+    ///     timestamp: number, // in milliseconds
     ///     confirmations: number,
     ///     transactionIndex: number,
     /// }
     /// ```
-    pub(crate) fn get_transaction_receipt(&self, _params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
-        /*
-        let hash = params.get(0).and_then(JsonValue::as_str)
-            .ok_or_else(|| object!{"message" => "Invalid transaction hash"})
-            .and_then(|s| Blake2bHash::from_str(s)
-                .map_err(|_| object!{"message" => "Invalid transaction hash"}))?;
+    pub(crate) fn get_transaction_receipt(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let hash = params.get(0)
+            .ok_or(object!{"message" => "First argument must be hash"})
+            .and_then(parse_hash)?;
 
         let transaction_info = self.blockchain.get_transaction_info_by_hash(&hash)
             .ok_or_else(|| object!{"message" => "Transaction not found"})?;
 
-        // Get block which contains the transaction. If we don't find the block (for what reason?),
-        // return an error
-        let block = self.blockchain.get_block(&transaction_info.block_hash, false, true);
-
-        let transaction_index = transaction_info.index;
-        Ok(self.transaction_receipt_to_obj(&transaction_info.into(),
-                                           Some(transaction_index),
-                                           block.as_ref()))
-        */
-        rpc_not_implemented()
+        Ok(self.transaction_receipt_to_obj(&transaction_info))
     }
 
     // Accounts
 
-    // Lists all stakes
+    // Lists all validators and stakes
     pub(crate) fn list_stakes(&self, _params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
-        let genesis_account = NetworkInfo::from_network_id(self.blockchain.network_id)
+        let genesis_account = self.blockchain.network_info
             .validator_registry_address().unwrap();
         let account = self.blockchain.get_account(&genesis_account);
         let contract = match account {
             Account::Staking(c) => c,
             _ => return Err("No contract at staking contract address".into()),
         };
+        let validators: Vec<JsonValue> = contract.validators_by_address
+            .values()
+            .map(|validator| BlockchainAlbatrossHandler::validator_to_obj(validator.borrow()))
+            .collect();
         let active_stakes: Vec<JsonValue> = contract.active_stake_by_address
             .values()
             .map(|stake| BlockchainAlbatrossHandler::active_stake_to_obj(stake.borrow()))
@@ -320,6 +333,7 @@ impl BlockchainAlbatrossHandler {
             .map(|(address, stake)| BlockchainAlbatrossHandler::inactive_stake_to_obj(address, stake))
             .collect();
         Ok(object! {
+            "validators" => validators,
             "activeStakes" => active_stakes,
             "inactiveStakes" => inactive_stakes,
         })
@@ -327,6 +341,31 @@ impl BlockchainAlbatrossHandler {
 
     // Helper functions
 
+    fn get_transaction_by_hash_helper(&self, hash: &Blake2bHash) -> Result<JsonValue, JsonValue> {
+        // Get transaction info, which includes the transaction itself and its block hash, block
+        // number and index. Return an error if the transaction doesn't exist.
+        let transaction_info = self.blockchain.get_transaction_info_by_hash(hash)
+            .ok_or_else(|| object!{"message" => "Transaction not found"})?;
+
+        Ok(transaction_to_obj(&transaction_info.transaction, Some(&TransactionContext {
+            block_hash: &transaction_info.block_hash.to_hex(),
+            block_number: transaction_info.block_number,
+            index: transaction_info.index,
+            timestamp: transaction_info.timestamp,
+        }), Some(self.blockchain.height())))
+    }
+
+    fn transaction_receipt_to_obj(&self, transaction_info: &TransactionInfo) -> JsonValue {
+        object!{
+            "transactionHash" => transaction_info.transaction_hash.to_hex(),
+            "blockNumber" => transaction_info.block_number,
+            "blockHash" => transaction_info.block_hash.to_hex(),
+            "confirmations" => self.blockchain.height() - transaction_info.block_number,
+            "timestamp" => transaction_info.timestamp,
+            "transactionIndex" => transaction_info.index,
+        }
+    }
+
     fn proof_to_object<M: signed::Message>(proof: &signed::AggregateProof<M>) -> JsonValue {
         object!{
             "signature" => format!("{}", proof.signature),
@@ -367,7 +406,7 @@ impl BlockchainAlbatrossHandler {
                     "hash" => hash.clone(),
                     "blockNumber" => block.header.block_number,
                     "viewNumber" => block.header.view_number,
-                    "epoch" => policy::epoch_at(block.header.block_number),
+                    "epoch" => self.blockchain.policy.epoch_at(block.header.block_number),
                     "parentMacroHash" => block.header.parent_macro_hash.to_hex(),
                     "parentHash" => block.header.parent_hash.to_hex(),
                     "seed" => block.header.seed.to_string(),
@@ -389,7 +428,7 @@ impl BlockchainAlbatrossHandler {
                     "hash" => hash.clone(),
                     "blockNumber" => block.header.block_number,
                     "viewNumber" => block.header.view_number,
-                    "epoch" => policy::epoch_at(block.header.block_number),
+                    "epoch" => self.blockchain.policy.epoch_at(block.header.block_number),
                     "parentHash" => block.header.parent_hash.to_hex(),
                     "stateRoot" => block.header.state_root.to_hex(),
                     "extrinsicsRoot" => block.header.extrinsics_root.to_hex(),
@@ -401,7 +440,7 @@ impl BlockchainAlbatrossHandler {
                         body.transactions.iter().enumerate().map(|(i, tx)| transaction_to_obj(tx, Some(&TransactionContext {
                             block_hash: &hash,
                             block_number: block.header.block_number,
-                            index: i as u16,
+                            index: i as u32,
                             timestamp: block.header.timestamp,
                         }), Some(blockchain_height ))).collect()
                     } else {
@@ -453,16 +492,22 @@ impl BlockchainAlbatrossHandler {
         }
     }
 
+    fn validator_to_obj(validator: &Validator) -> JsonValue {
+        object! {
+            "validatorAddress" => validator.validator_address.to_user_friendly_address(),
+            "balance" => u64::from(validator.balance),
+            "publicKey" => hex::encode(&validator.validator_key),
+            "rewardAddress" => validator.reward_address().to_user_friendly_address(),
+            "commission" => validator.commission,
+            "active" => validator.active,
+        }
+    }
+
     fn active_stake_to_obj(stake: &ActiveStake) -> JsonValue {
-        let reward_address = match stake.reward_address {
-            Some(ref a) => a,
-            None => &stake.staker_address,
-        }.to_user_friendly_address();
         object! {
             "stakerAddress" => stake.staker_address.to_user_friendly_address(),
             "balance" => u64::from(stake.balance),
-            "publicKey" => hex::encode(&stake.validator_key),
-            "rewardAddress" => reward_address,
+            "validatorAddress" => stake.validator_address.to_user_friendly_address(),
         }
     }
 
@@ -224,7 +224,7 @@ impl BlockchainNimiqHandler {
                 body.transactions.iter().enumerate().map(|(i, tx)| transaction_to_obj(tx, Some(&TransactionContext {
                     block_hash: &hash,
                     block_number: block.header.height,
-                    index: i as u16,
+                    index: i as u32,
                     timestamp: block.header.timestamp_in_millis(),
                 }), Some(height))).collect()
             } else {
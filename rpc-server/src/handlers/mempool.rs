@@ -58,35 +58,74 @@ impl<P: ConsensusProtocol + 'static> MempoolHandler<P> {
     /// ```text
     /// {
     ///     total: number,
-    ///     buckets: Array<number>,
+    ///     size: number, // total size of the transactions in bytes
+    ///     minFeePerByte: number, // minimum fee/byte for new transactions
+    ///     buckets: Array<number>, // non-empty buckets
+    ///     histogram: Array<{
+    ///         feePerByte: number, // lower bound of the bucket
+    ///         count: number,
+    ///         size: number,
+    ///     }>,
     /// }
     /// ```
+    /// Additionally, the number of transactions is reported with the bucket as key for all
+    /// non-empty buckets.
     pub(crate) fn mempool(&self, _params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
-        // Transactions sorted by fee/byte, ascending
-        let transactions = self.mempool.get_transactions(usize::max_value(), 0f64);
-        let bucket_values: [u64; 14] = [0, 1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000, 10000];
-        let mut bucket_counts: [u32; 14] = [0; 14];
-        let mut i = 0;
-
-        for transaction in &transactions {
-            while (transaction.fee_per_byte() as u64) < bucket_values[i] { i += 1; }
-            bucket_counts[i] += 1;
-        }
+        let histogram = self.mempool.fee_histogram();
 
         let mut transactions_per_bucket = Object::new();
         let mut buckets = Array::new();
-        transactions_per_bucket.insert("total", transactions.len().into());
-        for (&value, &count) in bucket_values.iter().zip(&bucket_counts) {
-            if count > 0 {
-                transactions_per_bucket.insert(value.to_string().as_str(), JsonValue::from(count));
-                buckets.push(value.into());
+        transactions_per_bucket.insert("total", histogram.iter().map(|bucket| bucket.count).sum::<usize>().into());
+        transactions_per_bucket.insert("size", self.mempool.size().into());
+        transactions_per_bucket.insert("minFeePerByte", self.mempool.min_fee_per_byte().into());
+        for bucket in histogram.iter() {
+            if bucket.count > 0 {
+                transactions_per_bucket.insert(bucket.fee_per_byte_min.to_string().as_str(), bucket.count.into());
+                buckets.push(bucket.fee_per_byte_min.into());
             }
         }
         transactions_per_bucket.insert("buckets", JsonValue::Array(buckets));
+        transactions_per_bucket.insert("histogram", JsonValue::Array(histogram.iter()
+            .map(|bucket| object! {
+                "feePerByte" => bucket.fee_per_byte_min,
+                "count" => bucket.count,
+                "size" => bucket.size,
+            })
+            .collect()));
 
         Ok(JsonValue::Object(transactions_per_bucket))
     }
 
+    /// Estimates the fee for a transaction to be included within a number of blocks, based on
+    /// the fees in recent blocks and the transactions in the mempool.
+    /// Parameters:
+    /// - targetBlocks (number, optional): Default is `1`.
+    ///
+    /// ```text
+    /// {
+    ///     targetBlocks: number,
+    ///     feePerByte: number, // in Luna
+    ///     fee: number, // for a basic transaction, in Luna
+    /// }
+    /// ```
+    pub(crate) fn estimate_fee(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let target_blocks = match params.get(0) {
+            Some(value) if !value.is_null() => value.as_u32()
+                .filter(|&target_blocks| target_blocks > 0)
+                .ok_or_else(|| object! {"message" => "Invalid number of target blocks"})?,
+            _ => 1,
+        };
+
+        let fee_per_byte = self.mempool.estimate_fee(target_blocks);
+        let fee = (fee_per_byte * Transaction::MIN_SIZE as f64).ceil() as u64;
+
+        Ok(object! {
+            "targetBlocks" => target_blocks,
+            "feePerByte" => fee_per_byte,
+            "fee" => fee,
+        })
+    }
+
     /// Sends a raw transaction.
     /// Parameters:
     /// - transaction (string)
@@ -215,6 +254,57 @@ impl<P: ConsensusProtocol + 'static> MempoolHandler<P> {
         }
     }
 
+    /// Replaces a pending transaction by the same transaction with a higher fee.
+    /// Requires the sender account to be a basic account and to be unlocked.
+    /// The original transaction can still be mined by nodes that received it before the
+    /// replacement. The replacement is then evicted from the mempool, but both transactions can
+    /// be mined if they reach different block producers.
+    /// Parameters:
+    /// - transactionHash (string)
+    /// - fee (number, optional): The new fee in Luna. Default is the minimum fee that is
+    ///     required to replace the transaction.
+    ///
+    /// Returns the hash of the new transaction.
+    pub(crate) fn bump_fee(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let hash = params.get(0).and_then(JsonValue::as_str)
+            .ok_or_else(|| object! {"message" => "Invalid transaction hash"})
+            .and_then(|s| Blake2bHash::from_str(s)
+                .map_err(|_| object! {"message" => "Invalid transaction hash"}))?;
+        let transaction = self.mempool.get_transaction(&hash)
+            .ok_or_else(|| object! {"message" => "Transaction is not in the mempool"})?;
+
+        if transaction.sender_type != AccountType::Basic {
+            return Err(object! {"message" => "Sender account is not a basic account"});
+        }
+
+        // The replacement has the same size, since only the fee and the signature change.
+        let size = transaction.serialized_size() as f64;
+        let min_fee_per_byte = self.mempool.replacement_fee_per_byte_min(&transaction)
+            .max(self.mempool.min_fee_per_byte());
+        let min_fee = (min_fee_per_byte * size).ceil() as u64;
+        let fee = match params.get(1) {
+            Some(value) if !value.is_null() => value.as_u64()
+                .ok_or_else(|| object! {"message" => "Invalid fee"})?,
+            _ => min_fee,
+        };
+        if fee < min_fee {
+            return Err(object! {"message" => format!("Fee too low, at least {} Luna are required", min_fee)});
+        }
+
+        let mut replacement = Transaction::clone(&transaction);
+        replacement.fee = Coin::try_from(fee)
+            .map_err(|e| object! {"message" => format!("Invalid fee: {}", e)})?;
+
+        let unlocked_wallets = self.unlocked_wallets.as_ref()
+            .ok_or_else(|| object! {"message" => "No wallets"})?;
+        let unlocked_wallets = unlocked_wallets.read();
+        let wallet_account = unlocked_wallets.get(&replacement.sender)
+            .ok_or_else(|| object! {"message" => "Sender account is locked"})?;
+        wallet_account.sign_transaction(&mut replacement);
+
+        self.push_transaction(replacement)
+    }
+
     // Helper functions
 
     pub(crate) fn push_transaction(&self, transaction: Transaction) -> Result<JsonValue, JsonValue> {
@@ -226,7 +316,7 @@ impl<P: ConsensusProtocol + 'static> MempoolHandler<P> {
     }
 }
 
-pub(crate) fn transaction_to_obj(transaction: &Transaction, context: Option<&TransactionContext>, head_height: Option<u32>) -> JsonValue {
+pub fn transaction_to_obj(transaction: &Transaction, context: Option<&TransactionContext>, head_height: Option<u32>) -> JsonValue {
     object! {
         "hash" => transaction.hash::<Blake2bHash>().to_hex(),
         "blockHash" => context.map(|c| c.block_hash.into()).unwrap_or(Null),
@@ -284,8 +374,7 @@ pub(crate) fn obj_to_transaction(obj: &JsonValue, current_height: u32, network_i
         .ok_or_else(|| object! {"message" => "Invalid transaction value"})?)
         .map_err(|_| object! {"message" => "Invalid transaction value"})?;
 
-    // FIXME Wrong fee
-    let fee = Coin::try_from(obj["value"].as_u64()
+    let fee = Coin::try_from(obj["fee"].as_u64()
         .ok_or_else(|| object! {"message" => "Invalid transaction fee"})?)
         .map_err(|_| object! {"message" => "Invalid transaction fee"})?;
 
@@ -316,10 +405,10 @@ pub(crate) fn obj_to_transaction(obj: &JsonValue, current_height: u32, network_i
     }
 }
 
-pub(crate) struct TransactionContext<'a> {
+pub struct TransactionContext<'a> {
     pub block_hash: &'a str,
     pub block_number: u32,
-    pub index: u16,
+    pub index: u32,
     pub timestamp: u64, // Milliseconds
 }
 
@@ -332,5 +421,7 @@ impl<P: ConsensusProtocol + 'static> Module for MempoolHandler<P> {
         "mempoolContent" => mempool_content,
         "mempool" => mempool,
         "getMempoolTransaction" => get_transaction,
+        "bumpFee" => bump_fee,
+        "estimateFee" => estimate_fee,
     }
 }
@@ -9,12 +9,11 @@ use blockchain_albatross::Blockchain;
 use bls::bls12_381::{CompressedPublicKey, CompressedSignature};
 use consensus::AlbatrossConsensusProtocol;
 use keys::Address;
-use network_primitives::networks::NetworkInfo;
 use nimiq_mempool::Mempool;
 use primitives::account::AccountType;
 use primitives::coin::Coin;
 use transaction::Transaction;
-use transaction::account::staking_contract::StakingTransactionData;
+use transaction::account::staking_contract::{MAX_COMMISSION, StakingTransactionData, StakingTransactionType, UpdateValidatorData, ValidatorKeyUpdate};
 
 use crate::handler::Method;
 use crate::handlers::Module;
@@ -46,55 +45,46 @@ impl MempoolAlbatrossHandler {
                 .map_err(|_| object! {"message" => format!("Invalid {} address", kind)}))
     }
 
-    /// Stakes NIM
-    /// Parameters:
-    /// - validator_key: Public key of validator (BLS)
-    /// - proof_of_knowledge: Proof of knowledge of validator key
-    /// - staker_address: NIM address used to stake
-    /// - amount: Amount in Luna to stake
-    /// - reward_address: NIM address to send rewards to (optional)
-    /// - fee: Fee for transaction in Luna
-    pub(crate) fn stake(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
-        let validator_key = params.get(0)
-            .and_then(JsonValue::as_str)
+    fn parse_amount(value: Option<&JsonValue>) -> Result<Coin, JsonValue> {
+        value.and_then(JsonValue::as_u64)
+            .ok_or_else(|| object! {"message" => "Invalid amount"})
+            .and_then(|it| Coin::try_from(it)
+                .map_err(|e| object! {"message" => format!("Invalid amount: {}", e)}))
+    }
+
+    fn parse_fee(value: Option<&JsonValue>) -> Result<Coin, JsonValue> {
+        value.and_then(JsonValue::as_u64)
+            .unwrap_or(0)
+            .try_into()
+            .map_err(|e| object! {"message" => format!("Invalid fee: {}", e)})
+    }
+
+    fn parse_validator_key(value: Option<&JsonValue>) -> Result<CompressedPublicKey, JsonValue> {
+        value.and_then(JsonValue::as_str)
             .ok_or_else(|| object! {"message" => "Invalid validator key"})
             .and_then(|it| hex::decode(it)
                 .map_err(|_| object! {"message" => "Validator key must be hex-encoded"}))
             .and_then(|it| CompressedPublicKey::deserialize_from_vec(&it)
-                .map_err(|_| object! {"message" => "Invalid public key"}))?;
-        let proof_of_knowledge = params.get(1)
-            .and_then(JsonValue::as_str)
+                .map_err(|_| object! {"message" => "Invalid public key"}))
+    }
+
+    fn parse_proof_of_knowledge(value: Option<&JsonValue>) -> Result<CompressedSignature, JsonValue> {
+        value.and_then(JsonValue::as_str)
             .ok_or_else(|| object! {"message" => "Invalid proof of knowledge"})
             .and_then(|it| hex::decode(it)
                 .map_err(|_| object! {"message" => "Proof of knowledge must be hex-encoded"}))
             .and_then(|it| CompressedSignature::deserialize_from_vec(&it)
-                .map_err(|_| object! {"message" => "Invalid proof of knowledge"}))?;
-        let staker_address = Self::parse_address(params.get(2).unwrap_or(&Null), "staker")?;
-        let amount = params.get(3)
-            .and_then(JsonValue::as_u64)
-            .ok_or_else(|| object! {"message" => "Invalid amount"})
-            .and_then(|it| Coin::try_from(it)
-                .map_err(|e| object! {"message" => format!("Invalid amount: {}", e)}))?;
-        let reward_address = if let Some(value) = params.get(4) {
-            Some(Self::parse_address(value, "reward")?)
-        } else { None };
-        let fee = params.get(5)
-            .and_then(JsonValue::as_u64)
-            .unwrap_or(0)
-            .try_into()
-            .map_err(|e| object! {"message" => format!("Invalid fee: {}", e)})?;
+                .map_err(|_| object! {"message" => "Invalid proof of knowledge"}))
+    }
 
+    /// Signs and pushes a transaction into the staking contract.
+    fn push_staking_transaction(&self, sender: Address, amount: Coin, fee: Coin, staking_data: StakingTransactionData) -> Result<JsonValue, JsonValue> {
         let network_id = self.mempool.network_id();
-        let staking_contract = NetworkInfo::from_network_id(network_id)
+        let staking_contract = self.mempool.network_info()
             .validator_registry_address().unwrap();
-        let staking_data = StakingTransactionData {
-            validator_key,
-            reward_address,
-            proof_of_knowledge,
-        };
 
         let mut tx = Transaction::new_extended(
-            staker_address, AccountType::Basic,    // sender
+            sender, AccountType::Basic,    // sender
             staking_contract.clone(), AccountType::Staking, // recipient
             amount, fee,    // amount, fee
             staking_data.serialize_to_vec(),       // data
@@ -114,27 +104,78 @@ impl MempoolAlbatrossHandler {
         self.generic.push_transaction(tx)
     }
 
+    /// Registers a validator and stakes NIM with it
+    /// Parameters:
+    /// - validator_key: Public key of validator (BLS)
+    /// - proof_of_knowledge: Proof of knowledge of validator key
+    /// - validator_address: NIM address used to register the validator and to stake. It identifies the validator.
+    /// - amount: Amount in Luna to stake
+    /// - reward_address: NIM address to send rewards to (optional)
+    /// - commission: Share of the rewards kept by the validator, in basis points (optional)
+    /// - fee: Fee for transaction in Luna
+    pub(crate) fn register_validator(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let validator_key = Self::parse_validator_key(params.get(0))?;
+        let proof_of_knowledge = Self::parse_proof_of_knowledge(params.get(1))?;
+        let validator_address = Self::parse_address(params.get(2).unwrap_or(&Null), "validator")?;
+        let amount = Self::parse_amount(params.get(3))?;
+        let reward_address = match params.get(4) {
+            Some(value) if !value.is_null() => Some(Self::parse_address(value, "reward")?),
+            _ => None,
+        };
+        let commission = match params.get(5) {
+            Some(value) if !value.is_null() => value.as_u16()
+                .filter(|commission| *commission <= MAX_COMMISSION)
+                .ok_or_else(|| object! {"message" => "Invalid commission"})?,
+            _ => 0,
+        };
+        let fee = Self::parse_fee(params.get(6))?;
+
+        let staking_data = StakingTransactionData::RegisterValidator {
+            validator_key,
+            reward_address,
+            commission,
+            proof_of_knowledge,
+        };
+
+        self.push_staking_transaction(validator_address, amount, fee, staking_data)
+    }
+
+    /// Stakes NIM by delegating them to a registered validator
+    /// Parameters:
+    /// - validator_address: NIM address identifying the validator
+    /// - staker_address: NIM address used to stake
+    /// - amount: Amount in Luna to stake
+    /// - fee: Fee for transaction in Luna
+    pub(crate) fn stake(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let validator_address = Self::parse_address(params.get(0).unwrap_or(&Null), "validator")?;
+        let staker_address = Self::parse_address(params.get(1).unwrap_or(&Null), "staker")?;
+        let amount = Self::parse_amount(params.get(2))?;
+        let fee = Self::parse_fee(params.get(3))?;
+
+        let staking_data = StakingTransactionData::Stake {
+            validator_address,
+        };
+
+        self.push_staking_transaction(staker_address, amount, fee, staking_data)
+    }
+
     /// Retires staked NIM
     /// Parameters:
     /// - staker_address: NIM address used to stake
     /// - amount: Amount to unstake
     pub(crate) fn retire(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
         let staker_address = Self::parse_address(params.get(0).unwrap_or(&Null), "staker")?;
-        let amount = params.get(1)
-            .and_then(JsonValue::as_u64)
-            .ok_or_else(|| object! {"message" => "Invalid amount"})
-            .and_then(|it| Coin::try_from(it)
-                .map_err(|e| object! {"message" => format!("Invalid amount: {}", e)}))?;
+        let amount = Self::parse_amount(params.get(1))?;
 
         let network_id = self.mempool.network_id();
-        let genesis_account = NetworkInfo::from_network_id(network_id)
+        let genesis_account = self.mempool.network_info()
             .validator_registry_address().unwrap();
 
         let mut tx = Transaction::new_extended(
             genesis_account.clone(), AccountType::Staking,
             genesis_account.clone(), AccountType::Staking,
             amount, Coin::try_from(0).unwrap(), // amount, fee
-            vec![],                             // data
+            StakingTransactionType::Retire.serialize_to_vec(), // data
             self.mempool.current_height(),      // validity_start_height
             network_id,                         // network_id
         );
@@ -149,20 +190,72 @@ impl MempoolAlbatrossHandler {
         self.generic.push_transaction(tx)
     }
 
+    /// Updates the key and/or reward address of a validator. The fee is paid from the validator's
+    /// own stake. The new key must not be in use by any validator.
+    /// Parameters:
+    /// - validator_address: NIM address identifying the validator
+    /// - validator_key: New public key of validator (BLS) or null
+    /// - proof_of_knowledge: Proof of knowledge of the new validator key or null
+    /// - reward_address: New NIM address to send rewards to or null
+    /// - fee: Fee for transaction in Luna
+    pub(crate) fn update_validator(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let validator_address = Self::parse_address(params.get(0).unwrap_or(&Null), "validator")?;
+        let new_key = match params.get(1) {
+            Some(value) if !value.is_null() => Some(ValidatorKeyUpdate {
+                validator_key: Self::parse_validator_key(Some(value))?,
+                proof_of_knowledge: Self::parse_proof_of_knowledge(params.get(2))?,
+            }),
+            _ => None,
+        };
+        let new_reward_address = match params.get(3) {
+            Some(value) if !value.is_null() => Some(Self::parse_address(value, "reward")?),
+            _ => None,
+        };
+        let fee = Self::parse_fee(params.get(4))?;
+
+        let update_data = UpdateValidatorData {
+            new_key,
+            new_reward_address,
+        };
+
+        let network_id = self.mempool.network_id();
+        let staking_contract = self.mempool.network_info()
+            .validator_registry_address().unwrap();
+
+        // The value is returned to the contract, but transactions can't have a zero value.
+        let amount = Coin::from_u64_unchecked(1);
+
+        let mut tx = Transaction::new_extended(
+            staking_contract.clone(), AccountType::Staking,
+            staking_contract.clone(), AccountType::Staking,
+            amount, fee,                        // amount, fee
+            update_data.to_data(),              // data
+            self.mempool.current_height(),      // validity_start_height
+            network_id,                         // network_id
+        );
+
+        debug!("Transaction data: {:#?}", update_data);
+
+        let unlocked_wallets = self.unlocked_wallets.as_ref()
+            .ok_or_else(|| object! {"message" => "No wallets"})?;
+        let unlocked_wallets = unlocked_wallets.read();
+        let wallet_account = unlocked_wallets.get(&validator_address)
+            .ok_or_else(|| object! {"message" => "Sender account is locked"})?;
+        wallet_account.sign_transaction(&mut tx);
+
+        self.generic.push_transaction(tx)
+    }
+
     /// Unstakes NIM
     /// Parameters:
     /// - staker_address: NIM address used to stake
     /// - amount: Amount to unstake
     pub(crate) fn unstake(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
         let staker_address = Self::parse_address(params.get(0).unwrap_or(&Null), "staker")?;
-        let amount = params.get(1)
-            .and_then(JsonValue::as_u64)
-            .ok_or_else(|| object! {"message" => "Invalid amount"})
-            .and_then(|it| Coin::try_from(it)
-                .map_err(|e| object! {"message" => format!("Invalid amount: {}", e)}))?;
+        let amount = Self::parse_amount(params.get(1))?;
 
         let network_id = self.mempool.network_id();
-        let staking_contract = NetworkInfo::from_network_id(network_id)
+        let staking_contract = self.mempool.network_info()
             .validator_registry_address().unwrap();
 
         let mut tx = Transaction::new_extended(
@@ -193,8 +286,12 @@ impl Module for MempoolAlbatrossHandler {
         "sendTransaction" => generic.send_transaction,
         "mempoolContent" => generic.mempool_content,
         "mempool" => generic.mempool,
+        "bumpFee" => generic.bump_fee,
+        "estimateFee" => generic.estimate_fee,
+        "registerValidator" => register_validator,
         "stake" => stake,
         "retire" => retire,
+        "updateValidator" => update_validator,
         "unstake" => unstake,
         "getTransaction" => generic.get_transaction,
     }
@@ -9,7 +9,8 @@ use parking_lot::RwLock;
 use beserial::{Deserialize, Serialize};
 use keys::{Address, KeyPair, PrivateKey, PublicKey, Signature};
 use nimiq_database::Environment;
-use nimiq_wallet::{WalletAccount, WalletStore};
+use nimiq_mnemonic::Mnemonic;
+use nimiq_wallet::{HdWallet, WalletAccount, WalletStore};
 use utils::otp::{Locked, Unlocked};
 
 use crate::handler::Method;
@@ -257,6 +258,158 @@ impl WalletHandler {
         Ok(JsonValue::Boolean(WalletAccount::verify_message(&public_key, &message, &signature)))
     }
 
+    /// Creates a new HD wallet from a random mnemonic.
+    /// Parameters:
+    /// - passphrase (optional, string): The passphrase to lock the wallet with.
+    /// - mnemonicPassword (optional, string): The password of the mnemonic, which is required
+    ///     together with the mnemonic to restore the wallet.
+    ///
+    /// The return value is an object:
+    /// ```text
+    /// {
+    ///     id: string, // user friendly address of the first account
+    ///     mnemonic: string,
+    /// }
+    /// ```
+    pub(crate) fn create_hd_wallet(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        // FIXME: We're not clearing the passphrase right now.
+        let passphrase = params.get(0).map(|s: &JsonValue| s.as_str()
+                .ok_or_else(|| object!{"message" => "Passphrase must be a string"})
+            ).unwrap_or_else(|| Ok(""))?;
+        let mnemonic_password = Self::mnemonic_password(params.get(1))?;
+
+        let wallet = HdWallet::generate(mnemonic_password)
+            .map_err(|e| object!{"message" => e.to_string()})?;
+        let mnemonic = wallet.mnemonic();
+        let id = self.put_hd_wallet(wallet, passphrase)?;
+
+        Ok(object!{
+            "id" => id.to_user_friendly_address(),
+            "mnemonic" => mnemonic.to_string(),
+        })
+    }
+
+    /// Imports an HD wallet from a mnemonic.
+    /// Parameters:
+    /// - mnemonic (string): The words of the mnemonic, separated by spaces.
+    /// - passphrase (optional, string): The passphrase to lock the wallet with.
+    /// - mnemonicPassword (optional, string): The password of the mnemonic.
+    /// Returns the id of the wallet, i.e. the user friendly address of its first account.
+    pub(crate) fn import_mnemonic(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let mnemonic: Mnemonic = params.get(0).unwrap_or(&Null).as_str()
+            .ok_or_else(|| object!{"message" => "Mnemonic must be a string"})?
+            .trim().parse()
+            .map_err(|_| object!{"message" => "Invalid mnemonic"})?;
+
+        // FIXME: We're not clearing the passphrase right now.
+        let passphrase = params.get(1).map(|s: &JsonValue| s.as_str()
+                .ok_or_else(|| object!{"message" => "Passphrase must be a string"})
+            ).unwrap_or_else(|| Ok(""))?;
+        let mnemonic_password = Self::mnemonic_password(params.get(2))?;
+
+        let wallet = HdWallet::from_mnemonic(&mnemonic, mnemonic_password)
+            .map_err(|e| object!{"message" => e.to_string()})?;
+        let id = self.put_hd_wallet(wallet, passphrase)?;
+
+        Ok(JsonValue::String(id.to_user_friendly_address()))
+    }
+
+    /// Derives an account from an HD wallet at Nimiq's standard derivation path and adds it to
+    /// the accounts in the store. The account is locked with the passphrase of the wallet.
+    /// Parameters:
+    /// - id (string): The id of the wallet.
+    /// - index (number): The index of the account.
+    /// - passphrase (optional, string): The passphrase of the wallet.
+    ///
+    /// The return value is an object:
+    /// ```text
+    /// {
+    ///     address: string,
+    ///     publicKey: string,
+    ///     path: string,
+    /// }
+    /// ```
+    pub(crate) fn derive_account(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let id = Self::hd_wallet_id(params.get(0))?;
+        let index = params.get(1).and_then(JsonValue::as_u32)
+            .filter(|&index| index < 0x8000_0000)
+            .ok_or_else(|| object!{"message" => "Invalid account index"})?;
+
+        // FIXME: We're not clearing the passphrase right now.
+        let passphrase = params.get(2).map(|s: &JsonValue| s.as_str()
+                .ok_or_else(|| object!{"message" => "Passphrase must be a string"})
+            ).unwrap_or_else(|| Ok(""))?;
+
+        let wallet = self.unlock_hd_wallet(&id, passphrase)?;
+        let account = wallet.derive_account(index)
+            .ok_or_else(|| object!{"message" => "Account can't be derived"})?;
+        let address = account.address.clone();
+        let public_key = account.key_pair.public;
+        let locked_account = Locked::with_defaults(account, passphrase.as_bytes())
+            .map_err(|e| object!{"message" => format!("Error while importing: {:?}", e)})?;
+
+        let mut txn = self.wallet_store.create_write_transaction();
+        self.wallet_store.put(&address, &locked_account, &mut txn);
+        txn.commit();
+
+        Ok(object!{
+            "address" => address.to_user_friendly_address(),
+            "publicKey" => public_key.to_hex(),
+            "path" => HdWallet::derivation_path(index),
+        })
+    }
+
+    /// Returns the mnemonic of an HD wallet.
+    /// Parameters:
+    /// - id (string): The id of the wallet.
+    /// - passphrase (optional, string): The passphrase of the wallet.
+    pub(crate) fn export_mnemonic(&self, params: &[JsonValue]) -> Result<JsonValue, JsonValue> {
+        let id = Self::hd_wallet_id(params.get(0))?;
+
+        // FIXME: We're not clearing the passphrase right now.
+        let passphrase = params.get(1).map(|s: &JsonValue| s.as_str()
+                .ok_or_else(|| object!{"message" => "Passphrase must be a string"})
+            ).unwrap_or_else(|| Ok(""))?;
+
+        let wallet = self.unlock_hd_wallet(&id, passphrase)?;
+        Ok(JsonValue::String(wallet.mnemonic().to_string()))
+    }
+
+    fn put_hd_wallet(&self, wallet: HdWallet, passphrase: &str) -> Result<Address, JsonValue> {
+        let id = wallet.id.clone();
+        let locked_wallet = Locked::with_defaults(wallet, passphrase.as_bytes())
+            .map_err(|e| object!{"message" => format!("Error while importing: {:?}", e)})?;
+
+        let mut txn = self.wallet_store.create_write_transaction();
+        self.wallet_store.put_hd_wallet(&id, &locked_wallet, &mut txn);
+        txn.commit();
+
+        Ok(id)
+    }
+
+    fn unlock_hd_wallet(&self, id: &Address, passphrase: &str) -> Result<Unlocked<HdWallet>, JsonValue> {
+        let wallet = self.wallet_store.get_hd_wallet(id, None)
+            .ok_or_else(|| object!{"message" => "Wallet does not exist"})?;
+        wallet.unlock(passphrase.as_bytes())
+            .map_err(|_| object!{"message" => "Invalid passphrase"})
+    }
+
+    fn hd_wallet_id(param: Option<&JsonValue>) -> Result<Address, JsonValue> {
+        Address::from_any_str(param
+            .unwrap_or_else(|| &Null).as_str()
+            .ok_or_else(|| object!{"message" => "Wallet id must be a string"})?)
+            .map_err(|_|  object!{"message" => "Wallet id invalid"})
+    }
+
+    fn mnemonic_password(param: Option<&JsonValue>) -> Result<Option<&str>, JsonValue> {
+        match param {
+            Some(value) if !value.is_null() => value.as_str()
+                .map(Some)
+                .ok_or_else(|| object!{"message" => "Mnemonic password must be a string"}),
+            _ => Ok(None),
+        }
+    }
+
     fn sign_message(&self, message: &[u8], wallet: &WalletAccount) -> JsonValue {
         let (public_key, signature) = wallet.sign_message(&message);
         let public_key = Serialize::serialize_to_vec(&public_key);
@@ -283,5 +436,9 @@ impl Module for WalletHandler {
 //        "sendTransaction" => send_transaction,
         "sign" => sign,
         "verifySignature" => verify_signature,
+        "createHdWallet" => create_hd_wallet,
+        "importMnemonic" => import_mnemonic,
+        "deriveAccount" => derive_account,
+        "exportMnemonic" => export_mnemonic,
     }
 }
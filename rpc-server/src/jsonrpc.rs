@@ -1,3 +1,4 @@
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use futures::{future, Future, IntoFuture, stream::Stream};
@@ -5,23 +6,32 @@ use hyper::{Body, Method, Request, Response, StatusCode};
 use hyper::header::HeaderValue;
 use json::{Array, JsonValue, Null, array, object};
 
-use crate::error::AuthenticationError;
+use crate::auth::Permissions;
+use crate::error::{AccessError, AuthenticationError};
 
 pub trait Handler: Send + Sync {
-    fn call_method(&self, name: &str, params: Array) -> Option<Result<JsonValue, JsonValue>>;
-    fn authorize(&self, _username: &str, _password: &str) -> Result<(), AuthenticationError> {
+    fn call_method(&self, name: &str, params: Array, permissions: &Permissions) -> Option<Result<JsonValue, JsonValue>>;
+    /// Authenticates a client and returns the methods it may call.
+    fn authorize(&self, _username: &str, _password: &str) -> Result<Permissions, AuthenticationError> {
+        Ok(Permissions::all())
+    }
+    /// Checks whether a request from the given IP address may be processed. This is called once
+    /// per HTTP request or websocket message.
+    fn check_access(&self, _ip: &IpAddr) -> Result<(), AccessError> {
         Ok(())
     }
 }
 
 pub struct Service<H> where H: Handler {
-    handler: Arc<H>
+    handler: Arc<H>,
+    remote_ip: IpAddr,
 }
 
 impl<H> Service<H> where H: Handler {
-    pub fn new(handler: Arc<H>) -> Self {
+    pub fn new(handler: Arc<H>, remote_ip: IpAddr) -> Self {
         Service {
             handler,
+            remote_ip,
         }
     }
 }
@@ -42,116 +52,112 @@ impl std::fmt::Display for Never {
     }
 }
 
-fn handle_request<H>(handler: Arc<H>, str_o: Result<&str, std::str::Utf8Error>) -> Response<Body> where H: Handler {
+/// Builds a JSON-RPC 2.0 error response.
+pub fn error_response(id: JsonValue, code: i32, message: &str) -> JsonValue {
+    object! {
+        "jsonrpc" => "2.0",
+        "id" => id,
+        "error" => object!{
+            "code" => code,
+            "message" => message
+        }
+    }
+}
+
+/// Processes a single JSON-RPC 2.0 request object and returns its response object.
+/// `call_method` dispatches the call and returns `None` if the method doesn't exist.
+pub fn handle_message<F>(msg: &JsonValue, call_method: F) -> JsonValue
+    where F: FnOnce(&str, Array) -> Option<Result<JsonValue, JsonValue>>
+{
+    if msg["jsonrpc"] != "2.0" || !msg.has_key("method") || !msg["method"].is_string() {
+        return error_response(msg["id"].clone(), -32600, "Invalid request");
+    }
+
+    let params = msg["params"].clone();
+    let params_array = match params {
+        JsonValue::Array(a) => a,
+        _ => vec![params]
+    };
+
+    let result_o = call_method(
+        msg["method"].as_str().unwrap(),
+        params_array,
+    );
+    if result_o.is_none() {
+        warn!("Unknown method called: {}", msg["method"]);
+        return error_response(msg["id"].clone(), -32601, "Method not found");
+    }
+
+    match result_o.unwrap() {
+        Ok(result) => object! {
+            "jsonrpc" => "2.0",
+            "id" => msg["id"].clone(),
+            "result" => result
+        },
+        Err(error) => object! {
+            "jsonrpc" => "2.0",
+            "id" => msg["id"].clone(),
+            "error" => error
+        }
+    }
+}
+
+/// Processes a parsed JSON-RPC 2.0 request, which is either a single request object or a batch
+/// of them. Returns the response (or array of responses), or an error response if the request
+/// is malformed.
+pub fn handle_json<F>(mut json: JsonValue, call_method: F) -> Result<JsonValue, JsonValue>
+    where F: Fn(&str, Array) -> Option<Result<JsonValue, JsonValue>>
+{
+    let single = json.is_object();
+    if single {
+        json = array![json];
+    }
+    if !json.is_array() {
+        return Err(error_response(Null, -32600, "Invalid request"));
+    }
+
+    let mut results: Vec<JsonValue> = json.members()
+        .map(|msg| handle_message(msg, &call_method))
+        .collect();
+
+    if single {
+        Ok(results.pop().unwrap_or(Null))
+    } else {
+        Ok(JsonValue::Array(results))
+    }
+}
+
+fn handle_request<H>(handler: Arc<H>, permissions: Permissions, str_o: Result<&str, std::str::Utf8Error>) -> Response<Body> where H: Handler {
     let mut builder = Response::builder();
     builder.header("Content-Type", "application/json");
     if str_o.is_err() {
         return builder
             .status(StatusCode::BAD_REQUEST)
-            .body(Body::from(json::stringify(object! {
-                "jsonrpc" => "2.0",
-                "id" => Null,
-                "error" => object!{
-                    "code" => -32600,
-                    "message" => "Invalid encoding"
-                }
-            })))
+            .body(Body::from(json::stringify(error_response(Null, -32600, "Invalid encoding"))))
             .unwrap();
     }
     let json_o = json::parse(str_o.unwrap());
     if json_o.is_err() {
         return builder
             .status(StatusCode::BAD_REQUEST)
-            .body(Body::from(json::stringify(object! {
-                "jsonrpc" => "2.0",
-                "id" => Null,
-                "error" => object!{
-                    "code" => -32600,
-                    "message" => "Invalid JSON"
-                }
-            })))
-            .unwrap();
-    }
-    let mut json = json_o.unwrap();
-    let single = json.is_object();
-    if single {
-        json = array![json];
-    }
-    if !json.is_array() {
-        return builder
-            .status(StatusCode::BAD_REQUEST)
-            .body(Body::from(json::stringify(object! {
-                "jsonrpc" => "2.0",
-                "id" => Null,
-                "error" => object!{
-                    "code" => -32600,
-                    "message" => "Invalid request"
-                }
-            })))
+            .body(Body::from(json::stringify(error_response(Null, -32600, "Invalid JSON"))))
             .unwrap();
     }
-    let mut results = vec![];
-    for msg in json.members() {
-        if msg["jsonrpc"] != "2.0" || !msg.has_key("method") || !msg["method"].is_string() {
-            results.push(object! {
-                "jsonrpc" => "2.0",
-                "id" => msg["id"].clone(),
-                "error" => object!{
-                    "code" => -32600,
-                    "message" => "Invalid request"
-                }
-            });
-            continue;
-        }
-
-        let params = msg["params"].clone();
-        let params_array = match params {
-            JsonValue::Array(a) => a,
-            _ => vec![params]
-        };
-
-        let result_o = handler.call_method(
-            msg["method"].as_str().unwrap(),
-            params_array,
-        );
-        if result_o.is_none() {
-            warn!("Unknown method called: {}", msg["method"]);
-            results.push(object! {
-                "jsonrpc" => "2.0",
-                "id" => msg["id"].clone(),
-                "error" => object!{
-                    "code" => -32601,
-                    "message" => "Method not found"
-                }
-            });
-            continue;
-        }
 
-        results.push(match result_o.unwrap() {
-            Ok(result) => object! {
-                "jsonrpc" => "2.0",
-                "id" => msg["id"].clone(),
-                "result" => result
-            },
-            Err(error) => object! {
-                "jsonrpc" => "2.0",
-                "id" => msg["id"].clone(),
-                "error" => error
-            }
-        });
-    }
-
-    if single {
-        builder.body(Body::from(results.pop().map(json::stringify).unwrap_or_else(String::new))).unwrap()
-    } else {
-        builder.body(Body::from(json::stringify(JsonValue::Array(results)))).unwrap()
+    match handle_json(json_o.unwrap(), |name, params| handler.call_method(name, params, &permissions)) {
+        Ok(response) => builder.body(Body::from(json::stringify(response))).unwrap(),
+        Err(error) => builder
+            .status(StatusCode::BAD_REQUEST)
+            .body(Body::from(json::stringify(error)))
+            .unwrap(),
     }
 }
 
-fn check_authentication<H: Handler>(handler: Arc<H>, authorization: Option<&HeaderValue>) -> Result<(), AuthenticationError> {
+/// Authenticates a client using the value of its HTTP `Authorization` header, which must use the
+/// basic scheme. Clients without the header are authenticated with empty credentials.
+pub fn check_authentication<H: Handler>(handler: &H, authorization: Option<&str>) -> Result<Permissions, AuthenticationError> {
     if let Some(authorization) = authorization {
-        let authorization = authorization.to_str()
-            .map_err(|_| AuthenticationError::InvalidHeader)?
+        let authorization = authorization
             .split_whitespace().collect::<Vec<&str>>();
         if authorization.len() != 2 || authorization[0] != "Basic" {
             return Err(AuthenticationError::InvalidHeader);
@@ -160,7 +166,7 @@ fn check_authentication<H: Handler>(handler: Arc<H>, authorization: Option<&Head
             .map_err(|_| AuthenticationError::InvalidHeader)?;
         let authorization = std::str::from_utf8(authorization
             .as_slice()).map_err(|_| AuthenticationError::InvalidHeader)?
-            .split(':').collect::<Vec<&str>>();
+            .splitn(2, ':').collect::<Vec<&str>>();
         if authorization.len() != 2 {
             return Err(AuthenticationError::IncorrectCredentials);
         }
@@ -188,19 +194,37 @@ impl<H> hyper::service::Service for Service<H> where H: Handler + 'static {
 
     fn call(&mut self, req: Request<<Self as hyper::service::Service>::ReqBody>) -> <Self as hyper::service::Service>::Future {
         let handler = Arc::clone(&self.handler);
+        if let Err(e) = handler.check_access(&self.remote_ip) {
+            info!("Access denied for {}: {}", self.remote_ip, e);
+            let status = match e {
+                AccessError::IpNotAllowed => StatusCode::FORBIDDEN,
+                AccessError::RateLimitExceeded => StatusCode::TOO_MANY_REQUESTS,
+            };
+            return Box::new(future::ok(Response::builder()
+                .status(status)
+                .body(Body::from(""))
+                .unwrap()));
+        }
         match *req.method() {
             Method::GET => Box::new(future::ok(Response::new(Body::from("Nimiq JSON-RPC Server")))),
             Method::POST => {
-                if let Err(e) = check_authentication(Arc::clone(&handler), req.headers().get("Authorization")) {
-                    info!("Authentication failed: {}", e);
-                    //return Box::new(future::ok(Response::new(Body::from(json::stringify(e)))));
-                    return Box::new(future::ok(Response::builder()
-                        .status(StatusCode::UNAUTHORIZED)
-                        .body(Body::from(""))
-                        .unwrap()));
-                }
+                let authorization = match req.headers().get("Authorization").map(HeaderValue::to_str).transpose() {
+                    Ok(authorization) => check_authentication(handler.as_ref(), authorization),
+                    Err(_) => Err(AuthenticationError::InvalidHeader),
+                };
+                let permissions = match authorization {
+                    Ok(permissions) => permissions,
+                    Err(e) => {
+                        info!("Authentication failed: {}", e);
+                        //return Box::new(future::ok(Response::new(Body::from(json::stringify(e)))));
+                        return Box::new(future::ok(Response::builder()
+                            .status(StatusCode::UNAUTHORIZED)
+                            .body(Body::from(""))
+                            .unwrap()));
+                    },
+                };
                 Box::new(req.into_body().concat2()
-                    .map(|b| handle_request(handler, std::str::from_utf8(&b))))
+                    .map(|b| handle_request(handler, permissions, std::str::from_utf8(&b))))
             },
             _ => Box::new(future::ok(Response::new(Body::from(""))))
         }
@@ -24,12 +24,18 @@ use std::sync::Arc;
 
 use futures::future::Future;
 use hyper::Server;
+use hyper::server::conn::AddrStream;
+use hyper::service::make_service_fn;
 use json::{JsonValue, object};
 
+use crate::access::IpFilter;
+use crate::auth::ApiToken;
 use crate::error::Error;
 pub use crate::handler::Handler;
 use futures::IntoFuture;
 
+pub mod access;
+pub mod auth;
 pub mod jsonrpc;
 pub mod error;
 pub mod handler;
@@ -44,8 +50,12 @@ fn rpc_not_implemented<T>() -> Result<T, JsonValue> {
 pub struct JsonRpcConfig {
     pub username: Option<String>,
     pub password: Option<String>,
+    /// Named API tokens, each restricted to a set of methods.
+    pub tokens: Vec<ApiToken>,
     pub methods: HashSet<String>,
-    pub allowip: (),
+    pub ip_filter: IpFilter,
+    /// Maximum number of requests per minute from a single IP address.
+    pub rate_limit: Option<usize>,
     pub corsdomain: Vec<String>,
 }
 
@@ -63,9 +73,9 @@ impl RpcServer {
 
         let handler2 = Arc::clone(&handler);
         let future = Box::new(Server::try_bind(&SocketAddr::new(ip, port))?
-            .serve(move || {
-                jsonrpc::Service::new(Arc::clone(&handler2))
-            })
+            .serve(make_service_fn(move |socket: &AddrStream| {
+                jsonrpc::Service::new(Arc::clone(&handler2), socket.remote_addr().ip())
+            }))
             .map_err(|e| error!("RPC server failed: {}", e)));
 
         Ok(RpcServer {
@@ -0,0 +1,50 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::error::SignerError;
+
+
+/// Address of a remote signer, either `tcp://host:port` or `unix:///path/to/socket`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SignerAddress {
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+impl FromStr for SignerAddress {
+    type Err = SignerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(host) = strip_prefix(s, "tcp://") {
+            if host.is_empty() {
+                return Err(SignerError::InvalidAddress(s.to_string()));
+            }
+            Ok(SignerAddress::Tcp(host.to_string()))
+        } else if let Some(path) = strip_prefix(s, "unix://") {
+            if path.is_empty() {
+                return Err(SignerError::InvalidAddress(s.to_string()));
+            }
+            Ok(SignerAddress::Unix(PathBuf::from(path)))
+        } else {
+            Err(SignerError::InvalidAddress(s.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for SignerAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignerAddress::Tcp(host) => write!(f, "tcp://{}", host),
+            SignerAddress::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+fn strip_prefix<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    if s.starts_with(prefix) {
+        Some(&s[prefix.len()..])
+    } else {
+        None
+    }
+}
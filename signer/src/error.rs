@@ -0,0 +1,38 @@
+use std::io::Error as IoError;
+
+use failure::Fail;
+
+use beserial::SerializingError;
+
+
+#[derive(Fail, Debug)]
+pub enum SignerError {
+    #[fail(display = "Invalid signer address: {}", _0)]
+    InvalidAddress(String),
+    #[fail(display = "{}", _0)]
+    IoError(#[cause] IoError),
+    #[fail(display = "{}", _0)]
+    SerializingError(#[cause] SerializingError),
+    #[fail(display = "Message too large: {} bytes", _0)]
+    MessageTooLarge(usize),
+    #[fail(display = "Signer refused to sign: {}", _0)]
+    Refused(String),
+    #[fail(display = "Unexpected response from signer")]
+    UnexpectedResponse,
+    #[fail(display = "Signer returned an invalid signature")]
+    InvalidSignature,
+    #[fail(display = "Signers reachable over TCP require a token")]
+    MissingToken,
+}
+
+impl From<IoError> for SignerError {
+    fn from(e: IoError) -> Self {
+        SignerError::IoError(e)
+    }
+}
+
+impl From<SerializingError> for SignerError {
+    fn from(e: SerializingError) -> Self {
+        SignerError::SerializingError(e)
+    }
+}
@@ -0,0 +1,89 @@
+#[macro_use]
+extern crate beserial_derive;
+#[macro_use]
+extern crate log;
+extern crate nimiq_block_albatross as block_albatross;
+extern crate nimiq_bls as bls;
+extern crate nimiq_database as database;
+extern crate nimiq_hash as hash;
+extern crate nimiq_network_primitives as network_primitives;
+extern crate nimiq_primitives as primitives;
+extern crate nimiq_vrf as vrf;
+
+pub mod address;
+pub mod error;
+pub mod local;
+pub mod protocol;
+pub mod remote;
+pub mod request;
+pub mod server;
+pub mod slashing_protection;
+
+use block_albatross::{MicroHeader, PbftCommitMessage, PbftPrepareMessage, SignedPbftCommitMessage, SignedPbftPrepareMessage};
+use block_albatross::signed::{Message, SignedMessage};
+use bls::bls12_381::{CompressedSignature, PublicKey, Signature};
+use vrf::VrfSeed;
+
+pub use crate::address::SignerAddress;
+pub use crate::error::SignerError;
+pub use crate::local::LocalSigner;
+pub use crate::remote::RemoteSigner;
+pub use crate::request::SigningRequest;
+
+
+/// Produces the signatures of a validator.
+///
+/// The signer only ever signs the messages described by `SigningRequest`, so a signer that
+/// doesn't run in the same process as the validator can inspect what it is asked to sign.
+pub trait Signer: Send + Sync {
+    /// The public key of the validator key.
+    fn public_key(&self) -> &PublicKey;
+
+    /// Signs the request with the validator key.
+    fn sign(&self, request: &SigningRequest) -> Result<Signature, SignerError>;
+}
+
+impl dyn Signer {
+    /// Signs a pBFT proposal, a view change message or a validator info.
+    pub fn sign_message<M>(&self, message: M, signer_idx: u16) -> Result<SignedMessage<M>, SignerError>
+        where M: Message, SigningRequest: From<M> {
+        let request = SigningRequest::from(message.clone());
+        self.sign_request(message, &request, signer_idx)
+    }
+
+    /// Signs a pBFT prepare for the proposal at `block_number` and `view_number`.
+    pub fn sign_pbft_prepare(&self, message: PbftPrepareMessage, block_number: u32, view_number: u32, signer_idx: u16) -> Result<SignedPbftPrepareMessage, SignerError> {
+        let request = SigningRequest::PbftPrepare { message: message.clone(), block_number, view_number };
+        self.sign_request(message, &request, signer_idx)
+    }
+
+    /// Signs a pBFT commit for the proposal at `block_number` and `view_number`.
+    pub fn sign_pbft_commit(&self, message: PbftCommitMessage, block_number: u32, view_number: u32, signer_idx: u16) -> Result<SignedPbftCommitMessage, SignerError> {
+        let request = SigningRequest::PbftCommit { message: message.clone(), block_number, view_number };
+        self.sign_request(message, &request, signer_idx)
+    }
+
+    fn sign_request<M: Message>(&self, message: M, request: &SigningRequest, signer_idx: u16) -> Result<SignedMessage<M>, SignerError> {
+        let signature = self.sign(request)?;
+        Ok(SignedMessage {
+            message,
+            signer_idx,
+            signature,
+        })
+    }
+
+    /// Signs the header of a micro block.
+    pub fn sign_micro_header(&self, header: &MicroHeader) -> Result<CompressedSignature, SignerError> {
+        Ok(self.sign(&SigningRequest::MicroHeader(header.clone()))?.compress())
+    }
+
+    /// Derives the next seed from `prev_seed`. This is equivalent to `VrfSeed::sign_next`.
+    pub fn sign_next_seed(&self, prev_seed: &VrfSeed) -> Result<VrfSeed, SignerError> {
+        Ok(VrfSeed::from(self.sign(&SigningRequest::VrfSeed(prev_seed.clone()))?.compress()))
+    }
+
+    /// Returns the proof of knowledge of the secret key, which is needed to register a validator.
+    pub fn proof_of_knowledge(&self) -> Result<CompressedSignature, SignerError> {
+        Ok(self.sign(&SigningRequest::ProofOfKnowledge)?.compress())
+    }
+}
@@ -0,0 +1,35 @@
+use bls::bls12_381::{KeyPair, PublicKey, Signature};
+
+use crate::Signer;
+use crate::error::SignerError;
+use crate::request::SigningRequest;
+
+
+/// Signs with a validator key that is held in memory.
+pub struct LocalSigner {
+    key_pair: KeyPair,
+}
+
+impl LocalSigner {
+    pub fn new(key_pair: KeyPair) -> Self {
+        LocalSigner {
+            key_pair,
+        }
+    }
+}
+
+impl From<KeyPair> for LocalSigner {
+    fn from(key_pair: KeyPair) -> Self {
+        Self::new(key_pair)
+    }
+}
+
+impl Signer for LocalSigner {
+    fn public_key(&self) -> &PublicKey {
+        &self.key_pair.public
+    }
+
+    fn sign(&self, request: &SigningRequest) -> Result<Signature, SignerError> {
+        Ok(self.key_pair.sign_hash(request.signing_hash(&self.key_pair.public)))
+    }
+}
@@ -0,0 +1,168 @@
+//! Wire protocol between the validator and a remote signer.
+//!
+//! Every message is serialized with beserial and prefixed with its length as big-endian `u32`.
+//! The validator sends a `Request` and the signer answers with exactly one `Response`.
+//!
+//! If the signer requires a token, the first request on every connection must authenticate with
+//! it. The connection is closed otherwise.
+
+use std::io::{Read, Write};
+
+use beserial::{Deserialize, DeserializeWithLength, ReadBytesExt, Serialize, SerializeWithLength, SerializingError, WriteBytesExt};
+use bls::bls12_381::{PublicKey, Signature};
+
+use crate::error::SignerError;
+use crate::request::SigningRequest;
+
+
+/// Frames larger than this are rejected.
+pub const MAX_FRAME_SIZE: usize = 1024 * 1024;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Request {
+    GetPublicKey,
+    Sign(SigningRequest),
+    Authenticate(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Response {
+    PublicKey(PublicKey),
+    Signature(Signature),
+    Error(String),
+    Authenticated,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[repr(u8)]
+enum RequestType {
+    GetPublicKey = 0,
+    Sign = 1,
+    Authenticate = 2,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[repr(u8)]
+enum ResponseType {
+    PublicKey = 0,
+    Signature = 1,
+    Error = 2,
+    Authenticated = 3,
+}
+
+impl Serialize for Request {
+    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
+        Ok(match self {
+            Request::GetPublicKey => Serialize::serialize(&RequestType::GetPublicKey, writer)?,
+            Request::Sign(request) => {
+                Serialize::serialize(&RequestType::Sign, writer)?
+                    + Serialize::serialize(request, writer)?
+            },
+            Request::Authenticate(token) => {
+                Serialize::serialize(&RequestType::Authenticate, writer)?
+                    + SerializeWithLength::serialize::<u16, W>(token, writer)?
+            },
+        })
+    }
+
+    fn serialized_size(&self) -> usize {
+        match self {
+            Request::GetPublicKey => Serialize::serialized_size(&RequestType::GetPublicKey),
+            Request::Sign(request) => {
+                Serialize::serialized_size(&RequestType::Sign)
+                    + Serialize::serialized_size(request)
+            },
+            Request::Authenticate(token) => {
+                Serialize::serialized_size(&RequestType::Authenticate)
+                    + SerializeWithLength::serialized_size::<u16>(token)
+            },
+        }
+    }
+}
+
+impl Deserialize for Request {
+    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        let ty: RequestType = Deserialize::deserialize(reader)?;
+        Ok(match ty {
+            RequestType::GetPublicKey => Request::GetPublicKey,
+            RequestType::Sign => Request::Sign(Deserialize::deserialize(reader)?),
+            RequestType::Authenticate => Request::Authenticate(DeserializeWithLength::deserialize::<u16, R>(reader)?),
+        })
+    }
+}
+
+impl Serialize for Response {
+    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
+        Ok(match self {
+            Response::PublicKey(public_key) => {
+                Serialize::serialize(&ResponseType::PublicKey, writer)?
+                    + Serialize::serialize(public_key, writer)?
+            },
+            Response::Signature(signature) => {
+                Serialize::serialize(&ResponseType::Signature, writer)?
+                    + Serialize::serialize(signature, writer)?
+            },
+            Response::Error(message) => {
+                Serialize::serialize(&ResponseType::Error, writer)?
+                    + SerializeWithLength::serialize::<u16, W>(message, writer)?
+            },
+            Response::Authenticated => Serialize::serialize(&ResponseType::Authenticated, writer)?,
+        })
+    }
+
+    fn serialized_size(&self) -> usize {
+        match self {
+            Response::PublicKey(public_key) => {
+                Serialize::serialized_size(&ResponseType::PublicKey)
+                    + Serialize::serialized_size(public_key)
+            },
+            Response::Signature(signature) => {
+                Serialize::serialized_size(&ResponseType::Signature)
+                    + Serialize::serialized_size(signature)
+            },
+            Response::Error(message) => {
+                Serialize::serialized_size(&ResponseType::Error)
+                    + SerializeWithLength::serialized_size::<u16>(message)
+            },
+            Response::Authenticated => Serialize::serialized_size(&ResponseType::Authenticated),
+        }
+    }
+}
+
+impl Deserialize for Response {
+    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        let ty: ResponseType = Deserialize::deserialize(reader)?;
+        Ok(match ty {
+            ResponseType::PublicKey => Response::PublicKey(Deserialize::deserialize(reader)?),
+            ResponseType::Signature => Response::Signature(Deserialize::deserialize(reader)?),
+            ResponseType::Error => Response::Error(DeserializeWithLength::deserialize::<u16, R>(reader)?),
+            ResponseType::Authenticated => Response::Authenticated,
+        })
+    }
+}
+
+/// Writes a length-prefixed message to `writer`.
+pub fn write_frame<W: Write, T: Serialize>(writer: &mut W, message: &T) -> Result<(), SignerError> {
+    let data = message.serialize_to_vec();
+    if data.len() > MAX_FRAME_SIZE {
+        return Err(SignerError::MessageTooLarge(data.len()));
+    }
+    Serialize::serialize(&(data.len() as u32), writer)?;
+    writer.write_all(&data)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads a length-prefixed message from `reader`.
+pub fn read_frame<R: Read, T: Deserialize>(reader: &mut R) -> Result<T, SignerError> {
+    let mut length = [0u8; 4];
+    reader.read_exact(&mut length)?;
+    let length = u32::deserialize_from_vec(&length[..])? as usize;
+    if length > MAX_FRAME_SIZE {
+        return Err(SignerError::MessageTooLarge(length));
+    }
+
+    let mut data = vec![0u8; length];
+    reader.read_exact(&mut data)?;
+    Ok(T::deserialize_from_vec(&data)?)
+}
@@ -0,0 +1,152 @@
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+use bls::bls12_381::{PublicKey, Signature};
+
+use crate::Signer;
+use crate::address::SignerAddress;
+use crate::error::SignerError;
+use crate::protocol::{read_frame, write_frame, Request, Response};
+use crate::request::SigningRequest;
+
+
+trait Connection: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Connection for T {}
+
+/// Signs by sending the requests to a signer on another host or in another process.
+///
+/// The connection is established lazily and re-established once if a request fails, e.g. because
+/// the signer was restarted. Signatures returned by the signer are verified before they are used.
+///
+/// Signers reachable over TCP require a token, which is sent at the start of every connection.
+pub struct RemoteSigner {
+    address: SignerAddress,
+    token: Option<String>,
+    timeout: Duration,
+    public_key: PublicKey,
+    connection: Mutex<Option<Box<dyn Connection>>>,
+}
+
+impl RemoteSigner {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Connects to the signer at `address`, authenticates with `token` and fetches its public key.
+    pub fn connect(address: SignerAddress, token: Option<String>) -> Result<Self, SignerError> {
+        Self::with_timeout(address, token, Self::DEFAULT_TIMEOUT)
+    }
+
+    pub fn with_timeout(address: SignerAddress, token: Option<String>, timeout: Duration) -> Result<Self, SignerError> {
+        if let SignerAddress::Tcp(_) = address {
+            if token.is_none() {
+                return Err(SignerError::MissingToken);
+            }
+        }
+
+        let mut connection = Self::open(&address, token.as_ref(), timeout)?;
+        write_frame(&mut connection, &Request::GetPublicKey)?;
+        let public_key = match read_frame(&mut connection)? {
+            Response::PublicKey(public_key) => public_key,
+            Response::Error(message) => return Err(SignerError::Refused(message)),
+            _ => return Err(SignerError::UnexpectedResponse),
+        };
+        info!("Connected to remote signer at {}: {}", address, public_key.compress());
+
+        Ok(RemoteSigner {
+            address,
+            token,
+            timeout,
+            public_key,
+            connection: Mutex::new(Some(connection)),
+        })
+    }
+
+    pub fn address(&self) -> &SignerAddress {
+        &self.address
+    }
+
+    fn open(address: &SignerAddress, token: Option<&String>, timeout: Duration) -> Result<Box<dyn Connection>, SignerError> {
+        let mut connection = Self::open_stream(address, timeout)?;
+        if let Some(token) = token {
+            match Self::exchange(&mut connection, &Request::Authenticate(token.clone()))? {
+                Response::Authenticated => {},
+                Response::Error(message) => return Err(SignerError::Refused(message)),
+                _ => return Err(SignerError::UnexpectedResponse),
+            }
+        }
+        Ok(connection)
+    }
+
+    fn open_stream(address: &SignerAddress, timeout: Duration) -> Result<Box<dyn Connection>, SignerError> {
+        match address {
+            SignerAddress::Tcp(host) => {
+                let socket_addr = host.to_socket_addrs()?
+                    .next()
+                    .ok_or_else(|| SignerError::InvalidAddress(address.to_string()))?;
+                let stream = TcpStream::connect_timeout(&socket_addr, timeout)?;
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                stream.set_nodelay(true)?;
+                Ok(Box::new(stream))
+            },
+            #[cfg(unix)]
+            SignerAddress::Unix(path) => {
+                let stream = UnixStream::connect(path)?;
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                Ok(Box::new(stream))
+            },
+            #[cfg(not(unix))]
+            SignerAddress::Unix(_) => Err(SignerError::InvalidAddress(address.to_string())),
+        }
+    }
+
+    fn request(&self, request: &Request) -> Result<Response, SignerError> {
+        let mut connection = self.connection.lock();
+
+        // Reuse the existing connection, but retry once with a fresh one if that fails.
+        if let Some(mut stream) = connection.take() {
+            match Self::exchange(&mut stream, request) {
+                Ok(response) => {
+                    *connection = Some(stream);
+                    return Ok(response);
+                },
+                Err(e) => debug!("Request to remote signer at {} failed, reconnecting: {}", self.address, e),
+            }
+        }
+
+        let mut stream = Self::open(&self.address, self.token.as_ref(), self.timeout)?;
+        let response = Self::exchange(&mut stream, request)?;
+        *connection = Some(stream);
+        Ok(response)
+    }
+
+    fn exchange(stream: &mut Box<dyn Connection>, request: &Request) -> Result<Response, SignerError> {
+        write_frame(stream, request)?;
+        read_frame(stream)
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    fn sign(&self, request: &SigningRequest) -> Result<Signature, SignerError> {
+        let signature = match self.request(&Request::Sign(request.clone()))? {
+            Response::Signature(signature) => signature,
+            Response::Error(message) => return Err(SignerError::Refused(message)),
+            _ => return Err(SignerError::UnexpectedResponse),
+        };
+
+        if !self.public_key.verify_hash(request.signing_hash(&self.public_key), &signature) {
+            return Err(SignerError::InvalidSignature);
+        }
+        Ok(signature)
+    }
+}
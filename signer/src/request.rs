@@ -0,0 +1,182 @@
+use std::fmt;
+
+use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteBytesExt};
+use block_albatross::{MicroHeader, PbftCommitMessage, PbftPrepareMessage, PbftProposal, ViewChange};
+use block_albatross::signed::Message;
+use bls::SigHash;
+use bls::bls12_381::PublicKey;
+use hash::Hash;
+use network_primitives::validator_info::ValidatorInfo;
+use vrf::VrfSeed;
+
+
+/// Everything a validator needs to sign.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SigningRequest {
+    MicroHeader(MicroHeader),
+    PbftProposal(PbftProposal),
+    /// A pBFT prepare for the proposal at `block_number` and `view_number`. Only the message is
+    /// signed, the block and view number are needed for the slashing protection.
+    PbftPrepare { message: PbftPrepareMessage, block_number: u32, view_number: u32 },
+    /// A pBFT commit for the proposal at `block_number` and `view_number`.
+    PbftCommit { message: PbftCommitMessage, block_number: u32, view_number: u32 },
+    ViewChange(ViewChange),
+    ValidatorInfo(ValidatorInfo),
+    /// Derive the seed of the next block from the given seed of the previous block.
+    VrfSeed(VrfSeed),
+    /// Sign the public key of the signer itself.
+    ProofOfKnowledge,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[repr(u8)]
+enum SigningRequestType {
+    MicroHeader = 0,
+    PbftProposal = 1,
+    PbftPrepare = 2,
+    PbftCommit = 3,
+    ViewChange = 4,
+    ValidatorInfo = 5,
+    VrfSeed = 6,
+    ProofOfKnowledge = 7,
+}
+
+impl SigningRequest {
+    /// Returns the hash that is signed for this request.
+    pub fn signing_hash(&self, public_key: &PublicKey) -> SigHash {
+        match self {
+            SigningRequest::MicroHeader(header) => header.hash(),
+            SigningRequest::PbftProposal(proposal) => proposal.hash_with_prefix(),
+            SigningRequest::PbftPrepare { message, .. } => message.hash_with_prefix(),
+            SigningRequest::PbftCommit { message, .. } => message.hash_with_prefix(),
+            SigningRequest::ViewChange(view_change) => view_change.hash_with_prefix(),
+            SigningRequest::ValidatorInfo(info) => info.hash_with_prefix(),
+            SigningRequest::VrfSeed(prev_seed) => prev_seed.next_seed_hash(),
+            SigningRequest::ProofOfKnowledge => public_key.hash(),
+        }
+    }
+
+    fn ty(&self) -> SigningRequestType {
+        match self {
+            SigningRequest::MicroHeader(_) => SigningRequestType::MicroHeader,
+            SigningRequest::PbftProposal(_) => SigningRequestType::PbftProposal,
+            SigningRequest::PbftPrepare { .. } => SigningRequestType::PbftPrepare,
+            SigningRequest::PbftCommit { .. } => SigningRequestType::PbftCommit,
+            SigningRequest::ViewChange(_) => SigningRequestType::ViewChange,
+            SigningRequest::ValidatorInfo(_) => SigningRequestType::ValidatorInfo,
+            SigningRequest::VrfSeed(_) => SigningRequestType::VrfSeed,
+            SigningRequest::ProofOfKnowledge => SigningRequestType::ProofOfKnowledge,
+        }
+    }
+}
+
+impl fmt::Display for SigningRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SigningRequest::MicroHeader(header) => write!(f, "micro header #{}.{}", header.block_number, header.view_number),
+            SigningRequest::PbftProposal(proposal) => write!(f, "pBFT proposal #{}.{}", proposal.header.block_number, proposal.header.view_number),
+            SigningRequest::PbftPrepare { message, block_number, view_number } => write!(f, "pBFT prepare #{}.{} {}", block_number, view_number, message.block_hash),
+            SigningRequest::PbftCommit { message, block_number, view_number } => write!(f, "pBFT commit #{}.{} {}", block_number, view_number, message.block_hash),
+            SigningRequest::ViewChange(view_change) => write!(f, "view change {}", view_change),
+            SigningRequest::ValidatorInfo(info) => write!(f, "validator info valid from #{}", info.valid_from),
+            SigningRequest::VrfSeed(prev_seed) => write!(f, "VRF seed following {}", prev_seed),
+            SigningRequest::ProofOfKnowledge => write!(f, "proof of knowledge"),
+        }
+    }
+}
+
+impl Serialize for SigningRequest {
+    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
+        let mut size = Serialize::serialize(&self.ty(), writer)?;
+        size += match self {
+            SigningRequest::MicroHeader(header) => Serialize::serialize(header, writer)?,
+            SigningRequest::PbftProposal(proposal) => Serialize::serialize(proposal, writer)?,
+            SigningRequest::PbftPrepare { message, block_number, view_number } => {
+                Serialize::serialize(message, writer)?
+                    + Serialize::serialize(block_number, writer)?
+                    + Serialize::serialize(view_number, writer)?
+            },
+            SigningRequest::PbftCommit { message, block_number, view_number } => {
+                Serialize::serialize(message, writer)?
+                    + Serialize::serialize(block_number, writer)?
+                    + Serialize::serialize(view_number, writer)?
+            },
+            SigningRequest::ViewChange(view_change) => Serialize::serialize(view_change, writer)?,
+            SigningRequest::ValidatorInfo(info) => Serialize::serialize(info, writer)?,
+            SigningRequest::VrfSeed(prev_seed) => Serialize::serialize(prev_seed, writer)?,
+            SigningRequest::ProofOfKnowledge => 0,
+        };
+        Ok(size)
+    }
+
+    fn serialized_size(&self) -> usize {
+        let mut size = Serialize::serialized_size(&self.ty());
+        size += match self {
+            SigningRequest::MicroHeader(header) => Serialize::serialized_size(header),
+            SigningRequest::PbftProposal(proposal) => Serialize::serialized_size(proposal),
+            SigningRequest::PbftPrepare { message, block_number, view_number } => {
+                Serialize::serialized_size(message)
+                    + Serialize::serialized_size(block_number)
+                    + Serialize::serialized_size(view_number)
+            },
+            SigningRequest::PbftCommit { message, block_number, view_number } => {
+                Serialize::serialized_size(message)
+                    + Serialize::serialized_size(block_number)
+                    + Serialize::serialized_size(view_number)
+            },
+            SigningRequest::ViewChange(view_change) => Serialize::serialized_size(view_change),
+            SigningRequest::ValidatorInfo(info) => Serialize::serialized_size(info),
+            SigningRequest::VrfSeed(prev_seed) => Serialize::serialized_size(prev_seed),
+            SigningRequest::ProofOfKnowledge => 0,
+        };
+        size
+    }
+}
+
+impl Deserialize for SigningRequest {
+    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        let ty: SigningRequestType = Deserialize::deserialize(reader)?;
+        Ok(match ty {
+            SigningRequestType::MicroHeader => SigningRequest::MicroHeader(Deserialize::deserialize(reader)?),
+            SigningRequestType::PbftProposal => SigningRequest::PbftProposal(Deserialize::deserialize(reader)?),
+            SigningRequestType::PbftPrepare => SigningRequest::PbftPrepare {
+                message: Deserialize::deserialize(reader)?,
+                block_number: Deserialize::deserialize(reader)?,
+                view_number: Deserialize::deserialize(reader)?,
+            },
+            SigningRequestType::PbftCommit => SigningRequest::PbftCommit {
+                message: Deserialize::deserialize(reader)?,
+                block_number: Deserialize::deserialize(reader)?,
+                view_number: Deserialize::deserialize(reader)?,
+            },
+            SigningRequestType::ViewChange => SigningRequest::ViewChange(Deserialize::deserialize(reader)?),
+            SigningRequestType::ValidatorInfo => SigningRequest::ValidatorInfo(Deserialize::deserialize(reader)?),
+            SigningRequestType::VrfSeed => SigningRequest::VrfSeed(Deserialize::deserialize(reader)?),
+            SigningRequestType::ProofOfKnowledge => SigningRequest::ProofOfKnowledge,
+        })
+    }
+}
+
+impl From<MicroHeader> for SigningRequest {
+    fn from(header: MicroHeader) -> Self {
+        SigningRequest::MicroHeader(header)
+    }
+}
+
+impl From<PbftProposal> for SigningRequest {
+    fn from(proposal: PbftProposal) -> Self {
+        SigningRequest::PbftProposal(proposal)
+    }
+}
+
+impl From<ViewChange> for SigningRequest {
+    fn from(view_change: ViewChange) -> Self {
+        SigningRequest::ViewChange(view_change)
+    }
+}
+
+impl From<ValidatorInfo> for SigningRequest {
+    fn from(info: ValidatorInfo) -> Self {
+        SigningRequest::ValidatorInfo(info)
+    }
+}
@@ -0,0 +1,91 @@
+use std::io::{ErrorKind, Read, Write};
+use std::sync::Arc;
+
+use crate::Signer;
+use crate::error::SignerError;
+use crate::protocol::{read_frame, write_frame, Request, Response};
+use crate::slashing_protection::SlashingProtection;
+
+
+/// Answers the requests of validators with a signer.
+///
+/// Every message that could get the validator slashed is checked against and recorded in the
+/// slashing protection of the server before it is signed, independent of what the validator
+/// checked itself. If a token is set, connections have to authenticate with it first.
+pub struct SignerServer {
+    signer: Arc<dyn Signer>,
+    slashing_protection: SlashingProtection,
+    token: Option<String>,
+}
+
+impl SignerServer {
+    pub fn new(signer: Arc<dyn Signer>, slashing_protection: SlashingProtection, token: Option<String>) -> Self {
+        SignerServer {
+            signer,
+            slashing_protection,
+            token,
+        }
+    }
+
+    /// Answers a single request of an authenticated connection.
+    pub fn handle_request(&self, request: Request) -> Response {
+        match request {
+            Request::GetPublicKey => Response::PublicKey(*self.signer.public_key()),
+            Request::Sign(request) => {
+                if let Err(e) = self.slashing_protection.record_request(&request) {
+                    warn!("Refusing to sign {}: {}", request, e);
+                    return Response::Error(e.to_string());
+                }
+
+                info!("Signing {}", request);
+                match self.signer.sign(&request) {
+                    Ok(signature) => Response::Signature(signature),
+                    Err(e) => Response::Error(e.to_string()),
+                }
+            },
+            // Connections are only authenticated once.
+            Request::Authenticate(_) => Response::Authenticated,
+        }
+    }
+
+    /// Serves requests on `stream` until the client closes the connection.
+    pub fn serve_connection<S: Read + Write>(&self, mut stream: S) -> Result<(), SignerError> {
+        let mut authenticated = self.token.is_none();
+        loop {
+            let request: Request = match read_frame(&mut stream) {
+                Ok(request) => request,
+                Err(SignerError::IoError(ref e)) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+
+            if !authenticated {
+                let response = match (&request, &self.token) {
+                    (Request::Authenticate(provided), Some(expected)) if secrets_equal(provided, expected) => {
+                        authenticated = true;
+                        Response::Authenticated
+                    },
+                    _ => Response::Error("Authentication failed".to_string()),
+                };
+                write_frame(&mut stream, &response)?;
+                if !authenticated {
+                    return Err(SignerError::Refused("Authentication failed".to_string()));
+                }
+                continue;
+            }
+
+            let response = self.handle_request(request);
+            write_frame(&mut stream, &response)?;
+        }
+    }
+}
+
+/// Compares a provided token to the expected one in constant time for tokens of the same length.
+fn secrets_equal(provided: &str, expected: &str) -> bool {
+    let provided = provided.as_bytes();
+    let expected = expected.as_bytes();
+    if provided.len() != expected.len() {
+        return false;
+    }
+    provided.iter().zip(expected.iter())
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
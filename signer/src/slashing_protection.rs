@@ -0,0 +1,452 @@
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+use failure::Fail;
+use json::{JsonValue, object};
+
+use block_albatross::{MacroHeader, MicroHeader, ViewChange};
+use bls::bls12_381::CompressedPublicKey;
+use database::{Database, Environment, ReadTransaction, Transaction, WriteTransaction};
+use database::{AsDatabaseBytes, FromDatabaseValue, IntoDatabaseValue};
+use database::cursor::ReadCursor;
+use hash::{Blake2bHash, Hash};
+use primitives::policy::AlbatrossPolicy;
+
+use crate::request::SigningRequest;
+
+/// The kinds of messages a validator signs that can get it slashed if it signs two conflicting
+/// ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum SigningKind {
+    MicroBlock = 0,
+    PbftProposal = 1,
+    PbftPrepare = 2,
+    PbftCommit = 3,
+    ViewChange = 4,
+}
+
+impl SigningKind {
+    const ALL: [SigningKind; 5] = [
+        SigningKind::MicroBlock,
+        SigningKind::PbftProposal,
+        SigningKind::PbftPrepare,
+        SigningKind::PbftCommit,
+        SigningKind::ViewChange,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            SigningKind::MicroBlock => "microBlock",
+            SigningKind::PbftProposal => "pbftProposal",
+            SigningKind::PbftPrepare => "pbftPrepare",
+            SigningKind::PbftCommit => "pbftCommit",
+            SigningKind::ViewChange => "viewChange",
+        }
+    }
+}
+
+impl TryFrom<u8> for SigningKind {
+    type Error = io::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        SigningKind::ALL.get(value as usize)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid signing kind"))
+    }
+}
+
+impl FromStr for SigningKind {
+    type Err = SlashingProtectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SigningKind::ALL.iter()
+            .find(|kind| kind.as_str() == s)
+            .cloned()
+            .ok_or_else(|| SlashingProtectionError::InvalidInterchange(format!("Unknown type: {}", s)))
+    }
+}
+
+impl fmt::Display for SigningKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Fail, Clone, PartialEq, Eq)]
+pub enum SlashingProtectionError {
+    #[fail(display = "Already signed a different {} at block #{}.{}", _0, _1, _2)]
+    Conflict(SigningKind, u32, u32),
+    #[fail(display = "Already signed a {} at a later block or view than #{}.{}", _0, _1, _2)]
+    Outdated(SigningKind, u32, u32),
+    #[fail(display = "Invalid interchange data: {}", _0)]
+    InvalidInterchange(String),
+    #[fail(display = "Interchange data belongs to another validator key")]
+    KeyMismatch,
+}
+
+/// Database key of a signature record. Keys are ordered by kind and then block number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct RecordKey {
+    kind: SigningKind,
+    block_number: u32,
+}
+
+impl AsDatabaseBytes for RecordKey {
+    fn as_database_bytes(&self) -> Cow<[u8]> {
+        let mut bytes = Vec::with_capacity(5);
+        bytes.push(self.kind as u8);
+        // Big endian, so that the byte order matches the numeric order.
+        bytes.extend_from_slice(&self.block_number.to_be_bytes());
+        Cow::Owned(bytes)
+    }
+}
+
+impl FromDatabaseValue for RecordKey {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self> where Self: Sized {
+        if bytes.len() != 5 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid record key"));
+        }
+        let mut block_number = [0u8; 4];
+        block_number.copy_from_slice(&bytes[1..]);
+        Ok(RecordKey {
+            kind: SigningKind::try_from(bytes[0])?,
+            block_number: u32::from_be_bytes(block_number),
+        })
+    }
+}
+
+/// The highest view at which a message was signed for a block number, and the hash of the
+/// signed message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SignedRecord {
+    view_number: u32,
+    hash: Blake2bHash,
+}
+
+impl SignedRecord {
+    const SIZE: usize = 4 + Blake2bHash::SIZE;
+}
+
+impl IntoDatabaseValue for SignedRecord {
+    fn database_byte_size(&self) -> usize {
+        Self::SIZE
+    }
+
+    fn copy_into_database(&self, bytes: &mut [u8]) {
+        bytes[..4].copy_from_slice(&self.view_number.to_be_bytes());
+        bytes[4..].copy_from_slice(self.hash.as_bytes());
+    }
+}
+
+impl FromDatabaseValue for SignedRecord {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self> where Self: Sized {
+        if bytes.len() != Self::SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid signed record"));
+        }
+        let mut view_number = [0u8; 4];
+        view_number.copy_from_slice(&bytes[..4]);
+        let mut hash = [0u8; Blake2bHash::SIZE];
+        hash.copy_from_slice(&bytes[4..]);
+        Ok(SignedRecord {
+            view_number: u32::from_be_bytes(view_number),
+            hash: Blake2bHash::from(hash),
+        })
+    }
+}
+
+/// Persistent record of the messages this validator signed, to prevent it from signing
+/// conflicting messages - e.g. after a restart or if it is accidentally run twice.
+///
+/// For every kind of message, only messages at or above the highest signed block number are
+/// allowed. At the same block number, the view number must not decrease and a message with a
+/// different hash must not be signed at the same view.
+pub struct SlashingProtection {
+    env: Environment,
+    db: Database,
+}
+
+impl SlashingProtection {
+    const DB_NAME: &'static str = "SlashingProtection";
+    const INTERCHANGE_VERSION: u32 = 1;
+
+    pub fn new(env: Environment) -> Self {
+        let db = env.open_database(Self::DB_NAME.to_string());
+        SlashingProtection { env, db }
+    }
+
+    pub fn record_micro_block(&self, header: &MicroHeader) -> Result<(), SlashingProtectionError> {
+        self.record(SigningKind::MicroBlock, header.block_number, header.view_number, header.hash())
+    }
+
+    pub fn record_pbft_proposal(&self, header: &MacroHeader) -> Result<(), SlashingProtectionError> {
+        self.record(SigningKind::PbftProposal, header.block_number, header.view_number, header.hash())
+    }
+
+    pub fn record_pbft_prepare(&self, block_number: u32, view_number: u32, block_hash: &Blake2bHash) -> Result<(), SlashingProtectionError> {
+        self.record(SigningKind::PbftPrepare, block_number, view_number, block_hash.clone())
+    }
+
+    pub fn record_pbft_commit(&self, block_number: u32, view_number: u32, block_hash: &Blake2bHash) -> Result<(), SlashingProtectionError> {
+        self.record(SigningKind::PbftCommit, block_number, view_number, block_hash.clone())
+    }
+
+    pub fn record_view_change(&self, view_change: &ViewChange) -> Result<(), SlashingProtectionError> {
+        self.record(SigningKind::ViewChange, view_change.block_number, view_change.new_view_number, view_change.hash())
+    }
+
+    /// Records the message of a signing request, see `record`. Requests that can't get the
+    /// validator slashed are always allowed.
+    pub fn record_request(&self, request: &SigningRequest) -> Result<(), SlashingProtectionError> {
+        match request {
+            SigningRequest::MicroHeader(header) => self.record_micro_block(header),
+            SigningRequest::PbftProposal(proposal) => self.record_pbft_proposal(&proposal.header),
+            SigningRequest::PbftPrepare { message, block_number, view_number } => {
+                self.record_pbft_prepare(*block_number, *view_number, &message.block_hash)
+            },
+            SigningRequest::PbftCommit { message, block_number, view_number } => {
+                self.record_pbft_commit(*block_number, *view_number, &message.block_hash)
+            },
+            SigningRequest::ViewChange(view_change) => self.record_view_change(view_change),
+            SigningRequest::ValidatorInfo(_) | SigningRequest::VrfSeed(_) | SigningRequest::ProofOfKnowledge => Ok(()),
+        }
+    }
+
+    /// Returns the view number of the pBFT prepare signed for `block_hash`, if it is the latest
+    /// prepare signed at `block_number`.
+    pub fn signed_pbft_prepare(&self, block_number: u32, block_hash: &Blake2bHash) -> Option<u32> {
+        let txn = ReadTransaction::new(&self.env);
+        let signed: Option<SignedRecord> = txn.get(&self.db, &RecordKey { kind: SigningKind::PbftPrepare, block_number });
+        signed.filter(|record| &record.hash == block_hash)
+            .map(|record| record.view_number)
+    }
+
+    /// Records that a message is about to be signed. Fails without recording anything if the
+    /// signature would conflict with an earlier one. Signing the same message again is allowed.
+    pub fn record(&self, kind: SigningKind, block_number: u32, view_number: u32, hash: Blake2bHash) -> Result<(), SlashingProtectionError> {
+        // Write transactions are exclusive, so checking and recording is atomic.
+        let mut txn = WriteTransaction::new(&self.env);
+        let record = SignedRecord { view_number, hash };
+        if let Err(e) = self.check(kind, block_number, &record, &txn) {
+            txn.abort();
+            return Err(e);
+        }
+
+        txn.put_reserve(&self.db, &RecordKey { kind, block_number }, &record);
+        txn.commit();
+        Ok(())
+    }
+
+    fn check(&self, kind: SigningKind, block_number: u32, record: &SignedRecord, txn: &Transaction) -> Result<(), SlashingProtectionError> {
+        if let Some(next_block_number) = block_number.checked_add(1) {
+            let mut cursor = txn.cursor(&self.db);
+            let next: Option<(RecordKey, SignedRecord)> = cursor.seek_range_key(&RecordKey { kind, block_number: next_block_number });
+            if let Some((key, _)) = next {
+                if key.kind == kind {
+                    return Err(SlashingProtectionError::Outdated(kind, block_number, record.view_number));
+                }
+            }
+        }
+
+        let signed: Option<SignedRecord> = txn.get(&self.db, &RecordKey { kind, block_number });
+        match signed {
+            Some(ref signed) if signed.view_number > record.view_number => {
+                Err(SlashingProtectionError::Outdated(kind, block_number, record.view_number))
+            },
+            Some(ref signed) if signed.view_number == record.view_number && signed.hash != record.hash => {
+                Err(SlashingProtectionError::Conflict(kind, block_number, record.view_number))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Removes records that are older than the previous epoch of `block_number`. The latest
+    /// record of each kind is always kept.
+    pub fn prune(&self, block_number: u32, policy: &AlbatrossPolicy) {
+        let cutoff = policy.first_block_of_registry(policy.epoch_at(block_number));
+        let mut txn = WriteTransaction::new(&self.env);
+
+        let mut obsolete = Vec::new();
+        {
+            let mut cursor = txn.cursor(&self.db);
+            let mut entry: Option<(RecordKey, SignedRecord)> = cursor.first();
+            while let Some((key, _)) = entry {
+                entry = cursor.next();
+                let is_latest = match entry {
+                    Some((ref next_key, _)) => next_key.kind != key.kind,
+                    None => true,
+                };
+                if key.block_number < cutoff && !is_latest {
+                    obsolete.push(key);
+                }
+            }
+        }
+
+        for key in obsolete.iter() {
+            txn.remove(&self.db, key);
+        }
+        txn.commit();
+    }
+
+    /// Exports all records in the interchange format.
+    pub fn export(&self, validator_key: &CompressedPublicKey) -> JsonValue {
+        let txn = ReadTransaction::new(&self.env);
+        let mut signatures = JsonValue::new_array();
+
+        let mut cursor = txn.cursor(&self.db);
+        let mut entry: Option<(RecordKey, SignedRecord)> = cursor.first();
+        while let Some((key, record)) = entry {
+            signatures.push(object!{
+                "type" => key.kind.as_str(),
+                "blockNumber" => key.block_number,
+                "viewNumber" => record.view_number,
+                "hash" => record.hash.to_hex(),
+            }).unwrap();
+            entry = cursor.next();
+        }
+
+        object!{
+            "version" => Self::INTERCHANGE_VERSION,
+            "validatorKey" => validator_key.to_hex(),
+            "signatures" => signatures,
+        }
+    }
+
+    /// Imports records from the interchange format and merges them with the existing ones.
+    /// Nothing is imported if the data is invalid, belongs to another key or conflicts with
+    /// existing records. Returns the number of imported records.
+    pub fn import(&self, interchange: &JsonValue, validator_key: &CompressedPublicKey) -> Result<usize, SlashingProtectionError> {
+        if interchange["version"].as_u32() != Some(Self::INTERCHANGE_VERSION) {
+            return Err(SlashingProtectionError::InvalidInterchange("Unsupported version".to_string()));
+        }
+        if interchange["validatorKey"].as_str() != Some(validator_key.to_hex().as_str()) {
+            return Err(SlashingProtectionError::KeyMismatch);
+        }
+        if !interchange["signatures"].is_array() {
+            return Err(SlashingProtectionError::InvalidInterchange("Missing signatures".to_string()));
+        }
+
+        let mut txn = WriteTransaction::new(&self.env);
+        let mut count = 0;
+        for signature in interchange["signatures"].members() {
+            let (key, record) = match Self::parse_signature(signature) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    txn.abort();
+                    return Err(e);
+                },
+            };
+
+            let signed: Option<SignedRecord> = txn.get(&self.db, &key);
+            match signed {
+                Some(ref signed) if signed.view_number > record.view_number => continue,
+                Some(ref signed) if signed.view_number == record.view_number => {
+                    if signed.hash != record.hash {
+                        txn.abort();
+                        return Err(SlashingProtectionError::Conflict(key.kind, key.block_number, record.view_number));
+                    }
+                    continue;
+                },
+                _ => {},
+            }
+
+            txn.put_reserve(&self.db, &key, &record);
+            count += 1;
+        }
+
+        txn.commit();
+        Ok(count)
+    }
+
+    fn parse_signature(signature: &JsonValue) -> Result<(RecordKey, SignedRecord), SlashingProtectionError> {
+        let invalid = |field: &str| SlashingProtectionError::InvalidInterchange(format!("Invalid {}", field));
+
+        let kind = signature["type"].as_str()
+            .ok_or_else(|| invalid("type"))?
+            .parse()?;
+        let block_number = signature["blockNumber"].as_u32()
+            .ok_or_else(|| invalid("blockNumber"))?;
+        let view_number = signature["viewNumber"].as_u32()
+            .ok_or_else(|| invalid("viewNumber"))?;
+        let hash = signature["hash"].as_str()
+            .and_then(|hash| hash.parse().ok())
+            .ok_or_else(|| invalid("hash"))?;
+
+        Ok((RecordKey { kind, block_number }, SignedRecord { view_number, hash }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bls::bls12_381::KeyPair;
+    use bls::SecureGenerate;
+    use database::volatile::VolatileEnvironment;
+
+    use super::*;
+
+    fn hash(byte: u8) -> Blake2bHash {
+        Blake2bHash::from([byte; Blake2bHash::SIZE])
+    }
+
+    #[test]
+    fn it_refuses_conflicting_signatures() {
+        let env = VolatileEnvironment::new(1).unwrap();
+        let protection = SlashingProtection::new(env);
+
+        assert_eq!(protection.record(SigningKind::MicroBlock, 10, 0, hash(1)), Ok(()));
+        // Signing the same message again is fine.
+        assert_eq!(protection.record(SigningKind::MicroBlock, 10, 0, hash(1)), Ok(()));
+        assert_eq!(protection.record(SigningKind::MicroBlock, 10, 0, hash(2)), Err(SlashingProtectionError::Conflict(SigningKind::MicroBlock, 10, 0)));
+        assert_eq!(protection.record(SigningKind::MicroBlock, 10, 1, hash(2)), Ok(()));
+        assert_eq!(protection.record(SigningKind::MicroBlock, 10, 0, hash(1)), Err(SlashingProtectionError::Outdated(SigningKind::MicroBlock, 10, 0)));
+        assert_eq!(protection.record(SigningKind::MicroBlock, 12, 0, hash(3)), Ok(()));
+        assert_eq!(protection.record(SigningKind::MicroBlock, 11, 0, hash(4)), Err(SlashingProtectionError::Outdated(SigningKind::MicroBlock, 11, 0)));
+
+        // Kinds are independent.
+        assert_eq!(protection.record(SigningKind::PbftPrepare, 11, 0, hash(4)), Ok(()));
+    }
+
+    #[test]
+    fn it_returns_the_latest_prepared_proposal() {
+        let env = VolatileEnvironment::new(1).unwrap();
+        let protection = SlashingProtection::new(env.clone());
+        assert_eq!(protection.signed_pbft_prepare(32, &hash(1)), None);
+
+        protection.record_pbft_prepare(32, 0, &hash(1)).unwrap();
+        assert_eq!(protection.signed_pbft_prepare(32, &hash(1)), Some(0));
+        assert_eq!(protection.signed_pbft_prepare(32, &hash(2)), None);
+
+        // Prepares survive a restart.
+        let protection = SlashingProtection::new(env);
+        protection.record_pbft_prepare(32, 1, &hash(2)).unwrap();
+        assert_eq!(protection.signed_pbft_prepare(32, &hash(1)), None);
+        assert_eq!(protection.signed_pbft_prepare(32, &hash(2)), Some(1));
+    }
+
+    #[test]
+    fn it_exports_and_imports_records() {
+        let key = KeyPair::generate_default_csprng().public.compress();
+
+        let env = VolatileEnvironment::new(1).unwrap();
+        let protection = SlashingProtection::new(env);
+        protection.record(SigningKind::MicroBlock, 10, 0, hash(1)).unwrap();
+        protection.record(SigningKind::PbftCommit, 32, 2, hash(2)).unwrap();
+        let interchange = protection.export(&key);
+
+        let env = VolatileEnvironment::new(1).unwrap();
+        let imported = SlashingProtection::new(env);
+        imported.record(SigningKind::MicroBlock, 10, 0, hash(1)).unwrap();
+        assert_eq!(imported.import(&interchange, &key), Ok(1));
+        assert_eq!(imported.record(SigningKind::PbftCommit, 32, 2, hash(3)), Err(SlashingProtectionError::Conflict(SigningKind::PbftCommit, 32, 2)));
+
+        let env = VolatileEnvironment::new(1).unwrap();
+        let conflicting = SlashingProtection::new(env);
+        conflicting.record(SigningKind::MicroBlock, 10, 0, hash(5)).unwrap();
+        assert_eq!(conflicting.import(&interchange, &key), Err(SlashingProtectionError::Conflict(SigningKind::MicroBlock, 10, 0)));
+        // Nothing was imported.
+        assert_eq!(conflicting.record(SigningKind::PbftCommit, 32, 2, hash(3)), Ok(()));
+    }
+}
@@ -0,0 +1,105 @@
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread;
+
+use beserial::{Deserialize, Serialize};
+use nimiq_block_albatross::{PbftPrepareMessage, ViewChange};
+use nimiq_bls::SecureGenerate;
+use nimiq_bls::bls12_381::KeyPair;
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_hash::Blake2bHash;
+use nimiq_signer::{LocalSigner, RemoteSigner, Signer, SignerAddress, SignerError, SigningRequest};
+use nimiq_signer::server::SignerServer;
+use nimiq_signer::slashing_protection::SlashingProtection;
+use nimiq_vrf::VrfSeed;
+
+const TOKEN: &str = "correct horse battery staple";
+
+fn start_signer(key_pair: KeyPair) -> SignerAddress {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = SignerAddress::Tcp(listener.local_addr().unwrap().to_string());
+    let signer: Arc<dyn Signer> = Arc::new(LocalSigner::new(key_pair));
+    let env = VolatileEnvironment::new(1).unwrap();
+    let server = SignerServer::new(signer, SlashingProtection::new(env), Some(TOKEN.to_string()));
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            // Connections that fail to authenticate are closed with an error.
+            let _ = server.serve_connection(stream.unwrap());
+        }
+    });
+    address
+}
+
+#[test]
+fn it_can_serialize_signing_requests() {
+    let request = SigningRequest::PbftPrepare {
+        message: PbftPrepareMessage { block_hash: Blake2bHash::from([1u8; 32]) },
+        block_number: 32,
+        view_number: 1,
+    };
+    let data = request.serialize_to_vec();
+    assert_eq!(data.len(), request.serialized_size());
+    assert_eq!(SigningRequest::deserialize_from_vec(&data).unwrap(), request);
+
+    let request = SigningRequest::ProofOfKnowledge;
+    assert_eq!(SigningRequest::deserialize_from_vec(&request.serialize_to_vec()).unwrap(), request);
+}
+
+#[test]
+fn it_can_sign_remotely() {
+    let key_pair = KeyPair::generate_default_csprng();
+    let local: Arc<dyn Signer> = Arc::new(LocalSigner::new(key_pair.clone()));
+    let remote: Arc<dyn Signer> = Arc::new(RemoteSigner::connect(start_signer(key_pair.clone()), Some(TOKEN.to_string())).unwrap());
+    assert_eq!(remote.public_key(), &key_pair.public);
+
+    let view_change = ViewChange { block_number: 42, new_view_number: 1 };
+    let signed = remote.sign_message(view_change.clone(), 3).unwrap();
+    assert_eq!(signed.signer_idx, 3);
+    assert!(signed.verify(&key_pair.public));
+    assert_eq!(signed.signature, local.sign_message(view_change, 3).unwrap().signature);
+
+    let prev_seed = VrfSeed::from(key_pair.sign(&key_pair.public).compress());
+    let seed = remote.sign_next_seed(&prev_seed).unwrap();
+    assert_eq!(seed, prev_seed.sign_next(&key_pair.secret));
+    assert!(seed.verify(&prev_seed, &key_pair.public).is_ok());
+
+    assert_eq!(remote.proof_of_knowledge().unwrap(), key_pair.sign(&key_pair.public).compress());
+}
+
+#[test]
+fn it_requires_a_token_over_tcp() {
+    let address = start_signer(KeyPair::generate_default_csprng());
+
+    match RemoteSigner::connect(address.clone(), None) {
+        Err(SignerError::MissingToken) => {},
+        _ => panic!("Connected without a token"),
+    }
+    match RemoteSigner::connect(address.clone(), Some("wrong token".to_string())) {
+        Err(SignerError::Refused(_)) => {},
+        _ => panic!("Connected with a wrong token"),
+    }
+    assert!(RemoteSigner::connect(address, Some(TOKEN.to_string())).is_ok());
+}
+
+#[test]
+fn it_refuses_to_sign_slashable_messages() {
+    let key_pair = KeyPair::generate_default_csprng();
+    let remote: Arc<dyn Signer> = Arc::new(RemoteSigner::connect(start_signer(key_pair), Some(TOKEN.to_string())).unwrap());
+
+    // The signer refuses to go back to an earlier view, no matter what the validator checked.
+    assert!(remote.sign_message(ViewChange { block_number: 42, new_view_number: 2 }, 0).is_ok());
+    match remote.sign_message(ViewChange { block_number: 42, new_view_number: 1 }, 0) {
+        Err(SignerError::Refused(_)) => {},
+        _ => panic!("Signed an outdated view change"),
+    }
+
+    // A prepare for a different proposal in the same view would conflict.
+    let prepare = |byte: u8| PbftPrepareMessage { block_hash: Blake2bHash::from([byte; 32]) };
+    assert!(remote.sign_pbft_prepare(prepare(1), 64, 0, 0).is_ok());
+    assert!(remote.sign_pbft_prepare(prepare(1), 64, 0, 0).is_ok());
+    match remote.sign_pbft_prepare(prepare(2), 64, 0, 0) {
+        Err(SignerError::Refused(_)) => {},
+        _ => panic!("Signed a conflicting prepare"),
+    }
+}
@@ -1,19 +1,213 @@
 extern crate nimiq_bls as bls;
+extern crate nimiq_utils as utils;
 
-use beserial::Serialize;
-use bls::{SecureGenerate};
-use bls::bls12_381::{PublicKey, SecretKey};
+use std::env;
+use std::fs;
+use std::io::{stdin, BufRead};
+use std::process::exit;
 
-fn main() {
-    let secret_key = SecretKey::generate_default_csprng();
-    let public_key = PublicKey::from_secret(&secret_key);
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand, crate_version, crate_authors, crate_description};
+use failure::{Error, Fail};
+
+use beserial::{Deserialize, Serialize};
+use bls::SecureGenerate;
+use bls::bls12_381::{KeyPair, SecretKey};
+use utils::key_store::KeyStore;
+
+/// Environment variable that contains the passphrase of the key file.
+const PASSPHRASE_ENV_VAR: &str = "NIMIQ_BLS_PASSPHRASE";
+
+
+fn key_file_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("key_file")
+        .short("k")
+        .long("key-file")
+        .value_name("FILE")
+        .help("Path to the key file.")
+        .takes_value(true)
+        .required(true)
+}
+
+fn passphrase_file_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("passphrase_file")
+        .short("p")
+        .long("passphrase-file")
+        .value_name("FILE")
+        .help("Read the passphrase from the first line of FILE instead of asking for it.")
+        .takes_value(true)
+}
 
+/// Reads the passphrase from the passphrase file, the environment or the terminal.
+fn passphrase(matches: &ArgMatches, arg: &str, prompt: &str, confirm: bool) -> Result<String, Error> {
+    if let Some(file) = matches.value_of(arg) {
+        let content = fs::read_to_string(file)?;
+        return Ok(content.lines().next().unwrap_or_default().to_string());
+    }
+
+    if let Ok(passphrase) = env::var(PASSPHRASE_ENV_VAR) {
+        return Ok(passphrase);
+    }
+
+    let passphrase = rpassword::read_password_from_tty(Some(prompt))?;
+    if confirm && passphrase != rpassword::read_password_from_tty(Some("Repeat passphrase: "))? {
+        return Err(AppError::PassphraseMismatch.into());
+    }
+    if passphrase.is_empty() {
+        return Err(AppError::EmptyPassphrase.into());
+    }
+    Ok(passphrase)
+}
+
+fn load_key(matches: &ArgMatches) -> Result<KeyPair, Error> {
+    let key_store = KeyStore::new(matches.value_of("key_file").unwrap().to_string());
+    let secret_key: SecretKey = if key_store.is_encrypted()? {
+        let passphrase = passphrase(matches, "passphrase_file", "Passphrase: ", false)?;
+        key_store.load_encrypted_key(passphrase.as_bytes())?
+    } else {
+        key_store.load_key()?
+    };
+    Ok(KeyPair::from(secret_key))
+}
+
+fn print_public_key(key_pair: &KeyPair) {
     println!("# Public Key:");
     println!();
-    let x: Vec<u8> = public_key.serialize_to_vec(); // need to apply a little bit of force to make it a slice
-    println!("{}", hex::encode(x));
+    println!("{}", key_pair.public);
+    println!();
+    println!("# Compressed Public Key:");
     println!();
-    println!("# Secret Key:");
+    println!("{}", key_pair.public.compress());
+}
+
+fn print_proof_of_knowledge(key_pair: &KeyPair) {
+    println!("# Proof of Knowledge:");
     println!();
-    println!("{}", hex::encode(secret_key.serialize_to_vec()));
-}
\ No newline at end of file
+    println!("{}", key_pair.sign(&key_pair.public).compress());
+}
+
+fn run_app() -> Result<(), Error> {
+    let matches = App::new("BLS key tool")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(SubCommand::with_name("generate")
+            .about("Generates a new key and stores it encrypted in the key file. Without key file, the secret key is printed.")
+            .arg(key_file_arg().required(false))
+            .arg(passphrase_file_arg()))
+        .subcommand(SubCommand::with_name("encrypt")
+            .about("Encrypts an unencrypted key file. If the key file doesn't exist, the hex-encoded secret key is read from STDIN.")
+            .arg(key_file_arg())
+            .arg(passphrase_file_arg()))
+        .subcommand(SubCommand::with_name("reencrypt")
+            .about("Changes the passphrase of an encrypted key file.")
+            .arg(key_file_arg())
+            .arg(passphrase_file_arg())
+            .arg(Arg::with_name("new_passphrase_file")
+                .long("new-passphrase-file")
+                .value_name("FILE")
+                .help("Read the new passphrase from the first line of FILE instead of asking for it.")
+                .takes_value(true)))
+        .subcommand(SubCommand::with_name("public-key")
+            .about("Shows the public key.")
+            .arg(key_file_arg())
+            .arg(passphrase_file_arg()))
+        .subcommand(SubCommand::with_name("proof-of-knowledge")
+            .about("Prints the proof of knowledge of the secret key, which is needed to register a validator.")
+            .arg(key_file_arg())
+            .arg(passphrase_file_arg()))
+        .get_matches();
+
+    match matches.subcommand() {
+        ("generate", Some(matches)) => {
+            let secret_key = SecretKey::generate_default_csprng();
+            let key_pair = KeyPair::from(secret_key);
+
+            if let Some(key_file) = matches.value_of("key_file") {
+                let key_store = KeyStore::new(key_file.to_string());
+                if key_store.exists() {
+                    return Err(AppError::KeyFileExists(key_file.to_string()).into());
+                }
+                let passphrase = passphrase(matches, "passphrase_file", "New passphrase: ", true)?;
+                key_store.save_encrypted_key(secret_key, passphrase.as_bytes())?;
+
+                print_public_key(&key_pair);
+                println!();
+                print_proof_of_knowledge(&key_pair);
+            } else {
+                print_public_key(&key_pair);
+                println!();
+                println!("# Secret Key:");
+                println!();
+                println!("{}", hex::encode(secret_key.serialize_to_vec()));
+            }
+        },
+        ("encrypt", Some(matches)) => {
+            let key_store = KeyStore::new(matches.value_of("key_file").unwrap().to_string());
+            let secret_key: SecretKey = if key_store.exists() {
+                if key_store.is_encrypted()? {
+                    return Err(AppError::AlreadyEncrypted.into());
+                }
+                key_store.load_key()?
+            } else {
+                let mut line = String::new();
+                stdin().lock().read_line(&mut line)?;
+                SecretKey::deserialize_from_vec(&hex::decode(line.trim())?)?
+            };
+
+            let passphrase = passphrase(matches, "passphrase_file", "New passphrase: ", true)?;
+            key_store.save_encrypted_key(secret_key, passphrase.as_bytes())?;
+            print_public_key(&KeyPair::from(secret_key));
+        },
+        ("reencrypt", Some(matches)) => {
+            let key_store = KeyStore::new(matches.value_of("key_file").unwrap().to_string());
+            let passphrase = passphrase(matches, "passphrase_file", "Old passphrase: ", false)?;
+            let secret_key: SecretKey = key_store.load_encrypted_key(passphrase.as_bytes())?;
+
+            let new_passphrase = if let Some(file) = matches.value_of("new_passphrase_file") {
+                fs::read_to_string(file)?.lines().next().unwrap_or_default().to_string()
+            } else {
+                let new_passphrase = rpassword::read_password_from_tty(Some("New passphrase: "))?;
+                if new_passphrase != rpassword::read_password_from_tty(Some("Repeat passphrase: "))? {
+                    return Err(AppError::PassphraseMismatch.into());
+                }
+                new_passphrase
+            };
+            if new_passphrase.is_empty() {
+                return Err(AppError::EmptyPassphrase.into());
+            }
+            key_store.save_encrypted_key(secret_key, new_passphrase.as_bytes())?;
+        },
+        ("public-key", Some(matches)) => {
+            print_public_key(&load_key(matches)?);
+        },
+        ("proof-of-knowledge", Some(matches)) => {
+            print_proof_of_knowledge(&load_key(matches)?);
+        },
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
+
+fn main() {
+    exit(match run_app() {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    });
+}
+
+#[derive(Debug, Fail)]
+enum AppError {
+    #[fail(display = "Key file already exists: {}", _0)]
+    KeyFileExists(String),
+    #[fail(display = "Key file is already encrypted")]
+    AlreadyEncrypted,
+    #[fail(display = "Passphrases don't match")]
+    PassphraseMismatch,
+    #[fail(display = "Passphrase must not be empty")]
+    EmptyPassphrase,
+}
@@ -0,0 +1,194 @@
+extern crate nimiq_bls as bls;
+extern crate nimiq_database as database;
+extern crate nimiq_signer as signer;
+extern crate nimiq_utils as utils;
+
+use std::env;
+use std::fs;
+use std::net::TcpListener;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+use std::process::exit;
+use std::sync::Arc;
+use std::thread;
+
+use clap::{App, Arg, ArgMatches, crate_version, crate_authors, crate_description};
+use failure::{Error, Fail};
+use log::{error, info, Level};
+
+use bls::bls12_381::{KeyPair, SecretKey};
+use database::lmdb::{LmdbEnvironment, open as LmdbFlags};
+use signer::{LocalSigner, Signer, SignerAddress};
+use signer::server::SignerServer;
+use signer::slashing_protection::SlashingProtection;
+use utils::key_store::KeyStore;
+
+/// Environment variable that contains the passphrase of the key file.
+const PASSPHRASE_ENV_VAR: &str = "NIMIQ_SIGNER_PASSPHRASE";
+
+/// By default, only local processes with access to the socket can connect.
+const DEFAULT_LISTEN_ADDRESS: &str = "unix://signer.sock";
+
+const DEFAULT_DATABASE: &str = "signer-db";
+
+
+/// Reads the passphrase from the passphrase file, the environment or the terminal.
+fn passphrase(matches: &ArgMatches) -> Result<String, Error> {
+    if let Some(file) = matches.value_of("passphrase_file") {
+        let content = fs::read_to_string(file)?;
+        return Ok(content.lines().next().unwrap_or_default().to_string());
+    }
+
+    if let Ok(passphrase) = env::var(PASSPHRASE_ENV_VAR) {
+        return Ok(passphrase);
+    }
+
+    Ok(rpassword::read_password_from_tty(Some("Passphrase: "))?)
+}
+
+fn load_key(matches: &ArgMatches) -> Result<KeyPair, Error> {
+    let key_store = KeyStore::new(matches.value_of("key_file").unwrap().to_string());
+    let secret_key: SecretKey = if key_store.is_encrypted()? {
+        key_store.load_encrypted_key(passphrase(matches)?.as_bytes())?
+    } else {
+        if !matches.is_present("allow_unencrypted") {
+            return Err(AppError::Unencrypted.into());
+        }
+        key_store.load_key()?
+    };
+    Ok(KeyPair::from(secret_key))
+}
+
+fn load_token(matches: &ArgMatches) -> Result<Option<String>, Error> {
+    match matches.value_of("token_file") {
+        Some(file) => {
+            let content = fs::read_to_string(file)?;
+            let token = content.lines().next().unwrap_or_default().trim().to_string();
+            if token.is_empty() {
+                return Err(AppError::EmptyToken.into());
+            }
+            Ok(Some(token))
+        },
+        None => Ok(None),
+    }
+}
+
+fn spawn_connection<S: std::io::Read + std::io::Write + Send + 'static>(server: &Arc<SignerServer>, stream: S, peer: String) {
+    let server = Arc::clone(server);
+    thread::spawn(move || {
+        info!("Validator connected: {}", peer);
+        match server.serve_connection(stream) {
+            Ok(()) => info!("Validator disconnected: {}", peer),
+            Err(e) => error!("Connection to {} failed: {}", peer, e),
+        }
+    });
+}
+
+fn run_app() -> Result<(), Error> {
+    let matches = App::new("Remote signer")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .arg(Arg::with_name("key_file")
+            .short("k")
+            .long("key-file")
+            .value_name("FILE")
+            .help("Path to the validator key file, as created by `nimiq-bls generate`.")
+            .takes_value(true)
+            .required(true))
+        .arg(Arg::with_name("passphrase_file")
+            .short("p")
+            .long("passphrase-file")
+            .value_name("FILE")
+            .help("Read the passphrase from the first line of FILE instead of asking for it.")
+            .takes_value(true))
+        .arg(Arg::with_name("allow_unencrypted")
+            .long("allow-unencrypted")
+            .help("Allow unencrypted key files. Only use this for testing.")
+            .takes_value(false))
+        .arg(Arg::with_name("listen")
+            .short("l")
+            .long("listen")
+            .value_name("ADDRESS")
+            .help("Listen on ADDRESS, either unix:///path/to/socket or tcp://host:port. Listening on TCP requires --token-file.")
+            .default_value(DEFAULT_LISTEN_ADDRESS)
+            .takes_value(true))
+        .arg(Arg::with_name("token_file")
+            .short("t")
+            .long("token-file")
+            .value_name("FILE")
+            .help("Only accept validators that authenticate with the token in the first line of FILE.")
+            .takes_value(true))
+        .arg(Arg::with_name("database")
+            .short("d")
+            .long("database")
+            .value_name("DIR")
+            .help("Directory of the slashing protection database.")
+            .default_value(DEFAULT_DATABASE)
+            .takes_value(true))
+        .get_matches();
+
+    simple_logger::init_with_level(Level::Info)?;
+
+    let address: SignerAddress = matches.value_of("listen").unwrap().parse()?;
+    let token = load_token(&matches)?;
+    if let SignerAddress::Tcp(_) = address {
+        // The token is the only thing that keeps others on the network from signing with the key.
+        if token.is_none() {
+            return Err(AppError::TcpWithoutToken.into());
+        }
+    }
+
+    let signer: Arc<dyn Signer> = Arc::new(LocalSigner::new(load_key(&matches)?));
+    info!("Public key: {}", signer.public_key().compress());
+
+    let env = LmdbEnvironment::new(matches.value_of("database").unwrap(), 0, 1, LmdbFlags::Flags::empty())?;
+    let server = Arc::new(SignerServer::new(signer, SlashingProtection::new(env), token));
+
+    match address {
+        SignerAddress::Tcp(host) => {
+            let listener = TcpListener::bind(&host)?;
+            info!("Listening on tcp://{}", listener.local_addr()?);
+            for stream in listener.incoming() {
+                let stream = stream?;
+                let peer = stream.peer_addr()?.to_string();
+                spawn_connection(&server, stream, peer);
+            }
+        },
+        #[cfg(unix)]
+        SignerAddress::Unix(path) => {
+            let listener = UnixListener::bind(&path)?;
+            info!("Listening on unix://{}", path.display());
+            for stream in listener.incoming() {
+                spawn_connection(&server, stream?, path.display().to_string());
+            }
+        },
+        #[cfg(not(unix))]
+        SignerAddress::Unix(_) => return Err(AppError::UnixSocketsUnsupported.into()),
+    }
+
+    Ok(())
+}
+
+fn main() {
+    exit(match run_app() {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    });
+}
+
+#[derive(Debug, Fail)]
+enum AppError {
+    #[fail(display = "Key file is not encrypted. Use `nimiq-bls encrypt` or pass --allow-unencrypted")]
+    Unencrypted,
+    #[fail(display = "Listening on TCP requires a token. Pass --token-file or listen on a unix socket")]
+    TcpWithoutToken,
+    #[fail(display = "Token file is empty")]
+    EmptyToken,
+    #[cfg(not(unix))]
+    #[fail(display = "Unix sockets are not supported on this platform")]
+    UnixSocketsUnsupported,
+}
@@ -3,7 +3,15 @@ use std::io::Error as IoError;
 
 use failure::Fail;
 
-use beserial::{Deserialize, Serialize};
+use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteBytesExt};
+use nimiq_hash::argon2kdf::Argon2Error;
+
+use crate::crc::Crc32Computer;
+use crate::otp::{Locked, Unlocked, Verify};
+
+/// Prefix of encrypted key files. Unencrypted key files only contain the serialized key.
+const ENCRYPTED_KEY_MAGIC: &[u8; 8] = b"NIMIQKEY";
+const ENCRYPTED_KEY_VERSION: u8 = 1;
 
 pub struct KeyStore {
     path: String,
@@ -16,9 +24,26 @@ impl KeyStore {
         }
     }
 
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn exists(&self) -> bool {
+        fs::metadata(&self.path).is_ok()
+    }
+
+    /// Returns whether the key file is encrypted with a passphrase.
+    pub fn is_encrypted(&self) -> Result<bool, Error> {
+        Ok(fs::read(&self.path)?.starts_with(ENCRYPTED_KEY_MAGIC))
+    }
+
+    /// Loads an unencrypted key.
     pub fn load_key<T: Serialize + Deserialize>(&self) -> Result<T, Error> {
         match fs::read(&self.path) {
             Ok(data) => {
+                if data.starts_with(ENCRYPTED_KEY_MAGIC) {
+                    return Err(Error::Encrypted);
+                }
                 Deserialize::deserialize_from_vec(&data).map_err(|_| Error::InvalidKey)
             },
             Err(e) => Err(Error::IoError(e)),
@@ -28,12 +53,116 @@ impl KeyStore {
     pub fn save_key<T: Serialize + Deserialize>(&self, key_pair: &T) -> Result<(), Error> {
         Ok(fs::write(&self.path, key_pair.serialize_to_vec())?)
     }
+
+    /// Loads a key that is encrypted with `passphrase`.
+    /// Calling code should make sure to clear the passphrase from memory after use.
+    pub fn load_encrypted_key<T: Default + Serialize + Deserialize>(&self, passphrase: &[u8]) -> Result<T, Error> {
+        let data = fs::read(&self.path)?;
+        if !data.starts_with(ENCRYPTED_KEY_MAGIC) {
+            return Err(Error::NotEncrypted);
+        }
+
+        let reader = &mut &data[ENCRYPTED_KEY_MAGIC.len()..];
+        let version: u8 = Deserialize::deserialize(reader).map_err(|_| Error::InvalidKey)?;
+        if version != ENCRYPTED_KEY_VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        let locked: Locked<CheckedKey<T>> = Deserialize::deserialize(reader).map_err(|_| Error::InvalidKey)?;
+        let unlocked = locked.unlock(passphrase).map_err(|_| Error::WrongPassphrase)?;
+        Ok(Unlocked::into_unlocked_data(unlocked).key)
+    }
+
+    /// Encrypts `key` with `passphrase` and writes it to the key file.
+    /// Calling code should make sure to clear the passphrase from memory after use.
+    pub fn save_encrypted_key<T: Default + Serialize + Deserialize>(&self, key: T, passphrase: &[u8]) -> Result<(), Error> {
+        let locked = Locked::with_defaults(CheckedKey::new(key), passphrase)?;
+
+        let mut data = ENCRYPTED_KEY_MAGIC.to_vec();
+        ENCRYPTED_KEY_VERSION.serialize(&mut data).map_err(|_| Error::InvalidKey)?;
+        locked.serialize(&mut data).map_err(|_| Error::InvalidKey)?;
+        Ok(fs::write(&self.path, data)?)
+    }
+
+    /// Loads the key, decrypting it with `passphrase` if the key file is encrypted.
+    pub fn load_key_with_passphrase<T: Default + Serialize + Deserialize>(&self, passphrase: Option<&[u8]>) -> Result<T, Error> {
+        if !self.is_encrypted()? {
+            return self.load_key();
+        }
+        self.load_encrypted_key(passphrase.ok_or(Error::Encrypted)?)
+    }
+}
+
+/// A key together with a checksum, which detects wrong passphrases when unlocking.
+#[derive(Default)]
+struct CheckedKey<T: Default + Serialize + Deserialize> {
+    key: T,
+    checksum: u32,
+}
+
+impl<T: Default + Serialize + Deserialize> CheckedKey<T> {
+    fn new(key: T) -> Self {
+        let checksum = Self::compute_checksum(&key);
+        CheckedKey {
+            key,
+            checksum,
+        }
+    }
+
+    fn compute_checksum(key: &T) -> u32 {
+        let mut data = key.serialize_to_vec();
+        let checksum = Crc32Computer::default().update(&data).result();
+
+        // Always overwrite unencrypted vector.
+        for byte in data.iter_mut() {
+            *byte = 0;
+        }
+        checksum
+    }
+}
+
+impl<T: Default + Serialize + Deserialize> Verify for CheckedKey<T> {
+    fn verify(&self) -> bool {
+        Self::compute_checksum(&self.key) == self.checksum
+    }
+}
+
+impl<T: Default + Serialize + Deserialize> Serialize for CheckedKey<T> {
+    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
+        let mut size = 0;
+        size += Serialize::serialize(&self.key, writer)?;
+        size += Serialize::serialize(&self.checksum, writer)?;
+        Ok(size)
+    }
+
+    fn serialized_size(&self) -> usize {
+        Serialize::serialized_size(&self.key) + Serialize::serialized_size(&self.checksum)
+    }
+}
+
+impl<T: Default + Serialize + Deserialize> Deserialize for CheckedKey<T> {
+    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        Ok(CheckedKey {
+            key: Deserialize::deserialize(reader)?,
+            checksum: Deserialize::deserialize(reader)?,
+        })
+    }
 }
 
 #[derive(Fail, Debug)]
 pub enum Error {
     #[fail(display = "Key could not be deserialized")]
     InvalidKey,
+    #[fail(display = "Key file is encrypted, but no passphrase was given")]
+    Encrypted,
+    #[fail(display = "Key file is not encrypted")]
+    NotEncrypted,
+    #[fail(display = "Wrong passphrase")]
+    WrongPassphrase,
+    #[fail(display = "Unsupported key file version: {}", _0)]
+    UnsupportedVersion(u8),
+    #[fail(display = "Key derivation failed: {}", _0)]
+    Argon2Error(#[cause] Argon2Error),
     #[fail(display = "{}", _0)]
     IoError(#[cause] IoError),
 }
@@ -43,3 +172,9 @@ impl From<IoError> for Error {
         Error::IoError(e)
     }
 }
+
+impl From<Argon2Error> for Error {
+    fn from(e: Argon2Error) -> Self {
+        Error::Argon2Error(e)
+    }
+}
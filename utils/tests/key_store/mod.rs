@@ -0,0 +1,48 @@
+use std::env;
+use std::fs;
+
+use nimiq_utils::key_store::{Error, KeyStore};
+
+fn key_store(name: &str) -> KeyStore {
+    let path = env::temp_dir().join(format!("nimiq-key-store-test-{}-{}", name, std::process::id()));
+    KeyStore::new(path.to_str().unwrap().to_string())
+}
+
+#[test]
+fn it_can_save_and_load_encrypted_keys() {
+    let key_store = key_store("encrypted");
+    let secret: u64 = 0x0102_0304_0506_0708;
+
+    key_store.save_encrypted_key(secret, b"passphrase").unwrap();
+    assert!(key_store.is_encrypted().unwrap());
+
+    match key_store.load_key::<u64>() {
+        Err(Error::Encrypted) => {},
+        _ => panic!("Encrypted key must not be loaded without passphrase"),
+    }
+    match key_store.load_encrypted_key::<u64>(b"wrong passphrase") {
+        Err(Error::WrongPassphrase) => {},
+        _ => panic!("Key must not be unlocked with a wrong passphrase"),
+    }
+    assert_eq!(key_store.load_encrypted_key::<u64>(b"passphrase").unwrap(), secret);
+    assert_eq!(key_store.load_key_with_passphrase::<u64>(Some(b"passphrase")).unwrap(), secret);
+
+    fs::remove_file(key_store.path()).unwrap();
+}
+
+#[test]
+fn it_can_load_unencrypted_keys_with_passphrase() {
+    let key_store = key_store("plain");
+    let secret: u64 = 42;
+
+    key_store.save_key(&secret).unwrap();
+    assert!(!key_store.is_encrypted().unwrap());
+    assert_eq!(key_store.load_key_with_passphrase::<u64>(None).unwrap(), secret);
+    assert_eq!(key_store.load_key_with_passphrase::<u64>(Some(b"passphrase")).unwrap(), secret);
+    match key_store.load_encrypted_key::<u64>(b"passphrase") {
+        Err(Error::NotEncrypted) => {},
+        _ => panic!("Unencrypted key must be reported"),
+    }
+
+    fs::remove_file(key_store.path()).unwrap();
+}
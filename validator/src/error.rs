@@ -2,6 +2,7 @@ use failure::Fail;
 
 use blockchain_base::BlockchainError;
 use consensus::Error as ConsensusError;
+use signer::SignerError;
 use utils::key_store::Error as KeyStoreError;
 
 
@@ -13,6 +14,8 @@ pub enum Error {
     ConsensusError(#[cause] ConsensusError),
     #[fail(display = "{}", _0)]
     KeyStoreError(#[cause] KeyStoreError),
+    #[fail(display = "{}", _0)]
+    SignerError(#[cause] SignerError),
 }
 
 impl From<ConsensusError> for Error {
@@ -32,3 +35,9 @@ impl From<BlockchainError> for Error {
         Error::BlockchainError(e)
     }
 }
+
+impl From<SignerError> for Error {
+    fn from(e: SignerError) -> Self {
+        Error::SignerError(e)
+    }
+}
@@ -21,6 +21,7 @@ extern crate nimiq_primitives as primitives;
 extern crate nimiq_blockchain_albatross as blockchain_albatross;
 extern crate nimiq_blockchain_base as blockchain_base;
 extern crate nimiq_block_production_albatross as block_production_albatross;
+extern crate nimiq_signer as signer;
 
 pub mod validator;
 pub mod validator_network;
@@ -29,4 +30,6 @@ pub mod error;
 pub mod slash;
 pub mod signature_aggregation;
 pub mod pool;
+#[cfg(feature = "metrics")]
+pub mod validator_metrics;
 
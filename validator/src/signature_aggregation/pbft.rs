@@ -193,7 +193,7 @@ pub struct PbftAggregation {
 
 
 impl PbftAggregation {
-    pub fn new(proposal_hash: Blake2bHash, node_id: usize, validators: Arc<RwLock<ValidatorPool>>, config: Option<Config>) -> Self {
+    pub fn new(proposal_hash: Blake2bHash, node_id: usize, validators: Arc<RwLock<ValidatorPool>>, threshold: usize, config: Option<Config>) -> Self {
         let config = config.unwrap_or_default();
 
         // create prepare aggregation
@@ -201,6 +201,7 @@ impl PbftAggregation {
             PbftPrepareMessage::from(proposal_hash.clone()),
             node_id,
             validators,
+            threshold,
         );
         let prepare_aggregation = Aggregation::new(prepare_protocol, config.clone());
 
@@ -7,7 +7,6 @@ use std::fmt;
 
 use parking_lot::RwLock;
 
-use primitives::policy::TWO_THIRD_SLOTS;
 use block_albatross::signed;
 use messages::Message;
 use bls::bls12_381::PublicKey;
@@ -30,7 +29,7 @@ use crate::pool::ValidatorPool;
 
 
 
-/// The evaluator used for voting
+/// The evaluator used for voting. Signatures are weighted by the number of slots of their signers.
 pub type VotingEvaluator = WeightedVote<ReplaceStore<BinomialPartitioner>, ValidatorRegistry, BinomialPartitioner>;
 
 
@@ -127,14 +126,15 @@ pub struct VotingProtocol<T: Tag> {
     partitioner: Arc<BinomialPartitioner>,
     store: Arc<RwLock<ReplaceStore<BinomialPartitioner>>>,
 
-    /// The evaluator being used. This either just counts votes
+    /// The evaluator being used. This weights the signatures by the slots of the signers.
     evaluator: Arc<VotingEvaluator>,
 
     sender: Arc<VotingSender<T>>,
 }
 
 impl<T: Tag> VotingProtocol<T> {
-    pub fn new(tag: T, node_id: usize, validators: Arc<RwLock<ValidatorPool>>) -> Self {
+    /// `threshold` is the number of slots whose signatures complete the aggregation.
+    pub fn new(tag: T, node_id: usize, validators: Arc<RwLock<ValidatorPool>>, threshold: usize) -> Self {
         let guard = validators.read();
 
         let num_validators = guard.active_validator_count();
@@ -150,7 +150,6 @@ impl<T: Tag> VotingProtocol<T> {
             tag.hash_with_prefix(),
             Arc::clone(&registry),
         ));
-        //let timeouts = Arc::new(LinearTimeout::new(config.timeout));
         let partitioner = Arc::new(BinomialPartitioner::new(
             node_id,
             num_validators,
@@ -160,7 +159,7 @@ impl<T: Tag> VotingProtocol<T> {
             Arc::clone(&store),
             Arc::clone(&registry),
             Arc::clone(&partitioner),
-            TWO_THIRD_SLOTS as usize,
+            threshold,
         ));
         let sender = Arc::new(VotingSender::new(tag.clone(), Arc::clone(&validators)));
 
@@ -237,9 +236,9 @@ pub struct VoteAggregation<T: Tag> {
 }
 
 impl<T: Tag> VoteAggregation<T> {
-    pub fn new(tag: T, node_id: usize, validators: Arc<RwLock<ValidatorPool>>, config: Option<Config>) -> Self {
+    pub fn new(tag: T, node_id: usize, validators: Arc<RwLock<ValidatorPool>>, threshold: usize, config: Option<Config>) -> Self {
         let config = config.unwrap_or_default();
-        let protocol = VotingProtocol::new(tag, node_id, validators);
+        let protocol = VotingProtocol::new(tag, node_id, validators, threshold);
         let aggregation = Aggregation::new(protocol, config);
         Self { inner: aggregation }
     }
@@ -29,12 +29,13 @@ use block_albatross::{
 use block_production_albatross::BlockProducer;
 use blockchain_albatross::Blockchain;
 use blockchain_base::BlockchainEvent;
-use bls::bls12_381::KeyPair;
+use bls::bls12_381::PublicKey;
 use consensus::{AlbatrossConsensusProtocol, Consensus, ConsensusEvent};
 use hash::{Blake2bHash, Hash};
 use macros::upgrade_weak;
-use network_primitives::networks::NetworkInfo;
-use network_primitives::validator_info::{SignedValidatorInfo, ValidatorInfo};
+use network_primitives::validator_info::ValidatorInfo;
+use signer::Signer;
+use signer::slashing_protection::SlashingProtection;
 use utils::mutable_once::MutableOnce;
 use utils::observer::ListenerHandle;
 use utils::timers::Timers;
@@ -42,6 +43,8 @@ use utils::timers::Timers;
 use crate::error::Error;
 use crate::slash::ForkProofPool;
 use crate::validator_network::{ValidatorNetwork, ValidatorNetworkEvent};
+#[cfg(feature = "metrics")]
+use crate::validator_metrics::ValidatorMetrics;
 
 #[derive(Clone, Debug)]
 pub enum SlotChange  {
@@ -68,7 +71,8 @@ pub struct Validator {
     block_producer: BlockProducer,
     consensus: Arc<Consensus<AlbatrossConsensusProtocol>>,
     pub validator_network: Arc<ValidatorNetwork>,
-    pub validator_key: KeyPair,
+    pub signer: Arc<dyn Signer>,
+    pub slashing_protection: SlashingProtection,
 
     timers: Timers<ValidatorTimer>,
 
@@ -76,6 +80,9 @@ pub struct Validator {
 
     self_weak: MutableOnce<Weak<Validator>>,
     listeners: MutableOnce<Option<ValidatorListeners>>,
+
+    #[cfg(feature = "metrics")]
+    pub metrics: ValidatorMetrics,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -97,17 +104,18 @@ impl Validator {
     const BLOCK_TIMEOUT: Duration = Duration::from_secs(10);
     //const PBFT_TIMEOUT: Duration = Duration::from_secs(60);
 
-    pub fn new(consensus: Arc<Consensus<AlbatrossConsensusProtocol>>, validator_key: KeyPair) -> Result<Arc<Self>, Error> {
-        let compressed_public_key = validator_key.public.compress();
+    pub fn new(consensus: Arc<Consensus<AlbatrossConsensusProtocol>>, signer: Arc<dyn Signer>) -> Result<Arc<Self>, Error> {
+        let compressed_public_key = signer.public_key().compress();
         let info = ValidatorInfo {
             public_key: compressed_public_key,
             peer_address: consensus.network.network_config.peer_address().clone(),
             udp_address: None,
             valid_from: consensus.blockchain.block_number(),
         };
-        let validator_network = ValidatorNetwork::new(consensus.network.clone(), consensus.blockchain.clone(), SignedValidatorInfo::from_message(info, &validator_key.secret, 0));
-        let block_producer = BlockProducer::new(consensus.blockchain.clone(), consensus.mempool.clone(), validator_key.clone());
+        let validator_network = ValidatorNetwork::new(consensus.network.clone(), consensus.blockchain.clone(), signer.sign_message(info, 0)?);
+        let block_producer = BlockProducer::new(consensus.blockchain.clone(), consensus.mempool.clone(), Arc::clone(&signer));
         let view_number = consensus.blockchain.next_view_number();
+        let slashing_protection = SlashingProtection::new(consensus.env.clone());
 
         debug!("Initializing validator");
 
@@ -117,7 +125,8 @@ impl Validator {
             consensus,
             validator_network,
 
-            validator_key,
+            signer,
+            slashing_protection,
             timers: Timers::new(),
 
             state: RwLock::new(ValidatorState {
@@ -132,6 +141,9 @@ impl Validator {
 
             self_weak: MutableOnce::new(Weak::new()),
             listeners: MutableOnce::new(None),
+
+            #[cfg(feature = "metrics")]
+            metrics: ValidatorMetrics::default(),
         });
         Validator::init_listeners(&this);
 
@@ -229,12 +241,19 @@ impl Validator {
         // Handle each block type (which is directly related to each event type).
         match event {
             BlockchainEvent::Finalized(hash) => {
+                #[cfg(feature = "metrics")]
+                self.note_missed_slots(hash);
+
                 // Init new validator epoch
                 self.init_epoch();
+                self.slashing_protection.prune(self.blockchain.block_number(), &self.blockchain.policy);
                 self.validator_network.on_blockchain_changed(hash);
             },
 
             BlockchainEvent::Extended(hash) => {
+                #[cfg(feature = "metrics")]
+                self.note_missed_slots(hash);
+
                 self.on_blockchain_extended(hash);
                 self.validator_network.on_blockchain_changed(hash);
             },
@@ -335,8 +354,7 @@ impl Validator {
                 self.on_slot_change(slot_change);
             },
             ValidatorNetworkEvent::PbftProposal(proposal) => {
-                let hash: Blake2bHash = proposal.header.hash();
-                self.on_pbft_proposal(&hash);
+                self.on_pbft_proposal(proposal);
             },
             ValidatorNetworkEvent::PbftPrepareComplete(hash) => {
                 self.on_pbft_prepare_complete(hash);
@@ -395,7 +413,7 @@ impl Validator {
         trace!("Next block producer: Slot #{}: {}", slot_number, slot.public_key());
 
         // Get our public key
-        let our_public_key = self.validator_key.public.compress();
+        let our_public_key = self.public_key().compress();
 
         // Check if we're the slot owner
         if slot.public_key().compressed() == &our_public_key {
@@ -413,8 +431,9 @@ impl Validator {
         }
     }
 
-    pub fn on_pbft_proposal(&self, hash: &Blake2bHash) {
-        let state = self.state.write();
+    pub fn on_pbft_proposal(&self, proposal: &PbftProposal) {
+        let hash: Blake2bHash = proposal.header.hash();
+        let mut state = self.state.write();
         trace!("Received proposal: {}", hash);
         // View change messages should only be sent by active validators.
         if state.status != ValidatorStatus::Active {
@@ -424,14 +443,22 @@ impl Validator {
         // Note: we don't verify this hash as the network validator already did.
         let pk_idx = state.pk_idx.expect("Already checked that we are an active validator before calling this function");
 
+        let (block_number, view_number) = (proposal.header.block_number, proposal.header.view_number);
+        if let Err(e) = self.slashing_protection.record_pbft_prepare(block_number, view_number, &hash) {
+            warn!("Refusing to sign pBFT prepare: {}", e);
+            return;
+        }
+
         drop(state);
 
         trace!("Signing prepare: pk_idx={}", pk_idx);
-        let prepare_message = SignedPbftPrepareMessage::from_message(
-            PbftPrepareMessage { block_hash: hash.clone() },
-            &self.validator_key.secret,
-            pk_idx
-        );
+        let prepare_message: SignedPbftPrepareMessage = match self.signer.sign_pbft_prepare(PbftPrepareMessage { block_hash: hash }, block_number, view_number, pk_idx) {
+            Ok(message) => message,
+            Err(e) => {
+                error!("Failed to sign pBFT prepare: {}", e);
+                return;
+            },
+        };
 
         self.validator_network.push_prepare(prepare_message)
             .unwrap_or_else(|e| debug!("Failed to push pBFT prepare: {}", e));
@@ -450,12 +477,30 @@ impl Validator {
 
         drop(state);
 
+        // We only commit to proposals we prepared. Prepares are looked up in the slashing
+        // protection, such that they are known after a restart.
+        let block_number = self.blockchain.policy.macro_block_after(self.blockchain.block_number());
+        let view_number = match self.slashing_protection.signed_pbft_prepare(block_number, hash) {
+            Some(view_number) => view_number,
+            None => {
+                warn!("Refusing to sign pBFT commit for a proposal we didn't prepare: {}", hash);
+                return;
+            },
+        };
+
+        if let Err(e) = self.slashing_protection.record_pbft_commit(block_number, view_number, hash) {
+            warn!("Refusing to sign pBFT commit: {}", e);
+            return;
+        }
+
         trace!("Signing commit message: pk_idx={}", pk_idx);
-        let commit_message = SignedPbftCommitMessage::from_message(
-            PbftCommitMessage { block_hash: hash.clone() },
-            &self.validator_key.secret,
-            pk_idx
-        );
+        let commit_message: SignedPbftCommitMessage = match self.signer.sign_pbft_commit(PbftCommitMessage { block_hash: hash.clone() }, block_number, view_number, pk_idx) {
+            Ok(message) => message,
+            Err(e) => {
+                error!("Failed to sign pBFT commit: {}", e);
+                return;
+            },
+        };
 
         self.validator_network.push_commit(commit_message)
             .unwrap_or_else(|e| debug!("Failed to push pBFT commit: {}", e));
@@ -480,6 +525,9 @@ impl Validator {
             // Automatically relays block.
             self.blockchain.push_block(block, false)
                 .unwrap_or_else(|e| panic!("Pushing macro block to blockchain failed: {:?}", e));
+
+            #[cfg(feature = "metrics")]
+            self.metrics.note_produced_block(BlockType::Macro);
         }
     }
 
@@ -504,33 +552,72 @@ impl Validator {
 
         info!("Starting view change to {}", message);
 
+        if let Err(e) = self.slashing_protection.record_view_change(&message) {
+            warn!("Refusing to sign view change: {}", e);
+            return;
+        }
+
         let pk_idx = state.pk_idx.expect("Checked above that we are an active validator");
-        let view_change_message = SignedViewChange::from_message(message.clone(), &self.validator_key.secret, pk_idx);
-        state.active_view_change = Some(message);
 
+        // Mark the view change as started, such that it isn't started again while we sign it. A
+        // remote signer might take a while, so we don't hold the lock while signing.
+        state.active_view_change = Some(message.clone());
         drop(state);
 
+        let view_change_message: SignedViewChange = match self.signer.sign_message(message.clone(), pk_idx) {
+            Ok(message) => message,
+            Err(e) => {
+                error!("Failed to sign view change: {}", e);
+                let mut state = self.state.write();
+                if state.active_view_change.as_ref() == Some(&message) {
+                    state.active_view_change = None;
+                }
+                return;
+            },
+        };
+
+        #[cfg(feature = "metrics")]
+        self.metrics.note_view_change_started();
+
         // Broadcast our view change number message to the other validators.
         self.validator_network.start_view_change(view_change_message);
      }
 
     fn get_pk_idx_and_slots(&self) -> Option<(u16, u16)> {
         self.blockchain.current_validators()
-            .find_idx_and_num_slots_by_public_key(&self.validator_key.public.compress())
+            .find_idx_and_num_slots_by_public_key(&self.public_key().compress())
     }
 
     fn produce_macro_block(&self, view_change: Option<ViewChangeProof>) {
-        let mut state = self.state.write();
-
-        // FIXME: Don't use network time
-        let timestamp = self.consensus.network.network_time.now();
-        let (pbft_proposal, proposed_extrinsics) = self.block_producer.next_macro_block_proposal(timestamp, state.view_number, view_change);
-        state.proposed_extrinsics.insert(pbft_proposal.header.hash(), proposed_extrinsics);
+        let state = self.state.read();
+        let view_number = state.view_number;
         let pk_idx = state.pk_idx.expect("Checked that we are an active validator before entering this function");
 
+        // The block producer signs the seed of the proposal, don't hold the lock while it does.
         drop(state);
 
-        let signed_proposal = SignedPbftProposal::from_message(pbft_proposal, &self.validator_key.secret, pk_idx);
+        // FIXME: Don't use network time
+        let timestamp = self.consensus.network.network_time.now();
+        let (pbft_proposal, proposed_extrinsics) = match self.block_producer.next_macro_block_proposal(timestamp, view_number, view_change) {
+            Ok(proposal) => proposal,
+            Err(e) => {
+                error!("Failed to produce macro block proposal: {}", e);
+                return;
+            },
+        };
+        if let Err(e) = self.slashing_protection.record_pbft_proposal(&pbft_proposal.header) {
+            error!("Refusing to sign pBFT proposal: {}", e);
+            return;
+        }
+        self.state.write().proposed_extrinsics.insert(pbft_proposal.header.hash(), proposed_extrinsics);
+
+        let signed_proposal: SignedPbftProposal = match self.signer.sign_message(pbft_proposal, pk_idx) {
+            Ok(proposal) => proposal,
+            Err(e) => {
+                error!("Failed to sign pBFT proposal: {}", e);
+                return;
+            },
+        };
         self.validator_network.start_pbft(signed_proposal)
             .unwrap_or_else(|e| error!("Failed to start pBFT proposal: {}", e));
 
@@ -550,7 +637,25 @@ impl Validator {
         // validator and blockchain lock are circular dependent.
         drop(state);
 
-        let block = self.block_producer.next_micro_block(fork_proofs, timestamp, view_number, vec![], view_change_proof);
+        let fork_proofs = self.blockchain.select_fork_proofs(fork_proofs);
+        let (header, extrinsics) = match self.block_producer.next_unsigned_micro_block(fork_proofs, timestamp, view_number, vec![]) {
+            Ok(block) => block,
+            Err(e) => {
+                error!("Failed to produce micro block: {}", e);
+                return;
+            },
+        };
+        if let Err(e) = self.slashing_protection.record_micro_block(&header) {
+            error!("Refusing to sign micro block: {}", e);
+            return;
+        }
+        let block = match self.block_producer.sign_micro_block(header, extrinsics, view_change_proof) {
+            Ok(block) => block,
+            Err(e) => {
+                error!("Failed to sign micro block: {}", e);
+                return;
+            },
+        };
         info!("Produced block #{}.{}: {}",
               block.header.block_number,
               block.header.view_number,
@@ -558,19 +663,60 @@ impl Validator {
 
         // Automatically relays block.
         match self.blockchain.push(Block::Micro(block)) {
-            Ok(r) => trace!("Push result: {:?}", r),
+            Ok(r) => {
+                trace!("Push result: {:?}", r);
+                #[cfg(feature = "metrics")]
+                self.metrics.note_produced_block(BlockType::Micro);
+            },
             Err(e) => error!("Failed to push produced micro block to blockchain: {:?}", e),
         }
     }
 
+    /// Counts the views of the block's slot that were skipped by view changes and belonged to us.
+    #[cfg(feature = "metrics")]
+    fn note_missed_slots(&self, hash: &Blake2bHash) {
+        let block = match self.blockchain.get_block(hash, false, false) {
+            Some(block) => block,
+            None => return,
+        };
+        let prev_view_number = match self.blockchain.get_block(block.parent_hash(), false, false) {
+            Some(parent) => parent.next_view_number(),
+            None => return,
+        };
+
+        let our_public_key = self.public_key().compress();
+        for view_number in prev_view_number..block.view_number() {
+            if let Some((slot, _)) = self.blockchain.get_slot_at(block.block_number(), view_number, None) {
+                if slot.public_key().compressed() == &our_public_key {
+                    self.metrics.note_missed_slot();
+                }
+            }
+        }
+    }
+
+    /// Returns the public key of the validator key.
+    pub fn public_key(&self) -> &PublicKey {
+        self.signer.public_key()
+    }
+
+    /// Returns the status of the validator.
+    pub fn status(&self) -> ValidatorStatus {
+        self.state.read().status
+    }
+
+    /// Returns the number of slots we have in the current epoch, if we are an active validator.
+    pub fn num_slots(&self) -> Option<u16> {
+        self.state.read().slots
+    }
+
     fn is_potential_validator(&self) -> bool {
-        let validator_registry = NetworkInfo::from_network_id(self.blockchain.network_id).validator_registry_address().expect("Albatross consensus always has the address set.");
+        let validator_registry = self.blockchain.network_info.validator_registry_address().expect("Albatross consensus always has the address set.");
         let contract = self.blockchain.state().accounts().get(validator_registry, None);
         if let Account::Staking(contract) = contract {
-            let public_key = self.validator_key.public.compress();
+            let public_key = self.public_key().compress();
 
             // FIXME: Inefficient linear scan.
-            contract.active_stake_sorted.iter().any(|stake| stake.validator_key == public_key)
+            contract.active_validators_sorted.iter().any(|validator| validator.validator_key == public_key)
         } else {
             panic!("Validator registry has a wrong account type.");
         }
@@ -13,7 +13,6 @@ use utils::observer::{PassThroughNotifier, weak_passthru_listener};
 use bls::bls12_381::CompressedPublicKey;
 use block_albatross::{SignedPbftProposal, ForkProof, ViewChange, PbftPrepareMessage,
                       PbftCommitMessage};
-use primitives::policy;
 use blockchain_albatross::Blockchain;
 use hash::{Hash, Blake2bHash};
 use handel::update::LevelUpdateMessage;
@@ -152,7 +151,7 @@ impl ValidatorAgent {
     fn on_fork_proof_message(&self, fork_proof: ForkProof) {
         debug!("[FORK-PROOF] Fork proof:");
 
-        if !fork_proof.is_valid_at(self.blockchain.block_number() + 1) {
+        if !fork_proof.is_valid_at(self.blockchain.block_number() + 1, &self.blockchain.policy) {
             debug!("[FORK-PROOF] Not valid");
             return;
         }
@@ -174,9 +173,9 @@ impl ValidatorAgent {
 
     fn check_view_change_epoch(&self, view_change: &ViewChange) -> bool {
         let current_block_number = self.blockchain.block_number() + 1;
-        let current_epoch = policy::epoch_at(current_block_number);
+        let current_epoch = self.blockchain.policy.epoch_at(current_block_number);
 
-        let view_change_epoch = policy::epoch_at(view_change.block_number);
+        let view_change_epoch = self.blockchain.policy.epoch_at(view_change.block_number);
 
         if view_change_epoch == current_epoch {
             true
@@ -226,7 +225,7 @@ impl ValidatorAgent {
         }
 
         // Reject proposal if it lies in another epoch
-        if policy::epoch_at(proposal_block) != policy::epoch_at(current_block) {
+        if self.blockchain.policy.epoch_at(proposal_block) != self.blockchain.policy.epoch_at(current_block) {
             warn!("[PBFT-PROPOSAL] Ignoring proposal in another epoch: {}",
                   proposal.message.header.hash::<Blake2bHash>());
             return;
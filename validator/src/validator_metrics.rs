@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use block_albatross::BlockType;
+
+#[derive(Default)]
+pub struct ValidatorMetrics {
+    micro_blocks_produced: AtomicUsize,
+    macro_blocks_produced: AtomicUsize,
+    missed_slots: AtomicUsize,
+    view_changes_started: AtomicUsize,
+}
+
+impl ValidatorMetrics {
+    #[inline]
+    pub fn note_produced_block(&self, block_type: BlockType) {
+        match block_type {
+            BlockType::Micro => self.micro_blocks_produced.fetch_add(1, Ordering::Release),
+            BlockType::Macro => self.macro_blocks_produced.fetch_add(1, Ordering::Release),
+        };
+    }
+
+    #[inline]
+    pub fn blocks_produced(&self, block_type: BlockType) -> usize {
+        match block_type {
+            BlockType::Micro => self.micro_blocks_produced.load(Ordering::Acquire),
+            BlockType::Macro => self.macro_blocks_produced.load(Ordering::Acquire),
+        }
+    }
+
+    /// Notes a view of ours that was skipped by a view change.
+    #[inline]
+    pub fn note_missed_slot(&self) {
+        self.missed_slots.fetch_add(1, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn missed_slots(&self) -> usize {
+        self.missed_slots.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn note_view_change_started(&self) {
+        self.view_changes_started.fetch_add(1, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn view_changes_started(&self) -> usize {
+        self.view_changes_started.load(Ordering::Acquire)
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, BTreeMap};
+use std::collections::{HashMap, HashSet, BTreeMap};
 use std::sync::{Arc, Weak};
 use std::fmt;
 
@@ -16,15 +16,17 @@ use block_albatross::{
 };
 use block_albatross::signed::AggregateProof;
 use blockchain_albatross::Blockchain;
+use blockchain_albatross::fork_detection::ForkEvent;
 use hash::{Blake2bHash, Hash};
 use messages::{Message, ViewChangeProofMessage};
 use network::{Network, NetworkEvent, Peer};
 use network_primitives::validator_info::{SignedValidatorInfo};
 use network_primitives::address::PeerId;
-use primitives::policy::{SLOTS, TWO_THIRD_SLOTS, is_macro_block_at};
 use utils::mutable_once::MutableOnce;
 use utils::observer::{Notifier, weak_listener, weak_passthru_listener};
 use handel::aggregation::AggregationEvent;
+use handel::config::Config;
+use handel::timeout::AdaptiveTimeout;
 use handel::update::LevelUpdateMessage;
 use bls::bls12_381::CompressedPublicKey;
 
@@ -32,6 +34,7 @@ use crate::validator_agent::{ValidatorAgent, ValidatorAgentEvent};
 use crate::signature_aggregation::view_change::ViewChangeAggregation;
 use crate::signature_aggregation::pbft::PbftAggregation;
 use crate::pool::ValidatorPool;
+use primitives::policy::AlbatrossPolicy;
 use primitives::slot::SlotCollection;
 
 
@@ -112,8 +115,8 @@ struct PbftState {
 }
 
 impl PbftState {
-    pub fn new(block_hash: Blake2bHash, proposal: SignedPbftProposal, node_id: usize, validators: Arc<RwLock<ValidatorPool>>) -> Self {
-        let aggregation = Arc::new(RwLock::new(PbftAggregation::new(block_hash.clone(), node_id, validators, None)));
+    pub fn new(block_hash: Blake2bHash, proposal: SignedPbftProposal, node_id: usize, validators: Arc<RwLock<ValidatorPool>>, threshold: usize, config: Config) -> Self {
+        let aggregation = Arc::new(RwLock::new(PbftAggregation::new(block_hash.clone(), node_id, validators, threshold, Some(config))));
         Self {
             proposal,
             block_hash,
@@ -188,6 +191,9 @@ struct ValidatorNetworkState {
 
     /// If we're an active validator, set our validator ID here
     validator_id: Option<usize>,
+
+    /// Fork proofs that were already relayed
+    fork_proofs: HashSet<ForkProof>,
 }
 
 impl ValidatorNetworkState {
@@ -214,6 +220,10 @@ pub struct ValidatorNetwork {
     /// NOTE: To avoid circular dead-locks, always acquire this after the validator pool lock.
     pub validators: Arc<RwLock<ValidatorPool>>,
 
+    /// Configuration of the signature aggregations. All aggregations share the timeout strategy,
+    /// such that it can adapt to the latency between the validators.
+    handel_config: Config,
+
     self_weak: MutableOnce<Weak<ValidatorNetwork>>,
     pub notifier: RwLock<Notifier<'static, ValidatorNetworkEvent>>,
 }
@@ -232,6 +242,10 @@ impl ValidatorNetwork {
             info,
             state: RwLock::new(ValidatorNetworkState::default()),
             validators: Arc::new(RwLock::new(pool)),
+            handel_config: Config {
+                timeout_strategy: Arc::new(AdaptiveTimeout::default()),
+                ..Config::default()
+            },
             self_weak: MutableOnce::new(Weak::new()),
             notifier: RwLock::new(Notifier::new()),
         });
@@ -244,6 +258,20 @@ impl ValidatorNetwork {
     fn init_listeners(this: &Arc<Self>, network: Arc<Network<Blockchain>>) {
         unsafe { this.self_weak.replace(Arc::downgrade(this)) };
 
+        // Relay fork proofs detected by the blockchain. This is called while the blockchain holds
+        // its push lock, so we handle it in a separate thread.
+        this.blockchain.fork_notifier.write().register(weak_listener(Arc::downgrade(this), |this, event| {
+            match event {
+                ForkEvent::Detected(fork_proof) => {
+                    let fork_proof = fork_proof.clone();
+                    tokio::spawn(future::lazy(move || {
+                        this.on_fork_proof(fork_proof);
+                        Ok(())
+                    }));
+                },
+            }
+        }));
+
         // Register for peers joining and leaving
         network.notifier.write().register(weak_listener(Arc::downgrade(this), |this, event| {
             match event {
@@ -328,6 +356,11 @@ impl ValidatorNetwork {
     }
 
     fn on_fork_proof(&self, fork_proof: ForkProof) {
+        // Only relay fork proofs once
+        if !self.state.write().fork_proofs.insert(fork_proof.clone()) {
+            return;
+        }
+
         self.notifier.read().notify(ValidatorNetworkEvent::ForkProof(Box::new(fork_proof.clone())));
         self.broadcast_fork_proof(fork_proof);
     }
@@ -346,6 +379,10 @@ impl ValidatorNetwork {
         // Clear pBFT states
         state.pbft_states.clear();
 
+        // Forget fork proofs that can't be included anymore
+        let next_block_number = self.blockchain.block_number() + 1;
+        state.fork_proofs.retain(|fork_proof| fork_proof.is_valid_at(next_block_number, &self.blockchain.policy));
+
         // Set validator ID
         state.validator_id = validator_id;
 
@@ -377,7 +414,7 @@ impl ValidatorNetwork {
         // Only the proposal with the highest view number will remain.
 
         // Check if next block will be a macro block
-        if !is_macro_block_at(new_height + 1) {
+        if !self.blockchain.policy.is_macro_block_at(new_height + 1) {
             return;
         }
 
@@ -434,7 +471,7 @@ impl ValidatorNetwork {
             let votes = aggregation.votes();
 
             if votes > votes_before {
-                debug!("View change progress: {}", fmt_vote_progress(aggregation.votes()));
+                debug!("View change progress: {}", fmt_vote_progress(aggregation.votes(), &self.blockchain.policy));
                 self.notifier.read().notify(ValidatorNetworkEvent::ViewChangeUpdate(Box::new(ViewChangeUpdateEvent {
                     view_change,
                     votes
@@ -465,7 +502,7 @@ impl ValidatorNetwork {
 
     /// When we receive a complete view change proof
     fn on_view_change_proof(&self, view_change: ViewChange, proof: ViewChangeProof) {
-        if let Err(e) = proof.verify(&view_change, &self.blockchain.current_validators(), TWO_THIRD_SLOTS) {
+        if let Err(e) = proof.verify(&view_change, &self.blockchain.current_validators(), self.blockchain.policy.two_third_slots()) {
             // TODO: Make this use Display instead, once the implementation for it is merged.
             debug!("Invalid view change proof: {:?}", e);
         }
@@ -537,10 +574,12 @@ impl ValidatorNetwork {
             signed_proposal.clone(),
             validator_id,
             Arc::clone(&self.validators),
+            self.blockchain.policy.two_third_slots() as usize,
+            self.handel_config.clone(),
         );
 
         let chain_height = self.blockchain.height();
-        let buffered = !is_macro_block_at(chain_height + 1);
+        let buffered = !self.blockchain.policy.is_macro_block_at(chain_height + 1);
 
         // Check validity if proposal not buffered
         if !buffered {
@@ -664,7 +703,7 @@ impl ValidatorNetwork {
             let (prepare_votes, commit_votes) = aggregation.votes();
 
             if prepare_votes > prepare_votes_before {
-                debug!("pBFT for {}: Prepare: {}, Commit: {}", hash, fmt_vote_progress(prepare_votes), fmt_vote_progress(commit_votes));
+                debug!("pBFT for {}: Prepare: {}, Commit: {}", hash, fmt_vote_progress(prepare_votes, &self.blockchain.policy), fmt_vote_progress(commit_votes, &self.blockchain.policy));
 
                 self.notifier.read().notify(ValidatorNetworkEvent::PbftUpdate(Box::new(PbftUpdateEvent {
                     hash,
@@ -693,7 +732,7 @@ impl ValidatorNetwork {
 
             let (prepare_votes, commit_votes) = aggregation.votes();
             if commit_votes > commit_votes_before {
-                debug!("pBFT for {}: Prepare: {}, Commit: {}", hash, fmt_vote_progress(prepare_votes), fmt_vote_progress(commit_votes));
+                debug!("pBFT for {}: Prepare: {}, Commit: {}", hash, fmt_vote_progress(prepare_votes, &self.blockchain.policy), fmt_vote_progress(commit_votes, &self.blockchain.policy));
 
                 self.notifier.read().notify(ValidatorNetworkEvent::PbftUpdate(Box::new(PbftUpdateEvent {
                     hash,
@@ -730,7 +769,8 @@ impl ValidatorNetwork {
             view_change.clone(),
             node_id,
             Arc::clone(&self.validators),
-            None
+            self.blockchain.policy.two_third_slots() as usize,
+            Some(self.handel_config.clone()),
         );
         debug!("New view change for: {}, node_id={}", view_change, node_id);
 
@@ -855,7 +895,7 @@ impl ValidatorNetwork {
 }
 
 /// Pretty-print voting progress
-fn fmt_vote_progress(slots: usize) -> String {
-    let done = slots >= (TWO_THIRD_SLOTS as usize);
-    format!("votes={: >3} / {}, done={}", slots, SLOTS, done)
+fn fmt_vote_progress(slots: usize, policy: &AlbatrossPolicy) -> String {
+    let done = slots >= (policy.two_third_slots() as usize);
+    format!("votes={: >3} / {}, done={}", slots, policy.slots, done)
 }
@@ -0,0 +1,110 @@
+use failure::Fail;
+
+use beserial::{Deserialize, Serialize};
+use key_derivation::ExtendedPrivateKey;
+use keys::{Address, KeyPair, SecureRng};
+use mnemonic::{Entropy, Mnemonic, MnemonicType, WORDLIST_EN};
+use nimiq_hash::pbkdf2::Pbkdf2Error;
+use nimiq_utils::key_rng::Rng;
+use nimiq_utils::otp::Verify;
+
+use crate::wallet_account::WalletAccount;
+
+/// Nimiq's standard derivation path for accounts. The account index is appended as the last,
+/// hardened segment.
+pub const NIMIQ_DERIVATION_PATH: &str = "m/44'/242'/0'";
+
+#[derive(Debug, Fail)]
+pub enum HdWalletError {
+    #[fail(display = "Invalid mnemonic")]
+    InvalidMnemonic,
+    #[fail(display = "Legacy mnemonics are not supported")]
+    LegacyMnemonic,
+    #[fail(display = "Seed computation failed: {:?}", _0)]
+    SeedError(Pbkdf2Error),
+}
+
+impl From<Pbkdf2Error> for HdWalletError {
+    fn from(e: Pbkdf2Error) -> Self {
+        HdWalletError::SeedError(e)
+    }
+}
+
+/// A hierarchical deterministic wallet, from which any number of accounts can be derived.
+///
+/// The wallet keeps the entropy of its mnemonic, such that the mnemonic can be exported again,
+/// and the seed computed from the mnemonic and its password, from which the accounts are derived.
+#[derive(Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HdWallet {
+    entropy: Entropy,
+    #[beserial(len_type(u8))]
+    seed: Vec<u8>,
+    /// The address of the first account, which identifies the wallet.
+    pub id: Address,
+}
+
+impl HdWallet {
+    /// Creates a wallet from a new random mnemonic.
+    pub fn generate(password: Option<&str>) -> Result<Self, HdWalletError> {
+        let mut entropy = [0u8; Entropy::SIZE];
+        SecureRng::default().fill(&mut entropy);
+        Self::from_entropy(Entropy::from(entropy), password)
+    }
+
+    /// Restores a wallet from a BIP39 mnemonic and its optional password.
+    pub fn from_mnemonic(mnemonic: &Mnemonic, password: Option<&str>) -> Result<Self, HdWalletError> {
+        match mnemonic.get_type(WORDLIST_EN) {
+            MnemonicType::BIP39 | MnemonicType::UNKNOWN => {},
+            MnemonicType::LEGACY => return Err(HdWalletError::LegacyMnemonic),
+            MnemonicType::INVALID => return Err(HdWalletError::InvalidMnemonic),
+        }
+        let entropy = mnemonic.to_entropy(WORDLIST_EN)
+            .ok_or(HdWalletError::InvalidMnemonic)?;
+        Self::from_entropy(entropy, password)
+    }
+
+    fn from_entropy(entropy: Entropy, password: Option<&str>) -> Result<Self, HdWalletError> {
+        let seed = entropy.to_mnemonic(WORDLIST_EN).to_seed(password)?;
+        let mut wallet = HdWallet {
+            entropy,
+            seed,
+            id: Address::default(),
+        };
+        wallet.id = wallet.derive_address(0)
+            .ok_or(HdWalletError::InvalidMnemonic)?;
+        Ok(wallet)
+    }
+
+    /// Returns the mnemonic of this wallet. The accounts can only be restored from it together
+    /// with the password that was used when the wallet was created.
+    pub fn mnemonic(&self) -> Mnemonic {
+        self.entropy.to_mnemonic(WORDLIST_EN)
+    }
+
+    /// Returns the derivation path of the account with the given index.
+    pub fn derivation_path(index: u32) -> String {
+        format!("{}/{}'", NIMIQ_DERIVATION_PATH, index)
+    }
+
+    /// Derives the account with the given index.
+    pub fn derive_account(&self, index: u32) -> Option<WalletAccount> {
+        let key = self.derive_key(index)?;
+        Some(WalletAccount::from(KeyPair::from(key.into_private_key())))
+    }
+
+    fn derive_address(&self, index: u32) -> Option<Address> {
+        Some(self.derive_key(index)?.to_address())
+    }
+
+    fn derive_key(&self, index: u32) -> Option<ExtendedPrivateKey> {
+        ExtendedPrivateKey::from_seed(self.seed.clone())
+            .derive_path(&Self::derivation_path(index))
+    }
+}
+
+impl Verify for HdWallet {
+    fn verify(&self) -> bool {
+        // Check that the seed corresponds to the wallet.
+        self.derive_address(0).map_or(false, |address| address == self.id)
+    }
+}
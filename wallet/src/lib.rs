@@ -2,12 +2,15 @@
 extern crate beserial_derive;
 extern crate nimiq_keys as keys;
 extern crate nimiq_key_derivation as key_derivation;
+extern crate nimiq_mnemonic as mnemonic;
 extern crate nimiq_primitives as primitives;
 extern crate nimiq_transaction as transaction;
 extern crate nimiq_database as database;
 
+mod hd_wallet;
 mod wallet_account;
 mod wallet_store;
 
+pub use hd_wallet::{HdWallet, HdWalletError, NIMIQ_DERIVATION_PATH};
 pub use wallet_account::WalletAccount;
 pub use wallet_store::WalletStore;
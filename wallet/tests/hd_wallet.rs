@@ -0,0 +1,62 @@
+extern crate beserial;
+extern crate nimiq_mnemonic as mnemonic;
+extern crate nimiq_utils as utils;
+extern crate nimiq_wallet as wallet;
+
+use beserial::{Deserialize, Serialize};
+use mnemonic::Mnemonic;
+use utils::otp::{Locked, Verify};
+use wallet::{HdWallet, HdWalletError};
+
+const MNEMONIC: &str = "all hour make first leader extend hole alien behind guard gospel lava path output census museum junior mass reopen famous sing advance salt reform";
+
+#[test]
+fn it_restores_wallets_from_mnemonics() {
+    let mnemonic = Mnemonic::from(MNEMONIC);
+    let wallet = HdWallet::from_mnemonic(&mnemonic, None).unwrap();
+    assert_eq!(wallet.mnemonic(), mnemonic);
+    assert_eq!(wallet.mnemonic().to_string(), MNEMONIC);
+
+    let restored = HdWallet::from_mnemonic(&wallet.mnemonic(), None).unwrap();
+    assert_eq!(restored.id, wallet.id);
+    assert_eq!(restored.derive_account(7), wallet.derive_account(7));
+
+    // The password is part of the seed.
+    let with_password = HdWallet::from_mnemonic(&mnemonic, Some("password")).unwrap();
+    assert_ne!(with_password.id, wallet.id);
+}
+
+#[test]
+fn it_derives_accounts() {
+    let wallet = HdWallet::generate(None).unwrap();
+    let account_0 = wallet.derive_account(0).unwrap();
+    let account_1 = wallet.derive_account(1).unwrap();
+
+    assert_eq!(account_0.address, wallet.id);
+    assert_ne!(account_0.address, account_1.address);
+    assert_eq!(HdWallet::derivation_path(1), "m/44'/242'/0'/1'");
+}
+
+#[test]
+fn it_rejects_invalid_mnemonics() {
+    let mnemonic = Mnemonic::from("all hour make first leader extend hole alien behind guard gospel lava path output census museum junior mass reopen famous sing advance salt notaword");
+    match HdWallet::from_mnemonic(&mnemonic, None) {
+        Err(HdWalletError::InvalidMnemonic) => {},
+        _ => panic!("Invalid mnemonic was accepted"),
+    }
+}
+
+#[test]
+fn it_can_be_locked() {
+    let wallet = HdWallet::generate(Some("password")).unwrap();
+    assert!(wallet.verify());
+
+    let serialized = wallet.serialize_to_vec();
+    let deserialized = HdWallet::deserialize_from_vec(&serialized).unwrap();
+    assert!(deserialized == wallet);
+
+    let locked = Locked::with_defaults(wallet.clone(), b"passphrase").unwrap();
+    let locked = locked.unlock(b"wrong passphrase").err().unwrap();
+    let unlocked = locked.unlock(b"passphrase").ok().unwrap();
+    assert!(*unlocked == wallet);
+}
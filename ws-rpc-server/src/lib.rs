@@ -1,11 +1,17 @@
 #[macro_use]
 extern crate log;
 
+extern crate nimiq_block_albatross as block_albatross;
 extern crate nimiq_consensus as consensus;
 extern crate nimiq_utils as utils;
 extern crate nimiq_blockchain_albatross as blockchain_albatross;
 extern crate nimiq_blockchain_base as blockchain_base;
 extern crate nimiq_hash as hash;
+extern crate nimiq_keys as keys;
+extern crate nimiq_mempool as mempool;
+extern crate nimiq_primitives as primitives;
+extern crate nimiq_rpc_server as rpc_server;
+extern crate nimiq_transaction as transaction;
 #[cfg(feature="validator")]
 extern crate nimiq_validator as validator;
 
@@ -14,25 +20,36 @@ use std::sync::Arc;
 use std::collections::HashMap;
 use std::io::{Error as IoError, ErrorKind};
 
-use futures::{Future, Stream, IntoFuture};
+use futures::{future, Future, Stream, IntoFuture};
 use futures::sink::Sink;
 use futures::sync::mpsc::{channel, Sender};
 use tokio::net::{TcpListener};
-use tokio_tungstenite::accept_async;
+use tokio_tungstenite::accept_hdr_async;
 use tokio_tungstenite::tungstenite::{Message, Error as WsError};
-use parking_lot::RwLock;
-use json::{JsonValue, object};
+use tokio_tungstenite::tungstenite::handshake::server::Request;
+use parking_lot::{Mutex, RwLock};
+use json::{Array, JsonValue, Null, object};
 
 use utils::unique_id::UniqueId;
 use consensus::{Consensus, AlbatrossConsensusProtocol};
 use blockchain_base::AbstractBlockchain;
+use blockchain_albatross::Blockchain;
 use blockchain_albatross::blockchain::BlockchainEvent;
+#[cfg(feature="validator")]
 use hash::{Hash, Blake2bHash};
+use mempool::{Mempool, MempoolEvent};
+use rpc_server::Handler;
+use rpc_server::auth::Permissions;
+use rpc_server::jsonrpc::{self, Handler as JsonRpcHandler};
 #[cfg(feature="validator")]
 use validator::validator_network::ValidatorNetworkEvent;
 #[cfg(feature="validator")]
 use validator::validator::Validator;
 
+use crate::subscriptions::{BlockchainUpdate, Subscription};
+
+pub mod subscriptions;
+
 pub type WsRpcServerFuture = Box<dyn Future<Item=(), Error=()> + Send + Sync + 'static>;
 
 type WsRpcConnections = Arc<RwLock<HashMap<UniqueId, WsRpcConnection>>>;
@@ -40,8 +57,18 @@ type WsRpcConnections = Arc<RwLock<HashMap<UniqueId, WsRpcConnection>>>;
 struct WsRpcConnection {
     address: SocketAddr,
     tx: Sender<Message>,
+    /// `None` until the client is authenticated.
+    permissions: Option<Permissions>,
+    subscriptions: HashMap<u64, Subscription>,
+    next_subscription_id: u64,
 }
 
+/// Serves JSON-RPC 2.0 over websockets. Besides the methods of the RPC `Handler`, clients can
+/// `subscribe` to events and `unsubscribe` again. Events are sent as `subscription`
+/// notifications to the connections that subscribed to them.
+///
+/// If credentials or API tokens are configured, clients authenticate either with HTTP basic auth
+/// during the handshake or by calling `auth` before any other method.
 pub struct WsRpcServer {
     future: WsRpcServerFuture,
     connections: WsRpcConnections,
@@ -50,7 +77,7 @@ pub struct WsRpcServer {
 impl WsRpcServer {
     const QUEUE_SIZE: usize = 64;
 
-    pub fn new(ip: IpAddr, port: u16) -> Result<Self, IoError>
+    pub fn new(ip: IpAddr, port: u16, handler: Arc<Handler>) -> Result<Self, IoError>
     {
         let socket = TcpListener::bind(&SocketAddr::new(ip, port))?;
 
@@ -62,13 +89,34 @@ impl WsRpcServer {
             .for_each(move |stream| {
                 let address = stream.peer_addr().unwrap();
                 let connection_id = UniqueId::new();
-                // TODO: IP filter here
+                if !handler.config.ip_filter.is_allowed(&address.ip()) {
+                    info!("Client rejected: {}, IP address is not allowed", address);
+                    return future::Either::A(future::ok(()));
+                }
                 info!("Client connected: {}, id={}", address, connection_id);
 
                 let connections_stream = Arc::clone(&connections_tcp);
                 let connections_err = Arc::clone(&connections_tcp);
-
-                accept_async(stream)
+                let handler_stream = Arc::clone(&handler);
+
+                // Authenticate with the `Authorization` header of the handshake, if present.
+                let handshake_permissions = Arc::new(Mutex::new(None));
+                let handshake_permissions_callback = Arc::clone(&handshake_permissions);
+                let handler_handshake = Arc::clone(&handler);
+                let callback = move |request: &Request| {
+                    let authorization = request.headers.find_first("Authorization")
+                        .and_then(|value| std::str::from_utf8(value).ok());
+                    match jsonrpc::check_authentication(handler_handshake.as_ref(), authorization) {
+                        Ok(permissions) => *handshake_permissions_callback.lock() = Some(permissions),
+                        // Clients without credentials can still authenticate with an `auth` request.
+                        Err(e) => if authorization.is_some() {
+                            info!("Authentication failed: {}: {}", address, e);
+                        },
+                    }
+                    Ok(None)
+                };
+
+                let future = accept_hdr_async(stream, callback)
                     .and_then(move |ws_stream| {
                         // Split stream
                         let (sink, stream) = ws_stream.split();
@@ -79,18 +127,12 @@ impl WsRpcServer {
                         // Send everything from the MSPC channel
                         let send_future = sink.send_all(rx.map_err(|_| WsError::ConnectionClosed));
 
-                        // Receive messages (and ignore them)
+                        // Receive JSON-RPC requests and send the responses through the MPSC channel
                         let connection_id_recv = connection_id;
                         let connections_recv = Arc::clone(&connections_stream);
+                        let tx_recv = tx.clone();
                         let recv_future = stream
-                            .for_each(move |message: Message| {
-                                // Received message. We ignore those. But we could use this for
-                                // authentication and just set a flag in the connection to enable
-                                // streaming events.
-                                //
-                                // TODO: We'll also receive close frames, which let's us close the
-                                // connection gracefully.
-
+                            .for_each(move |message: Message| -> Box<dyn Future<Item=(), Error=WsError> + Send> {
                                 // Log message
                                 debug!("Received message from #{}: {}", connection_id_recv, message);
 
@@ -98,18 +140,22 @@ impl WsRpcServer {
                                 match message {
                                     Message::Close(_close_frame_opt) => {
                                         // Remove connection from connections map
-                                        let _connection = connections_recv.write().remove(&connection_id_recv)
-                                            .ok_or(WsError::AlreadyClosed)?;
+                                        let result = connections_recv.write().remove(&connection_id_recv)
+                                            .map(|_| ())
+                                            .ok_or(WsError::AlreadyClosed);
 
-                                        Ok(())
+                                        Box::new(future::result(result))
                                     },
-                                    Message::Text(_message) => {
-                                        // TODO: Handle authentication
-                                        Ok(())
+                                    Message::Text(message) => {
+                                        let response = Self::handle_text(&handler_stream, &connections_recv, connection_id_recv, &address, &message);
+
+                                        Box::new(tx_recv.clone().send(Message::Text(response.dump()))
+                                            .map(|_| ())
+                                            .map_err(|_| WsError::ConnectionClosed))
                                     },
                                     _ => {
                                         // Abort connection for everything else
-                                        Err(WsError::ConnectionClosed)
+                                        Box::new(future::err(WsError::ConnectionClosed))
                                     }
                                 }
                             });
@@ -119,6 +165,9 @@ impl WsRpcServer {
                             .insert(connection_id.clone(), WsRpcConnection {
                                 address,
                                 tx,
+                                permissions: handshake_permissions.lock().take(),
+                                subscriptions: HashMap::new(),
+                                next_subscription_id: 1,
                             });
 
                         let connection_future = send_future
@@ -135,7 +184,9 @@ impl WsRpcServer {
                         tokio::spawn(connection_future);
                         Ok(())
                     })
-                    .map_err(|e| IoError::new(ErrorKind::BrokenPipe, format!("Connection error: {}", e)))
+                    .map_err(|e| IoError::new(ErrorKind::BrokenPipe, format!("Connection error: {}", e)));
+
+                future::Either::B(future)
             })
             .map_err(|e| {
                 error!("Server socket failed: {}", e);
@@ -147,14 +198,121 @@ impl WsRpcServer {
         })
     }
 
+    /// Handles a text frame containing a JSON-RPC request (or a batch of them) and returns the
+    /// response.
+    fn handle_text(handler: &Handler, connections: &WsRpcConnections, connection_id: UniqueId, address: &SocketAddr, text: &str) -> JsonValue {
+        if let Err(e) = handler.check_access(&address.ip()) {
+            info!("Access denied for {}: {}", address, e);
+            return jsonrpc::error_response(Null, -32000, &e.to_string());
+        }
+
+        let json = match json::parse(text) {
+            Ok(json) => json,
+            Err(_) => return jsonrpc::error_response(Null, -32600, "Invalid JSON"),
+        };
+
+        jsonrpc::handle_json(json, |name, params| {
+            if name == "auth" {
+                return Some(Self::auth(handler, connections, connection_id, params));
+            }
+
+            let permissions = match connections.read().get(&connection_id).and_then(|c| c.permissions.clone()) {
+                Some(permissions) => permissions,
+                None => return Some(Err(object!{"message" => "Not authenticated."})),
+            };
+
+            match name {
+                "subscribe" | "unsubscribe" if !permissions.allows(name) => {
+                    Some(Err(object!{"message" => "Method is not allowed."}))
+                },
+                "subscribe" => Some(Self::subscribe(connections, connection_id, params)),
+                "unsubscribe" => Some(Self::unsubscribe(connections, connection_id, params)),
+                _ => handler.call_method(name, params, &permissions),
+            }
+        }).unwrap_or_else(|error| error)
+    }
+
+    /// Authenticates the connection.
+    /// Parameters:
+    /// - username (string): The username or the name of the API token.
+    /// - password (string): The password or the API token.
+    fn auth(handler: &Handler, connections: &WsRpcConnections, connection_id: UniqueId, params: Array) -> Result<JsonValue, JsonValue> {
+        let username = params.get(0).and_then(JsonValue::as_str).unwrap_or_default();
+        let password = params.get(1).and_then(JsonValue::as_str).unwrap_or_default();
+
+        let permissions = handler.authorize(username, password)
+            .map_err(|e| object!{"message" => e.to_string()})?;
+
+        let mut connections = connections.write();
+        let connection = connections.get_mut(&connection_id)
+            .ok_or_else(|| object!{"message" => "Connection closed"})?;
+        connection.permissions = Some(permissions);
+
+        Ok(true.into())
+    }
+
+    /// Subscribes the connection to an event.
+    /// Parameters:
+    /// - event (string): One of `headChanged`, `newTransaction`, `mempoolTransaction`,
+    ///     `macroBlock` or `validatorEvent`.
+    /// - addresses (array, optional): For `newTransaction` and `mempoolTransaction`, only notify
+    ///     about transactions sent from or to one of these addresses.
+    ///
+    /// Returns the subscription ID, which is included in all notifications.
+    fn subscribe(connections: &WsRpcConnections, connection_id: UniqueId, params: Array) -> Result<JsonValue, JsonValue> {
+        let subscription = Subscription::from_params(&params)?;
+
+        let mut connections = connections.write();
+        let connection = connections.get_mut(&connection_id)
+            .ok_or_else(|| object!{"message" => "Connection closed"})?;
+
+        let subscription_id = connection.next_subscription_id;
+        connection.next_subscription_id += 1;
+        connection.subscriptions.insert(subscription_id, subscription);
+
+        Ok(subscription_id.into())
+    }
+
+    /// Cancels a subscription.
+    /// Parameters:
+    /// - subscription (number): The subscription ID returned by `subscribe`.
+    ///
+    /// Returns whether the subscription existed.
+    fn unsubscribe(connections: &WsRpcConnections, connection_id: UniqueId, params: Array) -> Result<JsonValue, JsonValue> {
+        let subscription_id = params.get(0).and_then(JsonValue::as_u64)
+            .ok_or_else(|| object!{"message" => "Subscription ID must be a number"})?;
+
+        let mut connections = connections.write();
+        let connection = connections.get_mut(&connection_id)
+            .ok_or_else(|| object!{"message" => "Connection closed"})?;
+
+        Ok(connection.subscriptions.remove(&subscription_id).is_some().into())
+    }
+
     pub fn register_blockchain(&self, consensus: Arc<Consensus<AlbatrossConsensusProtocol>>) {
         let connections_listener = Arc::clone(&self.connections);
+        let blockchain = Arc::clone(&consensus.blockchain);
 
         consensus.blockchain.register_listener(move |event: &BlockchainEvent| {
-            if !connections_listener.read().is_empty() {
-                if let Some(message) = Self::map_blockchain_event(event) {
-                    Self::broadcast_message(&connections_listener, message)
-                }
+            let include_body = match Self::subscriptions_state(&connections_listener) {
+                None => return,
+                Some(include_body) => include_body,
+            };
+
+            if let Some(update) = BlockchainUpdate::from_event(&blockchain, event, include_body) {
+                Self::notify_subscribers(&connections_listener, |subscription| update.notifications(subscription));
+            }
+        });
+    }
+
+    pub fn register_mempool(&self, mempool: Arc<Mempool<Blockchain>>) {
+        let connections_listener = Arc::clone(&self.connections);
+
+        mempool.notifier.write().register(move |event: &MempoolEvent| {
+            if let MempoolEvent::TransactionAdded(_, transaction) = event {
+                Self::notify_subscribers(&connections_listener, |subscription| {
+                    subscriptions::mempool_notification(subscription, transaction).into_iter().collect()
+                });
             }
         });
     }
@@ -164,45 +322,29 @@ impl WsRpcServer {
         let connections_listener = Arc::clone(&self.connections);
 
         validator.validator_network.notifier.write().register(move |event: &ValidatorNetworkEvent| {
-            if !connections_listener.read().is_empty() {
-                if let Some(message) = Self::map_validator_event(event) {
-                    Self::broadcast_message(&connections_listener, message)
-                }
+            if Self::subscriptions_state(&connections_listener).is_none() {
+                return;
+            }
+
+            if let Some(result) = Self::map_validator_event(event) {
+                Self::notify_subscribers(&connections_listener, |subscription| match subscription {
+                    Subscription::ValidatorEvent => vec![result.clone()],
+                    _ => Vec::new(),
+                });
             }
         });
     }
 
-    fn map_blockchain_event(event: &BlockchainEvent) -> Option<JsonValue> {
-        Some(match event {
-            BlockchainEvent::Extended(block_hash) => object!{
-                "eventType" => "blockchainExtended",
-                "blockHash" => block_hash.to_string(),
-            },
-            BlockchainEvent::Rebranched(reverted, rebranched) => {
-                let (old_head, _) = reverted.last()?;
-                let (new_head, _) = rebranched.last()?;
-
-                let reverted = JsonValue::Array(reverted.into_iter()
-                    .map(|(block_hash, _)|  JsonValue::String(block_hash.to_string()))
-                    .collect());
-
-                let rebranched = JsonValue::Array(rebranched.into_iter()
-                    .map(|(block_hash, _)|  JsonValue::String(block_hash.to_string()))
-                    .collect());
-
-                object!{
-                    "eventType" => "blockchainRebranched",
-                    "reverted" => reverted,
-                    "rebranched" => rebranched,
-                    "oldHead" => old_head.to_string(),
-                    "newHead" => new_head.to_string(),
-                }
-            },
-            BlockchainEvent::Finalized(block_hash) => object!{
-                "eventType" => "blockchainFinalized",
-                "blockHash" => block_hash.to_string(),
-            },
-        })
+    /// Returns `None` if there are no subscriptions at all. Otherwise returns whether any
+    /// subscription needs the bodies of new blocks.
+    fn subscriptions_state(connections: &WsRpcConnections) -> Option<bool> {
+        let connections = connections.read();
+        let mut subscriptions = connections.values()
+            .flat_map(|connection| connection.subscriptions.values())
+            .peekable();
+
+        subscriptions.peek()?;
+        Some(subscriptions.any(Subscription::needs_block_bodies))
     }
 
     #[cfg(feature="validator")]
@@ -229,16 +371,22 @@ impl WsRpcServer {
         })
     }
 
-    fn broadcast_message(connections: &WsRpcConnections, message: JsonValue) {
-        // Convert JSON message to Websocket TEXT frame
-        let message = Message::Text(message.dump());
-
+    /// Sends the notifications returned by `f` to the subscribers.
+    fn notify_subscribers<F>(connections: &WsRpcConnections, f: F)
+        where F: Fn(&Subscription) -> Vec<JsonValue>
+    {
         for (_connection_id, connection) in connections.read().iter() {
-            let mut tx = connection.tx.clone();
-
-            // If the buffer is full, we drop the event. It's not the end of the world.
-            if let Err(e) = tx.try_send(message.clone()) {
-                warn!("Unable to send event to {}: {}", connection.address, e);
+            for (subscription_id, subscription) in connection.subscriptions.iter() {
+                for result in f(subscription) {
+                    // Convert JSON message to Websocket TEXT frame
+                    let message = Message::Text(subscriptions::notification(*subscription_id, result).dump());
+
+                    // If the buffer is full, we drop the event. It's not the end of the world.
+                    let mut tx = connection.tx.clone();
+                    if let Err(e) = tx.try_send(message) {
+                        warn!("Unable to send event to {}: {}", connection.address, e);
+                    }
+                }
             }
         }
     }
@@ -253,3 +401,79 @@ impl IntoFuture for WsRpcServer {
         self.future
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use json::array;
+    use rpc_server::JsonRpcConfig;
+    use rpc_server::access::IpFilter;
+    use rpc_server::auth::ApiToken;
+    use rpc_server::handler::Method;
+
+    use super::*;
+
+    fn request(method: &str, params: JsonValue) -> String {
+        object!{
+            "jsonrpc" => "2.0",
+            "id" => 1,
+            "method" => method,
+            "params" => params
+        }.dump()
+    }
+
+    #[test]
+    fn clients_authenticate_with_auth_request() {
+        let mut methods = HashSet::new();
+        methods.insert("blockNumber".to_string());
+        let handler = Handler::new(JsonRpcConfig {
+            username: None,
+            password: None,
+            tokens: vec![ApiToken {
+                name: "reader".to_string(),
+                token: "secret".to_string(),
+                methods,
+            }],
+            methods: HashSet::new(),
+            ip_filter: IpFilter::default(),
+            rate_limit: None,
+            corsdomain: vec![],
+        });
+        handler.register_method("blockNumber", Method::new(|_| Ok(1.into())));
+        handler.register_method("sendTransaction", Method::new(|_| Ok(true.into())));
+
+        let address: SocketAddr = "127.0.0.1:8650".parse().unwrap();
+        let connection_id = UniqueId::new();
+        let (tx, _rx) = channel(WsRpcServer::QUEUE_SIZE);
+        let connections: WsRpcConnections = Arc::new(RwLock::new(HashMap::new()));
+        connections.write().insert(connection_id.clone(), WsRpcConnection {
+            address,
+            tx,
+            permissions: None,
+            subscriptions: HashMap::new(),
+            next_subscription_id: 1,
+        });
+        let handle = |text: String| WsRpcServer::handle_text(&handler, &connections, connection_id.clone(), &address, &text);
+
+        // Methods can't be called before authenticating.
+        let response = handle(request("blockNumber", array![]));
+        assert_eq!(response["error"]["message"], "Not authenticated.");
+
+        let response = handle(request("auth", array!["reader", "wrong"]));
+        assert_eq!(response["error"]["message"], "Incorrect credentials.");
+        let response = handle(request("blockNumber", array![]));
+        assert_eq!(response["error"]["message"], "Not authenticated.");
+
+        let response = handle(request("auth", array!["reader", "secret"]));
+        assert_eq!(response["result"], true);
+
+        // The token only allows the methods it was configured with.
+        let response = handle(request("blockNumber", array![]));
+        assert_eq!(response["result"], 1);
+        let response = handle(request("sendTransaction", array![]));
+        assert_eq!(response["error"]["message"], "Method is not allowed.");
+        let response = handle(request("subscribe", array!["headChanged"]));
+        assert_eq!(response["error"]["message"], "Method is not allowed.");
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashSet;
+
+use json::{JsonValue, object};
+
+use block_albatross::{Block, BlockType};
+use blockchain_albatross::Blockchain;
+use blockchain_albatross::blockchain::BlockchainEvent;
+use hash::Blake2bHash;
+use keys::Address;
+use primitives::policy::AlbatrossPolicy;
+use rpc_server::handlers::mempool::{transaction_to_obj, TransactionContext};
+use transaction::Transaction;
+
+/// Restricts transaction notifications to transactions sent from or to one of the addresses.
+/// An empty filter matches all transactions.
+#[derive(Debug, Clone, Default)]
+pub struct AddressFilter {
+    addresses: HashSet<Address>,
+}
+
+impl AddressFilter {
+    fn from_params(params: &[JsonValue]) -> Result<Self, JsonValue> {
+        let mut addresses = HashSet::new();
+        for param in params.iter().flat_map(|p| if p.is_array() { p.members().collect::<Vec<_>>() } else { vec![p] }) {
+            let address = param.as_str()
+                .ok_or_else(|| object!{"message" => "Address must be a string"})?;
+            addresses.insert(Address::from_any_str(address)
+                .map_err(|_| object!{"message" => "Invalid address"})?);
+        }
+        Ok(AddressFilter { addresses })
+    }
+
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        self.addresses.is_empty()
+            || self.addresses.contains(&transaction.sender)
+            || self.addresses.contains(&transaction.recipient)
+    }
+}
+
+/// An event stream a websocket client subscribed to.
+#[derive(Debug, Clone)]
+pub enum Subscription {
+    /// The head of the main chain changed.
+    HeadChanged,
+    /// A transaction was included in a block that was added to the main chain.
+    NewTransaction(AddressFilter),
+    /// A transaction was accepted into the mempool.
+    MempoolTransaction(AddressFilter),
+    /// A macro block was added to the main chain.
+    MacroBlock,
+    /// An event of the local validator.
+    #[cfg(feature="validator")]
+    ValidatorEvent,
+}
+
+impl Subscription {
+    /// Parses the parameters of a `subscribe` call: The event name, optionally followed by the
+    /// addresses to filter transactions by.
+    pub fn from_params(params: &[JsonValue]) -> Result<Self, JsonValue> {
+        let name = params.get(0).and_then(JsonValue::as_str)
+            .ok_or_else(|| object!{"message" => "Event name must be a string"})?;
+        Ok(match name {
+            "headChanged" => Subscription::HeadChanged,
+            "newTransaction" => Subscription::NewTransaction(AddressFilter::from_params(&params[1..])?),
+            "mempoolTransaction" => Subscription::MempoolTransaction(AddressFilter::from_params(&params[1..])?),
+            "macroBlock" => Subscription::MacroBlock,
+            #[cfg(feature="validator")]
+            "validatorEvent" => Subscription::ValidatorEvent,
+            _ => return Err(object!{"message" => format!("Unknown event: {}", name)}),
+        })
+    }
+
+    /// Whether notifications for this subscription require the bodies of new blocks.
+    pub fn needs_block_bodies(&self) -> bool {
+        match self {
+            Subscription::NewTransaction(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A blockchain event with the data needed to notify subscribers.
+pub struct BlockchainUpdate {
+    head: Block,
+    reverted: Vec<Blake2bHash>,
+    adopted: Vec<Block>,
+    policy: AlbatrossPolicy,
+}
+
+impl BlockchainUpdate {
+    /// Collects the blocks affected by the event. Block bodies are only loaded if
+    /// `include_body` is set.
+    pub fn from_event(blockchain: &Blockchain, event: &BlockchainEvent, include_body: bool) -> Option<Self> {
+        let (reverted, adopted) = match event {
+            BlockchainEvent::Extended(block_hash) | BlockchainEvent::Finalized(block_hash) => {
+                let block = blockchain.get_block(block_hash, false, include_body)?;
+                (Vec::new(), vec![block])
+            },
+            BlockchainEvent::Rebranched(reverted, adopted) => (
+                reverted.iter().map(|(block_hash, _)| block_hash.clone()).collect(),
+                adopted.iter().map(|(_, block)| block.clone()).collect(),
+            ),
+        };
+
+        Some(BlockchainUpdate {
+            head: adopted.last()?.clone(),
+            reverted,
+            adopted,
+            policy: blockchain.policy,
+        })
+    }
+
+    /// Returns the notifications for a subscription.
+    pub fn notifications(&self, subscription: &Subscription) -> Vec<JsonValue> {
+        match subscription {
+            Subscription::HeadChanged => vec![object!{
+                "blockHash" => self.head.hash().to_hex(),
+                "blockNumber" => self.head.block_number(),
+                "viewNumber" => self.head.view_number(),
+                "type" => Self::block_type(&self.head),
+                "reverted" => JsonValue::Array(self.reverted.iter()
+                    .map(|block_hash| JsonValue::String(block_hash.to_hex()))
+                    .collect()),
+            }],
+            Subscription::NewTransaction(filter) => self.adopted.iter()
+                .flat_map(|block| Self::block_transactions(block, filter))
+                .collect(),
+            Subscription::MacroBlock => self.adopted.iter()
+                .filter_map(|block| match block {
+                    Block::Macro(macro_block) => Some(object!{
+                        "blockHash" => block.hash().to_hex(),
+                        "blockNumber" => macro_block.header.block_number,
+                        "epoch" => self.policy.epoch_at(macro_block.header.block_number),
+                    }),
+                    Block::Micro(_) => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn block_transactions(block: &Block, filter: &AddressFilter) -> Vec<JsonValue> {
+        let transactions = match block.transactions() {
+            Some(transactions) => transactions,
+            None => return Vec::new(),
+        };
+
+        let block_hash = block.hash().to_hex();
+        transactions.iter().enumerate()
+            .filter(|(_, tx)| filter.matches(tx))
+            .map(|(index, tx)| transaction_to_obj(tx, Some(&TransactionContext {
+                block_hash: &block_hash,
+                block_number: block.block_number(),
+                index: index as u32,
+                timestamp: block.timestamp(),
+            }), None))
+            .collect()
+    }
+
+    fn block_type(block: &Block) -> JsonValue {
+        match block.ty() {
+            BlockType::Macro => "macro".into(),
+            BlockType::Micro => "micro".into(),
+        }
+    }
+}
+
+/// Returns the notification for a transaction that was accepted into the mempool.
+pub fn mempool_notification(subscription: &Subscription, transaction: &Transaction) -> Option<JsonValue> {
+    match subscription {
+        Subscription::MempoolTransaction(filter) if filter.matches(transaction) => {
+            Some(transaction_to_obj(transaction, None, None))
+        },
+        _ => None,
+    }
+}
+
+/// Builds a JSON-RPC 2.0 notification for a subscription.
+pub fn notification(subscription_id: u64, result: JsonValue) -> JsonValue {
+    object!{
+        "jsonrpc" => "2.0",
+        "method" => "subscription",
+        "params" => object!{
+            "subscription" => subscription_id,
+            "result" => result,
+        },
+    }
+}